@@ -6,7 +6,7 @@ fn d1(c: &mut Criterion) {
     let mut group = c.benchmark_group("gradient_1d");
 
     let noise = Noise::simplex(0.01);
-    group.bench_function("lib", |b| b.iter(|| noise.generate_1d(0.0, 1000000)));
+    group.bench_function("lib", |b| b.iter(|| noise.generate_1d::<f32>(0.0, 1000000)));
 
     //let settings = simdnoise::NoiseBuilder::gradient_1d(1000).wrap();
     //group.bench_function("simdnoise", move |b| {
@@ -22,7 +22,7 @@ fn d2(c: &mut Criterion) {
     let mut group = c.benchmark_group("gradient_2d");
     let noise = Noise::simplex(0.01);
     group.bench_function("lib", move |b| {
-        b.iter(|| noise.generate_2d(0.0, 0.0, 1000, 1000))
+        b.iter(|| noise.generate_2d::<f32>(0.0, 0.0, 1000, 1000))
     });
 
     //let setting = simdnoise::NoiseBuilder::gradient_2d(3840, 2160).wrap();
@@ -39,7 +39,7 @@ fn d3(c: &mut Criterion) {
     let mut group = c.benchmark_group("gradient_3d");
     let noise = Noise::simplex(0.01);
     group.bench_function("lib", move |b| {
-        b.iter(|| noise.generate_3d(0.0, 0.0, 0.0, 100, 100, 100))
+        b.iter(|| noise.generate_3d::<f32>(0.0, 0.0, 0.0, 100, 100, 100))
     });
 
     //let setting = simdnoise::NoiseBuilder::gradient_3d(16, 16, 16).wrap();
@@ -63,7 +63,7 @@ fn fbm_3d(c: &mut Criterion) {
         .range(0.1, -0.1, high, low)
         .mul(Noise::constant(2.0));
     group.bench_function("lib", move |b| {
-        b.iter(|| noise.generate_3d(0.0, 0.0, 0.0, 16, 16, 16))
+        b.iter(|| noise.generate_3d::<f32>(0.0, 0.0, 0.0, 16, 16, 16))
     });
 
     group
@@ -79,7 +79,7 @@ fn add_3d(c: &mut Criterion) {
     let noise2 = Noise::simplex(0.01).fbm(3, 1.0, 1.0);
     let noise = noise.add(noise2);
     group.bench_function("lib", move |b| {
-        b.iter(|| noise.generate_3d(0.0, 0.0, 0.0, 100, 100, 100))
+        b.iter(|| noise.generate_3d::<f32>(0.0, 0.0, 0.0, 100, 100, 100))
     });
 
     group
@@ -0,0 +1,4 @@
+fn main() {
+    // Read by the multiversion macro, see `targets_from_env`
+    println!("cargo:rerun-if-env-changed=FMC_NOISE_TARGETS");
+}
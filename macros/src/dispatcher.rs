@@ -3,8 +3,8 @@ use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use std::collections::HashMap;
 use syn::{
-    Attribute, Block, Ident, ItemFn, Result, ReturnType, Signature, Type, Visibility, parse_quote,
-    punctuated::Punctuated, token::RArrow,
+    Attribute, Block, Error, GenericParam, Ident, ItemFn, Result, ReturnType, Signature, Type,
+    TypeBareFn, Visibility, parse_quote, punctuated::Punctuated, spanned::Spanned, token::RArrow,
 };
 
 pub(crate) fn feature_fn_name(ident: &Ident, target: Option<&Target>) -> Ident {
@@ -21,10 +21,26 @@ pub(crate) fn feature_fn_name(ident: &Ident, target: Option<&Target>) -> Ident {
     Ident::new(&format!("{ident}_default_version"), ident.span())
 }
 
+/// With the `debug-bypass` feature, debug builds skip the versions for each target and the
+/// dispatcher returns the default version directly. Returns the cfg that enables the bypass.
+fn bypass_cfg() -> Option<TokenStream> {
+    cfg!(feature = "debug-bypass").then(|| quote! { debug_assertions })
+}
+
+/// How the dispatcher picks the version of the function to return
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DispatchMethod {
+    /// Detect the features on every call
+    Pointer,
+    /// Detect the features on the first call and store the result in a static
+    Cached,
+}
+
 pub(crate) struct Dispatcher {
     pub inner_attrs: Vec<Attribute>,
     pub targets: Vec<Target>,
     pub func: ItemFn,
+    pub dispatcher: DispatchMethod,
 }
 
 impl Dispatcher {
@@ -89,6 +105,13 @@ impl Dispatcher {
             }
         };
 
+        // Method versions are hidden siblings of the dispatcher
+        let (vis, mut hidden_attrs) = if self.is_method() {
+            (Visibility::Inherited, vec![parse_quote! { #[doc(hidden)] }])
+        } else {
+            (self.func.vis.clone(), Vec::new())
+        };
+
         let mut fns = Vec::new();
         for target in &self.targets {
             // This function will always be unsafe, regardless of the safety of the multiversioned
@@ -102,7 +125,7 @@ impl Dispatcher {
             // When target_feature 1.1 is available, this function can instead use the original
             // function safety.
             let mut f = ItemFn {
-                attrs: self.inner_attrs.clone(),
+                attrs: [self.inner_attrs.clone(), hidden_attrs.clone()].concat(),
                 vis: Visibility::Inherited,
                 sig: Signature {
                     ident: feature_fn_name(&self.func.sig.ident, Some(target)),
@@ -112,16 +135,20 @@ impl Dispatcher {
                 block: make_block(Some(target)),
             };
             f.attrs.extend(target.fn_attrs());
+            if let Some(bypass) = bypass_cfg() {
+                f.attrs.push(parse_quote! { #[cfg(not(#bypass))] });
+            }
             fns.push(f);
         }
 
         // Create default fn
         let mut attrs = self.inner_attrs.clone();
+        attrs.append(&mut hidden_attrs);
         attrs.push(parse_quote! { #[inline(always)] });
         let block = make_block(None);
         fns.push(ItemFn {
             attrs,
-            vis: self.func.vis.clone(),
+            vis,
             sig: Signature {
                 ident: feature_fn_name(&self.func.sig.ident, None),
                 ..self.func.sig.clone()
@@ -132,13 +159,29 @@ impl Dispatcher {
         Ok(fns)
     }
 
+    // Methods can't have their versions nested inside the dispatcher, since nested functions can't
+    // refer to `Self`. They are instead placed next to it in the impl block.
+    fn is_method(&self) -> bool {
+        self.func.sig.receiver().is_some()
+    }
+
+    // The path used to refer to a version of the function from inside the dispatcher.
+    fn fn_path(&self, target: Option<&Target>) -> TokenStream {
+        let function = feature_fn_name(&self.func.sig.ident, target);
+        if self.is_method() {
+            quote! { Self::#function }
+        } else {
+            quote! { #function }
+        }
+    }
+
     fn pointer_dispatcher_fn(&self) -> Result<Block> {
         let feature_detection = {
             let return_if_detected = self.targets.iter().filter_map(|target| {
                 if target.has_features_specified() {
                     let target_arch = target.target_arch();
                     let features_detected = target.features_detected();
-                    let function = feature_fn_name(&self.func.sig.ident, Some(target));
+                    let function = self.fn_path(Some(target));
                     Some(quote! {
                        #target_arch
                        {
@@ -151,7 +194,7 @@ impl Dispatcher {
                     None
                 }
             });
-            let default_fn = feature_fn_name(&self.func.sig.ident, None);
+            let default_fn = self.fn_path(None);
             quote! {
                 #(#return_if_detected)*
                 return #default_fn
@@ -165,20 +208,116 @@ impl Dispatcher {
         })
     }
 
-    fn create_fn(&self) -> Result<ItemFn> {
-        let block = self.pointer_dispatcher_fn()?;
+    fn cached_dispatcher_fn(&self, fn_type: &TypeBareFn) -> Result<Block> {
+        // A static can't depend on the generics of the function it's in, or on `Self`.
+        let generics = &self.func.sig.generics;
+        if generics.type_params().next().is_some() || generics.const_params().next().is_some() {
+            return Err(Error::new(
+                generics.span(),
+                "the cached dispatcher does not support generic functions",
+            ));
+        }
+        if let Some(receiver) = self.func.sig.receiver() {
+            return Err(Error::new(
+                receiver.span(),
+                "the cached dispatcher does not support methods",
+            ));
+        }
 
-        // If we already know that the current build target supports the best function choice, we
-        // can skip dispatching entirely.
-        //
-        // Here we check for one of two possibilities:
-        // * If the globally enabled features (the target-feature or target-cpu codegen options)
-        //   already support the highest priority function, skip dispatch entirely and call that
-        //   function.
-        // * If the current target isn't specified in the multiversioned list at all, we can skip
-        //   dispatch entirely and call the default function.
-        //
-        // In these cases, the default function is called instead.
+        let detect = self.pointer_dispatcher_fn()?;
+        Ok(parse_quote! {
+            {
+                static SELECTED: std::sync::OnceLock<#fn_type> = std::sync::OnceLock::new();
+                return *SELECTED.get_or_init(|| #detect);
+            }
+        })
+    }
+
+    // Generic arguments to call a version of the function with, for when they can't be inferred
+    // from the arguments.
+    fn turbofish(&self) -> TokenStream {
+        let params = self
+            .func
+            .sig
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(param) => Some(param.ident.to_token_stream()),
+                GenericParam::Const(param) => Some(param.ident.to_token_stream()),
+                GenericParam::Lifetime(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if params.is_empty() {
+            TokenStream::new()
+        } else {
+            quote! { ::<#(#params),*> }
+        }
+    }
+
+    // A future can't be returned through a function pointer, the versions of an async function
+    // return different types. Instead the dispatcher is an async function with the same
+    // signature that awaits the version for the detected features. The features are detected on
+    // every call, `std` caches the detection so this is cheap either way.
+    fn create_async_fn(&self) -> Result<ItemFn> {
+        let (normalized_signature, args) = util::normalize_signature(&self.func.sig)?;
+        let turbofish = self.turbofish();
+        let nested_fns = if self.is_method() {
+            Vec::new()
+        } else {
+            self.feature_fns()?
+        };
+
+        let return_if_detected = self.targets.iter().filter_map(|target| {
+            if target.has_features_specified() {
+                let target_arch = target.target_arch();
+                let features_detected = target.features_detected();
+                let function = self.fn_path(Some(target));
+                Some(quote! {
+                   #target_arch
+                   {
+                       if #features_detected {
+                           return unsafe { #function #turbofish(#(#args),*) }.await
+                       }
+                   }
+                })
+            } else {
+                None
+            }
+        });
+        let default_fn = self.fn_path(None);
+
+        let skips = self.skips();
+        Ok(ItemFn {
+            attrs: self.func.attrs.clone(),
+            vis: self.func.vis.clone(),
+            sig: normalized_signature,
+            block: Box::new(parse_quote! {
+                {
+                    #(#nested_fns)*
+
+                    #[cfg(not(#skips))]
+                    {
+                        #(#return_if_detected)*
+                    }
+                    return #default_fn #turbofish(#(#args),*).await
+                }
+            }),
+        })
+    }
+
+    // The cfg under which dispatching is skipped and the default version is called directly.
+    //
+    // Here we check for one of two possibilities:
+    // * If the globally enabled features (the target-feature or target-cpu codegen options)
+    //   already support the highest priority function, skip dispatch entirely and call that
+    //   function.
+    // * If the current target isn't specified in the multiversioned list at all, we can skip
+    //   dispatch entirely and call the default function.
+    //
+    // In these cases, the default function is called instead. It is also called when the
+    // dispatch is bypassed in debug builds.
+    fn skips(&self) -> TokenStream {
         let best_targets = self
             .targets
             .iter()
@@ -192,12 +331,33 @@ impl Dispatcher {
                 all(target_arch = #arch, #(target_feature = #feature),*)
             });
         }
+        skips.extend(bypass_cfg());
         let specified_arches = best_targets.keys().collect::<Vec<_>>();
-        let call_default = feature_fn_name(&self.func.sig.ident, None);
-        let (normalized_signature, _) = util::normalize_signature(&self.func.sig);
-        let feature_fns = self.feature_fns()?;
+        quote! {
+            any(
+                not(any(#(target_arch = #specified_arches),*)),
+                #(#skips),*
+            )
+        }
+    }
+
+    fn create_fn(&self) -> Result<ItemFn> {
+        // If we already know that the current build target supports the best function choice, we
+        // can skip dispatching entirely, see `skips`.
+        let skips = self.skips();
+        let call_default = self.fn_path(None);
+        let (normalized_signature, _) = util::normalize_signature(&self.func.sig)?;
+        let nested_fns = if self.is_method() {
+            Vec::new()
+        } else {
+            self.feature_fns()?
+        };
         let mut return_type = util::fn_type_from_signature(&self.func.sig)?;
         return_type.unsafety = Some(syn::token::Unsafe::default());
+        let block = match self.dispatcher {
+            DispatchMethod::Pointer => self.pointer_dispatcher_fn()?,
+            DispatchMethod::Cached => self.cached_dispatcher_fn(&return_type)?,
+        };
         Ok(ItemFn {
             attrs: self.func.attrs.clone(),
             vis: self.func.vis.clone(),
@@ -209,18 +369,12 @@ impl Dispatcher {
             },
             block: Box::new(parse_quote! {
                 {
-                    #(#feature_fns)*
+                    #(#nested_fns)*
 
-                    #[cfg(any(
-                        not(any(#(target_arch = #specified_arches),*)),
-                        #(#skips),*
-                    ))]
+                    #[cfg(#skips)]
                     { return #call_default }
 
-                    #[cfg(not(any(
-                        not(any(#(target_arch = #specified_arches),*)),
-                        #(#skips),*
-                    )))]
+                    #[cfg(not(#skips))]
                     #block
                 }
             }),
@@ -230,8 +384,24 @@ impl Dispatcher {
 
 impl ToTokens for Dispatcher {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        tokens.extend(match self.create_fn() {
-            Ok(val) => val.into_token_stream(),
+        let dispatcher = if self.func.sig.asyncness.is_some() {
+            self.create_async_fn()
+        } else {
+            self.create_fn()
+        };
+        let result = dispatcher.and_then(|dispatcher| {
+            let sibling_fns = if self.is_method() {
+                self.feature_fns()?
+            } else {
+                Vec::new()
+            };
+            Ok(quote! {
+                #(#sibling_fns)*
+                #dispatcher
+            })
+        });
+        tokens.extend(match result {
+            Ok(val) => val,
             Err(err) => err.to_compile_error(),
         })
     }
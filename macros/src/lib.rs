@@ -4,12 +4,14 @@ extern crate proc_macro;
 mod cfg;
 mod dispatcher;
 mod multiversion;
+mod noise;
+mod snapshot;
 mod target;
 mod util;
 
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
-use syn::{ItemFn, parse::Nothing, parse_macro_input, punctuated::Punctuated};
+use syn::{ItemFn, Type, parse::Nothing, parse_macro_input, punctuated::Punctuated};
 
 #[proc_macro_attribute]
 pub fn multiversion(
@@ -24,6 +26,49 @@ pub fn multiversion(
     .into()
 }
 
+/// Creates the feature snapshot shared by all multiversioned functions in the crate. Must be
+/// invoked once at the crate root.
+#[proc_macro]
+pub fn feature_snapshot(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as Nothing);
+    snapshot::snapshot_module().into()
+}
+
+/// Builds a `Noise` from a graph, checked while compiling.
+///
+/// The graph starts with a noise and is followed by the operations applied to it, optionally
+/// separated by `|>`. Each is written like the method of `Noise` it calls, with the parentheses
+/// left out when it takes no arguments. Where an operation takes another noise, it can be a
+/// nested graph, or a number for a constant noise. A noise built elsewhere can be used as a
+/// graph by wrapping it in braces.
+///
+/// Unknown operations and the wrong number of arguments are errors at the operation.
+///
+/// # Example
+/// ```ignore
+/// use fmc_noise::{noise, Noise};
+///
+/// let noise = noise! { perlin(0.01) fbm(5, 0.5, 2.0) |> abs |> add(0.5) };
+/// // Is equivalent to
+/// let noise = Noise::perlin(0.01)
+///     .fbm(5, 0.5, 2.0)
+///     .abs()
+///     .add(Noise::constant(0.5));
+///
+/// let mask = Noise::simplex(0.001);
+/// let terrain = noise! {
+///     { mask }
+///     |> lerp(simplex(0.01) |> fbm(4, 0.5, 2.0), 0.0)
+///     |> seed(42)
+/// };
+/// ```
+#[proc_macro]
+pub fn noise(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as noise::Graph)
+        .into_token_stream()
+        .into()
+}
+
 #[proc_macro]
 pub fn selected_target(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     parse_macro_input!(input as Nothing);
@@ -33,6 +78,20 @@ pub fn selected_target(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     .into()
 }
 
+/// The suggested number of lanes for a vector of the given element type on the selected target,
+/// or 1 if the target has no vector registers. Can be used in const items.
+#[proc_macro]
+pub fn simd_width(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ty = parse_macro_input!(input as Type);
+    quote! {
+        match __multiversion::FEATURES.suggested_simd_width::<#ty>() {
+            Some(size) => size,
+            None => 1,
+        }
+    }
+    .into()
+}
+
 #[proc_macro_attribute]
 pub fn target_cfg(
     attr: proc_macro::TokenStream,
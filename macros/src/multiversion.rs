@@ -1,38 +1,129 @@
-use crate::dispatcher::Dispatcher;
+use crate::dispatcher::{DispatchMethod, Dispatcher};
 use crate::target::Target;
-use proc_macro2::TokenStream;
+use crate::util;
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use syn::ItemFn;
+use syn::{
+    Error, Expr, ExprLit, ItemFn, Lit, LitStr, Meta, parse::Parser, punctuated::Punctuated,
+    spanned::Spanned, token::Comma,
+};
 
-pub(crate) fn make_multiversioned_fn(
-    _attr: TokenStream,
-    func: ItemFn,
-) -> Result<TokenStream, syn::Error> {
-    let targets = vec![
+fn default_targets() -> Vec<Target> {
+    vec![
+        // Target::new("x86_64", &["avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl"]),
         Target::new("x86_64", &["avx2", "fma"]),
         Target::new("x86_64", &["sse4.2"]),
+        // Target::new("x86", &["avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl"]),
         Target::new("x86", &["avx2", "fma"]),
         Target::new("x86", &["sse4.2"]),
         Target::new("x86", &["sse2"]),
         Target::new("aarch64", &["neon"]),
-    ];
-    // let default_targets = [
-    //     // "x86_64+avx512f+avx512bw+avx512cd+avx512dq+avx512vl",
-    //     "x86_64+avx2+fma",
-    //     "x86_64+sse4.2",
-    //     // "x86+avx512f+avx512bw+avx512cd+avx512dq+avx512vl",
-    //     "x86+avx2+fma",
-    //     "x86+sse4.2",
-    //     "x86+sse2",
-    //     "aarch64+neon",
-    //     // "arm+neon",
-    //     // "mips+msa",
-    //     // "mips64+msa",
-    //     // "powerpc+vsx",
-    //     // "powerpc+altivec",
-    //     // "powerpc64+vsx",
-    //     // "powerpc64+altivec",
-    // ];
+        // Target::new("arm", &["neon"]),
+        // Target::new("mips", &["msa"]),
+        // Target::new("mips64", &["msa"]),
+        // Target::new("powerpc", &["vsx"]),
+        // Target::new("powerpc", &["altivec"]),
+        // Target::new("powerpc64", &["vsx"]),
+        // Target::new("powerpc64", &["altivec"]),
+    ]
+}
+
+fn lit_str(expr: &Expr) -> Result<&LitStr, Error> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(s), ..
+        }) => Ok(s),
+        _ => Err(Error::new(expr.span(), "expected a string literal")),
+    }
+}
+
+/// Parses either `targets = "simd"` or `targets("x86_64+avx2+fma", "aarch64+neon", ...)`
+fn parse_targets(meta: &Meta) -> Result<Vec<Target>, Error> {
+    match meta {
+        Meta::NameValue(nv) => {
+            let s = lit_str(&nv.value)?;
+            if s.value() == "simd" {
+                Ok(default_targets())
+            } else {
+                Err(Error::new(
+                    s.span(),
+                    "expected \"simd\", use `targets(...)` to list targets",
+                ))
+            }
+        }
+        Meta::List(list) => list
+            .parse_args_with(Punctuated::<LitStr, Comma>::parse_terminated)?
+            .iter()
+            .map(Target::parse)
+            .collect(),
+        Meta::Path(path) => Err(Error::new(path.span(), "expected a list of targets")),
+    }
+}
+
+/// The targets can be overridden for the whole build by setting `FMC_NOISE_TARGETS` to a comma
+/// separated list of targets, e.g. `FMC_NOISE_TARGETS=x86_64+avx2+fma`. Setting it to "none"
+/// leaves only the default version of each function.
+fn targets_from_env(span: Span) -> Result<Option<Vec<Target>>, Error> {
+    let Ok(value) = std::env::var("FMC_NOISE_TARGETS") else {
+        return Ok(None);
+    };
+
+    let value = value.trim();
+    if value.is_empty() || value == "none" {
+        return Ok(Some(Vec::new()));
+    }
+
+    value
+        .split(',')
+        .map(|target| {
+            Target::parse_str(target.trim(), span)
+                .map_err(|err| Error::new(span, format!("FMC_NOISE_TARGETS: {err}")))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+pub(crate) fn make_multiversioned_fn(
+    attr: TokenStream,
+    func: ItemFn,
+) -> Result<TokenStream, syn::Error> {
+    util::check_signature(&func.sig)?;
+
+    let attr_span = attr.span();
+    let metas = Punctuated::<Meta, Comma>::parse_terminated.parse2(attr)?;
+
+    let mut targets = None;
+    let mut dispatcher = DispatchMethod::Pointer;
+    for meta in metas.iter() {
+        if meta.path().is_ident("targets") {
+            if targets.is_some() {
+                return Err(Error::new(
+                    meta.span(),
+                    "`targets` specified more than once",
+                ));
+            }
+            targets = Some(parse_targets(meta)?);
+        } else if meta.path().is_ident("dispatcher") {
+            let s = lit_str(&meta.require_name_value()?.value)?;
+            dispatcher = match s.value().as_str() {
+                "pointer" => DispatchMethod::Pointer,
+                "cached" => DispatchMethod::Cached,
+                _ => {
+                    return Err(Error::new(s.span(), "expected \"pointer\" or \"cached\""));
+                }
+            };
+        } else {
+            return Err(Error::new(meta.path().span(), "unknown option"));
+        }
+    }
+
+    let Some(mut targets) = targets else {
+        return Err(Error::new(attr_span, "expected `targets`"));
+    };
+
+    if let Some(env_targets) = targets_from_env(attr_span)? {
+        targets = env_targets;
+    }
 
     let inner_attrs = Vec::new();
 
@@ -40,6 +131,7 @@ pub(crate) fn make_multiversioned_fn(
         targets,
         func,
         inner_attrs,
+        dispatcher,
     }
     .to_token_stream())
 }
@@ -0,0 +1,288 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    braced, parenthesized,
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    Error, Expr, Ident, Lit, Result, Token,
+};
+
+// What an argument of an operation is parsed as
+#[derive(Clone, Copy)]
+enum Arg {
+    // Any expression, passed on as it is
+    Value(&'static str),
+    // A noise graph, or a number that becomes a constant noise
+    Noise(&'static str),
+}
+
+use Arg::{Noise, Value};
+
+// The constructors of `Noise` that can start a graph. Keep in sync with the builder methods of
+// `Noise` in fmc_noise.
+const SOURCES: &[(&str, &[Arg])] = &[
+    ("simplex", &[Value("frequency")]),
+    ("perlin", &[Value("frequency")]),
+    ("opensimplex2", &[Value("frequency")]),
+    ("value", &[Value("frequency")]),
+    ("value_cubic", &[Value("frequency")]),
+    ("cellular", &[Value("frequency")]),
+    (
+        "cellular_with",
+        &[Value("frequency"), Value("distance"), Value("return_type")],
+    ),
+    ("white", &[]),
+    ("pink", &[Value("frequency")]),
+    ("brown", &[Value("frequency")]),
+    (
+        "spectral",
+        &[
+            Value("lowest_frequency"),
+            Value("highest_frequency"),
+            Value("beta"),
+            Value("waves"),
+        ],
+    ),
+    (
+        "dots",
+        &[
+            Value("frequency"),
+            Value("density"),
+            Value("radius"),
+            Value("amplitude_jitter"),
+        ],
+    ),
+    ("constant", &[Value("value")]),
+    ("param", &[Value("name"), Value("value")]),
+    (
+        "falloff_radial",
+        &[
+            Value("center_x"),
+            Value("center_z"),
+            Value("inner_radius"),
+            Value("outer_radius"),
+        ],
+    ),
+    ("sdf", &[Value("shape")]),
+    (
+        "veins",
+        &[Value("frequency"), Value("thickness"), Value("elongation")],
+    ),
+];
+
+// The methods of `Noise` that can follow in a graph
+const OPERATIONS: &[(&str, &[Arg])] = &[
+    ("seed", &[Value("seed")]),
+    ("seed_str", &[Value("seed")]),
+    ("algorithm", &[Value("algorithm")]),
+    (
+        "fbm",
+        &[Value("octaves"), Value("gain"), Value("lacunarity")],
+    ),
+    (
+        "fbm_with",
+        &[
+            Value("octaves"),
+            Value("gain"),
+            Value("lacunarity"),
+            Value("op"),
+        ],
+    ),
+    ("fbm_custom", &[Value("octaves")]),
+    (
+        "ridged",
+        &[Value("octaves"), Value("gain"), Value("lacunarity")],
+    ),
+    (
+        "ping_pong",
+        &[
+            Value("octaves"),
+            Value("gain"),
+            Value("lacunarity"),
+            Value("strength"),
+        ],
+    ),
+    (
+        "wrap_domain",
+        &[Value("period_x"), Value("period_y"), Value("period_z")],
+    ),
+    ("warp", &[Noise("offset"), Value("amplitude")]),
+    ("slope", &[]),
+    ("extrude_2d", &[]),
+    ("interpolated", &[Value("spacing")]),
+    ("abs", &[]),
+    ("square", &[]),
+    ("dither", &[Value("min"), Value("max"), Value("levels")]),
+    ("add", &[Noise("other")]),
+    ("sub", &[Noise("other")]),
+    ("abs_diff", &[Noise("other")]),
+    ("avg", &[Noise("other")]),
+    ("mul", &[Noise("other")]),
+    ("div", &[Noise("other")]),
+    ("max", &[Noise("other")]),
+    ("min", &[Noise("other")]),
+    ("clamp", &[Value("min"), Value("max")]),
+    ("lerp", &[Noise("low"), Noise("high")]),
+    (
+        "lerp_with_range",
+        &[
+            Value("selector_min"),
+            Value("selector_max"),
+            Noise("low"),
+            Noise("high"),
+        ],
+    ),
+    ("lerp_values", &[Value("low"), Value("high")]),
+    (
+        "range",
+        &[
+            Value("low"),
+            Value("high"),
+            Noise("low_noise"),
+            Noise("high_noise"),
+        ],
+    ),
+];
+
+// A source followed by the operations applied to it, expands to a chain of builder calls.
+pub(crate) struct Graph {
+    source: TokenStream,
+    operations: Vec<TokenStream>,
+}
+
+impl Parse for Graph {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let source = parse_source(input)?;
+        let mut operations = Vec::new();
+        while !input.is_empty() {
+            // The pipe between operations is optional
+            if input.peek(Token![|]) && input.peek2(Token![>]) {
+                input.parse::<Token![|]>()?;
+                input.parse::<Token![>]>()?;
+            }
+            let name: Ident = input.parse()?;
+            let Some((_, args)) = OPERATIONS.iter().find(|(operation, _)| name == operation) else {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "unknown operation `{name}`, expected one of {}",
+                        names(OPERATIONS)
+                    ),
+                ));
+            };
+            let args = parse_args(input, &name, args)?;
+            operations.push(quote! { .#name(#(#args),*) });
+        }
+        Ok(Self { source, operations })
+    }
+}
+
+impl ToTokens for Graph {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let source = &self.source;
+        let operations = &self.operations;
+        tokens.extend(quote! { #source #(#operations)* });
+    }
+}
+
+fn parse_source(input: ParseStream) -> Result<TokenStream> {
+    // `{ expression }` starts from a noise built elsewhere
+    if input.peek(syn::token::Brace) {
+        let content;
+        braced!(content in input);
+        let expr: Expr = content.parse()?;
+        return Ok(quote! { (#expr) });
+    }
+
+    // A nested graph
+    if input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let graph: Graph = content.parse()?;
+        return Ok(quote! { (#graph) });
+    }
+
+    // A number is a constant
+    if input.peek(Token![-]) || input.peek(Lit) {
+        let negative = input.parse::<Option<Token![-]>>()?;
+        let lit: Lit = input.parse()?;
+        if !matches!(lit, Lit::Float(_) | Lit::Int(_)) {
+            return Err(Error::new(lit.span(), "expected a number"));
+        }
+        return Ok(quote! { ::fmc_noise::Noise::constant(#negative #lit as f32) });
+    }
+
+    let name: Ident = input.parse().map_err(|err| {
+        Error::new(
+            err.span(),
+            format!(
+                "expected a noise, one of {}, a number, or a noise expression in braces",
+                names(SOURCES)
+            ),
+        )
+    })?;
+    let Some((_, args)) = SOURCES.iter().find(|(source, _)| name == source) else {
+        return Err(Error::new(
+            name.span(),
+            format!("unknown noise `{name}`, expected one of {}", names(SOURCES)),
+        ));
+    };
+    let args = parse_args(input, &name, args)?;
+    Ok(quote! { ::fmc_noise::Noise::#name(#(#args),*) })
+}
+
+// Parses the arguments of a source or operation, the parentheses can be left out when it takes
+// none.
+fn parse_args(input: ParseStream, name: &Ident, expected: &[Arg]) -> Result<Vec<TokenStream>> {
+    let (span, args) = if input.peek(syn::token::Paren) {
+        let content;
+        let paren = parenthesized!(content in input);
+        let args =
+            Punctuated::<TokenStream, Token![,]>::parse_terminated_with(&content, |input| {
+                // Everything up to the next comma at this level
+                let mut tokens = TokenStream::new();
+                while !input.is_empty() && !input.peek(Token![,]) {
+                    tokens.extend([input.parse::<TokenTree>()?]);
+                }
+                Ok(tokens)
+            })?;
+        (paren.span.join(), args.into_iter().collect::<Vec<_>>())
+    } else {
+        (name.span(), Vec::new())
+    };
+
+    if args.len() != expected.len() {
+        let names = expected
+            .iter()
+            .map(|arg| match arg {
+                Value(name) | Noise(name) => *name,
+            })
+            .collect::<Vec<_>>();
+        let message = match names.len() {
+            0 => format!("`{name}` takes no arguments"),
+            1 => format!("`{name}` takes 1 argument: {}", names[0]),
+            count => format!("`{name}` takes {count} arguments: {}", names.join(", ")),
+        };
+        return Err(Error::new(span, message));
+    }
+
+    args.into_iter()
+        .zip(expected)
+        .map(|(tokens, arg)| {
+            if tokens.is_empty() {
+                return Err(Error::new(span, "empty argument"));
+            }
+            match arg {
+                Value(_) => Ok(syn::parse2::<Expr>(tokens)?.into_token_stream()),
+                Noise(_) => Ok(Graph::parse.parse2(tokens)?.into_token_stream()),
+            }
+        })
+        .collect()
+}
+
+fn names(list: &[(&str, &[Arg])]) -> String {
+    list.iter()
+        .map(|(name, _)| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,89 @@
+use crate::target::Target;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+// The features recorded in the snapshot, a feature's index is its bit in the snapshot. Targets
+// that use features outside of these fall back to detecting them on their own.
+const X86_FEATURES: &[&str] = &[
+    "sse", "sse2", "sse3", "ssse3", "sse4.1", "sse4.2", "popcnt", "avx", "avx2", "fma", "f16c",
+    "bmi1", "bmi2", "lzcnt", "avx512f", "avx512bw", "avx512cd", "avx512dq", "avx512vl",
+];
+const AARCH64_FEATURES: &[&str] = &["neon", "aes", "sha2", "crc", "fp16"];
+
+// Set once the snapshot has been taken, so that a snapshot without any features isn't retaken.
+const INITIALIZED_BIT: u64 = 1 << 63;
+
+fn feature_table(arch: &str) -> &'static [&'static str] {
+    match arch {
+        "x86" | "x86_64" => X86_FEATURES,
+        "aarch64" => AARCH64_FEATURES,
+        _ => &[],
+    }
+}
+
+/// The bits of the snapshot that must be set for the target to be supported, if all of its
+/// features are part of the snapshot.
+pub(crate) fn target_mask(target: &Target) -> Option<u64> {
+    let table = feature_table(target.arch());
+    let mut mask = 0;
+    for feature in target.features() {
+        let index = table.iter().position(|f| f == feature)?;
+        mask |= 1 << index;
+    }
+    Some(mask)
+}
+
+/// The module holding the snapshot, created once per crate by `feature_snapshot!()`.
+pub(crate) fn snapshot_module() -> TokenStream {
+    // Constants can't be used directly in the repetitions
+    let x86_features = X86_FEATURES;
+    let x86_bits = 0..X86_FEATURES.len() as u32;
+    let aarch64_features = AARCH64_FEATURES;
+    let aarch64_bits = 0..AARCH64_FEATURES.len() as u32;
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(unused)]
+        pub(crate) mod __multiversion_snapshot {
+            use std::sync::atomic::{AtomicU64, Ordering};
+
+            static DETECTED: AtomicU64 = AtomicU64::new(0);
+
+            /// The features of the cpu, one bit per feature.
+            #[inline]
+            pub(crate) fn detected() -> u64 {
+                let features = DETECTED.load(Ordering::Relaxed);
+                if features != 0 {
+                    return features;
+                }
+
+                // Racing threads will all store the same value
+                let features = detect() | #INITIALIZED_BIT;
+                DETECTED.store(features, Ordering::Relaxed);
+                features
+            }
+
+            #[cold]
+            fn detect() -> u64 {
+                let mut features = 0u64;
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                {
+                    #(
+                        if std::arch::is_x86_feature_detected!(#x86_features) {
+                            features |= 1 << #x86_bits;
+                        }
+                    )*
+                }
+                #[cfg(target_arch = "aarch64")]
+                {
+                    #(
+                        if std::arch::is_aarch64_feature_detected!(#aarch64_features) {
+                            features |= 1 << #aarch64_bits;
+                        }
+                    )*
+                }
+                features
+            }
+        }
+    }
+}
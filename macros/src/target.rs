@@ -1,7 +1,7 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, format_ident, quote};
-use syn::{Attribute, LitStr, parse_quote};
-use target_features::Architecture;
+use syn::{Attribute, Error, LitStr, Result, parse_quote};
+use target_features::{Architecture, Feature};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Target {
@@ -30,6 +30,34 @@ impl Target {
         }
     }
 
+    /// Parse a target of the form "arch+feature+feature", e.g. "x86_64+avx2+fma"
+    pub fn parse(s: &LitStr) -> Result<Self> {
+        Self::parse_str(&s.value(), s.span())
+    }
+
+    /// Same as [Target::parse], errors are reported at `span`
+    pub fn parse_str(s: &str, span: Span) -> Result<Self> {
+        let mut parts = s.split('+');
+
+        let arch = parts.next().unwrap();
+        let architecture = Architecture::from_str(arch);
+        if architecture == Architecture::Unsupported {
+            return Err(Error::new(span, format!("unknown architecture '{arch}'")));
+        }
+
+        let features = parts.collect::<Vec<_>>();
+        for feature in &features {
+            if Feature::new(architecture, feature).is_err() {
+                return Err(Error::new(
+                    span,
+                    format!("unknown target feature '{feature}' for architecture '{arch}'"),
+                ));
+            }
+        }
+
+        Ok(Self::new(arch, &features))
+    }
+
     pub fn arch(&self) -> &str {
         &self.architecture
     }
@@ -71,6 +99,12 @@ impl Target {
     }
 
     pub fn features_detected(&self) -> TokenStream {
+        if let Some(mask) = crate::snapshot::target_mask(self) {
+            return quote! {
+                (crate::__multiversion_snapshot::detected() & #mask) == #mask
+            };
+        }
+
         let feature = self.features.iter();
         let is_feature_detected =
             format_ident!("is_{}_feature_detected", match self.architecture.as_str() {
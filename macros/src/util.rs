@@ -1,29 +1,85 @@
 use syn::{
-    BareFnArg, Error, Expr, FnArg, Ident, Lifetime, Pat, PatIdent, PatType, Result, Signature,
-    TypeBareFn, parse_quote, spanned::Spanned, visit_mut::VisitMut,
+    BareFnArg, Error, Expr, FnArg, Ident, Lifetime, Pat, PatIdent, PatType, Result, ReturnType,
+    Signature, TypeBareFn, TypeImplTrait, parse_quote, spanned::Spanned, visit::Visit,
+    visit_mut::VisitMut,
 };
 
-pub(crate) fn arg_exprs(sig: &Signature) -> Vec<Expr> {
+// Finds the first `impl Trait` in a type
+#[derive(Default)]
+struct ImplTraitFinder<'a> {
+    found: Option<&'a TypeImplTrait>,
+}
+
+impl<'a> Visit<'a> for ImplTraitFinder<'a> {
+    fn visit_type_impl_trait(&mut self, i: &'a TypeImplTrait) {
+        self.found.get_or_insert(i);
+    }
+}
+
+/// Checks that the signature can be multiversioned, so that the errors point at the part of the
+/// signature that is the problem instead of somewhere in the generated code.
+pub(crate) fn check_signature(sig: &Signature) -> Result<()> {
+    if let Some(variadic) = &sig.variadic {
+        return Err(Error::new(
+            variadic.span(),
+            "cannot multiversion a function with variadic arguments",
+        ));
+    }
+
+    // The versions of an async function are awaited instead of returned as a function pointer,
+    // which can't hold `impl Trait`.
+    if sig.asyncness.is_none() {
+        let mut finder = ImplTraitFinder::default();
+        for input in sig.inputs.iter() {
+            if let FnArg::Typed(arg) = input {
+                finder.visit_type(&arg.ty);
+            }
+        }
+        if let Some(impl_trait) = finder.found {
+            return Err(Error::new(
+                impl_trait.span(),
+                "`impl Trait` arguments are not supported in multiversioned functions, use a \
+                 generic type parameter instead",
+            ));
+        }
+        if let ReturnType::Type(_, ty) = &sig.output {
+            finder.visit_type(ty);
+        }
+        if let Some(impl_trait) = finder.found {
+            return Err(Error::new(
+                impl_trait.span(),
+                "multiversioned functions can't return `impl Trait`",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn arg_exprs(sig: &Signature) -> Result<Vec<Expr>> {
     sig.inputs
         .iter()
         .map(|x| match x {
             FnArg::Receiver(rec) => {
                 let self_token = rec.self_token;
-                parse_quote! { #self_token }
+                Ok(parse_quote! { #self_token })
             }
             FnArg::Typed(arg) => {
                 if let Pat::Ident(ident) = &*arg.pat {
                     let ident = &ident.ident;
-                    parse_quote! { #ident }
+                    Ok(parse_quote! { #ident })
                 } else {
-                    panic!("pattern should have been ident")
+                    Err(Error::new(
+                        arg.pat.span(),
+                        "expected an identifier, the signature should have been normalized",
+                    ))
                 }
             }
         })
         .collect()
 }
 
-pub(crate) fn normalize_signature(sig: &Signature) -> (Signature, Vec<Expr>) {
+pub(crate) fn normalize_signature(sig: &Signature) -> Result<(Signature, Vec<Expr>)> {
     let args = sig
         .inputs
         .iter()
@@ -49,8 +105,8 @@ pub(crate) fn normalize_signature(sig: &Signature) -> (Signature, Vec<Expr>) {
         inputs: parse_quote! { #(#args),* },
         ..sig.clone()
     };
-    let callable_args = arg_exprs(&sig);
-    (sig, callable_args)
+    let callable_args = arg_exprs(&sig)?;
+    Ok((sig, callable_args))
 }
 
 struct LifetimeRenamer;
@@ -66,24 +122,23 @@ pub(crate) fn fn_type_from_signature(sig: &Signature) -> Result<TypeBareFn> {
     let args = sig
         .inputs
         .iter()
-        .map(|x| {
-            Ok(BareFnArg {
-                attrs: Vec::new(),
-                name: None,
-                ty: match x {
-                    FnArg::Receiver(rec) => Err(Error::new(
-                        rec.self_token.span,
-                        "cannot determine type of associated fn",
-                    )),
-                    FnArg::Typed(arg) => Ok(arg.ty.as_ref().clone()),
-                }?,
-            })
+        .map(|x| BareFnArg {
+            attrs: Vec::new(),
+            name: None,
+            ty: match x {
+                // `self`, `&self`, `&mut self` and `self: Box<Self>` all carry their full type, i.e.
+                // `Self`, `&Self`, `&mut Self` and `Box<Self>`.
+                FnArg::Receiver(rec) => rec.ty.as_ref().clone(),
+                FnArg::Typed(arg) => arg.ty.as_ref().clone(),
+            },
         })
-        .collect::<Result<Vec<_>>>()?;
-    assert!(
-        sig.variadic.is_none(),
-        "cannot multiversion function with variadic arguments"
-    );
+        .collect::<Vec<_>>();
+    if let Some(variadic) = &sig.variadic {
+        return Err(Error::new(
+            variadic.span(),
+            "cannot multiversion a function with variadic arguments",
+        ));
+    }
     let mut fn_ty = TypeBareFn {
         lifetimes: if lifetimes.is_empty() {
             None
@@ -2,10 +2,11 @@ use std::simd::{num::SimdFloat, LaneCount, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::NoisePipeline;
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn abs<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn abs<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -13,3 +14,14 @@ where
     pipeline.results.push(noise.abs());
     pipeline.next();
 }
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn abs_diff<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let left = pipeline.results.pop().unwrap();
+    let right = pipeline.results.pop().unwrap();
+    pipeline.results.push((left - right).abs());
+    pipeline.next();
+}
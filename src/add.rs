@@ -1,11 +1,12 @@
-use std::simd::{LaneCount, SupportedLaneCount};
+use std::simd::{LaneCount, Simd, SupportedLaneCount};
 
 use multiversion::multiversion;
 
-use crate::NoisePipeline;
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn add<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn add<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -14,3 +15,34 @@ where
     pipeline.results.push(left + right);
     pipeline.next();
 }
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sub<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The other noise was added last, its result is on top.
+    let other = pipeline.results.pop().unwrap();
+    let noise = pipeline.results.pop().unwrap();
+    pipeline.results.push(noise - other);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn average<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Average { count } = *settings else {
+        unreachable!()
+    };
+
+    let mut sum = Simd::splat(0.0);
+    for _ in 0..count {
+        sum += pipeline.results.pop().unwrap();
+    }
+    let scale = Simd::splat(1.0 / count as Float);
+    pipeline.results.push(sum * scale);
+    pipeline.next();
+}
@@ -0,0 +1,168 @@
+use crate::{Noise, NoiseResult, NoiseSettings};
+
+/// A parameter of an [AnimatedNoise]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    /// A factor on the frequencies of all the sources, 1 keeps them as they are. Zooms in and
+    /// out of the noise.
+    FrequencyScale,
+    /// A factor on the frequency of the source at this index of [Noise::settings], 1 keeps it
+    /// as it is.
+    Frequency(usize),
+    /// The value of the [Noise::constant] at this index of [Noise::settings]
+    Constant(usize),
+}
+
+/// A [Noise] with parameters that change over time. Each parameter has keyframes, values at
+/// given times, and is interpolated linearly between them. Before the first keyframe and after
+/// the last the parameter keeps the value of the keyframe.
+///
+/// [AnimatedNoise::at] is the noise at a time, the noise is patched with the values of the
+/// parameters so the rest of it stays as it was built.
+///
+/// # Example
+/// ```rust
+/// // Clouds that drift in over 10 seconds, getting denser and smaller.
+/// let noise = Noise::simplex(0.01).fbm(4, 0.5, 2.0).add(Noise::constant(-1.0));
+/// let cover = noise
+///     .settings()
+///     .iter()
+///     .position(|settings| matches!(settings, NoiseSettings::Constant { .. }))
+///     .unwrap();
+/// let clouds = AnimatedNoise::new(noise)
+///     .keyframes(Parameter::Constant(cover), &[(0.0, -1.0), (10.0, 0.2)])
+///     .keyframes(Parameter::FrequencyScale, &[(0.0, 1.0), (10.0, 1.5)]);
+///
+/// let time = 4.2;
+/// let frame = clouds.generate_2d_at(time, 0.0, 0.0, 256, 256);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimatedNoise {
+    noise: Noise,
+    // (time, value) sorted by time
+    parameters: Vec<(Parameter, Vec<(f32, f32)>)>,
+}
+
+impl AnimatedNoise {
+    /// Animates a noise, it is the noise at every time until parameters are added.
+    pub fn new(noise: Noise) -> Self {
+        Self {
+            noise,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// Sets the keyframes of a parameter, (time, value) in order of time. Replaces the earlier
+    /// keyframes of the parameter.
+    pub fn keyframes(mut self, parameter: Parameter, keyframes: &[(f32, f32)]) -> Self {
+        assert!(!keyframes.is_empty(), "There must be 1 or more keyframes");
+        assert!(
+            keyframes.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+            "The keyframes must be in order of time"
+        );
+        let settings = self.noise.settings();
+        match parameter {
+            Parameter::FrequencyScale => (),
+            Parameter::Frequency(index) => assert!(
+                matches!(
+                    settings.get(index),
+                    Some(
+                        NoiseSettings::Simplex { .. }
+                            | NoiseSettings::Perlin { .. }
+                            | NoiseSettings::Colored { .. }
+                            | NoiseSettings::Spectral { .. }
+                            | NoiseSettings::Dots { .. }
+                            | NoiseSettings::Cellular { .. }
+                            | NoiseSettings::OpenSimplex2 { .. }
+                            | NoiseSettings::Value { .. }
+                            | NoiseSettings::ValueCubic { .. }
+                    )
+                ),
+                "The node at index {index} has no frequency"
+            ),
+            Parameter::Constant(index) => assert!(
+                matches!(settings.get(index), Some(NoiseSettings::Constant { .. })),
+                "The node at index {index} isn't a constant"
+            ),
+        }
+
+        self.parameters.retain(|(other, _)| *other != parameter);
+        self.parameters.push((parameter, keyframes.to_vec()));
+        self
+    }
+
+    /// The value of a parameter at `time`, None if it has no keyframes.
+    pub fn value(&self, parameter: Parameter, time: f32) -> Option<f32> {
+        let (_, keyframes) = self
+            .parameters
+            .iter()
+            .find(|(other, _)| *other == parameter)?;
+        // The first keyframe after the time
+        let next = keyframes.partition_point(|(key, _)| *key <= time);
+        let value = match (keyframes.get(next.wrapping_sub(1)), keyframes.get(next)) {
+            (Some(&(start, from)), Some(&(end, to))) => {
+                from + (to - from) * ((time - start) / (end - start))
+            }
+            (Some(&(_, value)), None) | (None, Some(&(_, value))) => value,
+            (None, None) => unreachable!(),
+        };
+        Some(value)
+    }
+
+    /// The noise at `time`
+    pub fn at(&self, time: f32) -> Noise {
+        let mut noise = self.noise.clone();
+        if self.parameters.is_empty() {
+            return noise;
+        }
+
+        let pipeline = noise.pipeline.to_mut();
+        for (parameter, _) in &self.parameters {
+            let value = self.value(*parameter, time).unwrap();
+            match *parameter {
+                Parameter::FrequencyScale => {
+                    for settings in pipeline.iter_mut() {
+                        settings.scale_frequency(value);
+                    }
+                }
+                Parameter::Frequency(index) => pipeline[index].scale_frequency(value),
+                Parameter::Constant(index) => {
+                    pipeline[index] = NoiseSettings::Constant { value };
+                }
+            }
+        }
+        noise
+    }
+
+    /// The noise at `time` generated like [Noise::generate_1d]
+    pub fn generate_1d_at(&self, time: f32, x: impl Into<f64>, width: usize) -> NoiseResult {
+        self.at(time).generate_1d(x, width)
+    }
+
+    /// The noise at `time` generated like [Noise::generate_2d]
+    pub fn generate_2d_at(
+        &self,
+        time: f32,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> NoiseResult {
+        self.at(time).generate_2d(x, y, width, height)
+    }
+
+    /// The noise at `time` generated like [Noise::generate_3d]
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_at(
+        &self,
+        time: f32,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> NoiseResult {
+        self.at(time).generate_3d(x, y, z, width, height, depth)
+    }
+}
@@ -0,0 +1,97 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float32Array, Float64Array, RecordBatch, UInt32Array};
+
+use crate::{Float, NoiseResult};
+
+impl<T: Float> NoiseResult<T> {
+    /// Builds an Arrow record batch with one row per value. The position of the value in the
+    /// grid is in the `x` and `y` columns, and `z` for 3d results, followed by a column for
+    /// each of the `columns` with its name. The results must all have the same size.
+    ///
+    /// The positions are counted in values from the first value of the result, add the origin
+    /// the results were generated at to get the coordinates in the noise.
+    ///
+    /// Requires the `arrow` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let moisture = Noise::simplex(0.02).seed(1).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let batch = NoiseResult::to_record_batch(&[("height", &height), ("moisture", &moisture)]);
+    /// assert_eq!(batch.num_rows(), 256 * 256);
+    /// ```
+    pub fn to_record_batch(columns: &[(&str, &NoiseResult<T>)]) -> RecordBatch {
+        let (_, first) = columns.first().expect("There must be 1 or more columns");
+        let (width, height, depth) = (first.width(), first.height(), first.depth());
+        assert!(
+            columns.iter().all(|(_, result)| {
+                (result.width(), result.height(), result.depth()) == (width, height, depth)
+            }),
+            "The results must all have the same size"
+        );
+
+        // Same order as the values, x is the outermost axis and y the innermost.
+        let len = first.len() as u32;
+        let (height, depth) = (height as u32, depth as u32);
+        let mut positions: Vec<(&str, ArrayRef)> = vec![
+            (
+                "x",
+                Arc::new(UInt32Array::from_iter_values(
+                    (0..len).map(|i| i / (depth * height)),
+                )),
+            ),
+            (
+                "y",
+                Arc::new(UInt32Array::from_iter_values((0..len).map(|i| i % height))),
+            ),
+        ];
+        if depth > 1 {
+            positions.push((
+                "z",
+                Arc::new(UInt32Array::from_iter_values(
+                    (0..len).map(|i| i / height % depth),
+                )),
+            ));
+        }
+
+        let values = columns.iter().map(|(name, result)| (*name, column(result)));
+        RecordBatch::try_from_iter(positions.into_iter().chain(values))
+            .expect("The columns have the same length")
+    }
+
+    /// Writes the record batch of [NoiseResult::to_record_batch] to a Parquet file.
+    ///
+    /// Requires the `parquet` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let file = std::fs::File::create("height.parquet").unwrap();
+    /// NoiseResult::write_parquet(file, &[("height", &height)]).unwrap();
+    /// ```
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(
+        writer: impl std::io::Write + Send,
+        columns: &[(&str, &NoiseResult<T>)],
+    ) -> Result<(), parquet::errors::ParquetError> {
+        let batch = Self::to_record_batch(columns);
+        let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+// The values as a Float32 or Float64 column
+fn column<T: Float>(result: &NoiseResult<T>) -> ArrayRef {
+    let values: &dyn Any = &result.values;
+    if let Some(values) = values.downcast_ref::<Vec<f32>>() {
+        Arc::new(Float32Array::from(values.clone()))
+    } else if let Some(values) = values.downcast_ref::<Vec<f64>>() {
+        Arc::new(Float64Array::from(values.clone()))
+    } else {
+        unreachable!()
+    }
+}
@@ -0,0 +1,100 @@
+use crate::NoiseResult;
+
+/// A brick of [Bricks]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Brick {
+    /// All the values of the brick are at or below the threshold, they weren't generated.
+    Below,
+    /// All the values of the brick are above the threshold, they weren't generated.
+    Above,
+    /// The brick may cross the threshold, its values were generated. The values of a brick that
+    /// is cut off at the end of the volume only cover the part that is left.
+    Values(NoiseResult),
+}
+
+/// A cube of noise split into bricks, where only the bricks that may cross a threshold are
+/// generated, produced by [Noise::generate_3d_bricks](crate::Noise::generate_3d_bricks).
+///
+/// The bricks are positioned and ordered the same way as the blocks of
+/// [BlockRanges](crate::BlockRanges), the brick at (x, y, z) covers the values from `x * size`
+/// up to `(x + 1) * size` along the first dimension and so on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bricks {
+    size: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    threshold: f32,
+    bricks: Vec<Brick>,
+}
+
+impl Bricks {
+    pub(crate) fn new(
+        size: usize,
+        width: usize,
+        height: usize,
+        depth: usize,
+        threshold: f32,
+        bricks: Vec<Brick>,
+    ) -> Self {
+        Self {
+            size,
+            width,
+            height,
+            depth,
+            threshold,
+            bricks,
+        }
+    }
+
+    /// Size of the bricks along every dimension
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of bricks along the first dimension
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of bricks along the vertical dimension
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of bricks along the third dimension
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The brick at (x, y, z), counted in bricks.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &Brick {
+        &self.bricks[(x * self.depth + z) * self.height + y]
+    }
+
+    /// Whether the value at (x, y, z), counted in values, is above the threshold.
+    pub fn is_above(&self, x: usize, y: usize, z: usize) -> bool {
+        let size = self.size;
+        match self.get(x / size, y / size, z / size) {
+            Brick::Below => false,
+            Brick::Above => true,
+            Brick::Values(values) => {
+                let (x, y, z) = (x % size, y % size, z % size);
+                values[(x * values.depth() + z) * values.height() + y] > self.threshold
+            }
+        }
+    }
+
+    /// The number of bricks whose values were generated
+    pub fn generated(&self) -> usize {
+        self.bricks
+            .iter()
+            .filter(|brick| matches!(brick, Brick::Values(_)))
+            .count()
+    }
+
+    /// All the bricks, in the order of the bricks
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
+}
@@ -0,0 +1,688 @@
+use std::sync::Arc;
+
+use crate::{
+    Algorithm, Buffer, CellularDistance, CellularReturn, Frequency, Noise, NoiseResult,
+    NoiseSettings, SdfShape,
+};
+
+// Layout, all numbers are little endian:
+//   magic: b"FMCN"
+//   version: u8
+//   seed: u64
+//   algorithm: u8, from version 2, earlier versions are always Algorithm::V1
+//   node count: u32
+//   nodes: a u8 tag followed by the node's fields
+//   buffer count: u32, from version 3
+//   buffers: the results sampled by the nodes, the width, height and depth as u32 followed by
+//     the values. Sample nodes refer to them by their index.
+//
+// Tags are never reused. New nodes get a new tag, and if the fields of a node change the version
+// must be bumped. Earlier versions can still be decoded.
+const MAGIC: [u8; 4] = *b"FMCN";
+const VERSION: u8 = 3;
+
+const ALGORITHM_V1: u8 = 1;
+
+const SIMPLEX: u8 = 0;
+const PERLIN: u8 = 1;
+const CONSTANT: u8 = 2;
+const FBM: u8 = 3;
+const ABS: u8 = 4;
+const ADD: u8 = 5;
+const MUL: u8 = 6;
+const CLAMP: u8 = 7;
+const MAX: u8 = 8;
+const MIN: u8 = 9;
+const LERP: u8 = 10;
+const RANGE: u8 = 11;
+const SQUARE: u8 = 12;
+const FALLOFF_RADIAL: u8 = 13;
+const WRAP_DOMAIN: u8 = 14;
+const WRAP_BLEND: u8 = 15;
+const RESTORE_COORDINATES: u8 = 16;
+const LERP_VALUES: u8 = 17;
+const ABS_DIFF: u8 = 18;
+const OFFSET_DOMAIN: u8 = 19;
+const SLOPE: u8 = 20;
+const AVERAGE: u8 = 21;
+const COLORED: u8 = 22;
+const PARAMETER: u8 = 23;
+const DITHER: u8 = 24;
+const EXTRUDE_2D: u8 = 25;
+const SAMPLE: u8 = 26;
+const INTERPOLATE: u8 = 27;
+const SEED_OFFSET: u8 = 28;
+const SPECTRAL: u8 = 29;
+const SDF_SPHERE: u8 = 30;
+const SDF_BOX: u8 = 31;
+const SDF_PLANE: u8 = 32;
+const SDF_CAPSULE: u8 = 33;
+const DOTS: u8 = 34;
+const CELLULAR: u8 = 35;
+const VALUE: u8 = 36;
+const VALUE_CUBIC: u8 = 37;
+const OPENSIMPLEX2: u8 = 38;
+const WHITE: u8 = 39;
+const WARP: u8 = 40;
+const RIDGED: u8 = 41;
+const PING_PONG: u8 = 42;
+const SUB: u8 = 43;
+const DIV: u8 = 44;
+
+/// Returned by [Noise::from_bytes] when the bytes are not a valid encoding of a noise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes don't start with the expected magic number.
+    InvalidMagic,
+    /// The noise was encoded by an incompatible version of the library.
+    UnsupportedVersion(u8),
+    /// The bytes ended in the middle of the noise.
+    UnexpectedEnd,
+    /// There are bytes left over after the noise.
+    TrailingBytes,
+    /// A node has a tag that isn't known to this version of the library.
+    UnknownNode(u8),
+    /// The noise uses an [Algorithm](crate::Algorithm) that isn't known to this version of the
+    /// library.
+    UnknownAlgorithm(u8),
+    /// The nodes don't form a complete noise, e.g. an operation is missing one of its inputs.
+    InvalidPipeline,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not an encoded noise"),
+            Self::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "unsupported encoding version {version}, expected {VERSION} or earlier"
+                )
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of the encoded noise"),
+            Self::TrailingBytes => write!(f, "trailing bytes after the encoded noise"),
+            Self::UnknownNode(tag) => write!(f, "unknown node tag {tag}"),
+            Self::UnknownAlgorithm(algorithm) => write!(f, "unknown algorithm {algorithm}"),
+            Self::InvalidPipeline => write!(f, "the nodes do not form a valid noise"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub(crate) fn encode(noise: &Noise) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&noise.seed.to_le_bytes());
+    bytes.push(match noise.algorithm {
+        Algorithm::V1 => ALGORITHM_V1,
+    });
+    bytes.extend_from_slice(&(noise.pipeline.len() as u32).to_le_bytes());
+
+    for settings in noise.pipeline.iter() {
+        match settings {
+            NoiseSettings::Simplex { frequency } => {
+                bytes.push(SIMPLEX);
+                write_frequency(&mut bytes, frequency);
+            }
+            NoiseSettings::Perlin { frequency } => {
+                bytes.push(PERLIN);
+                write_frequency(&mut bytes, frequency);
+            }
+            NoiseSettings::OpenSimplex2 { frequency } => {
+                bytes.push(OPENSIMPLEX2);
+                write_frequency(&mut bytes, frequency);
+            }
+            NoiseSettings::Value { frequency } => {
+                bytes.push(VALUE);
+                write_frequency(&mut bytes, frequency);
+            }
+            NoiseSettings::ValueCubic { frequency } => {
+                bytes.push(VALUE_CUBIC);
+                write_frequency(&mut bytes, frequency);
+            }
+            NoiseSettings::Cellular {
+                frequency,
+                distance,
+                return_type,
+            } => {
+                bytes.push(CELLULAR);
+                write_frequency(&mut bytes, frequency);
+                bytes.push(match distance {
+                    CellularDistance::Euclidean => 0,
+                    CellularDistance::Manhattan => 1,
+                });
+                bytes.push(match return_type {
+                    CellularReturn::F1 => 0,
+                    CellularReturn::F2 => 1,
+                    CellularReturn::F2MinusF1 => 2,
+                    CellularReturn::CellValue => 3,
+                    CellularReturn::EdgeDistance => 4,
+                });
+            }
+            NoiseSettings::White => bytes.push(WHITE),
+            NoiseSettings::Constant { value } => {
+                bytes.push(CONSTANT);
+                write_f32(&mut bytes, *value);
+            }
+            NoiseSettings::Parameter { name, value } => {
+                bytes.push(PARAMETER);
+                bytes.extend_from_slice(&name.to_le_bytes());
+                write_f32(&mut bytes, *value);
+            }
+            NoiseSettings::Colored {
+                frequency,
+                exponent,
+            } => {
+                bytes.push(COLORED);
+                write_f32(&mut bytes, *frequency);
+                write_f32(&mut bytes, *exponent);
+            }
+            NoiseSettings::Spectral {
+                lowest_frequency,
+                highest_frequency,
+                beta,
+                waves,
+            } => {
+                bytes.push(SPECTRAL);
+                write_f32(&mut bytes, *lowest_frequency);
+                write_f32(&mut bytes, *highest_frequency);
+                write_f32(&mut bytes, *beta);
+                bytes.extend_from_slice(&waves.to_le_bytes());
+            }
+            NoiseSettings::Dots {
+                frequency,
+                density,
+                radius,
+                amplitude_jitter,
+            } => {
+                bytes.push(DOTS);
+                write_frequency(&mut bytes, frequency);
+                write_f32(&mut bytes, *density);
+                write_f32(&mut bytes, *radius);
+                write_f32(&mut bytes, *amplitude_jitter);
+            }
+            NoiseSettings::Ridged {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            } => {
+                bytes.push(RIDGED);
+                bytes.extend_from_slice(&octaves.to_le_bytes());
+                write_f32(&mut bytes, *gain);
+                write_f32(&mut bytes, *first_octave_amplitude);
+            }
+            NoiseSettings::PingPong {
+                octaves,
+                gain,
+                strength,
+                first_octave_amplitude,
+            } => {
+                bytes.push(PING_PONG);
+                bytes.extend_from_slice(&octaves.to_le_bytes());
+                write_f32(&mut bytes, *gain);
+                write_f32(&mut bytes, *strength);
+                write_f32(&mut bytes, *first_octave_amplitude);
+            }
+            NoiseSettings::Fbm {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            } => {
+                bytes.push(FBM);
+                bytes.extend_from_slice(&octaves.to_le_bytes());
+                write_f32(&mut bytes, *gain);
+                write_f32(&mut bytes, *first_octave_amplitude);
+            }
+            NoiseSettings::Abs => bytes.push(ABS),
+            NoiseSettings::AbsDiff => bytes.push(ABS_DIFF),
+            NoiseSettings::Add => bytes.push(ADD),
+            NoiseSettings::Sub => bytes.push(SUB),
+            NoiseSettings::Average { count } => {
+                bytes.push(AVERAGE);
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+            NoiseSettings::Mul => bytes.push(MUL),
+            NoiseSettings::Div => bytes.push(DIV),
+            NoiseSettings::Clamp { min, max } => {
+                bytes.push(CLAMP);
+                write_f32(&mut bytes, *min);
+                write_f32(&mut bytes, *max);
+            }
+            NoiseSettings::Max => bytes.push(MAX),
+            NoiseSettings::Min => bytes.push(MIN),
+            NoiseSettings::Lerp {
+                selector_min,
+                selector_max,
+            } => {
+                bytes.push(LERP);
+                write_f32(&mut bytes, *selector_min);
+                write_f32(&mut bytes, *selector_max);
+            }
+            NoiseSettings::LerpValues { low, high } => {
+                bytes.push(LERP_VALUES);
+                write_f32(&mut bytes, *low);
+                write_f32(&mut bytes, *high);
+            }
+            NoiseSettings::Range { low, high } => {
+                bytes.push(RANGE);
+                write_f32(&mut bytes, *low);
+                write_f32(&mut bytes, *high);
+            }
+            NoiseSettings::Square => bytes.push(SQUARE),
+            NoiseSettings::Dither { min, max, levels } => {
+                bytes.push(DITHER);
+                write_f32(&mut bytes, *min);
+                write_f32(&mut bytes, *max);
+                bytes.extend_from_slice(&levels.to_le_bytes());
+            }
+            NoiseSettings::FalloffRadial {
+                center_x,
+                center_z,
+                inner_radius,
+                outer_radius,
+            } => {
+                bytes.push(FALLOFF_RADIAL);
+                write_f32(&mut bytes, *center_x);
+                write_f32(&mut bytes, *center_z);
+                write_f32(&mut bytes, *inner_radius);
+                write_f32(&mut bytes, *outer_radius);
+            }
+            NoiseSettings::Sdf { shape } => match shape {
+                SdfShape::Sphere { center, radius } => {
+                    bytes.push(SDF_SPHERE);
+                    center.iter().for_each(|v| write_f32(&mut bytes, *v));
+                    write_f32(&mut bytes, *radius);
+                }
+                SdfShape::Box { center, half_size } => {
+                    bytes.push(SDF_BOX);
+                    center.iter().for_each(|v| write_f32(&mut bytes, *v));
+                    half_size.iter().for_each(|v| write_f32(&mut bytes, *v));
+                }
+                SdfShape::Plane { normal, offset } => {
+                    bytes.push(SDF_PLANE);
+                    normal.iter().for_each(|v| write_f32(&mut bytes, *v));
+                    write_f32(&mut bytes, *offset);
+                }
+                SdfShape::Capsule { start, end, radius } => {
+                    bytes.push(SDF_CAPSULE);
+                    start.iter().for_each(|v| write_f32(&mut bytes, *v));
+                    end.iter().for_each(|v| write_f32(&mut bytes, *v));
+                    write_f32(&mut bytes, *radius);
+                }
+            },
+            NoiseSettings::WrapDomain { period, corner } => {
+                bytes.push(WRAP_DOMAIN);
+                period.iter().for_each(|p| write_f32(&mut bytes, *p));
+                bytes.push(*corner);
+            }
+            NoiseSettings::WrapBlend { period } => {
+                bytes.push(WRAP_BLEND);
+                period.iter().for_each(|p| write_f32(&mut bytes, *p));
+            }
+            NoiseSettings::OffsetDomain { offset } => {
+                bytes.push(OFFSET_DOMAIN);
+                offset.iter().for_each(|o| write_f32(&mut bytes, *o));
+            }
+            NoiseSettings::Warp { amplitude } => {
+                bytes.push(WARP);
+                write_f32(&mut bytes, *amplitude);
+            }
+            NoiseSettings::Slope { step } => {
+                bytes.push(SLOPE);
+                write_f32(&mut bytes, *step);
+            }
+            NoiseSettings::Sample {
+                buffer,
+                origin,
+                spacing,
+            } => {
+                bytes.push(SAMPLE);
+                let index = noise
+                    .buffers
+                    .iter()
+                    .position(|other| other.id == *buffer)
+                    .unwrap();
+                bytes.extend_from_slice(&(index as u32).to_le_bytes());
+                origin.iter().for_each(|o| write_f32(&mut bytes, *o));
+                write_f32(&mut bytes, *spacing);
+            }
+            NoiseSettings::Extrude2d => bytes.push(EXTRUDE_2D),
+            NoiseSettings::Interpolate { spacing } => {
+                bytes.push(INTERPOLATE);
+                write_f32(&mut bytes, *spacing);
+            }
+            NoiseSettings::SeedOffset { offset } => {
+                bytes.push(SEED_OFFSET);
+                bytes.extend_from_slice(&offset.to_le_bytes());
+            }
+            NoiseSettings::RestoreCoordinates { rewind } => {
+                bytes.push(RESTORE_COORDINATES);
+                bytes.push(*rewind as u8);
+            }
+        }
+    }
+
+    bytes.extend_from_slice(&(noise.buffers.len() as u32).to_le_bytes());
+    for buffer in &noise.buffers {
+        let result = &buffer.result;
+        for size in [result.width(), result.height(), result.depth()] {
+            bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        }
+        result
+            .iter()
+            .for_each(|value| write_f32(&mut bytes, *value));
+    }
+
+    bytes
+}
+
+fn write_f32(bytes: &mut Vec<u8>, value: f32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_frequency(bytes: &mut Vec<u8>, frequency: &Frequency) {
+    write_f32(bytes, frequency.x);
+    write_f32(bytes, frequency.y);
+    write_f32(bytes, frequency.z);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let Some((taken, rest)) = self.bytes.split_first_chunk::<N>() else {
+            return Err(DecodeError::UnexpectedEnd);
+        };
+        self.bytes = rest;
+        Ok(*taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError> {
+        Ok(f32::from_le_bytes(self.take()?))
+    }
+
+    fn vec3(&mut self) -> Result<[f32; 3], DecodeError> {
+        Ok([self.f32()?, self.f32()?, self.f32()?])
+    }
+
+    fn frequency(&mut self) -> Result<Frequency, DecodeError> {
+        Ok(Frequency {
+            x: self.f32()?,
+            y: self.f32()?,
+            z: self.f32()?,
+        })
+    }
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Noise, DecodeError> {
+    let mut reader = Reader { bytes };
+
+    if reader.take::<4>().ok() != Some(MAGIC) {
+        return Err(DecodeError::InvalidMagic);
+    }
+    let version = reader.u8()?;
+    if version == 0 || version > VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let seed = reader.u64()?;
+    let algorithm = if version >= 2 {
+        match reader.u8()? {
+            ALGORITHM_V1 => Algorithm::V1,
+            algorithm => return Err(DecodeError::UnknownAlgorithm(algorithm)),
+        }
+    } else {
+        Algorithm::V1
+    };
+    let count = reader.u32()?;
+
+    // Don't trust the count for the allocation, every node is at least one byte.
+    let mut pipeline = Vec::with_capacity((count as usize).min(reader.bytes.len()));
+    for _ in 0..count {
+        let tag = reader.u8()?;
+        let settings = match tag {
+            SIMPLEX => NoiseSettings::Simplex {
+                frequency: reader.frequency()?,
+            },
+            PERLIN => NoiseSettings::Perlin {
+                frequency: reader.frequency()?,
+            },
+            CONSTANT => NoiseSettings::Constant {
+                value: reader.f32()?,
+            },
+            PARAMETER => NoiseSettings::Parameter {
+                name: reader.u64()?,
+                value: reader.f32()?,
+            },
+            COLORED => NoiseSettings::Colored {
+                frequency: reader.f32()?,
+                exponent: reader.f32()?,
+            },
+            SPECTRAL => NoiseSettings::Spectral {
+                lowest_frequency: reader.f32()?,
+                highest_frequency: reader.f32()?,
+                beta: reader.f32()?,
+                waves: reader.u32()?,
+            },
+            OPENSIMPLEX2 => NoiseSettings::OpenSimplex2 {
+                frequency: reader.frequency()?,
+            },
+            VALUE => NoiseSettings::Value {
+                frequency: reader.frequency()?,
+            },
+            VALUE_CUBIC => NoiseSettings::ValueCubic {
+                frequency: reader.frequency()?,
+            },
+            // A distance or return type that isn't known is from a later version of the node.
+            CELLULAR => NoiseSettings::Cellular {
+                frequency: reader.frequency()?,
+                distance: match reader.u8()? {
+                    0 => CellularDistance::Euclidean,
+                    1 => CellularDistance::Manhattan,
+                    _ => return Err(DecodeError::UnknownNode(tag)),
+                },
+                return_type: match reader.u8()? {
+                    0 => CellularReturn::F1,
+                    1 => CellularReturn::F2,
+                    2 => CellularReturn::F2MinusF1,
+                    3 => CellularReturn::CellValue,
+                    4 => CellularReturn::EdgeDistance,
+                    _ => return Err(DecodeError::UnknownNode(tag)),
+                },
+            },
+            WHITE => NoiseSettings::White,
+            DOTS => NoiseSettings::Dots {
+                frequency: reader.frequency()?,
+                density: reader.f32()?,
+                radius: reader.f32()?,
+                amplitude_jitter: reader.f32()?,
+            },
+            FBM => NoiseSettings::Fbm {
+                octaves: reader.u32()?,
+                gain: reader.f32()?,
+                first_octave_amplitude: reader.f32()?,
+            },
+            RIDGED => NoiseSettings::Ridged {
+                octaves: reader.u32()?,
+                gain: reader.f32()?,
+                first_octave_amplitude: reader.f32()?,
+            },
+            PING_PONG => NoiseSettings::PingPong {
+                octaves: reader.u32()?,
+                gain: reader.f32()?,
+                strength: reader.f32()?,
+                first_octave_amplitude: reader.f32()?,
+            },
+            ABS => NoiseSettings::Abs,
+            ABS_DIFF => NoiseSettings::AbsDiff,
+            ADD => NoiseSettings::Add,
+            SUB => NoiseSettings::Sub,
+            AVERAGE => NoiseSettings::Average {
+                count: reader.u32()?,
+            },
+            MUL => NoiseSettings::Mul,
+            DIV => NoiseSettings::Div,
+            CLAMP => NoiseSettings::Clamp {
+                min: reader.f32()?,
+                max: reader.f32()?,
+            },
+            MAX => NoiseSettings::Max,
+            MIN => NoiseSettings::Min,
+            LERP => NoiseSettings::Lerp {
+                selector_min: reader.f32()?,
+                selector_max: reader.f32()?,
+            },
+            LERP_VALUES => NoiseSettings::LerpValues {
+                low: reader.f32()?,
+                high: reader.f32()?,
+            },
+            RANGE => NoiseSettings::Range {
+                low: reader.f32()?,
+                high: reader.f32()?,
+            },
+            SQUARE => NoiseSettings::Square,
+            DITHER => NoiseSettings::Dither {
+                min: reader.f32()?,
+                max: reader.f32()?,
+                levels: reader.u32()?,
+            },
+            FALLOFF_RADIAL => NoiseSettings::FalloffRadial {
+                center_x: reader.f32()?,
+                center_z: reader.f32()?,
+                inner_radius: reader.f32()?,
+                outer_radius: reader.f32()?,
+            },
+            SDF_SPHERE => NoiseSettings::Sdf {
+                shape: SdfShape::Sphere {
+                    center: reader.vec3()?,
+                    radius: reader.f32()?,
+                },
+            },
+            SDF_BOX => NoiseSettings::Sdf {
+                shape: SdfShape::Box {
+                    center: reader.vec3()?,
+                    half_size: reader.vec3()?,
+                },
+            },
+            SDF_PLANE => NoiseSettings::Sdf {
+                shape: SdfShape::Plane {
+                    normal: reader.vec3()?,
+                    offset: reader.f32()?,
+                },
+            },
+            SDF_CAPSULE => NoiseSettings::Sdf {
+                shape: SdfShape::Capsule {
+                    start: reader.vec3()?,
+                    end: reader.vec3()?,
+                    radius: reader.f32()?,
+                },
+            },
+            WRAP_DOMAIN => NoiseSettings::WrapDomain {
+                period: reader.vec3()?,
+                corner: reader.u8()?,
+            },
+            WRAP_BLEND => NoiseSettings::WrapBlend {
+                period: reader.vec3()?,
+            },
+            OFFSET_DOMAIN => NoiseSettings::OffsetDomain {
+                offset: reader.vec3()?,
+            },
+            WARP => NoiseSettings::Warp {
+                amplitude: reader.f32()?,
+            },
+            SLOPE => NoiseSettings::Slope {
+                step: reader.f32()?,
+            },
+            // The index of the buffer until the buffers are read
+            SAMPLE => NoiseSettings::Sample {
+                buffer: reader.u32()? as u64,
+                origin: reader.vec3()?,
+                spacing: reader.f32()?,
+            },
+            EXTRUDE_2D => NoiseSettings::Extrude2d,
+            INTERPOLATE => NoiseSettings::Interpolate {
+                spacing: reader.f32()?,
+            },
+            SEED_OFFSET => NoiseSettings::SeedOffset {
+                offset: reader.u64()?,
+            },
+            RESTORE_COORDINATES => NoiseSettings::RestoreCoordinates {
+                rewind: reader.u8()? != 0,
+            },
+            _ => return Err(DecodeError::UnknownNode(tag)),
+        };
+        pipeline.push(settings);
+    }
+
+    if !NoiseSettings::validate(&pipeline) {
+        return Err(DecodeError::InvalidPipeline);
+    }
+
+    let mut buffers = Vec::new();
+    if version >= 3 {
+        let count = reader.u32()?;
+        for _ in 0..count {
+            let (width, height, depth) = (
+                reader.u32()? as usize,
+                reader.u32()? as usize,
+                reader.u32()? as usize,
+            );
+            // Don't trust the size for the allocation either, a size that overflows can't be
+            // followed by that many values.
+            let len = width
+                .checked_mul(height)
+                .and_then(|len| len.checked_mul(depth));
+            let Some(len) = len.filter(|len| {
+                len.checked_mul(4)
+                    .is_some_and(|size| size <= reader.bytes.len())
+            }) else {
+                return Err(DecodeError::UnexpectedEnd);
+            };
+            let values = (0..len)
+                .map(|_| reader.f32())
+                .collect::<Result<Vec<_>, _>>()?;
+            let (min, max) = values
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(min, max), value| {
+                    (min.min(*value), max.max(*value))
+                });
+            let result = NoiseResult::new((values, min, max), width, height, depth);
+            buffers.push(Buffer::new(Arc::new(result)));
+        }
+    }
+    for settings in pipeline.iter_mut() {
+        if let NoiseSettings::Sample { buffer, .. } = settings {
+            match buffers.get(*buffer as usize) {
+                Some(Buffer { id, result }) if !result.is_empty() => *buffer = *id,
+                _ => return Err(DecodeError::InvalidPipeline),
+            }
+        }
+    }
+
+    if !reader.bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+
+    Ok(Noise {
+        seed,
+        algorithm,
+        pipeline: pipeline.into(),
+        buffers,
+    })
+}
@@ -0,0 +1,251 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::gradient::hash3d;
+use super::simplex::{X_PRIME, Y_PRIME, Z_PRIME};
+use super::Float;
+use crate::{CellularDistance, CellularReturn, NoisePipeline, NoiseSettings};
+
+// Hash of the cell with its lowest corner at `cell`, the coordinates must be whole numbers.
+#[inline(always)]
+pub fn cell_hash<const N: usize>(seed: Simd<i32, N>, cell: [Simd<Float, N>; 3]) -> Simd<i32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let [i, j, k] = cell.map(|coordinate| coordinate.cast::<i32>());
+    hash3d(
+        seed,
+        i * Simd::splat(X_PRIME),
+        j * Simd::splat(Y_PRIME),
+        k * Simd::splat(Z_PRIME),
+    )
+}
+
+// Random value 0..1 for a component of the feature of a cell, from the hash of the cell
+#[inline(always)]
+pub fn random<const N: usize>(hash: Simd<i32, N>, component: i32) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut hash = hash ^ Simd::splat(component.wrapping_mul(0x2c1b3c6d));
+    hash *= Simd::splat(0x27d4eb2d);
+    hash ^= hash >> Simd::splat(15);
+    hash *= Simd::splat(0x297a2d39);
+    hash.cast::<Float>()
+        .mul_add(Simd::splat(0.5 / 2147483648.0), Simd::splat(0.5))
+}
+
+// How far the feature points are kept from the borders of their cells, so that the closest one
+// is always in the cells searched. The farthest the closest point can be is from a corner of a
+// cell, with the points of all the cells around the corner in their far corners. A point two
+// cells away must not be closer than that, e.g. for euclidean distances in 3d
+// `3 * (1 - margin)^2 <= (1 + margin)^2 + 2 * margin^2`.
+#[inline(always)]
+fn margin<const AXES: usize>(distance: CellularDistance) -> Float {
+    match (distance, AXES) {
+        (_, 1) => 0.0,
+        (CellularDistance::Euclidean, 2) => 1.0 / 6.0,
+        (CellularDistance::Euclidean, _) => 0.25,
+        (CellularDistance::Manhattan, 2) => 0.25,
+        (CellularDistance::Manhattan, _) => 1.0 / 3.0,
+    }
+}
+
+// The hash of the cell at `neighbour` and the offset to the coordinates from its feature point
+#[inline(always)]
+fn feature<const N: usize, const AXES: usize>(
+    seed: Simd<i32, N>,
+    margin: Float,
+    point: [Simd<Float, N>; 3],
+    neighbour: [Simd<Float, N>; 3],
+) -> (Simd<i32, N>, [Simd<Float, N>; 3])
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let hash = cell_hash(seed, neighbour);
+    let offset = std::array::from_fn(|axis| {
+        if axis < AXES {
+            let position = random(hash, 1 + axis as i32)
+                .mul_add(Simd::splat(1.0 - 2.0 * margin), Simd::splat(margin));
+            point[axis] - neighbour[axis] - position
+        } else {
+            Simd::splat(0.0)
+        }
+    });
+    (hash, offset)
+}
+
+// Each cell of the grid has a feature point at a random position within it, away from its
+// borders by the `margin`. The closest and second closest of the points in the cell of the
+// coordinates and the cells next to it along the first `AXES` axes are found.
+#[inline(always)]
+fn cellular<const N: usize, const AXES: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    point: [Simd<Float, N>; 3],
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Cellular {
+        frequency,
+        distance,
+        return_type,
+    } = *settings
+    else {
+        unreachable!()
+    };
+    // The borders are planes halfway between the points only for straight line distances
+    let distance = match return_type {
+        CellularReturn::EdgeDistance => CellularDistance::Euclidean,
+        _ => distance,
+    };
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    let margin = margin::<AXES>(distance);
+    // The second dimension of 2d noise is stored in y, it is the z axis of the frequency.
+    let frequency = match AXES {
+        2 => [frequency.x, frequency.z, 0.0],
+        _ => [frequency.x, frequency.y, frequency.z],
+    };
+    let point: [Simd<Float, N>; 3] =
+        std::array::from_fn(|axis| point[axis] * Simd::splat(frequency[axis] as Float));
+    let cell = point.map(|coordinate| coordinate.floor());
+    let reach = |axis: usize| if axis < AXES { -1..=1 } else { 0..=0 };
+
+    let mut closest = Simd::splat(Float::MAX);
+    let mut second = Simd::splat(Float::MAX);
+    let mut closest_hash = Simd::splat(0);
+    let mut closest_offset = [Simd::splat(0.0); 3];
+    let mut closest_cell = cell;
+    for dx in reach(0) {
+        for dy in reach(1) {
+            for dz in reach(2) {
+                let neighbour: [Simd<Float, N>; 3] = std::array::from_fn(|axis| {
+                    cell[axis] + Simd::splat([dx, dy, dz][axis] as Float)
+                });
+                let (hash, offset) = feature::<N, AXES>(seed, margin, point, neighbour);
+
+                let mut feature_distance = Simd::splat(0.0);
+                for offset in &offset[..AXES] {
+                    feature_distance = match distance {
+                        CellularDistance::Euclidean => offset.mul_add(*offset, feature_distance),
+                        CellularDistance::Manhattan => feature_distance + offset.abs(),
+                    };
+                }
+
+                let is_closest = feature_distance.simd_lt(closest);
+                second = is_closest.select(closest, second.simd_min(feature_distance));
+                closest = is_closest.select(feature_distance, closest);
+                closest_hash = is_closest.cast::<i32>().select(hash, closest_hash);
+                for axis in 0..AXES {
+                    closest_offset[axis] = is_closest.select(offset[axis], closest_offset[axis]);
+                    closest_cell[axis] = is_closest.select(neighbour[axis], closest_cell[axis]);
+                }
+            }
+        }
+    }
+
+    // The euclidean distances were compared squared
+    if distance == CellularDistance::Euclidean {
+        closest = closest.sqrt();
+        second = second.sqrt();
+    }
+
+    let result = match return_type {
+        CellularReturn::F1 => closest,
+        CellularReturn::F2 => second,
+        CellularReturn::F2MinusF1 => second - closest,
+        CellularReturn::CellValue => {
+            random(closest_hash, 0).mul_add(Simd::splat(2.0), Simd::splat(-1.0))
+        }
+        CellularReturn::EdgeDistance => {
+            edge_distance::<N, AXES>(seed, margin, point, closest_cell, closest_offset)
+        }
+    };
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+// The border between the closest point and another point lies on the plane halfway between them.
+// The distance to it is the projection of the coordinates onto the line between the points,
+// measured from the midpoint, and the distance to the border of the cell is the smallest over all
+// the other points. The points that share a border with the closest one can be up to two cells
+// from its cell, which is the one searched around.
+#[inline(always)]
+fn edge_distance<const N: usize, const AXES: usize>(
+    seed: Simd<i32, N>,
+    margin: Float,
+    point: [Simd<Float, N>; 3],
+    closest_cell: [Simd<Float, N>; 3],
+    closest_offset: [Simd<Float, N>; 3],
+) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let reach = |axis: usize| if axis < AXES { -2..=2 } else { 0..=0 };
+    let closest_length = dot::<N, AXES>(closest_offset, closest_offset);
+    let mut edge = Simd::splat(Float::MAX);
+    for dx in reach(0) {
+        for dy in reach(1) {
+            for dz in reach(2) {
+                let neighbour: [Simd<Float, N>; 3] = std::array::from_fn(|axis| {
+                    closest_cell[axis] + Simd::splat([dx, dy, dz][axis] as Float)
+                });
+                let (_, offset) = feature::<N, AXES>(seed, margin, point, neighbour);
+                let between: [Simd<Float, N>; 3] =
+                    std::array::from_fn(|axis| offset[axis] - closest_offset[axis]);
+                let separation = dot::<N, AXES>(between, between);
+                let bisector = (dot::<N, AXES>(offset, offset) - closest_length)
+                    / (Simd::splat(2.0) * separation.sqrt());
+                // The closest point has no border with itself
+                edge = separation
+                    .simd_gt(Simd::splat(0.0))
+                    .select(edge.simd_min(bisector), edge);
+            }
+        }
+    }
+    edge
+}
+
+#[inline(always)]
+fn dot<const N: usize, const AXES: usize>(
+    a: [Simd<Float, N>; 3],
+    b: [Simd<Float, N>; 3],
+) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut sum = Simd::splat(0.0);
+    for axis in 0..AXES {
+        sum = a[axis].mul_add(b[axis], sum);
+    }
+    sum
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn cellular_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let zero = Simd::splat(0.0);
+    cellular::<N, 1>(pipeline, [pipeline.x, zero, zero]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn cellular_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    cellular::<N, 2>(pipeline, [pipeline.x, pipeline.y, Simd::splat(0.0)]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn cellular_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    cellular::<N, 3>(pipeline, [pipeline.x, pipeline.y, pipeline.z]);
+}
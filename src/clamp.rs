@@ -2,20 +2,21 @@ use std::simd::{prelude::*, LaneCount, Simd, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn clamp<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn clamp<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
-    let NoiseSettings::Clamp { min, max } = node.settings else {
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Clamp { min, max } = *settings else {
         unreachable!()
     };
 
     let noise = pipeline.results.pop().unwrap();
-    let result = noise.simd_clamp(Simd::splat(min), Simd::splat(max));
+    let result = noise.simd_clamp(Simd::splat(min as Float), Simd::splat(max as Float));
     pipeline.results.push(result);
     pipeline.next();
 }
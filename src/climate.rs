@@ -0,0 +1,351 @@
+//! Biome selection from multiple noises.
+//!
+//! A [Climate] is a set of named channels, e.g. temperature, humidity and continentalness, each
+//! defined by a [Noise]. Biomes are described by the range of values they occupy in each channel,
+//! and every sample is assigned the biome whose ranges it falls into.
+//!
+//! # Example
+//! ```rust
+//! use fmc_noise::{climate::Climate, Noise};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum Biome {
+//!     Desert,
+//!     Forest,
+//!     Tundra,
+//! }
+//!
+//! let climate = Climate::new()
+//!     .channel("temperature", Noise::simplex(0.001).seed(1))
+//!     .channel("humidity", Noise::simplex(0.002).seed(2))
+//!     .biome(Biome::Desert, &[("temperature", 0.3..1.0), ("humidity", -1.0..0.0)])
+//!     .biome(Biome::Forest, &[("temperature", -0.3..0.5), ("humidity", 0.0..1.0)])
+//!     .biome(Biome::Tundra, &[("temperature", -1.0..-0.3)]);
+//!
+//! let samples = climate.generate_2d(0.0, 0.0, 16, 16);
+//! let biome = samples.biomes[0];
+//! let temperature = samples.channels[0][0];
+//! ```
+
+use std::ops::Range;
+
+use crate::Noise;
+
+/// A set of noise channels and the biomes they map to. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Climate<B> {
+    channels: Vec<Channel>,
+    biomes: Vec<BiomeRanges<B>>,
+}
+
+#[derive(Clone, Debug)]
+struct Channel {
+    name: String,
+    noise: Noise,
+}
+
+#[derive(Clone, Debug)]
+struct BiomeRanges<B> {
+    biome: B,
+    // (channel index, min, max)
+    ranges: Vec<(usize, f32, f32)>,
+}
+
+/// The values generated by [Climate::generate_2d] and [Climate::generate_3d]. Both are indexed
+/// the same way as the values produced by the `generate_*` functions of [Noise].
+#[derive(Clone, Debug)]
+pub struct ClimateSamples<B> {
+    /// The biome of each sample
+    pub biomes: Vec<B>,
+    /// The values of each channel, in the order the channels were added.
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl<B: Copy> Default for Climate<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Copy> Climate<B> {
+    pub fn new() -> Self {
+        Self {
+            channels: Vec::new(),
+            biomes: Vec::new(),
+        }
+    }
+
+    /// Adds a channel, the values of the channel are generated by the `noise`.
+    pub fn channel(mut self, name: impl Into<String>, noise: Noise) -> Self {
+        let name = name.into();
+        assert!(
+            self.channel_index(&name).is_none(),
+            "There is already a channel named '{name}'"
+        );
+        self.channels.push(Channel { name, noise });
+        self
+    }
+
+    /// Adds a biome that occupies the given range of each listed channel. Channels that aren't
+    /// listed can have any value. The channels must have been added before the biome.
+    ///
+    /// A sample belongs to the first biome whose ranges contain all of its channel values. If no
+    /// biome contains it, the biome whose ranges are closest is chosen, so every sample always
+    /// has a biome.
+    pub fn biome(mut self, biome: B, ranges: &[(&str, Range<f32>)]) -> Self {
+        let ranges = ranges
+            .iter()
+            .map(|(name, range)| {
+                let Some(index) = self.channel_index(name) else {
+                    panic!("There is no channel named '{name}'");
+                };
+                (index, range.start, range.end)
+            })
+            .collect();
+        self.biomes.push(BiomeRanges { biome, ranges });
+        self
+    }
+
+    /// The position of the channel in [ClimateSamples::channels].
+    pub fn channel_index(&self, name: &str) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|channel| channel.name == name)
+    }
+
+    /// Finds the biome of a sample given the value of each channel, in the order the channels
+    /// were added.
+    pub fn classify(&self, values: &[f32]) -> B {
+        assert!(!self.biomes.is_empty(), "There must be 1 or more biomes");
+        assert_eq!(
+            values.len(),
+            self.channels.len(),
+            "There must be one value per channel"
+        );
+
+        let mut closest = &self.biomes[0];
+        let mut closest_distance = f32::MAX;
+        for biome in self.biomes.iter() {
+            let mut distance = 0.0;
+            for &(index, min, max) in biome.ranges.iter() {
+                distance += distance_squared(values[index], min, max);
+            }
+
+            if distance == 0.0 {
+                return biome.biome;
+            } else if distance < closest_distance {
+                closest = biome;
+                closest_distance = distance;
+            }
+        }
+
+        closest.biome
+    }
+
+    /// Generates the channels over a plane and classifies every sample.
+    pub fn generate_2d(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> ClimateSamples<B> {
+        let (x, y) = (x.into(), y.into());
+        let channels = self
+            .channels
+            .iter()
+            .map(|channel| channel.noise.generate_2d(x, y, width, height).into_vec())
+            .collect();
+        self.classify_all(channels, width * height)
+    }
+
+    /// Generates the channels over a volume and classifies every sample.
+    pub fn generate_3d(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> ClimateSamples<B> {
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let channels = self
+            .channels
+            .iter()
+            .map(|channel| {
+                channel
+                    .noise
+                    .generate_3d(x, y, z, width, height, depth)
+                    .into_vec()
+            })
+            .collect();
+        self.classify_all(channels, width * height * depth)
+    }
+
+    fn classify_all(&self, channels: Vec<Vec<f32>>, len: usize) -> ClimateSamples<B> {
+        let mut values = vec![0.0; channels.len()];
+        let biomes = (0..len)
+            .map(|i| {
+                for (value, channel) in values.iter_mut().zip(channels.iter()) {
+                    *value = channel[i];
+                }
+                self.classify(&values)
+            })
+            .collect();
+
+        ClimateSamples { biomes, channels }
+    }
+}
+
+/// A Whittaker style table of biome ids by temperature and precipitation, and optionally
+/// elevation. Unlike [Climate] it classifies channels that have already been generated, e.g.
+/// when they are also needed for something else, and produces a compact map of ids.
+///
+/// # Example
+/// ```rust
+/// use fmc_noise::{climate::WhittakerTable, Noise};
+///
+/// const TUNDRA: u8 = 0;
+/// const DESERT: u8 = 1;
+/// const RAINFOREST: u8 = 2;
+/// const PEAKS: u8 = 3;
+///
+/// // The first biome that contains a sample is chosen, so the peaks come before the biomes
+/// // they overlap.
+/// let table = WhittakerTable::new()
+///     .biome_at_elevation(PEAKS, -1.0..1.0, -1.0..1.0, 0.7..1.0)
+///     .biome(TUNDRA, -1.0..-0.3, -1.0..1.0)
+///     .biome(DESERT, -0.3..1.0, -1.0..0.0)
+///     .biome(RAINFOREST, -0.3..1.0, 0.0..1.0);
+///
+/// let temperature = Noise::simplex(0.001).seed(1).generate_2d::<f32>(0.0, 0.0, 64, 64);
+/// let precipitation = Noise::simplex(0.002).seed(2).generate_2d::<f32>(0.0, 0.0, 64, 64);
+/// let elevation = Noise::simplex(0.005).seed(3).generate_2d::<f32>(0.0, 0.0, 64, 64);
+/// let biomes = table.classify(&temperature, &precipitation, Some(&elevation));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct WhittakerTable {
+    biomes: Vec<WhittakerBiome>,
+}
+
+#[derive(Clone, Debug)]
+struct WhittakerBiome {
+    id: u8,
+    temperature: (f32, f32),
+    precipitation: (f32, f32),
+    elevation: Option<(f32, f32)>,
+}
+
+impl WhittakerTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a biome that covers the ranges of temperature and precipitation at any elevation.
+    pub fn biome(mut self, id: u8, temperature: Range<f32>, precipitation: Range<f32>) -> Self {
+        self.biomes.push(WhittakerBiome {
+            id,
+            temperature: (temperature.start, temperature.end),
+            precipitation: (precipitation.start, precipitation.end),
+            elevation: None,
+        });
+        self
+    }
+
+    /// Adds a biome that only covers a range of elevation, e.g. snowy peaks. It is left out when
+    /// classifying without an elevation.
+    pub fn biome_at_elevation(
+        mut self,
+        id: u8,
+        temperature: Range<f32>,
+        precipitation: Range<f32>,
+        elevation: Range<f32>,
+    ) -> Self {
+        self.biomes.push(WhittakerBiome {
+            id,
+            temperature: (temperature.start, temperature.end),
+            precipitation: (precipitation.start, precipitation.end),
+            elevation: Some((elevation.start, elevation.end)),
+        });
+        self
+    }
+
+    /// Finds the id of the biome of a single sample.
+    ///
+    /// A sample belongs to the first biome whose ranges contain it. If no biome contains it, the
+    /// biome whose ranges are closest is chosen, the same as with [Climate::classify].
+    pub fn classify_sample(
+        &self,
+        temperature: f32,
+        precipitation: f32,
+        elevation: Option<f32>,
+    ) -> u8 {
+        let mut closest = None;
+        let mut closest_distance = f32::MAX;
+        for biome in self.biomes.iter() {
+            let elevation_distance = match (biome.elevation, elevation) {
+                (Some((min, max)), Some(elevation)) => distance_squared(elevation, min, max),
+                (Some(_), None) => continue,
+                (None, _) => 0.0,
+            };
+            let (min, max) = biome.temperature;
+            let mut distance = distance_squared(temperature, min, max) + elevation_distance;
+            let (min, max) = biome.precipitation;
+            distance += distance_squared(precipitation, min, max);
+
+            if distance == 0.0 {
+                return biome.id;
+            } else if distance < closest_distance {
+                closest = Some(biome.id);
+                closest_distance = distance;
+            }
+        }
+
+        closest.expect("There must be 1 or more biomes that can be classified")
+    }
+
+    /// Finds the id of the biome of every sample of the channels. They must all have the same
+    /// length, the ids are in the same order as the values.
+    pub fn classify(
+        &self,
+        temperature: &[f32],
+        precipitation: &[f32],
+        elevation: Option<&[f32]>,
+    ) -> Vec<u8> {
+        assert_eq!(
+            temperature.len(),
+            precipitation.len(),
+            "The channels must have the same length"
+        );
+        if let Some(elevation) = elevation {
+            assert_eq!(
+                temperature.len(),
+                elevation.len(),
+                "The channels must have the same length"
+            );
+        }
+
+        (0..temperature.len())
+            .map(|i| {
+                self.classify_sample(
+                    temperature[i],
+                    precipitation[i],
+                    elevation.map(|elevation| elevation[i]),
+                )
+            })
+            .collect()
+    }
+}
+
+// Squared distance from the value to the range, 0 inside it.
+fn distance_squared(value: f32, min: f32, max: f32) -> f32 {
+    let outside = if value < min {
+        min - value
+    } else if value >= max {
+        value - max
+    } else {
+        0.0
+    };
+    outside * outside
+}
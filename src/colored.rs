@@ -0,0 +1,64 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::simplex::{X_PRIME, Y_PRIME};
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// Random value -1..1 for a cell of an octave
+#[inline(always)]
+fn value<const N: usize>(seed: Simd<i32, N>, cell: Simd<i32, N>, octave: i32) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut hash = seed ^ (cell * Simd::splat(X_PRIME)) ^ Simd::splat(octave.wrapping_mul(Y_PRIME));
+    hash *= hash * hash * Simd::splat(60493);
+    hash.cast::<Float>() * Simd::splat(1.0 / 2147483648.0)
+}
+
+/// Samples colored noise along the first dimension.
+///
+/// Produces a value -1 ≤ n ≤ 1.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn colored<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::Colored {
+        frequency,
+        exponent,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let (first_octave_amplitude, ratio) = NoiseSettings::colored_amplitudes(exponent);
+
+    let seed = Simd::splat(pipeline.rng.next());
+    let x = pipeline.x * Simd::splat(frequency as Float);
+
+    // The octaves are interpolated between their cells. Without it the steps of the slow octaves
+    // would leak power into the high frequencies and flatten the slope.
+    let mut amplitude = Simd::splat(first_octave_amplitude as Float);
+    let mut result = Simd::splat(0.0);
+    let mut scale = 1.0;
+    for octave in 0..NoiseSettings::COLORED_OCTAVES as i32 {
+        let position = x * Simd::splat(scale);
+        let floor = position.floor();
+        let t = position - floor;
+        // SAFETY: See simplex.rs
+        let cell: Simd<i32, N> = unsafe { floor.to_int_unchecked() };
+        let a = value(seed, cell, octave);
+        let b = value(seed, cell + Simd::splat(1), octave);
+        result += (b - a).mul_add(t, a) * amplitude;
+        amplitude *= Simd::splat(ratio as Float);
+        scale *= 0.5;
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
@@ -0,0 +1,246 @@
+use crate::{fnv1a, simd_target_dispatch, simd_width, Noise, NoisePipeline, NoiseSettings};
+
+/// A [Noise] with [parameters](Noise::param) that can be changed in place, e.g. by the sliders
+/// of an editor. The pipeline of the noise is built the first time it is generated, and kept
+/// when the parameters change.
+///
+/// It dereferences to the noise, so it is generated like any other noise.
+///
+/// # Example
+/// ```rust
+/// let noise = Noise::simplex(0.01).mul(Noise::param("amplitude", 1.0));
+/// let mut noise = CompiledNoise::new(noise);
+/// for frame in 0..60 {
+///     noise.set_param("amplitude", 1.0 + frame as f32 / 60.0);
+///     let values = noise.generate_2d::<f32>(0.0, 0.0, 64, 64);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledNoise {
+    noise: Noise,
+    // The hashed name of each parameter and the indices of its nodes
+    params: Vec<(u64, Vec<usize>)>,
+}
+
+impl CompiledNoise {
+    /// Finds the parameters of the noise.
+    pub fn new(noise: Noise) -> Self {
+        let mut params: Vec<(u64, Vec<usize>)> = Vec::new();
+        for (index, settings) in noise.settings().iter().enumerate() {
+            let NoiseSettings::Parameter { name, .. } = *settings else {
+                continue;
+            };
+            match params.iter_mut().find(|(other, _)| *other == name) {
+                Some((_, indices)) => indices.push(index),
+                None => params.push((name, vec![index])),
+            }
+        }
+        Self { noise, params }
+    }
+
+    /// Sets the value of every parameter named `name`.
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        let name = fnv1a(name);
+        let Some((_, indices)) = self.params.iter().find(|(other, _)| *other == name) else {
+            panic!("The noise has no parameter with that name");
+        };
+        let pipeline = self.noise.pipeline.to_mut();
+        for &index in indices {
+            pipeline[index] = NoiseSettings::Parameter { name, value };
+        }
+    }
+
+    /// The value of the parameter named `name`, None if the noise doesn't have it.
+    pub fn param(&self, name: &str) -> Option<f32> {
+        let name = fnv1a(name);
+        let (_, indices) = self.params.iter().find(|(other, _)| *other == name)?;
+        match self.noise.settings()[indices[0]] {
+            NoiseSettings::Parameter { value, .. } => Some(value),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The noise with the current values of the parameters
+    pub fn noise(&self) -> &Noise {
+        &self.noise
+    }
+
+    /// The noise with the current values of the parameters
+    pub fn into_noise(self) -> Noise {
+        self.noise
+    }
+
+    /// How the noise is run on this cpu, see [ExecutionPlan].
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = CompiledNoise::new(Noise::simplex(0.01).abs().square());
+    /// let plan = noise.explain();
+    /// assert!(plan.nodes[1].fused);
+    /// println!("{plan}");
+    /// ```
+    pub fn explain(&self) -> ExecutionPlan {
+        let settings = self.noise.settings();
+        // The fused runs are the same for every lane width and float type.
+        let fused = NoisePipeline::<4, f32>::fused_runs(settings);
+
+        let mut depth = 0;
+        let mut max_depth = 0;
+        let nodes = settings
+            .iter()
+            .enumerate()
+            .map(|(index, settings)| {
+                let (inputs, outputs) = settings.stack_effect();
+                depth = depth - inputs + outputs;
+                max_depth = max_depth.max(depth);
+                PlannedNode {
+                    settings: *settings,
+                    fused: fused
+                        .iter()
+                        .any(|&(first, count, _)| (first + 1..first + count).contains(&index)),
+                    depth,
+                    cost: cost(settings),
+                }
+            })
+            .collect();
+
+        ExecutionPlan {
+            nodes,
+            max_depth,
+            target: unsafe { (simd_target_dispatch())() },
+            lane_width: simd_width(),
+        }
+    }
+}
+
+/// How a [CompiledNoise] is run, returned by [CompiledNoise::explain]. Its [Display] is a table
+/// with a row for each node.
+///
+/// [Display]: std::fmt::Display
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionPlan {
+    /// The nodes in the order they are run
+    pub nodes: Vec<PlannedNode>,
+    /// The most results held at once while the noise is run
+    pub max_depth: usize,
+    /// The target the kernels were compiled for that is used on this cpu, e.g.
+    /// "x86_64+avx2+fma".
+    pub target: String,
+    /// The number of values computed at a time, see [simd_width]. It is the global width when
+    /// the plan was made, a later [Noise::calibrate] can change it.
+    pub lane_width: usize,
+}
+
+impl ExecutionPlan {
+    /// The estimated cost of all the nodes, see [PlannedNode::cost].
+    pub fn cost(&self) -> [f32; 3] {
+        let mut total = [0.0; 3];
+        for node in &self.nodes {
+            for (total, cost) in total.iter_mut().zip(node.cost) {
+                *total += cost;
+            }
+        }
+        total
+    }
+}
+
+impl std::fmt::Display for ExecutionPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "target {}, {} lanes", self.target, self.lane_width)?;
+        writeln!(
+            f,
+            "{:>5} {:>5} {:>20}  node",
+            "index", "depth", "cost 1d/2d/3d"
+        )?;
+        for (index, node) in self.nodes.iter().enumerate() {
+            let [x, xy, xyz] = node.cost;
+            write!(
+                f,
+                "{index:>5} {:>5} {x:>6.2} {xy:>6.2} {xyz:>6.2}  {:?}",
+                node.depth, node.settings
+            )?;
+            if node.fused {
+                write!(f, " (fused with an earlier node)")?;
+            }
+            writeln!(f)?;
+        }
+        let [x, xy, xyz] = self.cost();
+        writeln!(f, "{:>11} {x:>6.2} {xy:>6.2} {xyz:>6.2}", "total")?;
+        write!(f, "max depth {}", self.max_depth)
+    }
+}
+
+/// A node of an [ExecutionPlan]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlannedNode {
+    /// The node, see [Noise::settings]
+    pub settings: NoiseSettings,
+    /// The node is done by the kernel of an earlier node, which saves calling it.
+    pub fused: bool,
+    /// The number of results held after the node is run
+    pub depth: usize,
+    /// Rough cost of the node per value when generating 1d, 2d and 3d noise, relative to a
+    /// constant node. It is an estimate for comparing the nodes of a noise, not a measurement,
+    /// the time spent also depends on the cpu and the lane width.
+    pub cost: [f32; 3],
+}
+
+// Measured on x86_64 with avx2, rounded. Operations that take several results or copies of a
+// sub-noise only count their own work, the sub-noises are nodes of their own.
+fn cost(settings: &NoiseSettings) -> [f32; 3] {
+    match settings {
+        NoiseSettings::Simplex { .. } | NoiseSettings::Perlin { .. } => [6.0, 7.0, 9.0],
+        NoiseSettings::Colored { .. } => [15.0; 3],
+        NoiseSettings::Spectral { waves, .. } => {
+            let waves = *waves as f32;
+            [0.2 * waves, 0.25 * waves, 0.35 * waves]
+        }
+        // One hashed cell for each neighbour
+        NoiseSettings::Dots { .. } => [3.0, 9.0, 27.0],
+        NoiseSettings::Cellular { .. } => [3.0, 9.0, 27.0],
+        NoiseSettings::White => [1.0; 3],
+        NoiseSettings::OpenSimplex2 { .. } => [6.0, 8.0, 12.0],
+        NoiseSettings::Value { .. } => [2.0, 4.0, 8.0],
+        NoiseSettings::ValueCubic { .. } => [4.0, 16.0, 64.0],
+        NoiseSettings::Constant { .. } | NoiseSettings::Parameter { .. } => [1.0; 3],
+        NoiseSettings::Fbm { octaves, .. } => [0.25 * *octaves as f32; 3],
+        NoiseSettings::Ridged { octaves, .. } | NoiseSettings::PingPong { octaves, .. } => {
+            [0.5 * *octaves as f32; 3]
+        }
+        NoiseSettings::Average { count } => [0.25 * *count as f32; 3],
+        NoiseSettings::WrapBlend { period } => {
+            [0.5 * NoiseSettings::wrap_corners(*period) as f32; 3]
+        }
+        NoiseSettings::Abs
+        | NoiseSettings::AbsDiff
+        | NoiseSettings::Add
+        | NoiseSettings::Sub
+        | NoiseSettings::Mul
+        | NoiseSettings::Div
+        | NoiseSettings::Max
+        | NoiseSettings::Min
+        | NoiseSettings::Square
+        | NoiseSettings::Clamp { .. }
+        | NoiseSettings::LerpValues { .. }
+        | NoiseSettings::OffsetDomain { .. }
+        | NoiseSettings::Warp { .. }
+        | NoiseSettings::Extrude2d
+        | NoiseSettings::SeedOffset { .. }
+        | NoiseSettings::RestoreCoordinates { .. } => [0.25; 3],
+        // The interpolation, the sub-noise is mostly skipped.
+        NoiseSettings::Interpolate { .. } => [1.0, 2.0, 3.0],
+        NoiseSettings::Lerp { .. } | NoiseSettings::Range { .. } => [0.5; 3],
+        NoiseSettings::WrapDomain { .. } | NoiseSettings::Slope { .. } => [1.0; 3],
+        NoiseSettings::Dither { .. } | NoiseSettings::FalloffRadial { .. } => [2.0; 3],
+        NoiseSettings::Sdf { .. } => [2.0; 3],
+        NoiseSettings::Sample { .. } => [1.0, 2.0, 4.0],
+    }
+}
+
+impl std::ops::Deref for CompiledNoise {
+    type Target = Noise;
+
+    fn deref(&self) -> &Self::Target {
+        &self.noise
+    }
+}
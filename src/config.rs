@@ -0,0 +1,962 @@
+//! The config format, a versioned description of a noise for files written by hand, e.g. the
+//! world generation of a data pack.
+//!
+//! A config is a tree of the builder methods of [Noise], each node names the method and holds
+//! its arguments, with the noise it is applied to in `noise`. It is independent of
+//! [NoiseSettings](crate::NoiseSettings), so configs keep loading when the internals change.
+//! Configs can be written in JSON or RON, the names are the same in both.
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "seed": "my world",
+//!     "noise": {
+//!         "lerp_values": {
+//!             "noise": {
+//!                 "fbm": {
+//!                     "noise": { "simplex": { "frequency": 0.01 } },
+//!                     "octaves": 4,
+//!                     "gain": 0.5,
+//!                     "lacunarity": 2.0
+//!                 }
+//!             },
+//!             "low": 40.0,
+//!             "high": 120.0
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! ```ron
+//! (
+//!     version: 1,
+//!     seed: "my world",
+//!     noise: lerp_values(
+//!         noise: fbm(noise: simplex(frequency: 0.01), octaves: 4, gain: 0.5, lacunarity: 2.0),
+//!         low: 40.0,
+//!         high: 120.0,
+//!     ),
+//! )
+//! ```
+//!
+//! Noises can also be written as expressions, the builder methods of [Noise] written like in
+//! Rust and combined with `+`, `-`, `*` and `/`. A number is a [constant](Noise::constant)
+//! where a noise is expected. Arguments are
+//! positional, in the order of the arguments of the method. Frequencies and lacunarities are a
+//! number or `[x, y, z]`, options like the distance of cellular noise are written as their name
+//! in the config, and the shapes of [Noise::sdf] like the variants of [SdfShape], e.g.
+//! `sdf(sphere([0, 0, 0], 16))`. An expression is read into a config without a seed, see
+//! [NoiseConfig::from_expression], and can be written out as JSON or RON from there.
+//!
+//! ```text
+//! simplex(0.01).fbm(4, 0.5, 2.0).lerp_values(40, 120) + param("height", 0) * 10
+//! ```
+//!
+//! Every config starts with the version of the format it was written for. The format only
+//! changes by adding a new version, configs of earlier versions are migrated to the current one
+//! when they are loaded.
+//!
+//! Requires the `config` feature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Algorithm, CellularDistance, CellularReturn, Frequency, Lacunarity, Noise, OctaveOp,
+    OctaveParams, SdfShape,
+};
+
+/// The version of the config format written by this version of the library
+pub const CONFIG_VERSION: u32 = 1;
+
+/// A noise in the config format, see the [module](self) docs.
+///
+/// # Example
+/// ```
+/// let config = NoiseConfig::from_json(r#"{
+///     "version": 1,
+///     "noise": { "abs": { "noise": { "simplex": { "frequency": 0.01 } } } }
+/// }"#).unwrap();
+/// let noise = config.build().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoiseConfig {
+    /// The version of the config format, see [CONFIG_VERSION]
+    pub version: u32,
+    /// See [Noise::seed] and [Noise::seed_str], 0 if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SeedConfig>,
+    /// The number of the [Algorithm], 1 for [Algorithm::V1]. The first if left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<u32>,
+    /// The noise
+    pub noise: NodeConfig,
+}
+
+/// The seed of a [NoiseConfig]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SeedConfig {
+    /// See [Noise::seed]
+    Number(u32),
+    /// See [Noise::seed_str]
+    Name(String),
+}
+
+/// The frequency of a [NodeConfig], one for all axes or one for each axis.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FrequencyConfig {
+    /// The same frequency along all axes
+    Uniform(f32),
+    /// The frequency along x, y and z
+    Axes([f32; 3]),
+}
+
+/// The lacunarity of a [NodeConfig::Fbm], [NodeConfig::Ridged] or [NodeConfig::PingPong], one for
+/// all axes or one for each axis, see [Lacunarity].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LacunarityConfig {
+    /// The same lacunarity along all axes
+    Uniform(f32),
+    /// The lacunarity along x, y and z
+    Axes([f32; 3]),
+}
+
+/// An octave of a [NodeConfig::FbmCustom], see [OctaveParams]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OctaveConfig {
+    pub frequency_mul: f32,
+    pub amplitude: f32,
+    /// 0 if left out
+    #[serde(default)]
+    pub seed_offset: u32,
+}
+
+/// The shape of a [NodeConfig::Sdf], see [SdfShape]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SdfShapeConfig {
+    /// See [SdfShape::Sphere]
+    Sphere { center: [f32; 3], radius: f32 },
+    /// See [SdfShape::Box]
+    Box {
+        center: [f32; 3],
+        half_size: [f32; 3],
+    },
+    /// See [SdfShape::Plane]
+    Plane { normal: [f32; 3], offset: f32 },
+    /// See [SdfShape::Capsule]
+    Capsule {
+        start: [f32; 3],
+        end: [f32; 3],
+        radius: f32,
+    },
+}
+
+/// See [CellularDistance]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellularDistanceConfig {
+    /// See [CellularDistance::Euclidean]
+    #[default]
+    Euclidean,
+    /// See [CellularDistance::Manhattan]
+    Manhattan,
+}
+
+/// See [CellularReturn]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellularReturnConfig {
+    /// See [CellularReturn::F1]
+    #[default]
+    F1,
+    /// See [CellularReturn::F2]
+    F2,
+    /// See [CellularReturn::F2MinusF1]
+    F2MinusF1,
+    /// See [CellularReturn::CellValue]
+    CellValue,
+    /// See [CellularReturn::EdgeDistance]
+    EdgeDistance,
+}
+
+/// See [OctaveOp]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OctaveOpConfig {
+    /// See [OctaveOp::Abs]
+    Abs,
+    /// See [OctaveOp::Square]
+    Square,
+    /// See [OctaveOp::Ridge]
+    Ridge,
+}
+
+/// A node of a [NoiseConfig], one for each builder method of [Noise]. Sub-noises are in
+/// `noise`, and the other noises an operation takes are in fields named like the arguments of
+/// the method.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum NodeConfig {
+    /// See [Noise::simplex]
+    Simplex { frequency: FrequencyConfig },
+    /// See [Noise::perlin]
+    Perlin { frequency: FrequencyConfig },
+    /// See [Noise::opensimplex2]
+    #[serde(rename = "opensimplex2")]
+    OpenSimplex2 { frequency: FrequencyConfig },
+    /// See [Noise::value]
+    Value { frequency: FrequencyConfig },
+    /// See [Noise::value_cubic]
+    ValueCubic { frequency: FrequencyConfig },
+    /// See [Noise::cellular] and [Noise::cellular_with]
+    Cellular {
+        frequency: FrequencyConfig,
+        /// Euclidean if left out
+        #[serde(default)]
+        distance: CellularDistanceConfig,
+        /// F1 if left out
+        #[serde(default)]
+        return_type: CellularReturnConfig,
+    },
+    /// See [Noise::white]
+    White {},
+    /// See [Noise::pink]
+    Pink { frequency: f32 },
+    /// See [Noise::brown]
+    Brown { frequency: f32 },
+    /// See [Noise::spectral]
+    Spectral {
+        lowest_frequency: f32,
+        highest_frequency: f32,
+        beta: f32,
+        waves: u32,
+    },
+    /// See [Noise::dots]
+    Dots {
+        frequency: FrequencyConfig,
+        density: f32,
+        radius: f32,
+        amplitude_jitter: f32,
+    },
+    /// See [Noise::constant]
+    Constant { value: f32 },
+    /// See [Noise::param]
+    Param { name: String, value: f32 },
+    /// See [Noise::falloff_radial]
+    FalloffRadial {
+        center_x: f32,
+        center_z: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// See [Noise::sdf]
+    Sdf { shape: SdfShapeConfig },
+    /// See [Noise::veins]
+    Veins {
+        frequency: f32,
+        thickness: f32,
+        elongation: f32,
+    },
+    /// See [Noise::fbm] and [Noise::fbm_with]
+    Fbm {
+        noise: Box<NodeConfig>,
+        octaves: u32,
+        gain: f32,
+        lacunarity: LacunarityConfig,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        op: Option<OctaveOpConfig>,
+    },
+    /// See [Noise::ridged]
+    Ridged {
+        noise: Box<NodeConfig>,
+        octaves: u32,
+        gain: f32,
+        lacunarity: LacunarityConfig,
+    },
+    /// See [Noise::ping_pong]
+    PingPong {
+        noise: Box<NodeConfig>,
+        octaves: u32,
+        gain: f32,
+        lacunarity: LacunarityConfig,
+        strength: f32,
+    },
+    /// See [Noise::fbm_custom]
+    FbmCustom {
+        noise: Box<NodeConfig>,
+        octaves: Vec<OctaveConfig>,
+    },
+    /// See [Noise::wrap_domain], a period of 0 leaves the axis unwrapped.
+    WrapDomain {
+        noise: Box<NodeConfig>,
+        period: [f32; 3],
+    },
+    /// See [Noise::warp]
+    Warp {
+        noise: Box<NodeConfig>,
+        offset: Box<NodeConfig>,
+        amplitude: f32,
+    },
+    /// See [Noise::slope]
+    Slope { noise: Box<NodeConfig> },
+    /// See [Noise::extrude_2d]
+    #[serde(rename = "extrude_2d")]
+    Extrude2d { noise: Box<NodeConfig> },
+    /// See [Noise::interpolated]
+    Interpolated {
+        noise: Box<NodeConfig>,
+        spacing: f32,
+    },
+    /// See [Noise::abs]
+    Abs { noise: Box<NodeConfig> },
+    /// See [Noise::square]
+    Square { noise: Box<NodeConfig> },
+    /// See [Noise::dither]
+    Dither {
+        noise: Box<NodeConfig>,
+        min: f32,
+        max: f32,
+        levels: u32,
+    },
+    /// See [Noise::add]
+    Add {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::sub]
+    Sub {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::abs_diff]
+    AbsDiff {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::avg_n]
+    Avg { noises: Vec<NodeConfig> },
+    /// See [Noise::mul]
+    Mul {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::div]
+    Div {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::max]
+    Max {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::min]
+    Min {
+        noise: Box<NodeConfig>,
+        other: Box<NodeConfig>,
+    },
+    /// See [Noise::clamp]
+    Clamp {
+        noise: Box<NodeConfig>,
+        min: f32,
+        max: f32,
+    },
+    /// See [Noise::lerp] and [Noise::lerp_with_range], the range of the selector is -1..1 if
+    /// left out.
+    Lerp {
+        noise: Box<NodeConfig>,
+        low: Box<NodeConfig>,
+        high: Box<NodeConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector_range: Option<[f32; 2]>,
+    },
+    /// See [Noise::lerp_values]
+    LerpValues {
+        noise: Box<NodeConfig>,
+        low: f32,
+        high: f32,
+    },
+    /// See [Noise::range]
+    Range {
+        noise: Box<NodeConfig>,
+        low: f32,
+        high: f32,
+        low_noise: Box<NodeConfig>,
+        high_noise: Box<NodeConfig>,
+    },
+}
+
+/// Returned when a [NoiseConfig] can't be loaded or built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The text isn't valid JSON, RON or expression syntax, or doesn't follow the format. The
+    /// message has the position of the error in the text.
+    Syntax(String),
+    /// The config was written for a later version of the format than this version of the
+    /// library can read.
+    UnsupportedVersion(u32),
+    /// A parameter is outside of the values it can take.
+    InvalidParameter {
+        /// Where the parameter is in the config, e.g. `noise.fbm.octaves`
+        path: String,
+        /// What is wrong with it
+        message: String,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "{message}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported config version {version}, expected {CONFIG_VERSION} or earlier"
+            ),
+            Self::InvalidParameter { path, message } => write!(f, "{path}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Only the version, to find out how to read the rest
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl NoiseConfig {
+    /// Reads a config from JSON, migrating it to the current version of the format.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let syntax = |err: serde_json::Error| ConfigError::Syntax(err.to_string());
+        let Version { version } = serde_json::from_str(json).map_err(syntax)?;
+        Self::check_version(version)?;
+        serde_json::from_str(json).map_err(syntax)
+    }
+
+    /// Reads a config from RON, migrating it to the current version of the format.
+    pub fn from_ron(ron: &str) -> Result<Self, ConfigError> {
+        let syntax = |err: ron::error::SpannedError| ConfigError::Syntax(err.to_string());
+        let Version { version } = ron_options().from_str(ron).map_err(syntax)?;
+        Self::check_version(version)?;
+        ron_options().from_str(ron).map_err(syntax)
+    }
+
+    // When the format changes, the previous version is kept to read earlier configs and
+    // converted to the current one after it is parsed.
+    fn check_version(version: u32) -> Result<(), ConfigError> {
+        match version {
+            1 => Ok(()),
+            _ => Err(ConfigError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Writes the config as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Configs can always be written as JSON")
+    }
+
+    /// Writes the config as RON
+    pub fn to_ron(&self) -> String {
+        ron_options()
+            .to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("Configs can always be written as RON")
+    }
+
+    /// Builds the noise, after checking that all the parameters are valid.
+    pub fn build(&self) -> Result<Noise, ConfigError> {
+        if self.version != CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(self.version));
+        }
+
+        let mut noise = self.noise.build("noise")?;
+        noise = match &self.seed {
+            Some(SeedConfig::Number(seed)) => noise.seed(*seed),
+            Some(SeedConfig::Name(name)) => noise.seed_str(name),
+            None => noise,
+        };
+        let algorithm = match self.algorithm {
+            None | Some(1) => Algorithm::V1,
+            Some(algorithm) => {
+                return Err(ConfigError::InvalidParameter {
+                    path: "algorithm".to_owned(),
+                    message: format!("unknown algorithm {algorithm}"),
+                })
+            }
+        };
+        Ok(noise.algorithm(algorithm))
+    }
+}
+
+// Optional fields are written without Some(..)
+fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+impl NodeConfig {
+    fn build(&self, path: &str) -> Result<Noise, ConfigError> {
+        let invalid = |name: &str, message: &str| ConfigError::InvalidParameter {
+            path: format!("{path}.{}.{name}", self.name()),
+            message: message.to_owned(),
+        };
+        let finite = |name: &str, values: &[f32]| {
+            if values.iter().all(|value| value.is_finite()) {
+                Ok(())
+            } else {
+                Err(invalid(name, "must be a finite number"))
+            }
+        };
+        let child =
+            |name: &str, node: &NodeConfig| node.build(&format!("{path}.{}.{name}", self.name()));
+
+        let noise = match self {
+            Self::Simplex { frequency }
+            | Self::Perlin { frequency }
+            | Self::OpenSimplex2 { frequency }
+            | Self::Value { frequency }
+            | Self::ValueCubic { frequency } => {
+                let frequency = match *frequency {
+                    FrequencyConfig::Uniform(frequency) => Frequency::splat(frequency),
+                    FrequencyConfig::Axes([x, y, z]) => Frequency { x, y, z },
+                };
+                finite("frequency", &[frequency.x, frequency.y, frequency.z])?;
+                match self {
+                    Self::Simplex { .. } => Noise::simplex(frequency),
+                    Self::Perlin { .. } => Noise::perlin(frequency),
+                    Self::OpenSimplex2 { .. } => Noise::opensimplex2(frequency),
+                    Self::Value { .. } => Noise::value(frequency),
+                    _ => Noise::value_cubic(frequency),
+                }
+            }
+            Self::Cellular {
+                frequency,
+                distance,
+                return_type,
+            } => {
+                let frequency = match *frequency {
+                    FrequencyConfig::Uniform(frequency) => Frequency::splat(frequency),
+                    FrequencyConfig::Axes([x, y, z]) => Frequency { x, y, z },
+                };
+                finite("frequency", &[frequency.x, frequency.y, frequency.z])?;
+                let distance = match distance {
+                    CellularDistanceConfig::Euclidean => CellularDistance::Euclidean,
+                    CellularDistanceConfig::Manhattan => CellularDistance::Manhattan,
+                };
+                let return_type = match return_type {
+                    CellularReturnConfig::F1 => CellularReturn::F1,
+                    CellularReturnConfig::F2 => CellularReturn::F2,
+                    CellularReturnConfig::F2MinusF1 => CellularReturn::F2MinusF1,
+                    CellularReturnConfig::CellValue => CellularReturn::CellValue,
+                    CellularReturnConfig::EdgeDistance => CellularReturn::EdgeDistance,
+                };
+                Noise::cellular_with(frequency, distance, return_type)
+            }
+            Self::White {} => Noise::white(),
+            Self::Pink { frequency } | Self::Brown { frequency } => {
+                finite("frequency", &[*frequency])?;
+                if *frequency <= 0.0 {
+                    return Err(invalid("frequency", "must be larger than 0"));
+                }
+                if matches!(self, Self::Pink { .. }) {
+                    Noise::pink(*frequency)
+                } else {
+                    Noise::brown(*frequency)
+                }
+            }
+            Self::Spectral {
+                lowest_frequency,
+                highest_frequency,
+                beta,
+                waves,
+            } => {
+                finite("lowest_frequency", &[*lowest_frequency])?;
+                finite("highest_frequency", &[*highest_frequency])?;
+                finite("beta", &[*beta])?;
+                if *lowest_frequency <= 0.0 {
+                    return Err(invalid("lowest_frequency", "must be larger than 0"));
+                }
+                if *highest_frequency < *lowest_frequency {
+                    return Err(invalid(
+                        "highest_frequency",
+                        "can't be below the lowest frequency",
+                    ));
+                }
+                if *waves == 0 {
+                    return Err(invalid("waves", "there must be 1 or more waves"));
+                }
+                Noise::spectral(*lowest_frequency, *highest_frequency, *beta, *waves)
+            }
+            Self::Dots {
+                frequency,
+                density,
+                radius,
+                amplitude_jitter,
+            } => {
+                let frequency = match *frequency {
+                    FrequencyConfig::Uniform(frequency) => Frequency::splat(frequency),
+                    FrequencyConfig::Axes([x, y, z]) => Frequency { x, y, z },
+                };
+                finite("frequency", &[frequency.x, frequency.y, frequency.z])?;
+                finite("density", &[*density])?;
+                finite("radius", &[*radius])?;
+                finite("amplitude_jitter", &[*amplitude_jitter])?;
+                if !(0.0..=1.0).contains(density) {
+                    return Err(invalid("density", "must be within 0..=1"));
+                }
+                if *radius <= 0.0 || *radius > 1.0 {
+                    return Err(invalid("radius", "must be larger than 0 and at most 1"));
+                }
+                if !(0.0..=1.0).contains(amplitude_jitter) {
+                    return Err(invalid("amplitude_jitter", "must be within 0..=1"));
+                }
+                Noise::dots(frequency, *density, *radius, *amplitude_jitter)
+            }
+            Self::Constant { value } => {
+                finite("value", &[*value])?;
+                Noise::constant(*value)
+            }
+            Self::Param { name, value } => {
+                finite("value", &[*value])?;
+                Noise::param(name, *value)
+            }
+            Self::FalloffRadial {
+                center_x,
+                center_z,
+                inner_radius,
+                outer_radius,
+            } => {
+                finite("center_x", &[*center_x])?;
+                finite("center_z", &[*center_z])?;
+                finite("inner_radius", &[*inner_radius])?;
+                finite("outer_radius", &[*outer_radius])?;
+                if outer_radius <= inner_radius {
+                    return Err(invalid(
+                        "outer_radius",
+                        "must be larger than the inner radius",
+                    ));
+                }
+                Noise::falloff_radial(*center_x, *center_z, *inner_radius, *outer_radius)
+            }
+            Self::Sdf { shape } => {
+                let shape = match *shape {
+                    SdfShapeConfig::Sphere { center, radius } => {
+                        finite("shape.center", &center)?;
+                        finite("shape.radius", &[radius])?;
+                        if radius < 0.0 {
+                            return Err(invalid("shape.radius", "can't be negative"));
+                        }
+                        SdfShape::Sphere { center, radius }
+                    }
+                    SdfShapeConfig::Box { center, half_size } => {
+                        finite("shape.center", &center)?;
+                        finite("shape.half_size", &half_size)?;
+                        if half_size.iter().any(|size| *size < 0.0) {
+                            return Err(invalid("shape.half_size", "can't be negative"));
+                        }
+                        SdfShape::Box { center, half_size }
+                    }
+                    SdfShapeConfig::Plane { normal, offset } => {
+                        finite("shape.normal", &normal)?;
+                        finite("shape.offset", &[offset])?;
+                        if normal == [0.0; 3] {
+                            return Err(invalid("shape.normal", "can't be 0"));
+                        }
+                        SdfShape::Plane { normal, offset }
+                    }
+                    SdfShapeConfig::Capsule { start, end, radius } => {
+                        finite("shape.start", &start)?;
+                        finite("shape.end", &end)?;
+                        finite("shape.radius", &[radius])?;
+                        if radius < 0.0 {
+                            return Err(invalid("shape.radius", "can't be negative"));
+                        }
+                        SdfShape::Capsule { start, end, radius }
+                    }
+                };
+                Noise::sdf(shape)
+            }
+            Self::Veins {
+                frequency,
+                thickness,
+                elongation,
+            } => {
+                finite("frequency", &[*frequency])?;
+                finite("thickness", &[*thickness])?;
+                finite("elongation", &[*elongation])?;
+                if *thickness <= 0.0 {
+                    return Err(invalid("thickness", "must be larger than 0"));
+                }
+                if *elongation < 1.0 {
+                    return Err(invalid("elongation", "must be 1 or more"));
+                }
+                Noise::veins(*frequency, *thickness, *elongation)
+            }
+            Self::Fbm {
+                noise,
+                octaves,
+                gain,
+                lacunarity,
+                op,
+            } => {
+                let noise = child("noise", noise)?;
+                if *octaves == 0 {
+                    return Err(invalid("octaves", "there must be 1 or more octaves"));
+                }
+                finite("gain", &[*gain])?;
+                let lacunarity = match *lacunarity {
+                    LacunarityConfig::Uniform(lacunarity) => Lacunarity::splat(lacunarity),
+                    LacunarityConfig::Axes([x, y, z]) => Lacunarity { x, y, z },
+                };
+                finite("lacunarity", &[lacunarity.x, lacunarity.y, lacunarity.z])?;
+                match op {
+                    Some(op) => {
+                        let op = match op {
+                            OctaveOpConfig::Abs => OctaveOp::Abs,
+                            OctaveOpConfig::Square => OctaveOp::Square,
+                            OctaveOpConfig::Ridge => OctaveOp::Ridge,
+                        };
+                        noise.fbm_with(*octaves, *gain, lacunarity, op)
+                    }
+                    None => noise.fbm(*octaves, *gain, lacunarity),
+                }
+            }
+            Self::Ridged {
+                noise,
+                octaves,
+                gain,
+                lacunarity,
+            } => {
+                let noise = child("noise", noise)?;
+                if *octaves == 0 {
+                    return Err(invalid("octaves", "there must be 1 or more octaves"));
+                }
+                finite("gain", &[*gain])?;
+                let lacunarity = match *lacunarity {
+                    LacunarityConfig::Uniform(lacunarity) => Lacunarity::splat(lacunarity),
+                    LacunarityConfig::Axes([x, y, z]) => Lacunarity { x, y, z },
+                };
+                finite("lacunarity", &[lacunarity.x, lacunarity.y, lacunarity.z])?;
+                noise.ridged(*octaves, *gain, lacunarity)
+            }
+            Self::PingPong {
+                noise,
+                octaves,
+                gain,
+                lacunarity,
+                strength,
+            } => {
+                let noise = child("noise", noise)?;
+                if *octaves == 0 {
+                    return Err(invalid("octaves", "there must be 1 or more octaves"));
+                }
+                finite("gain", &[*gain])?;
+                let lacunarity = match *lacunarity {
+                    LacunarityConfig::Uniform(lacunarity) => Lacunarity::splat(lacunarity),
+                    LacunarityConfig::Axes([x, y, z]) => Lacunarity { x, y, z },
+                };
+                finite("lacunarity", &[lacunarity.x, lacunarity.y, lacunarity.z])?;
+                finite("strength", &[*strength])?;
+                noise.ping_pong(*octaves, *gain, lacunarity, *strength)
+            }
+            Self::FbmCustom { noise, octaves } => {
+                let noise = child("noise", noise)?;
+                if octaves.is_empty() {
+                    return Err(invalid("octaves", "there must be 1 or more octaves"));
+                }
+                let octaves: Vec<OctaveParams> = octaves
+                    .iter()
+                    .map(|octave| OctaveParams {
+                        frequency_mul: octave.frequency_mul,
+                        amplitude: octave.amplitude,
+                        seed_offset: octave.seed_offset,
+                    })
+                    .collect();
+                for octave in &octaves {
+                    finite("octaves", &[octave.frequency_mul, octave.amplitude])?;
+                }
+                noise.fbm_custom(&octaves)
+            }
+            Self::WrapDomain { noise, period } => {
+                let noise = child("noise", noise)?;
+                finite("period", period)?;
+                if period.iter().any(|period| *period < 0.0) {
+                    return Err(invalid("period", "can't be negative"));
+                }
+                noise.wrap_domain(period[0], period[1], period[2])
+            }
+            Self::Warp {
+                noise,
+                offset,
+                amplitude,
+            } => {
+                let noise = child("noise", noise)?;
+                let offset = child("offset", offset)?;
+                finite("amplitude", &[*amplitude])?;
+                noise.warp(offset, *amplitude)
+            }
+            Self::Slope { noise } => child("noise", noise)?.slope(),
+            Self::Extrude2d { noise } => child("noise", noise)?.extrude_2d(),
+            Self::Interpolated { noise, spacing } => {
+                let noise = child("noise", noise)?;
+                finite("spacing", &[*spacing])?;
+                if *spacing <= 0.0 {
+                    return Err(invalid("spacing", "must be larger than 0"));
+                }
+                noise.interpolated(*spacing)
+            }
+            Self::Abs { noise } => child("noise", noise)?.abs(),
+            Self::Square { noise } => child("noise", noise)?.square(),
+            Self::Dither {
+                noise,
+                min,
+                max,
+                levels,
+            } => {
+                let noise = child("noise", noise)?;
+                finite("min", &[*min])?;
+                finite("max", &[*max])?;
+                if *levels < 2 {
+                    return Err(invalid("levels", "there must be 2 or more levels"));
+                }
+                noise.dither(*min, *max, *levels)
+            }
+            Self::Add { noise, other } => child("noise", noise)?.add(child("other", other)?),
+            Self::Sub { noise, other } => child("noise", noise)?.sub(child("other", other)?),
+            Self::AbsDiff { noise, other } => {
+                child("noise", noise)?.abs_diff(child("other", other)?)
+            }
+            Self::Mul { noise, other } => child("noise", noise)?.mul(child("other", other)?),
+            Self::Div { noise, other } => child("noise", noise)?.div(child("other", other)?),
+            Self::Max { noise, other } => child("noise", noise)?.max(child("other", other)?),
+            Self::Min { noise, other } => child("noise", noise)?.min(child("other", other)?),
+            Self::Avg { noises } => {
+                if noises.is_empty() {
+                    return Err(invalid(
+                        "noises",
+                        "there must be 1 or more noises to average",
+                    ));
+                }
+                let noises = noises
+                    .iter()
+                    .enumerate()
+                    .map(|(i, noise)| child(&format!("noises.{i}"), noise))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Noise::avg_n(noises)
+            }
+            Self::Clamp { noise, min, max } => {
+                let noise = child("noise", noise)?;
+                finite("min", &[*min])?;
+                finite("max", &[*max])?;
+                if min > max {
+                    return Err(invalid("max", "must be at least min"));
+                }
+                noise.clamp(*min, *max)
+            }
+            Self::Lerp {
+                noise,
+                low,
+                high,
+                selector_range,
+            } => {
+                let (noise, low, high) = (
+                    child("noise", noise)?,
+                    child("low", low)?,
+                    child("high", high)?,
+                );
+                let [selector_min, selector_max] = selector_range.unwrap_or([-1.0, 1.0]);
+                finite("selector_range", &[selector_min, selector_max])?;
+                noise.lerp_with_range(selector_min, selector_max, low, high)
+            }
+            Self::LerpValues { noise, low, high } => {
+                let noise = child("noise", noise)?;
+                finite("low", &[*low])?;
+                finite("high", &[*high])?;
+                noise.lerp_values(*low, *high)
+            }
+            Self::Range {
+                noise,
+                low,
+                high,
+                low_noise,
+                high_noise,
+            } => {
+                let noise = child("noise", noise)?;
+                finite("low", &[*low])?;
+                finite("high", &[*high])?;
+                let (low_noise, high_noise) = (
+                    child("low_noise", low_noise)?,
+                    child("high_noise", high_noise)?,
+                );
+                noise.range(*low, *high, low_noise, high_noise)
+            }
+        };
+        Ok(noise)
+    }
+
+    // The name of the node in the config
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Simplex { .. } => "simplex",
+            Self::Perlin { .. } => "perlin",
+            Self::OpenSimplex2 { .. } => "opensimplex2",
+            Self::Value { .. } => "value",
+            Self::ValueCubic { .. } => "value_cubic",
+            Self::Cellular { .. } => "cellular",
+            Self::White {} => "white",
+            Self::Pink { .. } => "pink",
+            Self::Brown { .. } => "brown",
+            Self::Spectral { .. } => "spectral",
+            Self::Dots { .. } => "dots",
+            Self::Constant { .. } => "constant",
+            Self::Param { .. } => "param",
+            Self::FalloffRadial { .. } => "falloff_radial",
+            Self::Sdf { .. } => "sdf",
+            Self::Veins { .. } => "veins",
+            Self::Fbm { .. } => "fbm",
+            Self::Ridged { .. } => "ridged",
+            Self::PingPong { .. } => "ping_pong",
+            Self::FbmCustom { .. } => "fbm_custom",
+            Self::WrapDomain { .. } => "wrap_domain",
+            Self::Warp { .. } => "warp",
+            Self::Slope { .. } => "slope",
+            Self::Extrude2d { .. } => "extrude_2d",
+            Self::Interpolated { .. } => "interpolated",
+            Self::Abs { .. } => "abs",
+            Self::Square { .. } => "square",
+            Self::Dither { .. } => "dither",
+            Self::Add { .. } => "add",
+            Self::Sub { .. } => "sub",
+            Self::AbsDiff { .. } => "abs_diff",
+            Self::Avg { .. } => "avg",
+            Self::Mul { .. } => "mul",
+            Self::Div { .. } => "div",
+            Self::Max { .. } => "max",
+            Self::Min { .. } => "min",
+            Self::Clamp { .. } => "clamp",
+            Self::Lerp { .. } => "lerp",
+            Self::LerpValues { .. } => "lerp_values",
+            Self::Range { .. } => "range",
+        }
+    }
+}
+
+impl Noise {
+    /// Loads a noise from a JSON config, see [config](crate::config).
+    ///
+    /// Requires the `config` feature.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        NoiseConfig::from_json(json)?.build()
+    }
+
+    /// Loads a noise from a RON config, see [config](crate::config).
+    ///
+    /// Requires the `config` feature.
+    pub fn from_ron(ron: &str) -> Result<Self, ConfigError> {
+        NoiseConfig::from_ron(ron)?.build()
+    }
+}
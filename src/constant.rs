@@ -2,20 +2,22 @@ use std::simd::{LaneCount, Simd, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn constant<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn constant<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Constant { value } = node.settings else {
+    let (NoiseSettings::Constant { value } | NoiseSettings::Parameter { value, .. }) = *settings
+    else {
         unreachable!()
     };
 
-    let result = Simd::splat(value);
+    let result = Simd::splat(value as Float);
     pipeline.results.push(result);
     pipeline.next();
 }
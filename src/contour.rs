@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::NoiseResult;
+
+// An edge between two neighbouring samples, given by the position of its first sample and whether
+// it runs along the first dimension. Points on the contours are identified by the edge they cross
+// so the segments of neighbouring cells can be joined exactly.
+type Edge = (usize, usize, bool);
+
+// Marching squares. Every cell between four samples produces the segments that cross it, and the
+// segments are then joined into lines at the edges they share.
+pub(crate) fn contours(result: &NoiseResult, threshold: f32) -> Vec<Vec<[f32; 2]>> {
+    let (width, height) = (result.width(), result.height());
+    let value = |x: usize, y: usize| result[x * height + y];
+
+    let mut segments: Vec<[Edge; 2]> = Vec::new();
+    for x in 0..width.saturating_sub(1) {
+        for y in 0..height.saturating_sub(1) {
+            // Corners in order around the cell, edge i is between corner i and i + 1.
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let inside = corners.map(|(x, y)| value(x, y) >= threshold);
+            let edges = [
+                (x, y, true),
+                (x + 1, y, false),
+                (x, y + 1, true),
+                (x, y, false),
+            ];
+
+            let mut crossed = [0; 4];
+            let mut count = 0;
+            for i in 0..4 {
+                if inside[i] != inside[(i + 1) % 4] {
+                    crossed[count] = i;
+                    count += 1;
+                }
+            }
+
+            if count == 2 {
+                segments.push([edges[crossed[0]], edges[crossed[1]]]);
+            } else if count == 4 {
+                // Saddle, the average of the corners decides whether the two opposite corners
+                // that are inside connect through the middle of the cell.
+                let center = corners.iter().map(|&(x, y)| value(x, y)).sum::<f32>() / 4.0;
+                if (center >= threshold) == inside[0] {
+                    segments.push([edges[0], edges[1]]);
+                    segments.push([edges[2], edges[3]]);
+                } else {
+                    segments.push([edges[3], edges[0]]);
+                    segments.push([edges[1], edges[2]]);
+                }
+            }
+        }
+    }
+
+    let mut ends: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        for edge in segment {
+            ends.entry(*edge).or_default().push(i);
+        }
+    }
+
+    let point = |(x, y, along_x): Edge| {
+        let (x1, y1) = if along_x { (x + 1, y) } else { (x, y + 1) };
+        let (a, b) = (value(x, y), value(x1, y1));
+        let t = (threshold - a) / (b - a);
+        [
+            x as f32 + t * (x1 - x) as f32,
+            y as f32 + t * (y1 - y) as f32,
+        ]
+    };
+
+    // Lines that aren't closed start and end at the border of the grid, where an edge only
+    // belongs to one segment. They are traced first so that only closed lines are left after.
+    let open_ends: Vec<Edge> = segments
+        .iter()
+        .flatten()
+        .filter(|edge| ends[edge].len() == 1)
+        .copied()
+        .collect();
+    let starts = open_ends
+        .into_iter()
+        .chain(segments.iter().map(|segment| segment[0]));
+
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    for start in starts {
+        let mut line = vec![point(start)];
+        let mut edge = start;
+        while let Some(&segment) = ends[&edge].iter().find(|&&segment| !used[segment]) {
+            used[segment] = true;
+            let [a, b] = segments[segment];
+            edge = if a == edge { b } else { a };
+            line.push(point(edge));
+        }
+
+        if line.len() > 1 {
+            lines.push(line);
+        }
+    }
+
+    lines
+}
@@ -0,0 +1,74 @@
+use crate::{Noise, NoiseSettings};
+
+/// Returned when a noise contains an operation that has no analytical derivative, see
+/// [Noise::generate_3d_with_derivatives].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotDifferentiable {
+    /// Name of the operation
+    pub node: &'static str,
+}
+
+impl std::fmt::Display for NotDifferentiable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' has no analytical derivative", self.node)
+    }
+}
+
+impl std::error::Error for NotDifferentiable {}
+
+// Finds the first node of the noise that can't be generated with derivatives.
+pub(crate) fn check(noise: &Noise) -> Result<(), NotDifferentiable> {
+    check_nodes(&noise.pipeline)
+}
+
+// Same as `check`, for a part of a pipeline
+pub(crate) fn check_nodes(nodes: &[NoiseSettings]) -> Result<(), NotDifferentiable> {
+    for settings in nodes {
+        let node = match settings {
+            NoiseSettings::OpenSimplex2 { .. } => "opensimplex2",
+            NoiseSettings::Value { .. } => "value",
+            NoiseSettings::ValueCubic { .. } => "value_cubic",
+            NoiseSettings::Cellular { .. } => "cellular",
+            NoiseSettings::White => "white",
+            NoiseSettings::Colored { .. } => "colored",
+            NoiseSettings::Spectral { .. } => "spectral",
+            NoiseSettings::Dots { .. } => "dots",
+            NoiseSettings::Dither { .. } => "dither",
+            NoiseSettings::Sdf { .. } => "sdf",
+            NoiseSettings::Sample { .. } => "sample",
+            NoiseSettings::Interpolate { .. } => "interpolated",
+            NoiseSettings::Simplex { .. }
+            | NoiseSettings::Perlin { .. }
+            | NoiseSettings::Constant { .. }
+            | NoiseSettings::Parameter { .. }
+            | NoiseSettings::Fbm { .. }
+            | NoiseSettings::Ridged { .. }
+            | NoiseSettings::PingPong { .. }
+            | NoiseSettings::Abs
+            | NoiseSettings::AbsDiff
+            | NoiseSettings::Add
+            | NoiseSettings::Sub
+            | NoiseSettings::Average { .. }
+            | NoiseSettings::Mul
+            | NoiseSettings::Div
+            | NoiseSettings::Clamp { .. }
+            | NoiseSettings::Max
+            | NoiseSettings::Min
+            | NoiseSettings::Lerp { .. }
+            | NoiseSettings::LerpValues { .. }
+            | NoiseSettings::Range { .. }
+            | NoiseSettings::Square
+            | NoiseSettings::FalloffRadial { .. }
+            | NoiseSettings::WrapDomain { .. }
+            | NoiseSettings::WrapBlend { .. }
+            | NoiseSettings::OffsetDomain { .. }
+            | NoiseSettings::Warp { .. }
+            | NoiseSettings::Extrude2d
+            | NoiseSettings::SeedOffset { .. }
+            | NoiseSettings::Slope { .. }
+            | NoiseSettings::RestoreCoordinates { .. } => continue,
+        };
+        return Err(NotDifferentiable { node });
+    }
+    Ok(())
+}
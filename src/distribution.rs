@@ -0,0 +1,213 @@
+use std::simd::prelude::*;
+
+use multiversion::{multiversion, simd_width};
+
+// Number of steps of the table, the values between two steps are interpolated.
+const TABLE_STEPS: usize = 1024;
+
+/// The distribution a [Reshape] gives the values of a noise.
+#[derive(Clone, Copy, Debug)]
+pub enum Distribution {
+    /// Every value between `min` and `max` is equally likely
+    Uniform { min: f32, max: f32 },
+    /// A bell curve around `mean`
+    Normal { mean: f32, standard_deviation: f32 },
+    /// Any distribution given by its cumulative distribution function, the fraction of the
+    /// values that are less than or equal to a value. It must rise from 0 at `min` to 1 at
+    /// `max` without ever falling.
+    Cdf {
+        cdf: fn(f32) -> f32,
+        min: f32,
+        max: f32,
+    },
+}
+
+impl Distribution {
+    // The value that a fraction `p` of the values are below.
+    fn quantile(&self, p: f64) -> f32 {
+        match *self {
+            Distribution::Uniform { min, max } => min + (max - min) * p as f32,
+            Distribution::Normal {
+                mean,
+                standard_deviation,
+            } => mean + standard_deviation * inverse_normal(p) as f32,
+            Distribution::Cdf { cdf, min, max } => {
+                // Bisection, the cdf can be any shape
+                let (mut low, mut high) = (min, max);
+                for _ in 0..40 {
+                    let middle = low + (high - low) * 0.5;
+                    if (cdf(middle) as f64) < p {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                low + (high - low) * 0.5
+            }
+        }
+    }
+}
+
+/// Reshapes the values of a noise so they follow a [Distribution], e.g. to make every height
+/// equally common, or to control how much of a map is above a threshold.
+///
+/// The distribution of the noise is measured from a sample of its values, and a table maps each
+/// value to the value that has the same fraction of the target distribution below it. The
+/// mapping keeps the order of the values, so the shapes of the noise stay the same. The sample
+/// should be generated the way the noise is used, with the same number of dimensions and over an
+/// area covering many of its longest wavelengths, the distribution of the noise changes with
+/// both. Values outside the range of the sample are mapped like the smallest or largest value
+/// of the sample.
+///
+/// # Example
+/// ```rust
+/// let terrain = Noise::simplex(0.01).fbm(4, 0.5, 2.0);
+/// let sample = terrain.generate_2d::<f32>(0.0, 0.0, 1024, 1024);
+/// let uniform = Reshape::new(&sample, Distribution::Uniform { min: 0.0, max: 1.0 });
+///
+/// // Sea level at 0.3 covers 30% of the map
+/// let height = terrain.generate_2d::<f32>(4096.0, 0.0, 256, 256).reshape(&uniform);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reshape {
+    // Start of the range of the sample and the number of table steps per unit
+    start: f32,
+    scale: f32,
+    // The reshaped value at each step, TABLE_STEPS + 1 of them
+    table: Vec<f32>,
+}
+
+impl Reshape {
+    /// Measures the distribution of the `sample` and builds the table that reshapes values of
+    /// the same noise into the `distribution`.
+    pub fn new(sample: &[f32], distribution: Distribution) -> Self {
+        assert!(!sample.is_empty(), "The sample can't be empty");
+
+        let (min, max) = sample
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            });
+        let scale = if max > min {
+            TABLE_STEPS as f32 / (max - min)
+        } else {
+            0.0
+        };
+
+        let mut histogram = vec![0u64; TABLE_STEPS];
+        for &value in sample {
+            let bin = ((value - min) * scale) as usize;
+            histogram[bin.min(TABLE_STEPS - 1)] += 1;
+        }
+
+        // The fraction of the sample below each step. It is kept half a value inside of 0 and 1
+        // so that the ends of the sample map to finite values of unbounded distributions.
+        let count = sample.len() as f64;
+        let edge = 0.5 / count;
+        let mut below = 0;
+        let mut table = Vec::with_capacity(TABLE_STEPS + 1);
+        table.push(distribution.quantile(edge));
+        for bin in histogram {
+            below += bin;
+            let p = (below as f64 / count).clamp(edge, 1.0 - edge);
+            table.push(distribution.quantile(p));
+        }
+
+        Self {
+            start: min,
+            scale,
+            table,
+        }
+    }
+
+    /// The reshaped value
+    pub fn get(&self, value: f32) -> f32 {
+        let position = ((value - self.start) * self.scale).clamp(0.0, TABLE_STEPS as f32);
+        let index = (position as usize).min(TABLE_STEPS - 1);
+        let t = position - index as f32;
+        let low = self.table[index];
+        low + (self.table[index + 1] - low) * t
+    }
+
+    /// Reshapes the values in place
+    pub fn apply(&self, values: &mut [f32]) {
+        unsafe { (apply())(self, values) }
+    }
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn apply(reshape: &Reshape, values: &mut [f32]) {
+    const N: usize = simd_width!(f32);
+
+    let start = Simd::<f32, N>::splat(reshape.start);
+    let scale = Simd::splat(reshape.scale);
+    let last = Simd::splat(TABLE_STEPS as f32);
+    let last_index = Simd::splat(TABLE_STEPS - 1);
+
+    let mut chunks = values.chunks_exact_mut(N);
+    for chunk in &mut chunks {
+        let position =
+            ((Simd::from_slice(chunk) - start) * scale).simd_clamp(Simd::splat(0.0), last);
+        let index = position.cast::<usize>().simd_min(last_index);
+        let t = position - index.cast::<f32>();
+        let low = Simd::gather_or_default(&reshape.table, index);
+        let high = Simd::gather_or_default(&reshape.table, index + Simd::splat(1));
+        (low + (high - low) * t).copy_to_slice(chunk);
+    }
+    for value in chunks.into_remainder() {
+        *value = reshape.get(*value);
+    }
+}
+
+// Acklam's rational approximation of the inverse of the standard normal cdf, relative error
+// below 1.2e-9.
+fn inverse_normal(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    // The tails are mirrored
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < LOW {
+        tail(p)
+    } else if p > 1.0 - LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
@@ -0,0 +1,95 @@
+use std::simd::{prelude::*, LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::{Float, Int};
+use crate::{NoisePipeline, NoiseSettings};
+
+// Position of the integer coordinates in an 8x8 Bayer matrix, 0..64. The bits of the position
+// are the bits of x ^ y and y interleaved in reverse, so that neighbouring values are as far
+// apart as possible.
+#[inline(always)]
+fn bayer<const N: usize>(x: Simd<Int, N>, y: Simd<Int, N>) -> Simd<Int, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let c = x ^ y;
+    let bit = |value: Simd<Int, N>, mask: Int| value & Simd::splat(mask);
+    (bit(c, 1) << 5)
+        | (bit(y, 1) << 4)
+        | (bit(c, 2) << 2)
+        | (bit(y, 2) << 1)
+        | (bit(c, 4) >> 1)
+        | (bit(y, 4) >> 2)
+}
+
+// The 1d equivalent, the bits of x reversed and scaled to 0..64
+#[inline(always)]
+fn bayer_1d<const N: usize>(x: Simd<Int, N>) -> Simd<Int, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    ((x & Simd::splat(1)) << 5) | ((x & Simd::splat(2)) << 3) | ((x & Simd::splat(4)) << 1)
+}
+
+// The integer part of the coordinate, only the 3 lowest bits are kept.
+#[inline(always)]
+fn cell<const N: usize>(coordinate: Simd<Float, N>) -> Simd<Int, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let wrapped = coordinate - (coordinate * Simd::splat(0.125)).floor() * Simd::splat(8.0);
+    // SAFETY: The wrapped coordinate is in 0..=8, it can be 8 when rounded up.
+    let cell: Simd<Int, N> = unsafe { wrapped.floor().to_int_unchecked() };
+    cell & Simd::splat(7)
+}
+
+#[inline(always)]
+fn dither<const N: usize>(pipeline: &mut NoisePipeline<N, Float>, index: Simd<Int, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Dither { min, max, levels } = *settings else {
+        unreachable!()
+    };
+
+    let step = (max as Float - min as Float) / (levels - 1) as Float;
+    let threshold = (index.cast::<Float>() + Simd::splat(0.5)) * Simd::splat(1.0 / 64.0);
+    let noise = pipeline.results.pop().unwrap();
+    let level = ((noise - Simd::splat(min as Float)) * Simd::splat(1.0 / step) + threshold)
+        .floor()
+        .simd_clamp(Simd::splat(0.0), Simd::splat((levels - 1) as Float));
+    let result = level.mul_add(Simd::splat(step), Simd::splat(min as Float));
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dither_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let index = bayer_1d(cell(pipeline.x));
+    dither(pipeline, index);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dither_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    let index = bayer(cell(pipeline.x), cell(pipeline.y));
+    dither(pipeline, index);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dither_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The pattern is in the horizontal plane, the same as in 2d
+    let index = bayer(cell(pipeline.x), cell(pipeline.z));
+    dither(pipeline, index);
+}
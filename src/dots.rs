@@ -0,0 +1,99 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::cellular::{cell_hash, random};
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// Each cell of the grid has a dot with a probability of `density`, at a random position within
+// the cell. A dot reaches at most one cell away, so the dots of the cell of the point and the
+// cells next to it along the first `AXES` axes are all that can cover it.
+#[inline(always)]
+fn dots<const N: usize, const AXES: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    point: [Simd<Float, N>; 3],
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Dots {
+        frequency,
+        density,
+        radius,
+        amplitude_jitter,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    // The second dimension of 2d noise is stored in y, it is the z axis of the frequency.
+    let frequency = match AXES {
+        2 => [frequency.x, frequency.z, 0.0],
+        _ => [frequency.x, frequency.y, frequency.z],
+    };
+    let point: [Simd<Float, N>; 3] =
+        std::array::from_fn(|axis| point[axis] * Simd::splat(frequency[axis] as Float));
+    let cell = point.map(|coordinate| coordinate.floor());
+    let reach = |axis: usize| if axis < AXES { -1..=1 } else { 0..=0 };
+    let inverse_squared_radius = Simd::splat(1.0 / (radius as Float * radius as Float));
+
+    let mut result = Simd::splat(0.0);
+    for dx in reach(0) {
+        for dy in reach(1) {
+            for dz in reach(2) {
+                let neighbour: [Simd<Float, N>; 3] = std::array::from_fn(|axis| {
+                    cell[axis] + Simd::splat([dx, dy, dz][axis] as Float)
+                });
+                let hash = cell_hash(seed, neighbour);
+
+                let mut squared_distance = Simd::splat(0.0);
+                for axis in 0..AXES {
+                    let offset = point[axis] - neighbour[axis] - random(hash, 1 + axis as i32);
+                    squared_distance = offset.mul_add(offset, squared_distance);
+                }
+                // (1 - d²/r²)³, a smooth bump that is 0 from the radius out
+                let falloff = squared_distance
+                    .mul_add(-inverse_squared_radius, Simd::splat(1.0))
+                    .simd_max(Simd::splat(0.0));
+                let amplitude = random(hash, 4)
+                    .mul_add(Simd::splat(-amplitude_jitter as Float), Simd::splat(1.0));
+                let bump = falloff * falloff * falloff * amplitude;
+
+                let present = random(hash, 0).simd_lt(Simd::splat(density as Float));
+                result = present.select(result.simd_max(bump), result);
+            }
+        }
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dots_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let zero = Simd::splat(0.0);
+    dots::<N, 1>(pipeline, [pipeline.x, zero, zero]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dots_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    dots::<N, 2>(pipeline, [pipeline.x, pipeline.y, Simd::splat(0.0)]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn dots_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    dots::<N, 3>(pipeline, [pipeline.x, pipeline.y, pipeline.z]);
+}
@@ -0,0 +1,1077 @@
+// Noise generated along with its partial derivatives, see `Noise::generate_3d_with_derivatives`.
+// Every result is carried as a `Dual`, its value and its derivatives along the axes of the grid,
+// and each node applies the chain rule to the duals of its inputs. The coordinates are duals as
+// well, so nodes that move them, like warps, carry their derivatives into the sub-noise. The
+// values are computed the same way as by the kernels, and the kernel of a Slope uses it for the
+// gradient of its sub-noise.
+
+use std::ops::Range;
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::{multiversion, simd_width};
+
+use super::gradient::{grad2, grad3d_dot, hash2d, hash3d};
+use super::simplex::{F2, G2, X_PRIME, Y_PRIME, Z_PRIME};
+use super::{Float, Int};
+use crate::{
+    lane_coordinates, Dimensions, Frequency, Gradient, Noise, NoisePipeline, NoiseSettings, Rng,
+    SlopeNodes,
+};
+
+#[derive(Clone, Copy)]
+struct Dual<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value: Simd<Float, N>,
+    // Partial derivatives along x, y and z
+    gradient: [Simd<Float, N>; 3],
+}
+
+impl<const N: usize> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline(always)]
+    fn constant(value: Simd<Float, N>) -> Self {
+        Self {
+            value,
+            gradient: [Simd::splat(0.0); 3],
+        }
+    }
+
+    // `value` of a function of `self`, with the derivative `derivative` at `self`
+    #[inline(always)]
+    fn chain(self, value: Simd<Float, N>, derivative: Simd<Float, N>) -> Self {
+        Self {
+            value,
+            gradient: self.gradient.map(|gradient| gradient * derivative),
+        }
+    }
+
+    // `value` of a function of `self` and `other`, with the derivatives `d_self` and `d_other`
+    // along each of them
+    #[inline(always)]
+    fn chain2(
+        self,
+        other: Self,
+        value: Simd<Float, N>,
+        d_self: Simd<Float, N>,
+        d_other: Simd<Float, N>,
+    ) -> Self {
+        Self {
+            value,
+            gradient: std::array::from_fn(|axis| {
+                self.gradient[axis].mul_add(d_self, other.gradient[axis] * d_other)
+            }),
+        }
+    }
+
+    // The lanes of `self` where `mask` is set and those of `other` elsewhere
+    #[inline(always)]
+    fn select(self, mask: Mask<Int, N>, other: Self) -> Self {
+        Self {
+            value: mask.select(self.value, other.value),
+            gradient: std::array::from_fn(|axis| {
+                mask.select(self.gradient[axis], other.gradient[axis])
+            }),
+        }
+    }
+
+    // Interpolates from `a` to `b` by `t`, like the lerp of the perlin kernels
+    #[inline(always)]
+    fn lerp(a: Self, b: Self, t: Self) -> Self {
+        let difference = b.value - a.value;
+        Self {
+            value: t.value.mul_add(difference, a.value),
+            gradient: std::array::from_fn(|axis| {
+                let gradient = t
+                    .value
+                    .mul_add(b.gradient[axis] - a.gradient[axis], a.gradient[axis]);
+                difference.mul_add(t.gradient[axis], gradient)
+            }),
+        }
+    }
+}
+
+// -1 where `value` is negative and 1 elsewhere, the derivative of its absolute value
+#[inline(always)]
+fn sign<const N: usize>(value: Simd<Float, N>) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value
+        .simd_lt(Simd::splat(0.0))
+        .select(Simd::splat(-1.0), Simd::splat(1.0))
+}
+
+// The frequency along each coordinate, the second dimension of 2d noise is stored in y and it is
+// the z axis of the frequency.
+#[inline(always)]
+fn frequencies(frequency: Frequency, dimensions: Dimensions) -> [Float; 3] {
+    match dimensions {
+        Dimensions::XY => [frequency.x as Float, frequency.z as Float, 0.0],
+        _ => [
+            frequency.x as Float,
+            frequency.y as Float,
+            frequency.z as Float,
+        ],
+    }
+}
+
+// A source sampled at `point` scaled by `frequency`. `sample` returns the dual of the source
+// with its derivatives along the scaled coordinates, they are carried back to the axes of the
+// grid through the derivatives of the coordinates.
+#[inline(always)]
+fn source<const N: usize>(
+    point: &[Dual<N>; 3],
+    frequency: [Float; 3],
+    sample: impl FnOnce([Simd<Float, N>; 3]) -> Dual<N>,
+) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let scaled = std::array::from_fn(|axis| point[axis].value * Simd::splat(frequency[axis]));
+    let local = sample(scaled);
+
+    let mut gradient = [Simd::splat(0.0); 3];
+    for (coordinate, (derivative, frequency)) in
+        point.iter().zip(local.gradient.into_iter().zip(frequency))
+    {
+        let derivative = derivative * Simd::splat(frequency);
+        for (gradient, coordinate) in gradient.iter_mut().zip(coordinate.gradient) {
+            *gradient = coordinate.mul_add(derivative, *gradient);
+        }
+    }
+    Dual {
+        value: local.value,
+        gradient,
+    }
+}
+
+// See `simplex::simplex_2d`. Within a simplex the offsets from its corners move one to one with
+// the coordinates.
+#[inline(always)]
+fn simplex_2d<const N: usize>(seed: Simd<i32, N>, [x, y, _]: [Simd<Float, N>; 3]) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let f = Simd::splat(F2) * (x + y);
+    let mut x0 = (x + f).floor();
+    let mut y0 = (y + f).floor();
+
+    let i = unsafe { x0.to_int_unchecked() * Simd::splat(X_PRIME) };
+    let j = unsafe { y0.to_int_unchecked() * Simd::splat(Y_PRIME) };
+
+    let g = Simd::splat(G2) * (x0 + y0);
+    x0 = x - (x0 - g);
+    y0 = y - (y0 - g);
+
+    let i1 = x0.simd_gt(y0);
+
+    let x1 = i1.select(x0 - Simd::splat(1.0), x0) + Simd::splat(G2);
+    let y1 = i1.select(y0, y0 - Simd::splat(1.0)) + Simd::splat(G2);
+
+    let x2 = x0 + Simd::splat(G2 * 2.0 - 1.0);
+    let y2 = y0 + Simd::splat(G2 * 2.0 - 1.0);
+
+    // The falloff to the 4th, the gradient and the derivatives of their product
+    let (zero, one) = (Simd::splat(0.0), Simd::splat(1.0));
+    let corner = |hash: Simd<i32, N>, x: Simd<Float, N>, y: Simd<Float, N>| {
+        let a = x
+            .mul_add(-x, y.mul_add(-y, Simd::splat(0.5)))
+            .simd_max(zero);
+        let a2 = a * a;
+        let t = a2 * a2;
+        let n = grad2(hash, x, y);
+        let slope = Simd::splat(-8.0) * a2 * a * n;
+        let dx = t.mul_add(grad2(hash, one, zero), slope * x);
+        let dy = t.mul_add(grad2(hash, zero, one), slope * y);
+        (t, n, [dx, dy])
+    };
+
+    let (t0, n0, d0) = corner(hash2d(seed, i, j), x0, y0);
+    let j1 = i1.select(j, j + Simd::splat(Y_PRIME));
+    let i1 = i1.select(i + Simd::splat(X_PRIME), i);
+    let (t1, n1, d1) = corner(hash2d(seed, i1, j1), x1, y1);
+    let i2 = i + Simd::splat(X_PRIME);
+    let j2 = j + Simd::splat(Y_PRIME);
+    let (t2, n2, d2) = corner(hash2d(seed, i2, j2), x2, y2);
+
+    let scale = Simd::splat(38.283687591552734375);
+    Dual {
+        value: scale * n0.mul_add(t0, n1.mul_add(t1, n2 * t2)),
+        gradient: [
+            scale * (d0[0] + d1[0] + d2[0]),
+            scale * (d0[1] + d1[1] + d2[1]),
+            zero,
+        ],
+    }
+}
+
+// See `simplex::simplex_3d`. The offsets from the corners of a simplex move with the coordinates
+// through the skew and the unskew, which is the identity less 2/3 along every axis.
+#[inline(always)]
+fn simplex_3d<const N: usize>(seed: Simd<i32, N>, [x, y, z]: [Simd<Float, N>; 3]) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const F3: Float = 1.0 / 3.0;
+    const G3: Float = 1.0 / 2.0;
+    let s = Simd::splat(F3) * (x + y + z);
+    let (x, y, z) = (x + s, y + s, z + s);
+
+    let mut x0 = x.floor();
+    let mut y0 = y.floor();
+    let mut z0 = z.floor();
+    let xi = x - x0;
+    let yi = y - y0;
+    let zi = z - z0;
+
+    let i = unsafe { x0.to_int_unchecked() * Simd::splat(X_PRIME) };
+    let j = unsafe { y0.to_int_unchecked() * Simd::splat(Y_PRIME) };
+    let k = unsafe { z0.to_int_unchecked() * Simd::splat(Z_PRIME) };
+
+    let x_ge_y = xi.simd_ge(yi);
+    let y_ge_z = yi.simd_ge(zi);
+    let x_ge_z = xi.simd_ge(zi);
+
+    let g = Simd::splat(G3) * (xi + yi + zi);
+    x0 = xi - g;
+    y0 = yi - g;
+    z0 = zi - g;
+
+    let i1 = x_ge_y & x_ge_z;
+    let j1 = y_ge_z & !x_ge_y;
+    let k1 = !x_ge_z & !y_ge_z;
+
+    let i2 = x_ge_y | x_ge_z;
+    let j2 = !x_ge_y | y_ge_z;
+    let k2 = x_ge_z & y_ge_z;
+
+    let x1 = i1.select(x0 - Simd::splat(1.0), x0) + Simd::splat(G3);
+    let y1 = j1.select(y0 - Simd::splat(1.0), y0) + Simd::splat(G3);
+    let z1 = k1.select(z0 - Simd::splat(1.0), z0) + Simd::splat(G3);
+    let x2 = i2.select(x0 - Simd::splat(1.0), x0) + Simd::splat(G3 * 2.0);
+    let y2 = j2.select(y0 - Simd::splat(1.0), y0) + Simd::splat(G3 * 2.0);
+    let z2 = k2.select(z0, z0 - Simd::splat(1.0)) + Simd::splat(G3 * 2.0);
+    let x3 = x0 + Simd::splat(G3 * 3.0 - 1.0);
+    let y3 = y0 + Simd::splat(G3 * 3.0 - 1.0);
+    let z3 = z0 + Simd::splat(G3 * 3.0 - 1.0);
+
+    // The falloff to the 4th, the gradient and the derivatives of their product along the
+    // offsets
+    let (zero, one) = (Simd::splat(0.0), Simd::splat(1.0));
+    let corner = |hash: Simd<i32, N>, x: Simd<Float, N>, y: Simd<Float, N>, z: Simd<Float, N>| {
+        let a = x
+            .mul_add(-x, y.mul_add(-y, z.mul_add(-z, Simd::splat(0.6))))
+            .simd_max(zero);
+        let a2 = a * a;
+        let t = a2 * a2;
+        let n = grad3d_dot(hash, x, y, z);
+        let slope = Simd::splat(-8.0) * a2 * a * n;
+        let dx = t.mul_add(grad3d_dot(hash, one, zero, zero), slope * x);
+        let dy = t.mul_add(grad3d_dot(hash, zero, one, zero), slope * y);
+        let dz = t.mul_add(grad3d_dot(hash, zero, zero, one), slope * z);
+        (t, n, [dx, dy, dz])
+    };
+
+    let (t0, n0, d0) = corner(hash3d(seed, i, j, k), x0, y0, z0);
+    let i1 = i1.select(i + Simd::splat(X_PRIME), i);
+    let j1 = j1.select(j + Simd::splat(Y_PRIME), j);
+    let k1 = k1.select(k + Simd::splat(Z_PRIME), k);
+    let (t1, n1, d1) = corner(hash3d(seed, i1, j1, k1), x1, y1, z1);
+    let i2 = i2.select(i + Simd::splat(X_PRIME), i);
+    let j2 = j2.select(j + Simd::splat(Y_PRIME), j);
+    let k2 = k2.select(k, k + Simd::splat(Z_PRIME));
+    let (t2, n2, d2) = corner(hash3d(seed, i2, j2, k2), x2, y2, z2);
+    let i3 = i + Simd::splat(X_PRIME);
+    let j3 = j + Simd::splat(Y_PRIME);
+    let k3 = k + Simd::splat(Z_PRIME);
+    let (t3, n3, d3) = corner(hash3d(seed, i3, j3, k3), x3, y3, z3);
+
+    let scale = Simd::splat(32.69428253173828125);
+    let offsets: [Simd<Float, N>; 3] =
+        std::array::from_fn(|axis| d0[axis] + d1[axis] + d2[axis] + d3[axis]);
+    let unskew = Simd::splat(2.0 / 3.0) * (offsets[0] + offsets[1] + offsets[2]);
+    Dual {
+        value: scale * n0.mul_add(t0, n1.mul_add(t1, n2.mul_add(t2, n3 * t3))),
+        gradient: offsets.map(|offset| scale * (offset - unskew)),
+    }
+}
+
+// The quintic curve of the perlin kernels, and its derivative
+#[inline(always)]
+fn quintic<const N: usize>(v: Simd<Float, N>, axis: usize) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut t = Dual::constant(
+        v * v
+            * v
+            * v.mul_add(
+                v.mul_add(Simd::splat(6.0), Simd::splat(-15.0)),
+                Simd::splat(10.0),
+            ),
+    );
+    let w = v - Simd::splat(1.0);
+    t.gradient[axis] = Simd::splat(30.0) * v * v * w * w;
+    t
+}
+
+// See `perlin::perlin_2d`
+#[inline(always)]
+fn perlin_2d<const N: usize>(seed: Simd<i32, N>, [x, y, _]: [Simd<Float, N>; 3]) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let xs = x.floor();
+    let ys = y.floor();
+
+    let x0 = unsafe { xs.to_int_unchecked() * Simd::splat(X_PRIME) };
+    let y0 = unsafe { ys.to_int_unchecked() * Simd::splat(Y_PRIME) };
+    let x1 = x0 + Simd::splat(X_PRIME);
+    let y1 = y0 + Simd::splat(Y_PRIME);
+
+    let xf0 = x - xs;
+    let yf0 = y - ys;
+    let xf1 = xf0 - Simd::splat(1.0);
+    let yf1 = yf0 - Simd::splat(1.0);
+
+    let (zero, one) = (Simd::splat(0.0), Simd::splat(1.0));
+    let corner = |hash: Simd<i32, N>, x: Simd<Float, N>, y: Simd<Float, N>| Dual {
+        value: grad2(hash, x, y),
+        gradient: [grad2(hash, one, zero), grad2(hash, zero, one), zero],
+    };
+
+    let (xs, ys) = (quintic(xf0, 0), quintic(yf0, 1));
+    let result = Dual::lerp(
+        Dual::lerp(
+            corner(hash2d(seed, x0, y0), xf0, yf0),
+            corner(hash2d(seed, x1, y0), xf1, yf0),
+            xs,
+        ),
+        Dual::lerp(
+            corner(hash2d(seed, x0, y1), xf0, yf1),
+            corner(hash2d(seed, x1, y1), xf1, yf1),
+            xs,
+        ),
+        ys,
+    );
+    let scale = Simd::splat(0.579106986522674560546875);
+    result.chain(scale * result.value, scale)
+}
+
+// See `perlin::perlin_3d`
+#[inline(always)]
+fn perlin_3d<const N: usize>(seed: Simd<i32, N>, [x, y, z]: [Simd<Float, N>; 3]) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let xs = x.floor();
+    let ys = y.floor();
+    let zs = z.floor();
+
+    let x0 = unsafe { xs.to_int_unchecked() * Simd::splat(X_PRIME) };
+    let y0 = unsafe { ys.to_int_unchecked() * Simd::splat(Y_PRIME) };
+    let z0 = unsafe { zs.to_int_unchecked() * Simd::splat(Z_PRIME) };
+    let x1 = x0 + Simd::splat(X_PRIME);
+    let y1 = y0 + Simd::splat(Y_PRIME);
+    let z1 = z0 + Simd::splat(Z_PRIME);
+
+    let xf0 = x - xs;
+    let yf0 = y - ys;
+    let zf0 = z - zs;
+    let xf1 = xf0 - Simd::splat(1.0);
+    let yf1 = yf0 - Simd::splat(1.0);
+    let zf1 = zf0 - Simd::splat(1.0);
+
+    let (zero, one) = (Simd::splat(0.0), Simd::splat(1.0));
+    let corner =
+        |hash: Simd<i32, N>, x: Simd<Float, N>, y: Simd<Float, N>, z: Simd<Float, N>| Dual {
+            value: grad3d_dot(hash, x, y, z),
+            gradient: [
+                grad3d_dot(hash, one, zero, zero),
+                grad3d_dot(hash, zero, one, zero),
+                grad3d_dot(hash, zero, zero, one),
+            ],
+        };
+
+    let (xs, ys, zs) = (quintic(xf0, 0), quintic(yf0, 1), quintic(zf0, 2));
+    let result = Dual::lerp(
+        Dual::lerp(
+            Dual::lerp(
+                corner(hash3d(seed, x0, y0, z0), xf0, yf0, zf0),
+                corner(hash3d(seed, x1, y0, z0), xf1, yf0, zf0),
+                xs,
+            ),
+            Dual::lerp(
+                corner(hash3d(seed, x0, y1, z0), xf0, yf1, zf0),
+                corner(hash3d(seed, x1, y1, z0), xf1, yf1, zf0),
+                xs,
+            ),
+            ys,
+        ),
+        Dual::lerp(
+            Dual::lerp(
+                corner(hash3d(seed, x0, y0, z1), xf0, yf0, zf1),
+                corner(hash3d(seed, x1, y0, z1), xf1, yf0, zf1),
+                xs,
+            ),
+            Dual::lerp(
+                corner(hash3d(seed, x0, y1, z1), xf0, yf1, zf1),
+                corner(hash3d(seed, x1, y1, z1), xf1, yf1, zf1),
+                xs,
+            ),
+            ys,
+        ),
+        zs,
+    );
+    let scale = Simd::splat(0.964921414852142333984375);
+    result.chain(scale * result.value, scale)
+}
+
+// See `wrap::wrap`, moving the coordinate by whole periods doesn't change its derivatives.
+#[inline(always)]
+fn wrap<const N: usize>(coordinate: Dual<N>, period: f32, shift: bool) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    if period <= 0.0 {
+        return coordinate;
+    }
+    let period = Simd::splat(period as Float);
+    let mut value = coordinate.value - (coordinate.value / period).floor() * period;
+    if shift {
+        value -= period;
+    }
+    Dual {
+        value,
+        gradient: coordinate.gradient,
+    }
+}
+
+// The coordinates, dimensions and seed saved by a node that starts a sub-noise
+struct Saved<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    point: [Dual<N>; 3],
+    dimensions: Dimensions,
+    rng_seed: u64,
+}
+
+// The nodes of a noise and the Slopes among them
+#[derive(Clone, Copy)]
+struct Nodes<'a> {
+    settings: &'a [NoiseSettings],
+    slopes: &'a [SlopeNodes],
+}
+
+// Runs the nodes within `range` at a vector of points, with the seeds drawn from `rng`. Whatever
+// is in `results` and `saved` is left as it was, they are only passed in to be reused. The nodes
+// must all have derivatives, see `derivatives::check`.
+#[inline(always)]
+fn evaluate<const N: usize>(
+    nodes: Nodes,
+    range: Range<usize>,
+    rng: &mut Rng,
+    mut dimensions: Dimensions,
+    mut point: [Dual<N>; 3],
+    results: &mut Vec<Dual<N>>,
+    saved: &mut Vec<Saved<N>>,
+) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (zero, one) = (Simd::splat(0.0), Simd::splat(1.0));
+    let mut next = range.start;
+    while next < range.end {
+        let index = next;
+        next += 1;
+        let result = match nodes.settings[index] {
+            NoiseSettings::Simplex { frequency } => {
+                let seed = Simd::splat(rng.next());
+                let frequency = frequencies(frequency, dimensions);
+                match dimensions {
+                    Dimensions::XY => source(&point, frequency, |p| simplex_2d(seed, p)),
+                    _ => source(&point, frequency, |p| simplex_3d(seed, p)),
+                }
+            }
+            NoiseSettings::Perlin { frequency } => {
+                let seed = Simd::splat(rng.next());
+                let frequency = frequencies(frequency, dimensions);
+                match dimensions {
+                    Dimensions::XY => source(&point, frequency, |p| perlin_2d(seed, p)),
+                    _ => source(&point, frequency, |p| perlin_3d(seed, p)),
+                }
+            }
+            NoiseSettings::Constant { value } | NoiseSettings::Parameter { value, .. } => {
+                Dual::constant(Simd::splat(value as Float))
+            }
+            NoiseSettings::Fbm {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            } => {
+                let gain = gain as Float;
+                let mut amplitude = first_octave_amplitude as Float;
+                let mut result = Dual::constant(zero);
+                for _ in 0..octaves {
+                    let noise = results.pop().unwrap();
+                    let amplitude_s = Simd::splat(amplitude);
+                    let value = result.value + noise.value * amplitude_s;
+                    result = noise.chain2(result, value, amplitude_s, one);
+                    amplitude *= gain;
+                }
+                result
+            }
+            NoiseSettings::Ridged {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            } => {
+                let gain = gain as Float;
+                let mut amplitude = first_octave_amplitude as Float;
+                let mut weight = Dual::constant(one);
+                let mut result = Dual::constant(zero);
+                for _ in 0..octaves {
+                    let noise = results.pop().unwrap();
+                    let ridge = noise.chain(one - noise.value.abs(), -sign(noise.value));
+                    let squared = ridge.value * ridge.value;
+                    let signal = ridge.chain2(
+                        weight,
+                        squared * weight.value,
+                        (ridge.value + ridge.value) * weight.value,
+                        squared,
+                    );
+                    let doubled = signal.value + signal.value;
+                    let inside = doubled.simd_gt(zero) & doubled.simd_lt(one);
+                    weight = signal.chain(
+                        doubled.simd_clamp(zero, one),
+                        inside.select(Simd::splat(2.0), zero),
+                    );
+                    let amplitude_s = Simd::splat(amplitude);
+                    let value = signal.value.mul_add(amplitude_s, result.value);
+                    result = signal.chain2(result, value, amplitude_s, one);
+                    amplitude *= gain;
+                }
+                result
+            }
+            NoiseSettings::PingPong {
+                octaves,
+                gain,
+                strength,
+                first_octave_amplitude,
+            } => {
+                let two = Simd::splat(2.0);
+                let strength = Simd::splat(strength as Float);
+                let gain = gain as Float;
+                let mut amplitude = first_octave_amplitude as Float;
+                let mut result = Dual::constant(zero);
+                for _ in 0..octaves {
+                    let noise = results.pop().unwrap();
+                    let t = (noise.value + one) * strength;
+                    let t = t - (t * Simd::splat(0.5)).floor() * two;
+                    let folded = one - (t - one).abs();
+                    let amplitude_s = Simd::splat(amplitude);
+                    let value = folded.mul_add(two, -one).mul_add(amplitude_s, result.value);
+                    let derivative = -sign(t - one) * strength * two * amplitude_s;
+                    result = noise.chain2(result, value, derivative, one);
+                    amplitude *= gain;
+                }
+                result
+            }
+            NoiseSettings::Abs => {
+                let noise = results.pop().unwrap();
+                noise.chain(noise.value.abs(), sign(noise.value))
+            }
+            NoiseSettings::AbsDiff => {
+                let left = results.pop().unwrap();
+                let right = results.pop().unwrap();
+                let difference = left.value - right.value;
+                let sign = sign(difference);
+                left.chain2(right, difference.abs(), sign, -sign)
+            }
+            NoiseSettings::Add => {
+                let left = results.pop().unwrap();
+                let right = results.pop().unwrap();
+                left.chain2(right, left.value + right.value, one, one)
+            }
+            NoiseSettings::Sub => {
+                let other = results.pop().unwrap();
+                let noise = results.pop().unwrap();
+                noise.chain2(other, noise.value - other.value, one, -one)
+            }
+            NoiseSettings::Average { count } => {
+                let mut sum = Dual::constant(zero);
+                for _ in 0..count {
+                    let noise = results.pop().unwrap();
+                    sum = sum.chain2(noise, sum.value + noise.value, one, one);
+                }
+                let scale = Simd::splat(1.0 / count as Float);
+                sum.chain(sum.value * scale, scale)
+            }
+            NoiseSettings::Mul => {
+                let left = results.pop().unwrap();
+                let right = results.pop().unwrap();
+                left.chain2(right, left.value * right.value, right.value, left.value)
+            }
+            NoiseSettings::Div => {
+                let other = results.pop().unwrap();
+                let noise = results.pop().unwrap();
+                let value = noise.value / other.value;
+                noise.chain2(other, value, other.value.recip(), -value / other.value)
+            }
+            NoiseSettings::Clamp { min, max } => {
+                let noise = results.pop().unwrap();
+                let (min, max) = (Simd::splat(min as Float), Simd::splat(max as Float));
+                let inside = noise.value.simd_gt(min) & noise.value.simd_lt(max);
+                noise.chain(noise.value.simd_clamp(min, max), inside.select(one, zero))
+            }
+            NoiseSettings::Max => {
+                let left = results.pop().unwrap();
+                let right = results.pop().unwrap();
+                let value = left.value.simd_max(right.value);
+                left.select(left.value.simd_ge(right.value), right)
+                    .chain(value, one)
+            }
+            NoiseSettings::Min => {
+                let left = results.pop().unwrap();
+                let right = results.pop().unwrap();
+                let value = left.value.simd_min(right.value);
+                left.select(left.value.simd_le(right.value), right)
+                    .chain(value, one)
+            }
+            NoiseSettings::Lerp {
+                selector_min,
+                selector_max,
+            } => {
+                let low = results.pop().unwrap();
+                let high = results.pop().unwrap();
+                let selector = results.pop().unwrap();
+                let (selector_min, selector_max) = (selector_min as Float, selector_max as Float);
+                let scale = 1.0 / (selector_max - selector_min);
+                let offset = -selector_min * scale;
+                let interpolation = selector.chain(
+                    selector
+                        .value
+                        .mul_add(Simd::splat(scale), Simd::splat(offset)),
+                    Simd::splat(scale),
+                );
+                // The same value as the kernel, an fma doesn't depend on the order of the factors
+                Dual::lerp(low, high, interpolation)
+            }
+            NoiseSettings::LerpValues { low, high } => {
+                let noise = results.pop().unwrap();
+                let (low, high) = (low as Float, high as Float);
+                let scale = Simd::splat((high - low) * 0.5);
+                let offset = Simd::splat((high + low) * 0.5);
+                noise.chain(noise.value.mul_add(scale, offset), scale)
+            }
+            NoiseSettings::Range { low, high } => {
+                let low_noise = results.pop().unwrap();
+                let high_noise = results.pop().unwrap();
+                let selector = results.pop().unwrap();
+                let (low, high) = (Simd::splat(low as Float), Simd::splat(high as Float));
+                let low_clipped = selector.value.simd_lt(low);
+                let high_clipped = selector.value.simd_gt(high);
+                let interpolation =
+                    selector.chain((selector.value - low) / (high - low), one / (high - low));
+                let result = Dual::lerp(low_noise, high_noise, interpolation);
+                let result = high_noise.select(high_clipped, result);
+                low_noise.select(low_clipped, result)
+            }
+            NoiseSettings::Square => {
+                let noise = results.pop().unwrap();
+                noise.chain(noise.value * noise.value, noise.value + noise.value)
+            }
+            NoiseSettings::FalloffRadial {
+                center_x,
+                center_z,
+                inner_radius,
+                outer_radius,
+            } => {
+                // The second dimension of 2d noise is stored in y
+                let [x, y, z] = point;
+                let second = match dimensions {
+                    Dimensions::XY => y,
+                    _ => z,
+                };
+                let dx = x.value - Simd::splat(center_x as Float);
+                let dz = second.value - Simd::splat(center_z as Float);
+                let distance = dx.mul_add(dx, dz * dz).sqrt();
+                // The derivatives of the distance along dx and dz, 0 at the center
+                let at_center = distance.simd_eq(zero);
+                let distance_dx = at_center.select(zero, dx / distance);
+                let distance_dz = at_center.select(zero, dz / distance);
+                let distance = x.chain2(second, distance, distance_dx, distance_dz);
+
+                let (inner, outer) = (inner_radius as Float, outer_radius as Float);
+                let scale = Simd::splat(1.0 / (outer - inner));
+                let t = (distance.value - Simd::splat(inner)) * scale;
+                let inside = t.simd_gt(zero) & t.simd_lt(one);
+                let t = t.simd_clamp(zero, one);
+                let value = one - t * t * (Simd::splat(3.0) - Simd::splat(2.0) * t);
+                let derivative = Simd::splat(-6.0) * t * (one - t) * scale;
+                distance.chain(value, inside.select(derivative, zero))
+            }
+            NoiseSettings::WrapDomain { period, corner } => {
+                saved.push(Saved {
+                    point,
+                    dimensions,
+                    rng_seed: rng.current_seed,
+                });
+                // The second dimension of 2d noise is stored in y
+                let axes: &[usize] = match dimensions {
+                    Dimensions::XY => &[0, 2],
+                    _ => &[0, 1, 2],
+                };
+                for (coordinate, &axis) in axes.iter().enumerate() {
+                    point[coordinate] =
+                        wrap(point[coordinate], period[axis], corner >> axis & 1 != 0);
+                }
+                continue;
+            }
+            NoiseSettings::WrapBlend { period } => {
+                let coordinates = match dimensions {
+                    Dimensions::XY => [Some(point[0]), None, Some(point[1])],
+                    _ => point.map(Some),
+                };
+                let mut fractions = Vec::with_capacity(3);
+                for (period, coordinate) in period.into_iter().zip(coordinates) {
+                    if period <= 0.0 {
+                        continue;
+                    }
+                    let fraction = match coordinate {
+                        Some(coordinate) => {
+                            let scale = Simd::splat(1.0 / period as Float);
+                            let wrapped = wrap(coordinate, period, false);
+                            wrapped.chain(wrapped.value / Simd::splat(period as Float), scale)
+                        }
+                        None => Dual::constant(zero),
+                    };
+                    fractions.push(fraction);
+                }
+
+                let mut result = Dual::constant(zero);
+                for corner in (0..1usize << fractions.len()).rev() {
+                    let mut weight = Dual::constant(one);
+                    for (axis, fraction) in fractions.iter().enumerate() {
+                        let fraction = if corner >> axis & 1 == 1 {
+                            *fraction
+                        } else {
+                            fraction.chain(one - fraction.value, -one)
+                        };
+                        weight = weight.chain2(
+                            fraction,
+                            weight.value * fraction.value,
+                            fraction.value,
+                            weight.value,
+                        );
+                    }
+                    let noise = results.pop().unwrap();
+                    let value = noise.value.mul_add(weight.value, result.value);
+                    let product = noise.chain2(
+                        weight,
+                        noise.value * weight.value,
+                        weight.value,
+                        noise.value,
+                    );
+                    result = product.chain2(result, value, one, one);
+                }
+                result
+            }
+            NoiseSettings::OffsetDomain { .. }
+                if let Some(slope) = nodes.slopes.iter().find(|s| s.copies[0].0 == index) =>
+            {
+                next = slope.slope + 1;
+                self::slope(nodes, slope, rng, dimensions, &point, results, saved)
+            }
+            NoiseSettings::OffsetDomain { offset } => {
+                saved.push(Saved {
+                    point,
+                    dimensions,
+                    rng_seed: rng.current_seed,
+                });
+                // The second dimension of 2d noise is stored in y
+                let axes: &[usize] = match dimensions {
+                    Dimensions::XY => &[0, 2],
+                    _ => &[0, 1, 2],
+                };
+                for (coordinate, &axis) in axes.iter().enumerate() {
+                    point[coordinate].value += Simd::splat(offset[axis] as Float);
+                }
+                continue;
+            }
+            NoiseSettings::Warp { amplitude } => {
+                let amplitude = Simd::splat(amplitude as Float);
+                let z = results.pop().unwrap();
+                let y = results.pop().unwrap();
+                let x = results.pop().unwrap();
+                saved.push(Saved {
+                    point,
+                    dimensions,
+                    rng_seed: rng.current_seed,
+                });
+                // The second dimension of 2d noise is stored in y
+                let offsets = match dimensions {
+                    Dimensions::XY => vec![x, z],
+                    _ => vec![x, y, z],
+                };
+                for (coordinate, offset) in point.iter_mut().zip(offsets) {
+                    let value = coordinate.value + offset.value * amplitude;
+                    *coordinate = coordinate.chain2(offset, value, one, amplitude);
+                }
+                continue;
+            }
+            NoiseSettings::Extrude2d => {
+                saved.push(Saved {
+                    point,
+                    dimensions,
+                    rng_seed: rng.current_seed,
+                });
+                if dimensions == Dimensions::XYZ {
+                    point[1] = point[2];
+                    dimensions = Dimensions::XY;
+                }
+                continue;
+            }
+            NoiseSettings::RestoreCoordinates { rewind } => {
+                let restored = saved.pop().unwrap();
+                point = restored.point;
+                dimensions = restored.dimensions;
+                if rewind {
+                    rng.current_seed = restored.rng_seed;
+                }
+                continue;
+            }
+            NoiseSettings::SeedOffset { offset } => {
+                rng.skip(offset);
+                continue;
+            }
+            _ => unreachable!(),
+        };
+        results.push(result);
+    }
+    results.pop().unwrap()
+}
+
+// The length of a gradient
+#[inline(always)]
+fn length<const N: usize>([dx, dy, dz]: [Simd<Float, N>; 3]) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    dx.mul_add(dx, dy.mul_add(dy, dz * dz)).sqrt()
+}
+
+// The gradient of the sub-noise of a Slope at `point`, along the coordinates themselves. The
+// copies of the sub-noise are the same, the first one is run.
+#[inline(always)]
+fn sub_noise_gradient<const N: usize>(
+    nodes: Nodes,
+    slope: &SlopeNodes,
+    rng: &mut Rng,
+    dimensions: Dimensions,
+    point: [Simd<Float, N>; 3],
+    results: &mut Vec<Dual<N>>,
+    saved: &mut Vec<Saved<N>>,
+) -> [Simd<Float, N>; 3]
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let point = std::array::from_fn(|axis| {
+        let mut coordinate = Dual::constant(point[axis]);
+        coordinate.gradient[axis] = Simd::splat(1.0);
+        coordinate
+    });
+    let (start, end) = slope.copies[0];
+    let noise = evaluate(
+        nodes,
+        start + 1..end,
+        rng,
+        dimensions,
+        point,
+        results,
+        saved,
+    );
+    noise.gradient
+}
+
+// A Slope in a noise generated with derivatives. Its value is the exact length of the gradient of
+// the sub-noise, like the kernel computes it. The derivatives of the length are central
+// differences a step before and after along each coordinate, carried to the axes of the grid
+// through the derivatives of the coordinates.
+#[inline(always)]
+fn slope<const N: usize>(
+    nodes: Nodes,
+    slope: &SlopeNodes,
+    rng: &mut Rng,
+    dimensions: Dimensions,
+    point: &[Dual<N>; 3],
+    results: &mut Vec<Dual<N>>,
+    saved: &mut Vec<Saved<N>>,
+) -> Dual<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let NoiseSettings::Slope { step } = nodes.settings[slope.slope] else {
+        unreachable!()
+    };
+    let step = step as Float;
+    let values = point.map(|coordinate| coordinate.value);
+
+    // Every run of the sub-noise draws the same seeds, the rng continues after one of them.
+    let mut after = rng.clone();
+    let gradient = sub_noise_gradient(nodes, slope, &mut after, dimensions, values, results, saved);
+
+    // The second dimension of 2d noise is stored in y
+    let coordinates = match dimensions {
+        Dimensions::XY => 2,
+        _ => 3,
+    };
+    let mut derivatives = [Simd::splat(0.0); 3];
+    for coordinate in 0..coordinates {
+        let mut lengths = [Simd::splat(0.0); 2];
+        for (length, offset) in lengths.iter_mut().zip([step, -step]) {
+            let mut shifted = values;
+            shifted[coordinate] += Simd::splat(offset);
+            let mut rng = rng.clone();
+            let gradient =
+                sub_noise_gradient(nodes, slope, &mut rng, dimensions, shifted, results, saved);
+            *length = self::length(gradient);
+        }
+        let derivative = (lengths[0] - lengths[1]) * Simd::splat(0.5 / step);
+        for (axis, sum) in derivatives.iter_mut().enumerate() {
+            *sum = point[coordinate].gradient[axis].mul_add(derivative, *sum);
+        }
+    }
+
+    *rng = after;
+    Dual {
+        value: length(gradient),
+        gradient: derivatives,
+    }
+}
+
+// The length of the gradient of the sub-noise of `slope` at the coordinates of the pipeline, see
+// `slope::slope_derivatives_2d`. The rng of the pipeline continues after the sub-noise.
+#[inline(always)]
+pub fn slope_length<const N: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    slope: &SlopeNodes,
+    dimensions: Dimensions,
+) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let nodes = Nodes {
+        settings: &pipeline.noise.pipeline,
+        slopes: &pipeline.slopes,
+    };
+    let point = [pipeline.x, pipeline.y, pipeline.z];
+    let mut rng = pipeline.rng.clone();
+    let gradient = sub_noise_gradient(
+        nodes,
+        slope,
+        &mut rng,
+        dimensions,
+        point,
+        &mut Vec::new(),
+        &mut Vec::new(),
+    );
+    pipeline.rng = rng;
+    length(gradient)
+}
+
+// Generates a 2d or 3d grid like `generate::generate_2d` and `generate::generate_3d`, in the same
+// order. The depth of 2d grids is 1, and their gradients have no z.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+pub(crate) fn generate(
+    noise: &Noise,
+    origin: [f64; 3],
+    size: [usize; 3],
+    dimensions: Dimensions,
+) -> (Vec<Float>, Float, Float, Gradient<Float>) {
+    with_lane_width!(
+        simd_width!(Float),
+        generate_lanes(noise, origin, size, dimensions)
+    )
+}
+
+#[inline(always)]
+fn generate_lanes<const N: usize>(
+    noise: &Noise,
+    [x, y, z]: [f64; 3],
+    [width, height, depth]: [usize; 3],
+    dimensions: Dimensions,
+) -> (Vec<Float>, Float, Float, Gradient<Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let len = width * height * depth;
+    let mut values = vec![0.0; len];
+    let mut gradient = Gradient {
+        x: vec![0.0; len],
+        y: vec![0.0; len],
+        z: match dimensions {
+            Dimensions::XY => Vec::new(),
+            _ => vec![0.0; len],
+        },
+    };
+    let mut min = Float::MAX;
+    let mut max = Float::MIN;
+
+    // The coordinates start out with a derivative of 1 along their own axis
+    let axis = |value: Simd<Float, N>, axis: usize| {
+        let mut coordinate = Dual::constant(value);
+        coordinate.gradient[axis] = Simd::splat(1.0);
+        coordinate
+    };
+
+    let nodes = Nodes {
+        settings: &noise.pipeline,
+        slopes: &NoiseSettings::slope_nodes(&noise.pipeline),
+    };
+    let mut results = Vec::new();
+    let mut saved = Vec::new();
+    let mut i = 0;
+    for xi in 0..width {
+        let px = axis(Simd::splat((x + xi as f64) as Float), 0);
+        for zi in 0..depth {
+            let pz = axis(Simd::splat((z + zi as f64) as Float), 2);
+            for yi in (0..height).step_by(N) {
+                let py = axis(lane_coordinates(y + yi as f64, 1.0), 1);
+                let mut rng = Rng::new(noise.seed);
+                let point = [px, py, pz];
+                let range = 0..noise.pipeline.len();
+                let result = evaluate(
+                    nodes,
+                    range,
+                    &mut rng,
+                    dimensions,
+                    point,
+                    &mut results,
+                    &mut saved,
+                );
+
+                let count = (height - yi).min(N);
+                let lanes = i..i + count;
+                values[lanes.clone()].copy_from_slice(&result.value.as_array()[..count]);
+                gradient.x[lanes.clone()].copy_from_slice(&result.gradient[0].as_array()[..count]);
+                gradient.y[lanes.clone()].copy_from_slice(&result.gradient[1].as_array()[..count]);
+                if dimensions != Dimensions::XY {
+                    gradient.z[lanes].copy_from_slice(&result.gradient[2].as_array()[..count]);
+                }
+                for &value in &result.value.as_array()[..count] {
+                    min = min.min(value);
+                    max = max.max(value);
+                }
+                i += count;
+            }
+        }
+    }
+    (values, min, max, gradient)
+}
@@ -0,0 +1,934 @@
+// Noises written as expressions, see `NoiseConfig::from_expression`. The text is split into
+// tokens, parsed into a tree of expressions and then converted to the nodes of a config, which
+// checks the parameters when it is built like any other config.
+
+use crate::config::{
+    CellularDistanceConfig, CellularReturnConfig, ConfigError, FrequencyConfig, LacunarityConfig,
+    NodeConfig, NoiseConfig, OctaveConfig, OctaveOpConfig, SdfShapeConfig, CONFIG_VERSION,
+};
+use crate::Noise;
+
+// How deep parentheses, arguments and method calls can be nested, so that a malicious or
+// broken expression can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+// Where a token starts in the text, both start at 1.
+#[derive(Clone, Copy, Debug)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+fn error(message: impl std::fmt::Display, position: Position) -> ConfigError {
+    ConfigError::Syntax(format!("{message} at {position}"))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    String(String),
+    // One of ( ) [ ] , . + - * /
+    Symbol(char),
+    End,
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, Position)>, ConfigError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut position = Position { line: 1, column: 1 };
+    // Moves past the next char, keeping track of the position
+    let advance = |chars: &mut std::iter::Peekable<std::str::Chars>, position: &mut Position| {
+        let c = chars.next();
+        if c == Some('\n') {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+        c
+    };
+
+    while let Some(&c) = chars.peek() {
+        let start = position;
+        let token = match c {
+            c if c.is_whitespace() => {
+                advance(&mut chars, &mut position);
+                continue;
+            }
+            '(' | ')' | '[' | ']' | ',' | '.' | '+' | '-' | '*' | '/' => {
+                advance(&mut chars, &mut position);
+                Token::Symbol(c)
+            }
+            '0'..='9' => {
+                let mut number = String::new();
+                let mut previous = ' ';
+                while let Some(&c) = chars.peek() {
+                    // A dot only belongs to the number if a digit follows it, `1.abs()` is a
+                    // method call on 1.
+                    let fraction = c == '.'
+                        && !number.contains(['.', 'e', 'E'])
+                        && chars.clone().nth(1).is_some_and(|c| c.is_ascii_digit());
+                    let exponent = matches!(c, 'e' | 'E') && !number.contains(['e', 'E']);
+                    let sign = matches!(c, '+' | '-') && matches!(previous, 'e' | 'E');
+                    if !(c.is_ascii_digit() || fraction || exponent || sign) {
+                        break;
+                    }
+                    number.push(c);
+                    previous = c;
+                    advance(&mut chars, &mut position);
+                }
+                match number.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => return Err(error(format!("invalid number '{number}'"), start)),
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut identifier = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    identifier.push(c);
+                    advance(&mut chars, &mut position);
+                }
+                Token::Identifier(identifier)
+            }
+            '"' => {
+                advance(&mut chars, &mut position);
+                let mut string = String::new();
+                loop {
+                    match advance(&mut chars, &mut position) {
+                        Some('"') => break,
+                        Some('\\') => match advance(&mut chars, &mut position) {
+                            Some(c @ ('"' | '\\')) => string.push(c),
+                            _ => return Err(error("invalid escape in string", start)),
+                        },
+                        Some(c) => string.push(c),
+                        None => return Err(error("unterminated string", start)),
+                    }
+                }
+                Token::String(string)
+            }
+            _ => return Err(error(format!("unexpected character '{c}'"), start)),
+        };
+        tokens.push((token, start));
+    }
+    tokens.push((Token::End, position));
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expression {
+    Number(f64),
+    String(String),
+    // A name on its own, e.g. the distance of cellular noise
+    Identifier(String),
+    Array(Vec<Located>),
+    // A function, `simplex(0.01)`
+    Call {
+        name: String,
+        arguments: Vec<Located>,
+    },
+    // A method called on a noise, `noise.fbm(4, 0.5, 2.0)`
+    Method {
+        receiver: Box<Located>,
+        name: String,
+        arguments: Vec<Located>,
+    },
+    // One of + - * /
+    Binary {
+        operator: char,
+        left: Box<Located>,
+        right: Box<Located>,
+    },
+    Negate(Box<Located>),
+}
+
+#[derive(Clone, Debug)]
+struct Located {
+    expression: Expression,
+    position: Position,
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    next: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &(Token, Position) {
+        &self.tokens[self.next]
+    }
+
+    fn advance(&mut self) -> (Token, Position) {
+        let token = self.tokens[self.next].clone();
+        if token.0 != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+
+    // Moves past the next token if it is the symbol
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek().0 == Token::Symbol(symbol) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ConfigError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{symbol}'")))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ConfigError {
+        let (token, position) = self.peek();
+        let found = match token {
+            Token::Number(number) => format!("{number}"),
+            Token::Identifier(identifier) => format!("'{identifier}'"),
+            Token::String(string) => format!("\"{string}\""),
+            Token::Symbol(symbol) => format!("'{symbol}'"),
+            Token::End => "the end".to_owned(),
+        };
+        error(format!("expected {expected}, found {found}"), *position)
+    }
+
+    // Sums bind the loosest, then products, then signs, then method calls
+    fn sum(&mut self) -> Result<Located, ConfigError> {
+        self.binary(&['+', '-'], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Located, ConfigError> {
+        self.binary(&['*', '/'], Self::unary)
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[char],
+        operand: fn(&mut Self) -> Result<Located, ConfigError>,
+    ) -> Result<Located, ConfigError> {
+        let mut left = operand(self)?;
+        while let (Token::Symbol(operator), position) = self.peek().clone() {
+            if !operators.contains(&operator) {
+                break;
+            }
+            self.advance();
+            let right = operand(self)?;
+            left = Located {
+                expression: Expression::Binary {
+                    operator,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                position,
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Located, ConfigError> {
+        let position = self.peek().1;
+        if self.eat('-') {
+            let operand = self.nested(Self::unary)?;
+            Ok(Located {
+                expression: Expression::Negate(Box::new(operand)),
+                position,
+            })
+        } else {
+            self.postfix()
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Located, ConfigError> {
+        let mut receiver = self.primary()?;
+        while self.eat('.') {
+            let (Token::Identifier(name), position) = self.peek().clone() else {
+                return Err(self.unexpected("a method"));
+            };
+            self.advance();
+            self.expect('(')?;
+            let arguments = self.list(')')?;
+            receiver = Located {
+                expression: Expression::Method {
+                    receiver: Box::new(receiver),
+                    name,
+                    arguments,
+                },
+                position,
+            };
+        }
+        Ok(receiver)
+    }
+
+    fn primary(&mut self) -> Result<Located, ConfigError> {
+        let (token, position) = self.peek().clone();
+        let expression = match token {
+            Token::Number(number) => {
+                self.advance();
+                Expression::Number(number)
+            }
+            Token::String(string) => {
+                self.advance();
+                Expression::String(string)
+            }
+            Token::Identifier(name) => {
+                self.advance();
+                if self.eat('(') {
+                    let arguments = self.list(')')?;
+                    Expression::Call { name, arguments }
+                } else {
+                    Expression::Identifier(name)
+                }
+            }
+            Token::Symbol('(') => {
+                self.advance();
+                let inner = self.nested(Self::sum)?;
+                self.expect(')')?;
+                return Ok(inner);
+            }
+            Token::Symbol('[') => {
+                self.advance();
+                Expression::Array(self.list(']')?)
+            }
+            _ => return Err(self.unexpected("a noise or a number")),
+        };
+        Ok(Located {
+            expression,
+            position,
+        })
+    }
+
+    // The comma separated expressions up to the closing symbol, the opening one has been read.
+    fn list(&mut self, close: char) -> Result<Vec<Located>, ConfigError> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(self.nested(Self::sum)?);
+            if !self.eat(',') && self.peek().0 != Token::Symbol(close) {
+                return Err(self.unexpected(&format!("',' or '{close}'")));
+            }
+        }
+        Ok(items)
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Located, ConfigError>,
+    ) -> Result<Located, ConfigError> {
+        if self.depth == MAX_DEPTH {
+            return Err(error("the expression is nested too deeply", self.peek().1));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+}
+
+impl Located {
+    // The value of expressions made only of numbers, e.g. `-0.5` or `1 / 3`
+    fn number(&self) -> Option<f64> {
+        match &self.expression {
+            Expression::Number(number) => Some(*number),
+            Expression::Negate(operand) => operand.number().map(|number| -number),
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let (left, right) = (left.number()?, right.number()?);
+                Some(match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ => left / right,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn node(&self) -> Result<NodeConfig, ConfigError> {
+        if let Some(value) = self.number() {
+            return Ok(NodeConfig::Constant {
+                value: value as f32,
+            });
+        }
+
+        match &self.expression {
+            Expression::Call { name, arguments } => {
+                let mut arguments = Arguments::new(name, arguments, self.position);
+                let node = source(name, &mut arguments)?;
+                arguments.finish()?;
+                Ok(node)
+            }
+            Expression::Method {
+                receiver,
+                name,
+                arguments,
+            } => {
+                let noise = Box::new(receiver.node()?);
+                let mut arguments = Arguments::new(name, arguments, self.position);
+                let node = method(noise, name, &mut arguments)?;
+                arguments.finish()?;
+                Ok(node)
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let noise = Box::new(left.node()?);
+                // Like the operators of `Noise`, subtracting and dividing by a number adds and
+                // multiplies by a constant.
+                Ok(match (operator, right.number()) {
+                    ('-', Some(value)) => NodeConfig::Add {
+                        noise,
+                        other: Box::new(NodeConfig::Constant {
+                            value: -value as f32,
+                        }),
+                    },
+                    ('/', Some(0.0)) => return Err(error("division by 0", self.position)),
+                    ('/', Some(divisor)) => NodeConfig::Mul {
+                        noise,
+                        other: Box::new(NodeConfig::Constant {
+                            value: (1.0 / divisor) as f32,
+                        }),
+                    },
+                    (operator, _) => {
+                        let other = Box::new(right.node()?);
+                        match operator {
+                            '+' => NodeConfig::Add { noise, other },
+                            '-' => NodeConfig::Sub { noise, other },
+                            '*' => NodeConfig::Mul { noise, other },
+                            _ => NodeConfig::Div { noise, other },
+                        }
+                    }
+                })
+            }
+            // Negated numbers were folded above, the noise is multiplied by -1 like `-noise`.
+            Expression::Negate(operand) => Ok(NodeConfig::Mul {
+                noise: Box::new(operand.node()?),
+                other: Box::new(NodeConfig::Constant { value: -1.0 }),
+            }),
+            _ => Err(error("expected a noise or a number", self.position)),
+        }
+    }
+}
+
+// The arguments of a function or method, taken in order
+struct Arguments<'a> {
+    name: &'a str,
+    arguments: &'a [Located],
+    next: usize,
+    position: Position,
+}
+
+impl<'a> Arguments<'a> {
+    fn new(name: &'a str, arguments: &'a [Located], position: Position) -> Self {
+        Self {
+            name,
+            arguments,
+            next: 0,
+            position,
+        }
+    }
+
+    fn next(&mut self, what: &str) -> Result<&'a Located, ConfigError> {
+        let Some(argument) = self.arguments.get(self.next) else {
+            return Err(error(
+                format!("'{}' is missing its {what}", self.name),
+                self.position,
+            ));
+        };
+        self.next += 1;
+        Ok(argument)
+    }
+
+    fn finish(self) -> Result<(), ConfigError> {
+        match self.arguments.get(self.next) {
+            Some(extra) => Err(error(
+                match self.next {
+                    0 => format!("'{}' takes no arguments", self.name),
+                    1 => format!("'{}' takes 1 argument", self.name),
+                    count => format!("'{}' takes {count} arguments", self.name),
+                },
+                extra.position,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn noise(&mut self, what: &str) -> Result<Box<NodeConfig>, ConfigError> {
+        Ok(Box::new(self.next(what)?.node()?))
+    }
+
+    fn number(&mut self, what: &str) -> Result<f32, ConfigError> {
+        let argument = self.next(what)?;
+        number(argument, what)
+    }
+
+    fn integer(&mut self, what: &str) -> Result<u32, ConfigError> {
+        let argument = self.next(what)?;
+        integer(argument, what)
+    }
+
+    fn string(&mut self, what: &str) -> Result<String, ConfigError> {
+        let argument = self.next(what)?;
+        match &argument.expression {
+            Expression::String(string) => Ok(string.clone()),
+            _ => Err(error(
+                format!("the {what} must be a string"),
+                argument.position,
+            )),
+        }
+    }
+
+    fn identifier(&mut self, what: &str) -> Result<(&'a str, Position), ConfigError> {
+        let argument = self.next(what)?;
+        match &argument.expression {
+            Expression::Identifier(identifier) => Ok((identifier.as_str(), argument.position)),
+            _ => Err(error(
+                format!("the {what} must be a name"),
+                argument.position,
+            )),
+        }
+    }
+
+    fn vector(&mut self, what: &str) -> Result<[f32; 3], ConfigError> {
+        let argument = self.next(what)?;
+        vector(argument, what)
+    }
+
+    // A number for all the axes or [x, y, z]
+    fn axes(&mut self, what: &str) -> Result<Result<f32, [f32; 3]>, ConfigError> {
+        let argument = self.next(what)?;
+        match &argument.expression {
+            Expression::Array(_) => Ok(Err(vector(argument, what)?)),
+            _ => Ok(Ok(number(argument, what)?)),
+        }
+    }
+
+    fn frequency(&mut self) -> Result<FrequencyConfig, ConfigError> {
+        Ok(match self.axes("frequency")? {
+            Ok(frequency) => FrequencyConfig::Uniform(frequency),
+            Err(axes) => FrequencyConfig::Axes(axes),
+        })
+    }
+
+    fn lacunarity(&mut self) -> Result<LacunarityConfig, ConfigError> {
+        Ok(match self.axes("lacunarity")? {
+            Ok(lacunarity) => LacunarityConfig::Uniform(lacunarity),
+            Err(axes) => LacunarityConfig::Axes(axes),
+        })
+    }
+}
+
+fn number(argument: &Located, what: &str) -> Result<f32, ConfigError> {
+    argument
+        .number()
+        .map(|number| number as f32)
+        .ok_or_else(|| error(format!("the {what} must be a number"), argument.position))
+}
+
+fn integer(argument: &Located, what: &str) -> Result<u32, ConfigError> {
+    match argument.number() {
+        Some(number) if number.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&number) => {
+            Ok(number as u32)
+        }
+        _ => Err(error(
+            format!("the {what} must be a whole number of 0 or more"),
+            argument.position,
+        )),
+    }
+}
+
+fn vector(argument: &Located, what: &str) -> Result<[f32; 3], ConfigError> {
+    let invalid = || error(format!("the {what} must be [x, y, z]"), argument.position);
+    let Expression::Array(items) = &argument.expression else {
+        return Err(invalid());
+    };
+    let [x, y, z] = items.as_slice() else {
+        return Err(invalid());
+    };
+    Ok([number(x, what)?, number(y, what)?, number(z, what)?])
+}
+
+// The functions that create a noise, named and taking the arguments of the function of `Noise`
+fn source(name: &str, arguments: &mut Arguments) -> Result<NodeConfig, ConfigError> {
+    Ok(match name {
+        "simplex" => NodeConfig::Simplex {
+            frequency: arguments.frequency()?,
+        },
+        "perlin" => NodeConfig::Perlin {
+            frequency: arguments.frequency()?,
+        },
+        "opensimplex2" => NodeConfig::OpenSimplex2 {
+            frequency: arguments.frequency()?,
+        },
+        "value" => NodeConfig::Value {
+            frequency: arguments.frequency()?,
+        },
+        "value_cubic" => NodeConfig::ValueCubic {
+            frequency: arguments.frequency()?,
+        },
+        "cellular" => NodeConfig::Cellular {
+            frequency: arguments.frequency()?,
+            distance: CellularDistanceConfig::default(),
+            return_type: CellularReturnConfig::default(),
+        },
+        "cellular_with" => {
+            let frequency = arguments.frequency()?;
+            let distance = match arguments.identifier("distance")? {
+                ("euclidean", _) => CellularDistanceConfig::Euclidean,
+                ("manhattan", _) => CellularDistanceConfig::Manhattan,
+                (_, position) => {
+                    return Err(error(
+                        "the distance must be euclidean or manhattan",
+                        position,
+                    ))
+                }
+            };
+            let return_type =
+                match arguments.identifier("return type")? {
+                    ("f1", _) => CellularReturnConfig::F1,
+                    ("f2", _) => CellularReturnConfig::F2,
+                    ("f2_minus_f1", _) => CellularReturnConfig::F2MinusF1,
+                    ("cell_value", _) => CellularReturnConfig::CellValue,
+                    ("edge_distance", _) => CellularReturnConfig::EdgeDistance,
+                    (_, position) => return Err(error(
+                        "the return type must be f1, f2, f2_minus_f1, cell_value or edge_distance",
+                        position,
+                    )),
+                };
+            NodeConfig::Cellular {
+                frequency,
+                distance,
+                return_type,
+            }
+        }
+        "white" => NodeConfig::White {},
+        "pink" => NodeConfig::Pink {
+            frequency: arguments.number("frequency")?,
+        },
+        "brown" => NodeConfig::Brown {
+            frequency: arguments.number("frequency")?,
+        },
+        "spectral" => NodeConfig::Spectral {
+            lowest_frequency: arguments.number("lowest frequency")?,
+            highest_frequency: arguments.number("highest frequency")?,
+            beta: arguments.number("beta")?,
+            waves: arguments.integer("waves")?,
+        },
+        "dots" => NodeConfig::Dots {
+            frequency: arguments.frequency()?,
+            density: arguments.number("density")?,
+            radius: arguments.number("radius")?,
+            amplitude_jitter: arguments.number("amplitude jitter")?,
+        },
+        "constant" => NodeConfig::Constant {
+            value: arguments.number("value")?,
+        },
+        "param" => NodeConfig::Param {
+            name: arguments.string("name")?,
+            value: arguments.number("value")?,
+        },
+        "falloff_radial" => NodeConfig::FalloffRadial {
+            center_x: arguments.number("center x")?,
+            center_z: arguments.number("center z")?,
+            inner_radius: arguments.number("inner radius")?,
+            outer_radius: arguments.number("outer radius")?,
+        },
+        "sdf" => NodeConfig::Sdf {
+            shape: shape(arguments.next("shape")?)?,
+        },
+        "veins" => NodeConfig::Veins {
+            frequency: arguments.number("frequency")?,
+            thickness: arguments.number("thickness")?,
+            elongation: arguments.number("elongation")?,
+        },
+        "avg_n" => {
+            let argument = arguments.next("noises")?;
+            let Expression::Array(noises) = &argument.expression else {
+                return Err(error(
+                    "the noises must be a list, [a, b, ..]",
+                    argument.position,
+                ));
+            };
+            NodeConfig::Avg {
+                noises: noises.iter().map(Located::node).collect::<Result<_, _>>()?,
+            }
+        }
+        _ => return Err(error(format!("unknown noise '{name}'"), arguments.position)),
+    })
+}
+
+// The shape of sdf noise, written like the variant of `SdfShape`, e.g. `sphere([0, 0, 0], 10)`
+fn shape(argument: &Located) -> Result<SdfShapeConfig, ConfigError> {
+    let Expression::Call { name, arguments } = &argument.expression else {
+        return Err(error(
+            "the shape must be a sphere, box, plane or capsule",
+            argument.position,
+        ));
+    };
+    let mut arguments = Arguments::new(name, arguments, argument.position);
+    let shape = match name.as_str() {
+        "sphere" => SdfShapeConfig::Sphere {
+            center: arguments.vector("center")?,
+            radius: arguments.number("radius")?,
+        },
+        "box" => SdfShapeConfig::Box {
+            center: arguments.vector("center")?,
+            half_size: arguments.vector("half size")?,
+        },
+        "plane" => SdfShapeConfig::Plane {
+            normal: arguments.vector("normal")?,
+            offset: arguments.number("offset")?,
+        },
+        "capsule" => SdfShapeConfig::Capsule {
+            start: arguments.vector("start")?,
+            end: arguments.vector("end")?,
+            radius: arguments.number("radius")?,
+        },
+        _ => return Err(error(format!("unknown shape '{name}'"), argument.position)),
+    };
+    arguments.finish()?;
+    Ok(shape)
+}
+
+// The methods that change or combine a noise, named and taking the arguments of the method of
+// `Noise`
+fn method(
+    noise: Box<NodeConfig>,
+    name: &str,
+    arguments: &mut Arguments,
+) -> Result<NodeConfig, ConfigError> {
+    Ok(match name {
+        "fbm" | "fbm_with" => NodeConfig::Fbm {
+            noise,
+            octaves: arguments.integer("octaves")?,
+            gain: arguments.number("gain")?,
+            lacunarity: arguments.lacunarity()?,
+            op: if name == "fbm_with" {
+                Some(match arguments.identifier("op")? {
+                    ("abs", _) => OctaveOpConfig::Abs,
+                    ("square", _) => OctaveOpConfig::Square,
+                    ("ridge", _) => OctaveOpConfig::Ridge,
+                    (_, position) => {
+                        return Err(error("the op must be abs, square or ridge", position))
+                    }
+                })
+            } else {
+                None
+            },
+        },
+        "ridged" => NodeConfig::Ridged {
+            noise,
+            octaves: arguments.integer("octaves")?,
+            gain: arguments.number("gain")?,
+            lacunarity: arguments.lacunarity()?,
+        },
+        "ping_pong" => NodeConfig::PingPong {
+            noise,
+            octaves: arguments.integer("octaves")?,
+            gain: arguments.number("gain")?,
+            lacunarity: arguments.lacunarity()?,
+            strength: arguments.number("strength")?,
+        },
+        "fbm_custom" => {
+            let argument = arguments.next("octaves")?;
+            let invalid = |position| {
+                error(
+                    "the octaves must be a list of [frequency_mul, amplitude, seed_offset], \
+                     the seed offset can be left out",
+                    position,
+                )
+            };
+            let Expression::Array(octaves) = &argument.expression else {
+                return Err(invalid(argument.position));
+            };
+            let octaves = octaves
+                .iter()
+                .map(|octave| {
+                    let Expression::Array(items) = &octave.expression else {
+                        return Err(invalid(octave.position));
+                    };
+                    let (frequency_mul, amplitude, seed_offset) = match items.as_slice() {
+                        [frequency_mul, amplitude] => (frequency_mul, amplitude, None),
+                        [frequency_mul, amplitude, seed_offset] => {
+                            (frequency_mul, amplitude, Some(seed_offset))
+                        }
+                        _ => return Err(invalid(octave.position)),
+                    };
+                    Ok(OctaveConfig {
+                        frequency_mul: number(frequency_mul, "frequency_mul")?,
+                        amplitude: number(amplitude, "amplitude")?,
+                        seed_offset: match seed_offset {
+                            Some(seed_offset) => integer(seed_offset, "seed_offset")?,
+                            None => 0,
+                        },
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            NodeConfig::FbmCustom { noise, octaves }
+        }
+        "wrap_domain" => NodeConfig::WrapDomain {
+            noise,
+            period: [
+                arguments.number("period x")?,
+                arguments.number("period y")?,
+                arguments.number("period z")?,
+            ],
+        },
+        "warp" => NodeConfig::Warp {
+            noise,
+            offset: arguments.noise("offset")?,
+            amplitude: arguments.number("amplitude")?,
+        },
+        "slope" => NodeConfig::Slope { noise },
+        "extrude_2d" => NodeConfig::Extrude2d { noise },
+        "interpolated" => NodeConfig::Interpolated {
+            noise,
+            spacing: arguments.number("spacing")?,
+        },
+        "abs" => NodeConfig::Abs { noise },
+        "square" => NodeConfig::Square { noise },
+        "dither" => NodeConfig::Dither {
+            noise,
+            min: arguments.number("min")?,
+            max: arguments.number("max")?,
+            levels: arguments.integer("levels")?,
+        },
+        "add" => NodeConfig::Add {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "sub" => NodeConfig::Sub {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "abs_diff" => NodeConfig::AbsDiff {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "avg" => NodeConfig::Avg {
+            noises: vec![*noise, *arguments.noise("other noise")?],
+        },
+        "mul" => NodeConfig::Mul {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "div" => NodeConfig::Div {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "max" => NodeConfig::Max {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "min" => NodeConfig::Min {
+            noise,
+            other: arguments.noise("other noise")?,
+        },
+        "clamp" => NodeConfig::Clamp {
+            noise,
+            min: arguments.number("min")?,
+            max: arguments.number("max")?,
+        },
+        "lerp" | "lerp_with_range" => {
+            let selector_range = if name == "lerp_with_range" {
+                Some([
+                    arguments.number("selector min")?,
+                    arguments.number("selector max")?,
+                ])
+            } else {
+                None
+            };
+            NodeConfig::Lerp {
+                noise,
+                low: arguments.noise("low noise")?,
+                high: arguments.noise("high noise")?,
+                selector_range,
+            }
+        }
+        "lerp_values" => NodeConfig::LerpValues {
+            noise,
+            low: arguments.number("low")?,
+            high: arguments.number("high")?,
+        },
+        "range" => NodeConfig::Range {
+            noise,
+            low: arguments.number("low")?,
+            high: arguments.number("high")?,
+            low_noise: arguments.noise("low noise")?,
+            high_noise: arguments.noise("high noise")?,
+        },
+        _ => {
+            return Err(error(
+                format!("unknown method '{name}'"),
+                arguments.position,
+            ))
+        }
+    })
+}
+
+impl NoiseConfig {
+    /// Reads a noise written as an expression, see the [module](crate::config) docs. The
+    /// config has no seed and the first [Algorithm](crate::Algorithm), like a noise built in
+    /// Rust.
+    ///
+    /// # Example
+    /// ```
+    /// let config = NoiseConfig::from_expression("perlin(0.01).fbm(5, 0.5, 2.0).abs() + 0.5")
+    ///     .unwrap();
+    /// // The same noise in the JSON format
+    /// let json = config.to_json();
+    /// ```
+    pub fn from_expression(expression: &str) -> Result<Self, ConfigError> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            next: 0,
+            depth: 0,
+        };
+        let noise = parser.sum()?;
+        if parser.peek().0 != Token::End {
+            return Err(parser.unexpected("an operator or the end"));
+        }
+        Ok(Self {
+            version: CONFIG_VERSION,
+            seed: None,
+            algorithm: None,
+            noise: noise.node()?,
+        })
+    }
+}
+
+impl Noise {
+    /// Builds a noise written as an expression, see [config](crate::config). Expressions are
+    /// the builder methods of [Noise] written like in Rust, combined with `+`, `-`, `*` and `/`.
+    ///
+    /// Requires the `config` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let terrain = Noise::from_expression(
+    ///     "simplex(0.005).fbm(5, 0.5, 2.0).warp(simplex(0.002), 30) * 40 + 64",
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn from_expression(expression: &str) -> Result<Self, ConfigError> {
+        NoiseConfig::from_expression(expression)?.build()
+    }
+}
@@ -0,0 +1,68 @@
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::NoisePipeline;
+
+/// Starts a sub-noise extruded from 2d. The nodes of the sub-noise are built for 2d, and the
+/// second dimension of 2d noise is stored in y. If the sub-noise was last run in the same column
+/// its result is reused, and the pipeline continues after the end of the sub-noise.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn extrude_2d_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let index = pipeline.index;
+    let column = pipeline
+        .columns
+        .iter()
+        .find(|column| column.start == index)
+        .unwrap();
+    if column.valid && column.x == pipeline.x && column.z == pipeline.z {
+        let (result, rng_seed, end) = (column.result, column.rng_seed, column.end);
+        pipeline.results.push(result);
+        pipeline.rng.current_seed = rng_seed;
+        pipeline.index = end;
+        pipeline.next();
+        return;
+    }
+
+    pipeline.save_coordinates();
+    pipeline.y = pipeline.z;
+    pipeline.next();
+}
+
+/// Ends a sub-noise extruded from 2d in 3d, and keeps its result for the rest of the column.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn extrude_2d_end<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    pipeline.restore_coordinates(false);
+    let index = pipeline.index;
+    let result = *pipeline.results.last().unwrap();
+    let (x, z, rng_seed) = (pipeline.x, pipeline.z, pipeline.rng.current_seed);
+    let column = pipeline
+        .columns
+        .iter_mut()
+        .find(|column| column.end == index)
+        .unwrap();
+    column.valid = true;
+    column.x = x;
+    column.z = z;
+    column.result = result;
+    column.rng_seed = rng_seed;
+    pipeline.next();
+}
+
+/// In 1d and 2d the coordinates are kept, they are only saved for the node that ends the
+/// sub-noise.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn extrude_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    pipeline.save_coordinates();
+    pipeline.next();
+}
@@ -0,0 +1,91 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// Smoothly goes from 1 at the inner radius to 0 at the outer radius.
+#[inline(always)]
+fn falloff<const N: usize>(distance: Simd<Float, N>, inner: Float, outer: Float) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let t = ((distance - Simd::splat(inner)) * Simd::splat(1.0 / (outer - inner)))
+        .simd_clamp(Simd::splat(0.0), Simd::splat(1.0));
+    // smoothstep
+    Simd::splat(1.0) - t * t * (Simd::splat(3.0) - Simd::splat(2.0) * t)
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn falloff_radial_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::FalloffRadial {
+        center_x,
+        inner_radius,
+        outer_radius,
+        ..
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let distance = (pipeline.x - Simd::splat(center_x as Float)).abs();
+    let result = falloff(distance, inner_radius as Float, outer_radius as Float);
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn falloff_radial_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::FalloffRadial {
+        center_x,
+        center_z,
+        inner_radius,
+        outer_radius,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    // The second dimension of 2d noise is stored in y
+    let dx = pipeline.x - Simd::splat(center_x as Float);
+    let dz = pipeline.y - Simd::splat(center_z as Float);
+    let distance = dx.mul_add(dx, dz * dz).sqrt();
+    let result = falloff(distance, inner_radius as Float, outer_radius as Float);
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn falloff_radial_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::FalloffRadial {
+        center_x,
+        center_z,
+        inner_radius,
+        outer_radius,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    // The falloff is a cylinder, it doesn't change with height.
+    let dx = pipeline.x - Simd::splat(center_x as Float);
+    let dz = pipeline.z - Simd::splat(center_z as Float);
+    let distance = dx.mul_add(dx, dz * dz).sqrt();
+    let result = falloff(distance, inner_radius as Float, outer_radius as Float);
+    pipeline.results.push(result);
+    pipeline.next();
+}
@@ -1,26 +1,28 @@
 use multiversion::multiversion;
 
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
-use std::simd::{LaneCount, Simd, SupportedLaneCount};
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn fbm<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn fbm<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
     let NoiseSettings::Fbm {
         octaves,
         gain,
         first_octave_amplitude,
-    } = node.settings
+    } = *settings
     else {
         unreachable!()
     };
 
-    let gain = Simd::splat(gain);
-    let mut amplitude = Simd::splat(first_octave_amplitude);
+    let gain = Simd::splat(gain as Float);
+    let mut amplitude = Simd::splat(first_octave_amplitude as Float);
     let mut result = Simd::splat(0.0);
 
     for _ in 0..octaves {
@@ -32,3 +34,82 @@ where
     pipeline.results.push(result);
     pipeline.next();
 }
+
+/// Sums the octaves as ridges, 1 - |n| squared, each weighted by the octave before it. The
+/// octaves are popped from the lowest frequency to the highest.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn ridged<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::Ridged {
+        octaves,
+        gain,
+        first_octave_amplitude,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let one = Simd::splat(1.0);
+    let gain = Simd::splat(gain as Float);
+    let mut amplitude = Simd::splat(first_octave_amplitude as Float);
+    let mut weight = one;
+    let mut result = Simd::splat(0.0);
+
+    for _ in 0..octaves {
+        let noise = pipeline.results.pop().unwrap();
+        let ridge = one - noise.abs();
+        let signal = ridge * ridge * weight;
+        // Like Musgrave's ridged multifractal, the next octave fades out where this one is far
+        // from a ridge.
+        weight = (signal + signal).simd_clamp(Simd::splat(0.0), one);
+        result = signal.mul_add(amplitude, result);
+        amplitude *= gain;
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+/// Sums the octaves folded by a triangle wave, like the PingPong fractal of FastNoiseLite. The
+/// octaves are popped from the lowest frequency to the highest.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn ping_pong<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::PingPong {
+        octaves,
+        gain,
+        strength,
+        first_octave_amplitude,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let one = Simd::splat(1.0);
+    let two = Simd::splat(2.0);
+    let strength = Simd::splat(strength as Float);
+    let gain = Simd::splat(gain as Float);
+    let mut amplitude = Simd::splat(first_octave_amplitude as Float);
+    let mut result = Simd::splat(0.0);
+
+    for _ in 0..octaves {
+        let noise = pipeline.results.pop().unwrap();
+        // Wrapped into 0..2, then folded back down from 1
+        let t = (noise + one) * strength;
+        let t = t - (t * Simd::splat(0.5)).floor() * two;
+        let folded = one - (t - one).abs();
+        result = folded.mul_add(two, -one).mul_add(amplitude, result);
+        amplitude *= gain;
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
@@ -0,0 +1,139 @@
+// Kernels that do the work of a run of adjacent nodes, see `NoisePipeline::build`. They produce
+// the same values as the nodes would separately, and skip the rest of the run when done.
+
+use std::simd::{num::SimdFloat, LaneCount, Simd, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+/// Abs followed by Square, |n|² is the same as n².
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn abs_square<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let noise = pipeline.results.pop().unwrap();
+    pipeline.results.push(noise * noise);
+    pipeline.next_fused();
+}
+
+/// Abs followed by LerpValues, e.g. the ridges of `OctaveOp::Ridge`.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn abs_lerp_values<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let NoiseSettings::LerpValues { low, high } = *pipeline.fused_settings() else {
+        unreachable!()
+    };
+
+    let noise = pipeline.results.pop().unwrap().abs();
+    let (low, high) = (low as Float, high as Float);
+    let scale = (high - low) * 0.5;
+    let offset = (high + low) * 0.5;
+    let result = noise.mul_add(Simd::splat(scale), Simd::splat(offset));
+
+    pipeline.results.push(result);
+    pipeline.next_fused();
+}
+
+/// Clamp followed by LerpValues
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn clamp_lerp_values<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let NoiseSettings::Clamp { min, max } = *pipeline.current_settings() else {
+        unreachable!()
+    };
+    let NoiseSettings::LerpValues { low, high } = *pipeline.fused_settings() else {
+        unreachable!()
+    };
+
+    let noise = pipeline.results.pop().unwrap();
+    let noise = noise.simd_clamp(Simd::splat(min as Float), Simd::splat(max as Float));
+    let (low, high) = (low as Float, high as Float);
+    let scale = (high - low) * 0.5;
+    let offset = (high + low) * 0.5;
+    let result = noise.mul_add(Simd::splat(scale), Simd::splat(offset));
+
+    pipeline.results.push(result);
+    pipeline.next_fused();
+}
+
+/// Mul followed by Add, where the other operand of the add is below the two multiplied noises
+/// on the stack, e.g. `a.add(b.mul(c))`.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn mul_add<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let left = pipeline.results.pop().unwrap();
+    let right = pipeline.results.pop().unwrap();
+    let other = pipeline.results.pop().unwrap();
+    // Not an fma, the result is rounded the same as with separate nodes.
+    pipeline.results.push(left * right + other);
+    pipeline.next_fused();
+}
+
+// The value of a constant or parameter node
+#[inline(always)]
+fn value(settings: &NoiseSettings) -> Float {
+    let (NoiseSettings::Constant { value } | NoiseSettings::Parameter { value, .. }) = *settings
+    else {
+        unreachable!()
+    };
+    value as Float
+}
+
+/// A constant followed by Add, the constant is added without taking a result.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn add_value<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let value = value(pipeline.current_settings());
+    let noise = pipeline.results.pop().unwrap();
+    pipeline.results.push(Simd::splat(value) + noise);
+    pipeline.next_fused();
+}
+
+/// A constant followed by Mul, the noise is scaled without taking a result.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn mul_value<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let value = value(pipeline.current_settings());
+    let noise = pipeline.results.pop().unwrap();
+    pipeline.results.push(Simd::splat(value) * noise);
+    pipeline.next_fused();
+}
+
+/// A constant followed by Mul, then another constant followed by Add, e.g.
+/// `noise.mul(Noise::constant(0.5)).add(Noise::constant(0.5))`.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn mul_add_value<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let scale = value(pipeline.current_settings());
+    let offset = value(pipeline.fused_settings_at(2));
+    let noise = pipeline.results.pop().unwrap();
+    // Not an fma, the result is rounded the same as with separate nodes.
+    pipeline
+        .results
+        .push(Simd::splat(offset) + Simd::splat(scale) * noise);
+    pipeline.next_fused_run(4);
+}
+
+/// An add of 0 or a multiplication by 1, the noise is left as it is.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn skip<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    pipeline.next_fused();
+}
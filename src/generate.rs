@@ -0,0 +1,353 @@
+// The drivers of the generate functions, they fill the result one vector of `Float`s at a time.
+// Blocks and statistics are recorded as f32, only the f32 results have them.
+
+use std::simd::prelude::*;
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use multiversion::{multiversion, simd_width};
+
+use super::Float;
+use crate::{lane_coordinates, BlockRanges, Dimensions, Noise, NoisePipeline, Statistics};
+
+// A vector for the result of a driver, the drivers write every value.
+fn uninit_result(len: usize) -> Vec<Float> {
+    let mut result = Vec::with_capacity(len);
+    unsafe {
+        result.set_len(len);
+    }
+    result
+}
+
+pub(crate) fn generate_1d(
+    noise: &Noise,
+    x: f64,
+    step: f64,
+    width: usize,
+) -> (Vec<Float>, Float, Float) {
+    let mut result = uninit_result(width);
+    let (min, max) = unsafe { (generate_1d_into())(noise, x, step, &mut result) };
+    (result, min, max)
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+pub(crate) fn generate_1d_into(
+    noise: &Noise,
+    x: f64,
+    step: f64,
+    result: &mut [Float],
+) -> (Float, Float) {
+    with_lane_width!(
+        simd_width!(Float),
+        generate_1d_lanes(noise, x, step, result)
+    )
+}
+
+#[inline(always)]
+fn generate_1d_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    step: f64,
+    result: &mut [Float],
+) -> (Float, Float)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut min_s = Simd::splat(Float::MAX);
+    let mut max_s = Simd::splat(Float::MIN);
+    let mut min = Float::MAX;
+    let mut max = Float::MIN;
+
+    let width = result.len();
+    let vector_width = N;
+    let remainder = width % vector_width;
+
+    let mut pipeline = NoisePipeline::<N, Float>::acquire(noise, Dimensions::X);
+
+    let mut i = 0;
+    for _ in 0..width / vector_width {
+        pipeline.x = lane_coordinates(x + i as f64 * step, step);
+        let f = pipeline.execute();
+        max_s = max_s.simd_max(f);
+        min_s = min_s.simd_min(f);
+        f.copy_to_slice(&mut result[i..]);
+        i += vector_width;
+    }
+    if remainder != 0 {
+        pipeline.x = lane_coordinates(x + i as f64 * step, step);
+        let f = pipeline.execute();
+        for j in 0..remainder {
+            let n = f[j];
+            unsafe {
+                *result.get_unchecked_mut(i) = n;
+            }
+            if n < min {
+                min = n;
+            }
+            if n > max {
+                max = n;
+            }
+            i += 1;
+        }
+    }
+    pipeline.release();
+    for i in 0..vector_width {
+        if min_s[i] < min {
+            min = min_s[i];
+        }
+        if max_s[i] > max {
+            max = max_s[i];
+        }
+    }
+    (min, max)
+}
+
+pub(crate) fn generate_2d(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    statistics: Option<&mut Statistics>,
+) -> (Vec<Float>, Float, Float) {
+    let mut result = uninit_result(width * height);
+    let (min, max) =
+        unsafe { (generate_2d_into())(noise, x, y, width, height, statistics, &mut result) };
+    (result, min, max)
+}
+
+// `result` holds `width * height` values
+#[multiversion(targets = "simd", dispatcher = "cached")]
+pub(crate) fn generate_2d_into(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    statistics: Option<&mut Statistics>,
+    result: &mut [Float],
+) -> (Float, Float) {
+    with_lane_width!(
+        simd_width!(Float),
+        generate_2d_lanes(noise, x, y, width, height, statistics, result)
+    )
+}
+
+#[inline(always)]
+fn generate_2d_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    mut statistics: Option<&mut Statistics>,
+    result: &mut [Float],
+) -> (Float, Float)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut min_s = Simd::splat(Float::MAX);
+    let mut max_s = Simd::splat(Float::MIN);
+    let mut min = Float::MAX;
+    let mut max = Float::MIN;
+
+    let vector_width = N;
+    let remainder = height % vector_width;
+
+    let mut pipeline = NoisePipeline::<N, Float>::acquire(noise, Dimensions::XY);
+
+    let mut i = 0;
+    for xi in 0..width {
+        pipeline.x = Simd::splat((x + xi as f64) as Float);
+        for yi in (0..height - remainder).step_by(vector_width) {
+            pipeline.y = lane_coordinates(y + yi as f64, 1.0);
+            let f = pipeline.execute();
+            max_s = max_s.simd_max(f);
+            min_s = min_s.simd_min(f);
+            f.copy_to_slice(&mut result[i..]);
+            if let Some(statistics) = statistics.as_deref_mut() {
+                statistics.record(f.cast::<f32>(), N);
+            }
+            i += vector_width;
+        }
+        if remainder != 0 {
+            pipeline.y = lane_coordinates(y + (height - remainder) as f64, 1.0);
+            let f = pipeline.execute();
+            if let Some(statistics) = statistics.as_deref_mut() {
+                statistics.record(f.cast::<f32>(), remainder);
+            }
+            for j in 0..remainder {
+                let n = f[j];
+                unsafe {
+                    *result.get_unchecked_mut(i) = n;
+                }
+                if n < min {
+                    min = n;
+                }
+                if n > max {
+                    max = n;
+                }
+                i += 1;
+            }
+        }
+    }
+    pipeline.release();
+    for i in 0..vector_width {
+        if min_s[i] < min {
+            min = min_s[i];
+        }
+        if max_s[i] > max {
+            max = max_s[i];
+        }
+    }
+    (min, max)
+}
+
+// Size of the tiles along each axis, 32³ values is 128KiB.
+const GENERATE_3D_TILE: usize = 32;
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn generate_3d(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    blocks: Option<&mut BlockRanges>,
+    statistics: Option<&mut Statistics>,
+) -> (Vec<Float>, Float, Float) {
+    let mut result = uninit_result(width * height * depth);
+    let origin = [x, y, z];
+    let size = [width, height, depth];
+    let (min, max) =
+        unsafe { (generate_3d_into())(noise, origin, None, size, blocks, statistics, &mut result) };
+    (result, min, max)
+}
+
+pub(crate) fn generate_4d(
+    noise: &Noise,
+    [x, y, z, w]: [f64; 4],
+    size: [usize; 3],
+) -> (Vec<Float>, Float, Float) {
+    let mut result = uninit_result(size.iter().product());
+    let (min, max) =
+        unsafe { (generate_3d_into())(noise, [x, y, z], Some(w), size, None, None, &mut result) };
+    (result, min, max)
+}
+
+// `result` holds `width * height * depth` values. A 4d cube is generated like a 3d one, with
+// the pipeline built for 4d and `w` the same for every value.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+pub(crate) fn generate_3d_into(
+    noise: &Noise,
+    origin: [f64; 3],
+    w: Option<f64>,
+    size: [usize; 3],
+    blocks: Option<&mut BlockRanges>,
+    statistics: Option<&mut Statistics>,
+    result: &mut [Float],
+) -> (Float, Float) {
+    with_lane_width!(
+        simd_width!(Float),
+        generate_3d_lanes(noise, origin, w, size, blocks, statistics, result)
+    )
+}
+
+#[inline(always)]
+fn generate_3d_lanes<const N: usize>(
+    noise: &Noise,
+    [x, y, z]: [f64; 3],
+    w: Option<f64>,
+    [width, height, depth]: [usize; 3],
+    mut blocks: Option<&mut BlockRanges>,
+    mut statistics: Option<&mut Statistics>,
+    result: &mut [Float],
+) -> (Float, Float)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut min_s = Simd::splat(Float::MAX);
+    let mut max_s = Simd::splat(Float::MIN);
+    let mut min = Float::MAX;
+    let mut max = Float::MIN;
+
+    let vector_width = N;
+
+    let mut pipeline = match w {
+        Some(w) => {
+            let mut pipeline = NoisePipeline::<N, Float>::acquire(noise, Dimensions::XYZW);
+            pipeline.w = Simd::splat(w as Float);
+            pipeline
+        }
+        None => NoisePipeline::<N, Float>::acquire(noise, Dimensions::XYZ),
+    };
+
+    // The volume is generated in tiles so that the part of the result being written stays in
+    // cache, sweeping whole axes of a large volume goes through memory faster than the values
+    // are generated. Within a tile, the values are generated in the same order as the result.
+    for tile_x in (0..width).step_by(GENERATE_3D_TILE) {
+        for tile_z in (0..depth).step_by(GENERATE_3D_TILE) {
+            for tile_y in (0..height).step_by(GENERATE_3D_TILE) {
+                let end = (tile_y + GENERATE_3D_TILE).min(height);
+                let remainder = (end - tile_y) % vector_width;
+
+                for xi in tile_x..(tile_x + GENERATE_3D_TILE).min(width) {
+                    pipeline.x = Simd::splat((x + xi as f64) as Float);
+                    for zi in tile_z..(tile_z + GENERATE_3D_TILE).min(depth) {
+                        pipeline.z = Simd::splat((z + zi as f64) as Float);
+                        let mut i = (xi * depth + zi) * height + tile_y;
+                        for yi in (tile_y..end - remainder).step_by(vector_width) {
+                            pipeline.y = lane_coordinates(y + yi as f64, 1.0);
+                            let f = pipeline.execute();
+                            max_s = max_s.simd_max(f);
+                            min_s = min_s.simd_min(f);
+                            f.copy_to_slice(&mut result[i..]);
+                            if let Some(blocks) = blocks.as_deref_mut() {
+                                blocks.record(xi, yi, zi, f.cast::<f32>().as_array());
+                            }
+                            if let Some(statistics) = statistics.as_deref_mut() {
+                                statistics.record(f.cast::<f32>(), N);
+                            }
+                            i += vector_width;
+                        }
+                        if remainder != 0 {
+                            pipeline.y = lane_coordinates(y + (end - remainder) as f64, 1.0);
+                            let f = pipeline.execute();
+                            if let Some(blocks) = blocks.as_deref_mut() {
+                                let values = f.cast::<f32>();
+                                blocks.record(xi, end - remainder, zi, &values[..remainder]);
+                            }
+                            if let Some(statistics) = statistics.as_deref_mut() {
+                                statistics.record(f.cast::<f32>(), remainder);
+                            }
+                            for j in 0..remainder {
+                                let n = f[j];
+                                unsafe {
+                                    *result.get_unchecked_mut(i) = n;
+                                }
+                                if n < min {
+                                    min = n;
+                                }
+                                if n > max {
+                                    max = n;
+                                }
+                                i += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    pipeline.release();
+    for i in 0..vector_width {
+        if min_s[i] < min {
+            min = min_s[i];
+        }
+        if max_s[i] > max {
+            max = max_s[i];
+        }
+    }
+    (min, max)
+}
@@ -0,0 +1,278 @@
+use std::sync::{Mutex, OnceLock};
+
+use crate::{shader, Dimensions, Noise, NoiseResult, UnsupportedNode};
+
+/// Returned by the `generate_*_gpu` functions of [Noise] when the noise can't be generated on
+/// the gpu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GpuError {
+    /// No gpu was found, or it could not be set up.
+    NoDevice,
+    /// The noise contains an operation that can't be translated to shader code.
+    Unsupported(UnsupportedNode),
+    /// A single slice of the first dimension has more values than fit in a gpu buffer.
+    TooLarge,
+}
+
+impl std::fmt::Display for GpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoDevice => write!(f, "no gpu is available"),
+            Self::Unsupported(unsupported) => unsupported.fmt(f),
+            Self::TooLarge => write!(f, "the noise is too large to generate on the gpu"),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+impl From<UnsupportedNode> for GpuError {
+    fn from(unsupported: UnsupportedNode) -> Self {
+        Self::Unsupported(unsupported)
+    }
+}
+
+// Number of compiled noises kept, the least recently used is dropped first.
+const GPU_PIPELINE_CACHE_SIZE: usize = 16;
+
+// Size of the uniform with the origin and the size, see `Noise::to_spirv`
+const PARAMS_SIZE: u64 = 32;
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    // Most recently used last
+    pipelines: Mutex<Vec<(Noise, Dimensions, wgpu::ComputePipeline)>>,
+}
+
+impl Gpu {
+    // The device is set up the first time it is needed and shared by all threads after.
+    fn get() -> Result<&'static Gpu, GpuError> {
+        static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+        GPU.get_or_init(|| pollster::block_on(Self::new()))
+            .as_ref()
+            .ok_or(GpuError::NoDevice)
+    }
+
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            })
+            .await
+            .ok()?;
+        // Ask for the largest buffers the adapter supports, so large volumes need fewer
+        // dispatches.
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("fmc_noise"),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            })
+            .await
+            .ok()?;
+
+        let entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("fmc_noise"),
+            entries: &[
+                entry(0, wgpu::BufferBindingType::Uniform),
+                entry(1, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("fmc_noise"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Some(Self {
+            device,
+            queue,
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn pipeline(
+        &self,
+        noise: &Noise,
+        dimensions: Dimensions,
+    ) -> Result<wgpu::ComputePipeline, UnsupportedNode> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+        if let Some(index) = pipelines
+            .iter()
+            .rposition(|(cached, cached_dimensions, _)| {
+                *cached_dimensions == dimensions && cached == noise
+            })
+        {
+            let entry = pipelines.remove(index);
+            let pipeline = entry.2.clone();
+            pipelines.push(entry);
+            return Ok(pipeline);
+        }
+
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("fmc_noise"),
+                source: wgpu::ShaderSource::Wgsl(shader::compute_module(noise)?.into()),
+            });
+        let entry_point = match dimensions {
+            Dimensions::X => "main_1d",
+            Dimensions::XY => "main_2d",
+            Dimensions::XYZ => "main_3d",
+            Dimensions::XYZW => unreachable!(),
+        };
+        let pipeline = self
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("fmc_noise"),
+                layout: Some(&self.pipeline_layout),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        if pipelines.len() == GPU_PIPELINE_CACHE_SIZE {
+            pipelines.remove(0);
+        }
+        pipelines.push((noise.clone(), dimensions, pipeline.clone()));
+        Ok(pipeline)
+    }
+}
+
+/// Generates the noise on the gpu with the same layout as the `generate_*` functions. Volumes
+/// larger than a gpu buffer are generated in slabs along the first dimension.
+pub(crate) fn generate(
+    noise: &Noise,
+    dimensions: Dimensions,
+    [x, y, z]: [f64; 3],
+    [width, height, depth]: [usize; 3],
+) -> Result<NoiseResult, GpuError> {
+    let gpu = Gpu::get()?;
+    let pipeline = gpu.pipeline(noise, dimensions)?;
+
+    let limits = gpu.device.limits();
+    let max_values = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size)
+        / std::mem::size_of::<f32>() as u64;
+    let slice = (height * depth) as u64;
+    if slice > max_values {
+        return Err(GpuError::TooLarge);
+    }
+    let slab_width = (max_values / slice.max(1)).min(width as u64) as usize;
+    let buffer_size = (slab_width * height * depth * std::mem::size_of::<f32>()) as u64;
+
+    let mut values = Vec::with_capacity(width * height * depth);
+    if buffer_size == 0 {
+        return Ok(NoiseResult::new(
+            (values, f32::MAX, f32::MIN),
+            width,
+            height,
+            depth,
+        ));
+    }
+
+    let params = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("fmc_noise params"),
+        size: PARAMS_SIZE,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let output = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("fmc_noise output"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("fmc_noise readback"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("fmc_noise"),
+        layout: &gpu.bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: output.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+    for start in (0..width).step_by(slab_width) {
+        let slab = slab_width.min(width - start);
+        let count = slab * height * depth;
+        let size = (count * std::mem::size_of::<f32>()) as u64;
+
+        // The origin is moved in f64 before it is converted, the same as the cpu does per lane.
+        let mut bytes = [0; PARAMS_SIZE as usize];
+        let origin = [x + start as f64, y, z].map(|coordinate| coordinate as f32);
+        for (chunk, value) in bytes[..12].chunks_exact_mut(4).zip(origin) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        for (chunk, value) in bytes[16..28].chunks_exact_mut(4).zip([slab, height, depth]) {
+            chunk.copy_from_slice(&(value as u32).to_le_bytes());
+        }
+        gpu.queue.write_buffer(&params, 0, &bytes);
+
+        let workgroups = count.div_ceil(64) as u32;
+        let max_workgroups = limits.max_compute_workgroups_per_dimension;
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                workgroups.min(max_workgroups),
+                workgroups.div_ceil(max_workgroups),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, size);
+        gpu.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..size);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        gpu.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|_| GpuError::NoDevice)?;
+        {
+            let mapped = slice.get_mapped_range();
+            for bytes in mapped.chunks_exact(4) {
+                let value = f32::from_le_bytes(bytes.try_into().unwrap());
+                min = min.min(value);
+                max = max.max(value);
+                values.push(value);
+            }
+        }
+        readback.unmap();
+    }
+
+    Ok(NoiseResult::new((values, min, max), width, height, depth))
+}
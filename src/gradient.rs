@@ -1,7 +1,20 @@
 use std::simd::{prelude::*, LaneCount, StdFloat, SupportedLaneCount};
 
+use super::{Bits, Float, Int};
+
+// Flips the sign of the lanes of `value` where bit 31 of `sign` is set, the other bits of `sign`
+// must be 0. The bit is moved to the sign bit of `Float`.
+#[inline(always)]
+fn xor_sign<const N: usize>(value: Simd<Float, N>, sign: Simd<i32, N>) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let sign = sign.cast::<u32>().cast::<Bits>() << Simd::splat((Bits::BITS - 32) as Bits);
+    Simd::from_bits(value.to_bits() ^ sign)
+}
+
 #[inline(always)]
-pub fn grad1<const N: usize>(seed: Simd<i32, N>, hash: Simd<i32, N>) -> Simd<f32, N>
+pub fn grad1<const N: usize>(seed: Simd<i32, N>, hash: Simd<i32, N>) -> Simd<Float, N>
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -9,19 +22,19 @@ where
     let v = (h & Simd::splat(7)).cast();
 
     let h_and_8 = (h & Simd::splat(8)).simd_eq(Simd::splat(0));
-    h_and_8.select(Simd::splat(0.0) - v, v)
+    h_and_8.cast::<Int>().select(Simd::splat(0.0) - v, v)
 }
 
 #[inline(always)]
 pub fn grad2<const N: usize>(
     hash: Simd<i32, N>,
-    mut x: Simd<f32, N>,
-    mut y: Simd<f32, N>,
-) -> Simd<f32, N>
+    mut x: Simd<Float, N>,
+    mut y: Simd<Float, N>,
+) -> Simd<Float, N>
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    const ROOT2: f32 = 1.4142135623730950488;
+    const ROOT2: Float = 1.4142135623730950488;
     // ( 1+R2, 1 ) ( -1-R2, 1 ) ( 1+R2, -1 ) ( -1-R2, -1 )
     // ( 1, 1+R2 ) ( 1, -1-R2 ) ( -1, 1+R2 ) ( -1, -1-R2 )
 
@@ -30,10 +43,10 @@ where
 
     // TODO: Implemented without knowing what the sign is supposed to be. Think it might need to be
     // negated.
-    let bit4 = Mask::from_int((hash << Simd::splat(29)) >> Simd::splat(31));
+    let bit4 = Mask::from_int((hash << Simd::splat(29)) >> Simd::splat(31)).cast::<Int>();
 
-    x = Simd::from_bits(x.to_bits() ^ bit1.cast::<u32>());
-    y = Simd::from_bits(y.to_bits() ^ bit2.cast::<u32>());
+    x = xor_sign(x, bit1);
+    y = xor_sign(y, bit2);
 
     let a = bit4.select(y, x);
     let b = bit4.select(x, y);
@@ -46,31 +59,30 @@ where
 #[inline(always)]
 pub fn grad3d_dot<const N: usize>(
     hash: Simd<i32, N>,
-    x: Simd<f32, N>,
-    y: Simd<f32, N>,
-    z: Simd<f32, N>,
-) -> Simd<f32, N>
+    x: Simd<Float, N>,
+    y: Simd<Float, N>,
+    z: Simd<Float, N>,
+) -> Simd<Float, N>
 where
     LaneCount<N>: SupportedLaneCount,
 {
     let hasha13 = hash & Simd::splat(13);
 
     //if h < 8 then x, else y
-    let u = hasha13.simd_lt(Simd::splat(8)).select(x, y);
+    let u = hasha13.simd_lt(Simd::splat(8)).cast::<Int>().select(x, y);
 
     //if h < 2 then y else if h is 12 or 14 then x else z
-    let mut v = hasha13.simd_eq(Simd::splat(12)).select(x, z);
-    v = hasha13.simd_lt(Simd::splat(2)).select(y, v);
+    let mut v = hasha13.simd_eq(Simd::splat(12)).cast::<Int>().select(x, z);
+    v = hasha13.simd_lt(Simd::splat(2)).cast::<Int>().select(y, v);
 
     //if h1 then -u else u
     //if h2 then -v else v
-    let h1 = (hash << Simd::splat(31)).cast::<u32>();
-    let h2 = ((hash & Simd::splat(2)) << Simd::splat(30)).cast::<u32>();
+    let h1 = hash << Simd::splat(31);
+    let h2 = (hash & Simd::splat(2)) << Simd::splat(30);
     //then add them
-    let a = Simd::<f32, N>::from_bits(u.to_bits() ^ h1);
-    let b = Simd::<f32, N>::from_bits(v.to_bits() ^ h2);
+    let a = xor_sign(u, h1);
+    let b = xor_sign(v, h2);
     return a + b;
-    //return Simd::<f32, N>::from_bits(u.to_bits() ^ h1) + Simd::from_bits(v.to_bits() ^ h2);
 }
 
 #[inline(always)]
@@ -104,3 +116,52 @@ where
     hash *= Simd::splat(0x27d4eb2d);
     return (hash >> Simd::splat(15)) ^ hash;
 }
+
+/// Picks one of the 32 gradients from the origin towards the middle of an edge of a double-unit
+/// tesseract, the ones with one component 0 and the others ±1, and computes its dot product with
+/// [x, y, z, w]
+#[inline(always)]
+pub fn grad4d_dot<const N: usize>(
+    hash: Simd<i32, N>,
+    x: Simd<Float, N>,
+    y: Simd<Float, N>,
+    z: Simd<Float, N>,
+    w: Simd<Float, N>,
+) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let h = hash & Simd::splat(31);
+
+    // The component that is 0 is w if h < 8, z if h < 16, y if h < 24 and x otherwise.
+    let a = h.simd_lt(Simd::splat(24)).cast::<Int>().select(x, y);
+    let b = h.simd_lt(Simd::splat(16)).cast::<Int>().select(y, z);
+    let c = h.simd_lt(Simd::splat(8)).cast::<Int>().select(z, w);
+
+    // The lowest 3 bits are the signs
+    let a = xor_sign(a, hash << Simd::splat(31));
+    let b = xor_sign(b, (hash & Simd::splat(2)) << Simd::splat(30));
+    let c = xor_sign(c, (hash & Simd::splat(4)) << Simd::splat(29));
+    a + b + c
+}
+
+#[inline(always)]
+pub fn hash4d<const N: usize>(
+    seed: Simd<i32, N>,
+    i: Simd<i32, N>,
+    j: Simd<i32, N>,
+    k: Simd<i32, N>,
+    l: Simd<i32, N>,
+) -> Simd<i32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut hash = seed;
+    hash ^= i;
+    hash ^= j;
+    hash ^= k;
+    hash ^= l;
+
+    hash *= Simd::splat(0x27d4eb2d);
+    return (hash >> Simd::splat(15)) ^ hash;
+}
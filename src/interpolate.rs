@@ -0,0 +1,189 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings, LATTICE_CELLS};
+
+// The values of the sub-noise at the corners of a cell, running it if the cell isn't kept. The
+// sub-noise is run at `w`, which only a 4d pipeline uses.
+#[inline(always)]
+fn cell_corners<const N: usize, const AXES: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    lattice: usize,
+    cell: [Float; 3],
+    w: Float,
+    spacing: Float,
+) -> [Float; 8]
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let key = [
+        cell[0] as i64,
+        cell[1] as i64,
+        cell[2] as i64,
+        w.to_bits() as i64,
+    ];
+    let hash = (key[0] as u64).wrapping_mul(0x9E3779B97F4A7C15)
+        ^ (key[1] as u64).wrapping_mul(0xC2B2AE3D27D4EB4F)
+        ^ (key[2] as u64).wrapping_mul(0x165667B19E3779F9)
+        ^ (key[3] as u64).wrapping_mul(0x27D4EB2F165667C5);
+    let slot = (hash >> 32) as usize % LATTICE_CELLS;
+    let kept = pipeline.lattices[lattice].cells[slot];
+    if kept.valid && kept.cell == key {
+        return kept.corners;
+    }
+
+    // Run the sub-noise with a corner in each lane, the lanes past the last corner repeat it.
+    let start = pipeline.index;
+    let count = 1 << AXES;
+    let mut corners = [0.0; 8];
+    for first in (0..count).step_by(N) {
+        let coordinate = |axis: usize| {
+            Simd::from_array(std::array::from_fn(|lane| {
+                let corner = (first + lane).min(count - 1);
+                (cell[axis] + (corner >> axis & 1) as Float) * spacing
+            }))
+        };
+        pipeline.save_coordinates();
+        pipeline.x = coordinate(0);
+        if AXES > 1 {
+            pipeline.y = coordinate(1);
+        }
+        if AXES > 2 {
+            pipeline.z = coordinate(2);
+        }
+        pipeline.w = Simd::splat(w);
+        pipeline.next();
+        pipeline.index = start;
+
+        let result = pipeline.lattices[lattice].result;
+        let len = N.min(count - first);
+        corners[first..first + len].copy_from_slice(&result.as_array()[..len]);
+    }
+
+    pipeline.lattices[lattice].cells[slot] = crate::LatticeCell {
+        valid: true,
+        cell: key,
+        corners,
+    };
+    corners
+}
+
+// Interpolates the sub-noise between the corners of the cells along the first `AXES` of x, y
+// and z, then continues after the end of the sub-noise. It isn't interpolated along w.
+#[inline(always)]
+fn interpolate<const N: usize, const AXES: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Interpolate { spacing } = *settings else {
+        unreachable!()
+    };
+    let spacing = spacing as Float;
+    let start = pipeline.index;
+    let lattice = pipeline
+        .lattices
+        .iter()
+        .position(|lattice| lattice.start == start)
+        .unwrap();
+
+    let coordinates = [pipeline.x, pipeline.y, pipeline.z];
+    let mut cells = [Simd::<Float, N>::splat(0.0); 3];
+    let mut weights = [Simd::<Float, N>::splat(0.0); 3];
+    for axis in 0..AXES {
+        let position = coordinates[axis] * Simd::splat(1.0 / spacing);
+        cells[axis] = position.floor();
+        weights[axis] = position - cells[axis];
+    }
+
+    // The value at each corner for each lane. Mostly all the lanes are in the same cell.
+    let mut corners = [Simd::<Float, N>::splat(0.0); 8];
+    let first_cell = cells.map(|cell| cell[0]);
+    let w = pipeline.w;
+    if (0..AXES).all(|axis| cells[axis].simd_eq(Simd::splat(first_cell[axis])).all())
+        && w.simd_eq(Simd::splat(w[0])).all()
+    {
+        let values = cell_corners::<N, AXES>(pipeline, lattice, first_cell, w[0], spacing);
+        for (corner, value) in corners.iter_mut().zip(values) {
+            *corner = Simd::splat(value);
+        }
+    } else {
+        let mut lanes = [[0.0; N]; 8];
+        for lane in 0..N {
+            let cell = cells.map(|cell| cell[lane]);
+            let values = cell_corners::<N, AXES>(pipeline, lattice, cell, w[lane], spacing);
+            for (corner, value) in lanes.iter_mut().zip(values) {
+                corner[lane] = value;
+            }
+        }
+        corners = lanes.map(Simd::from_array);
+    }
+
+    let mut result = Simd::splat(0.0);
+    for (index, corner) in corners.iter().enumerate().take(1 << AXES) {
+        let mut weight = Simd::<Float, N>::splat(1.0);
+        for (axis, axis_weight) in weights.iter().enumerate().take(AXES) {
+            weight *= if index >> axis & 1 == 1 {
+                *axis_weight
+            } else {
+                Simd::splat(1.0) - *axis_weight
+            };
+        }
+        result = weight.mul_add(*corner, result);
+    }
+
+    pipeline.results.push(result);
+    let lattice = &pipeline.lattices[lattice];
+    pipeline.rng.current_seed = lattice.rng_seed;
+    pipeline.index = lattice.end;
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn interpolate_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    interpolate::<N, 1>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn interpolate_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    interpolate::<N, 2>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn interpolate_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    interpolate::<N, 3>(pipeline);
+}
+
+/// Ends a run of an interpolated sub-noise at the corners of a cell. Keeps the result and
+/// returns to the node that started the sub-noise, instead of continuing the pipeline.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn interpolate_end<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let index = pipeline.index;
+    let result = pipeline.results.pop().unwrap();
+    let rng_seed = pipeline.rng.current_seed;
+    let lattice = pipeline
+        .lattices
+        .iter_mut()
+        .find(|lattice| lattice.end == index)
+        .unwrap();
+    lattice.result = result;
+    lattice.rng_seed = rng_seed;
+    // The next run of the sub-noise gets the same seeds.
+    pipeline.restore_coordinates(true);
+}
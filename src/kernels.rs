@@ -0,0 +1,335 @@
+// The kernels and the drivers that run them. This file is included in `single` and `double`,
+// which define `Float` as f32 and f64, so every kernel is compiled once for each. The paths are
+// relative to src.
+
+#[path = "abs.rs"]
+mod abs;
+#[path = "add.rs"]
+mod add;
+#[path = "cellular.rs"]
+mod cellular;
+#[path = "clamp.rs"]
+mod clamp;
+#[path = "colored.rs"]
+mod colored;
+#[path = "constant.rs"]
+mod constant;
+#[path = "dither.rs"]
+mod dither;
+#[path = "dots.rs"]
+mod dots;
+#[path = "dual.rs"]
+mod dual;
+#[path = "extrude.rs"]
+mod extrude;
+#[path = "falloff.rs"]
+mod falloff;
+#[path = "fbm.rs"]
+mod fbm;
+#[path = "fused.rs"]
+mod fused;
+#[path = "generate.rs"]
+pub(crate) mod generate;
+#[path = "gradient.rs"]
+mod gradient;
+#[path = "interpolate.rs"]
+mod interpolate;
+#[path = "lerp.rs"]
+mod lerp;
+#[path = "min_and_max.rs"]
+mod min_and_max;
+#[path = "mul.rs"]
+mod mul;
+#[path = "opensimplex.rs"]
+mod opensimplex;
+#[path = "perlin.rs"]
+mod perlin;
+#[path = "range.rs"]
+mod range;
+#[path = "sample.rs"]
+mod sample;
+#[path = "sdf.rs"]
+mod sdf;
+#[path = "seed.rs"]
+mod seed;
+#[path = "simplex.rs"]
+mod simplex;
+#[path = "slope.rs"]
+mod slope;
+#[path = "spectral.rs"]
+mod spectral;
+#[path = "square.rs"]
+mod square;
+#[path = "value.rs"]
+mod value;
+#[path = "warp.rs"]
+mod warp;
+#[path = "white.rs"]
+mod white;
+#[path = "wrap.rs"]
+mod wrap;
+
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use crate::sealed::Sealed;
+use crate::{Dimensions, Gradient, Kernel, Noise, NoisePipeline, NoiseSettings};
+
+impl Sealed for Float {
+    fn function<const N: usize>(
+        settings: &NoiseSettings,
+        dimensions: Dimensions,
+    ) -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        match settings {
+            NoiseSettings::Simplex { .. } => match dimensions {
+                Dimensions::X => simplex::simplex_1d(),
+                Dimensions::XY => simplex::simplex_2d(),
+                Dimensions::XYZ => simplex::simplex_3d(),
+                Dimensions::XYZW => simplex::simplex_4d(),
+            },
+            NoiseSettings::Perlin { .. } => match dimensions {
+                Dimensions::X => simplex::simplex_1d(),
+                Dimensions::XY => perlin::perlin_2d(),
+                Dimensions::XYZ => perlin::perlin_3d(),
+                Dimensions::XYZW => perlin::perlin_4d(),
+            },
+            NoiseSettings::OpenSimplex2 { .. } => match dimensions {
+                Dimensions::X => simplex::simplex_1d(),
+                Dimensions::XY => opensimplex::opensimplex2_2d(),
+                Dimensions::XYZ => opensimplex::opensimplex2_3d(),
+                Dimensions::XYZW => simplex::simplex_4d(),
+            },
+            NoiseSettings::Value { .. } => match dimensions {
+                Dimensions::X => value::value_1d(),
+                Dimensions::XY => value::value_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => value::value_3d(),
+            },
+            NoiseSettings::ValueCubic { .. } => match dimensions {
+                Dimensions::X => value::value_cubic_1d(),
+                Dimensions::XY => value::value_cubic_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => value::value_cubic_3d(),
+            },
+            NoiseSettings::Cellular { .. } => match dimensions {
+                Dimensions::X => cellular::cellular_1d(),
+                Dimensions::XY => cellular::cellular_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => cellular::cellular_3d(),
+            },
+            NoiseSettings::White => match dimensions {
+                Dimensions::X => white::white_1d(),
+                Dimensions::XY => white::white_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => white::white_3d(),
+            },
+            NoiseSettings::Constant { .. } | NoiseSettings::Parameter { .. } => {
+                constant::constant()
+            }
+            NoiseSettings::Colored { .. } => colored::colored(),
+            NoiseSettings::Spectral { .. } => match dimensions {
+                Dimensions::X => spectral::spectral_1d(),
+                Dimensions::XY => spectral::spectral_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => spectral::spectral_3d(),
+            },
+            NoiseSettings::Dots { .. } => match dimensions {
+                Dimensions::X => dots::dots_1d(),
+                Dimensions::XY => dots::dots_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => dots::dots_3d(),
+            },
+            NoiseSettings::Fbm { .. } => fbm::fbm(),
+            NoiseSettings::Ridged { .. } => fbm::ridged(),
+            NoiseSettings::PingPong { .. } => fbm::ping_pong(),
+            NoiseSettings::Abs { .. } => abs::abs(),
+            NoiseSettings::AbsDiff { .. } => abs::abs_diff(),
+            NoiseSettings::Add { .. } => add::add(),
+            NoiseSettings::Sub { .. } => add::sub(),
+            NoiseSettings::Average { .. } => add::average(),
+            NoiseSettings::Mul { .. } => mul::mul(),
+            NoiseSettings::Div { .. } => mul::div(),
+            NoiseSettings::Clamp { .. } => clamp::clamp(),
+            NoiseSettings::Max { .. } => min_and_max::max(),
+            NoiseSettings::Min { .. } => min_and_max::min(),
+            NoiseSettings::Lerp { .. } => lerp::lerp(),
+            NoiseSettings::LerpValues { .. } => lerp::lerp_values(),
+            NoiseSettings::Range { .. } => range::range(),
+            NoiseSettings::Square { .. } => square::square(),
+            NoiseSettings::Dither { .. } => match dimensions {
+                Dimensions::X => dither::dither_1d(),
+                Dimensions::XY => dither::dither_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => dither::dither_3d(),
+            },
+            NoiseSettings::Sdf { .. } => match dimensions {
+                Dimensions::X => sdf::sdf_1d(),
+                Dimensions::XY => sdf::sdf_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => sdf::sdf_3d(),
+            },
+            NoiseSettings::FalloffRadial { .. } => match dimensions {
+                Dimensions::X => falloff::falloff_radial_1d(),
+                Dimensions::XY => falloff::falloff_radial_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => falloff::falloff_radial_3d(),
+            },
+            NoiseSettings::Sample { .. } => match dimensions {
+                Dimensions::X => sample::sample_1d(),
+                Dimensions::XY => sample::sample_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => sample::sample_3d(),
+            },
+            NoiseSettings::WrapDomain { .. } => match dimensions {
+                Dimensions::X => wrap::wrap_domain_1d(),
+                Dimensions::XY => wrap::wrap_domain_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => wrap::wrap_domain_3d(),
+            },
+            NoiseSettings::WrapBlend { .. } => match dimensions {
+                Dimensions::X => wrap::wrap_blend_1d(),
+                Dimensions::XY => wrap::wrap_blend_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => wrap::wrap_blend_3d(),
+            },
+            NoiseSettings::OffsetDomain { .. } => match dimensions {
+                Dimensions::X => slope::offset_domain_1d(),
+                Dimensions::XY => slope::offset_domain_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => slope::offset_domain_3d(),
+            },
+            NoiseSettings::Warp { .. } => match dimensions {
+                Dimensions::X => warp::warp_1d(),
+                Dimensions::XY => warp::warp_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => warp::warp_3d(),
+            },
+            NoiseSettings::Extrude2d => match dimensions {
+                Dimensions::X | Dimensions::XY => extrude::extrude_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => extrude::extrude_2d_3d(),
+            },
+            NoiseSettings::Interpolate { .. } => match dimensions {
+                Dimensions::X => interpolate::interpolate_1d(),
+                Dimensions::XY => interpolate::interpolate_2d(),
+                Dimensions::XYZ | Dimensions::XYZW => interpolate::interpolate_3d(),
+            },
+            NoiseSettings::SeedOffset { .. } => seed::seed_offset(),
+            NoiseSettings::Slope { .. } => slope::slope(),
+            NoiseSettings::RestoreCoordinates { .. } => wrap::restore_coordinates(),
+        }
+    }
+
+    fn fused<const N: usize>(nodes: &[NoiseSettings]) -> Option<(Kernel<N, Self>, usize)>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        use NoiseSettings::{Abs, Add, Clamp, Constant, LerpValues, Mul, Parameter, Square};
+
+        // Only the values of constants are known when the pipeline is built, parameters can be
+        // changed afterwards without building it again.
+        let is_value =
+            |settings: &NoiseSettings| matches!(settings, Constant { .. } | Parameter { .. });
+        let fused = match nodes {
+            [Constant { value }, Add, ..] if *value == 0.0 => (fused::skip(), 2),
+            [Constant { value }, Mul, ..] if *value == 1.0 => (fused::skip(), 2),
+            [scale, Mul, offset, Add, ..] if is_value(scale) && is_value(offset) => {
+                (fused::mul_add_value(), 4)
+            }
+            [value, Add, ..] if is_value(value) => (fused::add_value(), 2),
+            [value, Mul, ..] if is_value(value) => (fused::mul_value(), 2),
+            [Abs, Square, ..] => (fused::abs_square(), 2),
+            [Abs, LerpValues { .. }, ..] => (fused::abs_lerp_values(), 2),
+            [Clamp { .. }, LerpValues { .. }, ..] => (fused::clamp_lerp_values(), 2),
+            [Mul, Add, ..] => (fused::mul_add(), 2),
+            _ => return None,
+        };
+        Some(fused)
+    }
+
+    fn extrude_2d_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        extrude::extrude_2d_end()
+    }
+
+    fn interpolate_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        interpolate::interpolate_end()
+    }
+
+    fn slope_derivatives<const N: usize>(
+        dimensions: Dimensions,
+    ) -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        match dimensions {
+            Dimensions::XY => slope::slope_derivatives_2d(),
+            _ => slope::slope_derivatives_3d(),
+        }
+    }
+
+    fn slope_skip_copy<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        slope::skip_copy()
+    }
+
+    fn slope_last_copy_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        slope::last_copy_end()
+    }
+
+    fn generate_1d(noise: &Noise, x: f64, width: usize) -> (Vec<Self>, Self, Self) {
+        generate::generate_1d(noise, x, 1.0, width)
+    }
+
+    fn generate_2d(
+        noise: &Noise,
+        x: f64,
+        y: f64,
+        width: usize,
+        height: usize,
+    ) -> (Vec<Self>, Self, Self) {
+        generate::generate_2d(noise, x, y, width, height, None)
+    }
+
+    fn generate_3d(
+        noise: &Noise,
+        [x, y, z]: [f64; 3],
+        [width, height, depth]: [usize; 3],
+    ) -> (Vec<Self>, Self, Self) {
+        generate::generate_3d(noise, x, y, z, width, height, depth, None, None)
+    }
+
+    fn generate_4d(noise: &Noise, origin: [f64; 4], size: [usize; 3]) -> (Vec<Self>, Self, Self) {
+        generate::generate_4d(noise, origin, size)
+    }
+
+    fn generate_1d_into(noise: &Noise, x: f64, out: &mut [Self]) -> (Self, Self) {
+        unsafe { (generate::generate_1d_into())(noise, x, 1.0, out) }
+    }
+
+    fn generate_2d_into(
+        noise: &Noise,
+        x: f64,
+        y: f64,
+        width: usize,
+        height: usize,
+        out: &mut [Self],
+    ) -> (Self, Self) {
+        unsafe { (generate::generate_2d_into())(noise, x, y, width, height, None, out) }
+    }
+
+    fn generate_3d_into(
+        noise: &Noise,
+        origin: [f64; 3],
+        size: [usize; 3],
+        out: &mut [Self],
+    ) -> (Self, Self) {
+        unsafe { (generate::generate_3d_into())(noise, origin, None, size, None, None, out) }
+    }
+
+    fn generate_derivatives(
+        noise: &Noise,
+        origin: [f64; 3],
+        size: [usize; 3],
+        dimensions: Dimensions,
+    ) -> (Vec<Self>, Self, Self, Gradient<Self>) {
+        unsafe { (dual::generate())(noise, origin, size, dimensions) }
+    }
+}
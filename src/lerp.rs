@@ -2,10 +2,11 @@ use std::simd::{LaneCount, Simd, StdFloat, SupportedLaneCount};
 
 use multiversion::multiversion;
 
-use crate::NoisePipeline;
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn lerp<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn lerp<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -13,11 +14,45 @@ where
     let high = pipeline.results.pop().unwrap();
     let selector = pipeline.results.pop().unwrap();
 
-    // This is a special proprety of the -1..1 range, so the selector noise is required to be in
-    // that range. It's shifted up to be 0..1
-    let interpolation = selector.mul_add(Simd::splat(0.5), Simd::splat(0.5));
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::Lerp {
+        selector_min,
+        selector_max,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    // The selector is shifted to be 0..1. For the default -1..1 range this is a multiply by 0.5
+    // and add 0.5.
+    let (selector_min, selector_max) = (selector_min as Float, selector_max as Float);
+    let scale = 1.0 / (selector_max - selector_min);
+    let offset = -selector_min * scale;
+    let interpolation = selector.mul_add(Simd::splat(scale), Simd::splat(offset));
     let result = (high - low).mul_add(interpolation, low);
 
     pipeline.results.push(result);
     pipeline.next();
 }
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn lerp_values<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::LerpValues { low, high } = *settings else {
+        unreachable!()
+    };
+
+    // -1..1 is scaled to the size of low..high and moved to be centered on it.
+    let noise = pipeline.results.pop().unwrap();
+    let (low, high) = (low as Float, high as Float);
+    let scale = (high - low) * 0.5;
+    let offset = (high + low) * 0.5;
+    let result = noise.mul_add(Simd::splat(scale), Simd::splat(offset));
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
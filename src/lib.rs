@@ -1,31 +1,78 @@
 #![feature(portable_simd)]
 
+// The `noise!` macro refers to the crate by name
+extern crate self as fmc_noise;
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::hash::{Hash, Hasher};
 use std::simd::prelude::*;
+use std::simd::{SimdElement, StdFloat};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError};
 
-use multiversion::{multiversion, selected_target};
+use multiversion::{multiversion, selected_target, simd_width};
+use sampler::{sampler_pipelines, SamplerPipelines};
 use std::simd::{LaneCount, SupportedLaneCount};
 
-mod abs;
-mod add;
-mod clamp;
-mod constant;
-mod fbm;
-mod gradient;
-mod lerp;
-mod min_and_max;
-mod mul;
-mod perlin;
-mod range;
-mod simplex;
-mod square;
-
-// TODO: Make a cargo feature "f64", makes it compile with f64 instead of f32
-//if cfg(f64)
-//type Float = f64;
-//type Int = i64;
-//else
-//type Float = f32;
-//type Int = i32;
+use sealed::Dimensions;
+
+mod animated;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod bricks;
+mod bytes;
+mod compiled;
+mod contour;
+mod derivatives;
+mod distribution;
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "npy")]
+mod npy;
+mod occupancy;
+mod parallel;
+mod result;
+mod river;
+#[cfg(feature = "rand")]
+mod rng;
+mod runs;
+mod sampler;
+mod seamless;
+mod shader;
+#[cfg(feature = "fft")]
+mod spectrum;
+mod static_noise;
+
+pub mod climate;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "config")]
+mod expression;
+pub mod scatter;
+
+pub use animated::{AnimatedNoise, Parameter};
+pub use bricks::{Brick, Bricks};
+pub use bytes::DecodeError;
+pub use compiled::{CompiledNoise, ExecutionPlan, PlannedNode};
+pub use derivatives::NotDifferentiable;
+pub use distribution::{Distribution, Reshape};
+#[cfg(feature = "gpu")]
+pub use gpu::GpuError;
+pub use multiversion::noise;
+pub use occupancy::Occupancy;
+pub use result::{BlockRanges, Filter, Gradient, NoiseResult, Pooling, Statistics};
+pub use river::Rivers;
+#[cfg(feature = "rand")]
+pub use rng::NoiseRng;
+pub use runs::{ColumnRuns, Run};
+pub use sampler::NoiseSampler;
+pub use shader::{UnsupportedNode, GLSL_LIBRARY, WGSL_LIBRARY};
+pub use static_noise::StaticNoise;
+
+// Features are detected once and shared by all the multiversioned functions
+multiversion::feature_snapshot!();
 
 /// Defines a noise
 ///
@@ -50,12 +97,89 @@ mod square;
 ///     .fbm(octaves, gain, lacunarity)
 ///     .abs()
 ///     .add(Noise::constant(0.5))
-///     .generate_3d(x, y, z, width, height, depth);
+///     .generate_3d::<f32>(x, y, z, width, height, depth);
+/// ```
+///
+/// Noises can also be combined with `+`, `-`, `*` and `/`, and with numbers, which are
+/// [constants](Noise::constant). The noise on the left keeps its seed, or the noise when the
+/// number is on the left.
+///
+/// ```rust
+/// let height = Noise::simplex(0.01).fbm(4, 0.5, 2.0) * 40.0 + 64.0;
+/// let caves = Noise::perlin(0.05) - Noise::simplex(0.02).abs() * 0.5;
 /// ```
-#[derive(Clone, Debug)]
+///
+/// Two noises compare equal when they have the same seed and [Algorithm] and are built from the
+/// same operations with bit-identical parameters, so a `Noise` can be used as a key in a cache of generated
+/// chunks.
+///
+/// The origins passed to the `generate_*` functions can be `f32` or `f64`. The coordinate of each
+/// value is computed in double precision before it is converted, so generating far from the
+/// origin gives the same values as generating the whole area at once.
+///
+/// [Noise::generate_1d], [Noise::generate_2d] and [Noise::generate_3d] can generate the values
+/// as `f32` or `f64`, see [Float].
+///
+/// Noises that never change can be built at compile time with [StaticNoise].
+///
+/// Before generating, the noise is built into a pipeline of functions for the dimension and the
+/// cpu. The pipelines last used on each thread are kept, so generating with the same noise again
+/// skips that step. A noise generated over and over by many threads can be wrapped in a
+/// [NoiseSampler], which builds its pipelines once and keeps them for every thread.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Noise {
     seed: u64,
-    pipeline: Vec<NoiseSettings>,
+    algorithm: Algorithm,
+    pipeline: Cow<'static, [NoiseSettings]>,
+    // The results sampled by the sample nodes
+    buffers: Vec<Buffer>,
+}
+
+// A result sampled by [NoiseSettings::Sample] nodes. It is known by a hash of its size and
+// values, so noises that sample the same values are equal and can share a pipeline.
+#[derive(Clone)]
+struct Buffer {
+    id: u64,
+    result: Arc<NoiseResult>,
+}
+
+impl Buffer {
+    fn new(result: Arc<NoiseResult>) -> Self {
+        let mut hasher = std::hash::DefaultHasher::new();
+        (result.width(), result.height(), result.depth()).hash(&mut hasher);
+        for value in result.iter() {
+            hasher.write_u32(value.to_bits());
+        }
+        Self {
+            id: hasher.finish(),
+            result,
+        }
+    }
+}
+
+impl PartialEq for Buffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Buffer {}
+
+impl Hash for Buffer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl std::fmt::Debug for Buffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Buffer")
+            .field("id", &self.id)
+            .field("width", &self.result.width())
+            .field("height", &self.result.height())
+            .field("depth", &self.result.depth())
+            .finish()
+    }
 }
 
 impl Noise {
@@ -68,9 +192,11 @@ impl Noise {
     pub fn simplex(frequency: impl Into<Frequency>) -> Self {
         return Self {
             seed: 0,
-            pipeline: vec![NoiseSettings::Simplex {
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Simplex {
                 frequency: frequency.into(),
-            }],
+            }]),
+            buffers: Vec::new(),
         };
     }
 
@@ -83,12 +209,270 @@ impl Noise {
     pub fn perlin(frequency: impl Into<Frequency>) -> Self {
         return Self {
             seed: 0,
-            pipeline: vec![NoiseSettings::Perlin {
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Perlin {
                 frequency: frequency.into(),
-            }],
+            }]),
+            buffers: Vec::new(),
         };
     }
 
+    /// OpenSimplex2 noise, gradient noise like [Noise::simplex] with gradients in more
+    /// directions, which hides the directional artifacts simplex noise shows in 2d. In 3d the
+    /// lattice is rotated so that no axis lines up with it. It is the same algorithm as the
+    /// OpenSimplex2 noise of FastNoiseLite. 1d noise is the same as [Noise::simplex]. The values
+    /// are -1..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// let heightmap = Noise::opensimplex2(0.005).fbm(5, 0.5, 2.0);
+    /// ```
+    pub fn opensimplex2(frequency: impl Into<Frequency>) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::OpenSimplex2 {
+                frequency: frequency.into(),
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Value noise, random values at the points of a lattice interpolated smoothly between
+    /// them. It is cheaper than [Noise::perlin] but blockier, as the lattice shows through,
+    /// which suits low frequency masks. `frequency` is the number of lattice points per unit of
+    /// distance. The values are -1..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mask = Noise::value(0.005).lerp_values(0.0, 1.0);
+    /// ```
+    pub fn value(frequency: impl Into<Frequency>) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Value {
+                frequency: frequency.into(),
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Value noise with cubic interpolation through the 4 lattice points around the coordinates
+    /// along each axis, smoother than [Noise::value] without the artifacts of gradient noise,
+    /// and more expensive. The values are -1..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// let hills = Noise::value_cubic(0.01).fbm(4, 0.5, 2.0);
+    /// ```
+    pub fn value_cubic(frequency: impl Into<Frequency>) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::ValueCubic {
+                frequency: frequency.into(),
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Cellular noise, also known as Worley noise, the distance to the closest of a set of
+    /// random points. The points are spread over a grid with one point at a random position
+    /// within each cell, kept far enough from its borders that the closest point is always in
+    /// one of the cells next to it. `frequency` is the number of cells per unit of distance. It
+    /// is the same as [Noise::cellular_with] with [CellularDistance::Euclidean] and
+    /// [CellularReturn::F1].
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::cellular(0.02);
+    /// ```
+    pub fn cellular(frequency: impl Into<Frequency>) -> Self {
+        Self::cellular_with(frequency, CellularDistance::Euclidean, CellularReturn::F1)
+    }
+
+    /// Cellular noise with the distances measured by `distance`, and the value of the noise
+    /// picked by `return_type`, see [CellularReturn]. The distances are in cells.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Caves along the borders between cells
+    /// let walls = Noise::cellular_with(0.02, CellularDistance::Euclidean, CellularReturn::F2MinusF1);
+    /// let caves = walls.generate_3d::<f32>(0.0, 0.0, 0.0, 16, 16, 16);
+    /// let air = caves.iter().map(|value| *value < 0.1);
+    /// // Biomes in patches, one random value for each cell
+    /// let biomes = Noise::cellular_with(0.005, CellularDistance::Euclidean, CellularReturn::CellValue);
+    /// // Paths of even width along the borders
+    /// let edges = Noise::cellular_with(0.01, CellularDistance::Euclidean, CellularReturn::EdgeDistance);
+    /// ```
+    pub fn cellular_with(
+        frequency: impl Into<Frequency>,
+        distance: CellularDistance,
+        return_type: CellularReturn,
+    ) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Cellular {
+                frequency: frequency.into(),
+                distance,
+                return_type,
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// White noise, a random value for every point with nothing smoothing it out. Points get the
+    /// same value every time they are sampled, but any other point, however close, gets an
+    /// unrelated one. Useful for scattering things like trees or ores one block at a time. The
+    /// values are evenly spread over -1..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// // An ore in about one in a hundred blocks
+    /// let ore = Noise::white().seed(7).generate_3d::<f32>(0.0, 0.0, 0.0, 16, 16, 16);
+    /// let placed = ore.iter().map(|value| *value > 0.98);
+    /// ```
+    pub fn white() -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::White]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Pink noise, noise with a 1/f spectrum where every octave has the same power. It is the
+    /// noise of many natural processes, and sounds and moves more evenly than white noise.
+    /// `frequency` is the number of random values per unit of distance, the spectrum falls off
+    /// from there down through 16 octaves. For audio it is the sample rate, so that every sample
+    /// gets a new value, see [Noise::generate_1d_at_rate].
+    ///
+    /// Colored noise only varies along the first dimension. The values are -1..1, but sum many
+    /// random octaves, so they are mostly within -0.3..0.3.
+    ///
+    /// # Example
+    /// ```rust
+    /// // One second of pink noise at 44.1khz
+    /// let noise = Noise::pink(44100.0).generate_1d_at_rate(0.0, 44100.0, 1.0);
+    /// ```
+    pub fn pink(frequency: f32) -> Self {
+        Self::colored(frequency, 1.0)
+    }
+
+    /// Brown noise, noise with a 1/f² spectrum, like a random walk. Slower octaves are stronger
+    /// than with [Noise::pink], which gives a deep rumble as audio and drifting, wandering
+    /// motion for animation. See [Noise::pink] for the frequency.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Camera shake that wanders, 10 seconds of it at 60 frames per second
+    /// let shake = Noise::brown(30.0).seed(3).generate_1d_at_rate(0.0, 60.0, 10.0);
+    /// ```
+    pub fn brown(frequency: f32) -> Self {
+        Self::colored(frequency, 2.0)
+    }
+
+    // Noise with a 1/f^exponent spectrum
+    fn colored(frequency: f32, exponent: f32) -> Self {
+        assert!(frequency > 0.0, "The frequency must be larger than 0");
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Colored {
+                frequency,
+                exponent,
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Noise with a radial power spectrum of 1/f^`beta` between `lowest_frequency` and
+    /// `highest_frequency`, the sum of `waves` cosine waves in random directions. The frequencies
+    /// of the waves are spread evenly between the two on a log scale and their amplitudes follow
+    /// the spectrum, so the radially averaged power spectrum of 2d results is a straight line of
+    /// slope -`beta` on a log-log plot. Terrain is often measured to have a beta around 2 to 3,
+    /// higher is smoother. More waves follow the spectrum more closely and cost more.
+    ///
+    /// The values are not bounded, they have a mean of 0 and a standard deviation of 0.25, so
+    /// they are almost always within -1..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Features from 1000 blocks down to 4 blocks across
+    /// let terrain = Noise::spectral(0.001, 0.25, 2.4, 64);
+    /// ```
+    pub fn spectral(lowest_frequency: f32, highest_frequency: f32, beta: f32, waves: u32) -> Self {
+        assert!(
+            lowest_frequency > 0.0,
+            "The lowest frequency must be larger than 0"
+        );
+        assert!(
+            highest_frequency >= lowest_frequency,
+            "The highest frequency can't be below the lowest"
+        );
+        assert!(waves > 0, "There must be 1 or more waves");
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Spectral {
+                lowest_frequency,
+                highest_frequency,
+                beta,
+                waves,
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Isolated round dots scattered over a grid of cells, e.g. for star fields, boulders or
+    /// speckles. Each cell has a dot with a probability of `density`, 0..1, at a random position
+    /// within the cell. `frequency` is the number of cells per unit of distance and `radius` the
+    /// radius of the dots as a fraction of a cell, 0..=1.
+    ///
+    /// A dot is 1 at its center and falls smoothly to 0 at its radius, the noise is 0 between
+    /// the dots. `amplitude_jitter`, 0..1, makes the dots weaker by a random fraction of up to
+    /// that much, so that they don't all have the same brightness. Where dots overlap the
+    /// strongest of them is taken, so the values are always within 0..1.
+    ///
+    /// # Example
+    /// ```rust
+    /// // A star field, a star in one of every ten cells of 8 by 8 pixels
+    /// let stars = Noise::dots(0.125, 0.1, 0.25, 0.8);
+    /// let sky = stars.generate_2d::<f32>(0.0, 0.0, 512, 512);
+    /// ```
+    pub fn dots(
+        frequency: impl Into<Frequency>,
+        density: f32,
+        radius: f32,
+        amplitude_jitter: f32,
+    ) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "The density must be within 0..=1"
+        );
+        assert!(
+            radius > 0.0 && radius <= 1.0,
+            "The radius must be larger than 0 and at most 1"
+        );
+        assert!(
+            (0.0..=1.0).contains(&amplitude_jitter),
+            "The amplitude jitter must be within 0..=1"
+        );
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Dots {
+                frequency: frequency.into(),
+                density,
+                radius,
+                amplitude_jitter,
+            }]),
+            buffers: Vec::new(),
+        }
+    }
+
     /// A constant number, useful for shifting values.
     ///
     /// # Example
@@ -101,621 +485,4794 @@ impl Noise {
     pub fn constant(value: f32) -> Self {
         return Self {
             seed: 0,
-            pipeline: vec![NoiseSettings::Constant { value }],
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Constant { value }]),
+            buffers: Vec::new(),
         };
     }
 
-    /// Set the seed of the random number generator.
+    /// A constant that can be changed after the noise is built, through [CompiledNoise] or by
+    /// building the noise again with another value. Noises that only differ in the values of
+    /// their parameters share the same pipeline, so changing a value doesn't build the noise
+    /// again. Parameters with the same name are all set together.
     ///
     /// # Example
     /// ```rust
-    /// let noise_left = Noise::simplex(0.01).seed(1);
-    /// let noise_right = Noise::simplex(0.01).seed(2);
-    /// // In cases like this the seed is always taken from the noise the method is called on,
-    /// // so the seed here will be 1.
-    /// let noise = noise_left.add(noise_right);
+    /// let terrain = Noise::simplex(0.01).fbm(4, 0.5, 2.0);
+    /// let land = terrain.add(Noise::param("sea_level", 0.3).mul(Noise::constant(-1.0)));
+    /// let mut land = CompiledNoise::new(land);
+    /// // Dragged by a slider in an editor
+    /// land.set_param("sea_level", 0.35);
+    /// let preview = land.generate_2d::<f32>(0.0, 0.0, 256, 256);
     /// ```
-    pub fn seed(mut self, seed: u32) -> Self {
-        self.seed = seed as u64;
-        self
+    pub fn param(name: &str, value: f32) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Parameter {
+                name: fnv1a(name),
+                value,
+            }]),
+            buffers: Vec::new(),
+        }
     }
 
-    /// Computes `octaves` layers of noise and adds them together, normalizing the result. Each
-    /// consecutive octave has its frequency multiplied by `lacunarity` and its amplitude
-    /// multiplied by `gain`.
+    /// A mask that is 1 within `inner_radius` of the center and smoothly falls off to 0 at
+    /// `outer_radius`, e.g. to shape terrain into an island by multiplying it with the mask. The
+    /// distance is measured in the first and second dimensions, in 3d the mask is the same at all
+    /// heights.
     ///
     /// # Example
     /// ```rust
-    /// // Common values for gain and lacunarity. Each octave has half the amplitude and twice
-    /// // the frequency, addding finer detail to the noise.
-    /// let noise = Noise::simplex(0.01).fbm(5, 0.5, 2.0);
+    /// // An island with a radius of 500
+    /// let noise = Noise::simplex(0.01)
+    ///     .add(Noise::constant(1.0))
+    ///     .mul(Noise::falloff_radial(0.0, 0.0, 300.0, 500.0));
     /// ```
-    pub fn fbm(mut self, octaves: u32, gain: f32, lacunarity: f32) -> Self {
-        assert!(octaves > 0, "There must be 1 or more octaves");
+    pub fn falloff_radial(
+        center_x: f32,
+        center_z: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    ) -> Self {
+        assert!(
+            outer_radius > inner_radius,
+            "The outer radius must be larger than the inner radius"
+        );
 
-        // The amplitude gets pre-scaled so that we can skip normalizing the result.
-        // e.g. if the gain is 0.5 and there are 2 octaves, the amplitude would be 1 + 0.5 = 1.5
-        // when both octaves are combined normally. Instead, we set the initial amplitude to be
-        // 1/1.5 == 2/3, the second octave's amplitude becomes 2/3 * 0.5 = 1/3 and we end up with a
-        // normalized result naturally.
-        let mut amp = gain;
-        let mut total_amplitude = 1.0;
-        for _ in 1..octaves {
-            total_amplitude += amp;
-            amp *= gain;
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::FalloffRadial {
+                center_x,
+                center_z,
+                inner_radius,
+                outer_radius,
+            }]),
+            buffers: Vec::new(),
         }
-        let first_octave_amplitude = 1.0 / total_amplitude;
-
-        // Add the octaves so that they go from most lacunarized to least since they are added in
-        // reverse order during computation.
-        let initial_pipeline = std::mem::replace(&mut self.pipeline, Vec::new());
-        for i in (0..octaves).rev() {
-            let mut pipeline = initial_pipeline.clone();
-
-            let lacunarity = lacunarity.powi(i as i32);
+    }
 
-            for settings in pipeline.iter_mut() {
-                match settings {
-                    NoiseSettings::Simplex { ref mut frequency } => {
-                        frequency.x *= lacunarity;
-                        frequency.y *= lacunarity;
-                        frequency.z *= lacunarity;
-                    }
-                    NoiseSettings::Perlin { ref mut frequency } => {
-                        frequency.x *= lacunarity;
-                        frequency.y *= lacunarity;
-                        frequency.z *= lacunarity;
-                    }
-                    _ => (),
-                }
+    /// The signed distance to a shape, negative inside of it and positive outside, e.g. to
+    /// carve structures into a 3d density where positive is solid. Combine it with the noise
+    /// using [Noise::min] to cut the shape out, or [Noise::max] with the negated distance to
+    /// fill it. The distances are in the units of the coordinates.
+    ///
+    /// The shapes are 3d, with y as the height. 2d noise is the slice through y = 0, its second
+    /// dimension is z, and 1d noise the line along x at y = z = 0.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Caves everywhere except a flat spawn platform of radius 20 at a height of 64
+    /// let caves = Noise::simplex(0.02);
+    /// let platform = Noise::sdf(SdfShape::Capsule {
+    ///     start: [0.0, 60.0, 0.0],
+    ///     end: [0.0, 64.0, 0.0],
+    ///     radius: 20.0,
+    /// });
+    /// let density = caves.max(platform.mul(Noise::constant(-1.0)));
+    /// ```
+    pub fn sdf(shape: SdfShape) -> Self {
+        match shape {
+            SdfShape::Sphere { radius, .. } | SdfShape::Capsule { radius, .. } => {
+                assert!(radius >= 0.0, "The radius can't be negative");
+            }
+            SdfShape::Box { half_size, .. } => {
+                assert!(
+                    half_size.iter().all(|size| *size >= 0.0),
+                    "The size of the box can't be negative"
+                );
+            }
+            SdfShape::Plane { normal, .. } => {
+                assert!(normal != [0.0; 3], "The normal of the plane can't be 0");
             }
-
-            self.pipeline.append(&mut pipeline);
         }
 
-        self.pipeline.push(NoiseSettings::Fbm {
-            octaves,
-            gain,
-            first_octave_amplitude,
-        });
-        self
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Sdf { shape }]),
+            buffers: Vec::new(),
+        }
     }
 
-    /// Computes the absolute value of the noise
-    pub fn abs(mut self) -> Self {
-        self.pipeline.push(NoiseSettings::Abs);
-        self
-    }
+    /// A noise that samples a result generated earlier, so that a later stage of generation
+    /// can read an earlier one without repeating its noise, e.g. a pass that carves rivers into
+    /// a cached heightmap.
+    ///
+    /// The values are interpolated linearly between the values of the result around the
+    /// coordinates. The position of a coordinate in the result is `(coordinate - origin) /
+    /// spacing`, so with the origin the result was generated at and a spacing of 1 the noise
+    /// gives the values of the result at the coordinates they were generated at. Outside of
+    /// the result the values at its edges are continued.
+    ///
+    /// A 1d result is sampled along x, a 2d result along x and y and a 3d result along all
+    /// three. To sample a 2d heightmap in the horizontal plane of 3d noise, extrude it with
+    /// [Noise::extrude_2d]. The noise can't be translated to shader code.
+    ///
+    /// # Example
+    /// ```rust
+    /// let base = Noise::simplex(0.005).fbm(5, 0.5, 2.0);
+    /// let heightmap = base.generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// // Detail on top of the cached heightmap, without generating the base again.
+    /// let detail = Noise::sample(heightmap, [0.0, 0.0, 0.0], 1.0).add(Noise::simplex(0.1));
+    /// let values = detail.generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// ```
+    pub fn sample(result: impl Into<Arc<NoiseResult>>, origin: [f32; 3], spacing: f32) -> Self {
+        assert!(spacing > 0.0, "The spacing must be larger than 0");
+        let buffer = Buffer::new(result.into());
+        assert!(!buffer.result.is_empty(), "The result can't be empty");
 
-    /// Add two noises, the result is not normalized.
-    pub fn add(mut self, mut other: Self) -> Self {
-        self.pipeline.append(&mut other.pipeline);
-        self.pipeline.push(NoiseSettings::Add);
-        self
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(vec![NoiseSettings::Sample {
+                buffer: buffer.id,
+                origin,
+                spacing,
+            }]),
+            buffers: vec![buffer],
+        }
     }
 
-    /// Multiply two noises, the result is not normalized.
-    pub fn mul(mut self, mut other: Self) -> Self {
-        self.pipeline.append(&mut other.pipeline);
-        self.pipeline.push(NoiseSettings::Mul);
-        self
-    }
+    /// Thin connected filaments for ore veins or crystals, 1 at the center of a vein and falling
+    /// to 0 at its edge, 0 everywhere else. `thickness` is the width of the veins as a fraction
+    /// of the range of the noise, 0.05 is a good start. The veins are stretched `elongation`
+    /// times along the first and third dimensions, 1 gives twisty veins and larger values long
+    /// straight ones.
+    ///
+    /// The veins are where two noises, stretched along different axes, are both close to 0.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Iron where the veins are at least half as strong as at their center
+    /// let veins = Noise::veins(0.02, 0.05, 3.0).seed(7);
+    /// let ore = veins.generate_3d::<f32>(0.0, 0.0, 0.0, 16, 16, 16);
+    /// let iron = ore.iter().map(|value| *value > 0.5);
+    /// ```
+    pub fn veins(frequency: f32, thickness: f32, elongation: f32) -> Self {
+        assert!(thickness > 0.0, "The thickness must be larger than 0");
+        assert!(elongation >= 1.0, "The elongation must be 1 or more");
 
-    /// Clamp the noise between min and max
-    pub fn clamp(mut self, min: f32, max: f32) -> Self {
-        self.pipeline.push(NoiseSettings::Clamp { min, max });
-        self
-    }
+        let stretched = frequency / elongation;
+        let along_x = Noise::simplex(Frequency {
+            x: stretched,
+            y: frequency,
+            z: frequency,
+        });
+        let along_z = Noise::simplex(Frequency {
+            x: frequency,
+            y: frequency,
+            z: stretched,
+        });
 
-    /// Take the maximum of the two noises
-    pub fn max(mut self, mut other: Self) -> Self {
-        self.pipeline.append(&mut other.pipeline);
-        self.pipeline.push(NoiseSettings::Max);
-        self
+        // max(|a|, |b|) is 0 at the center of a vein and `thickness` at its edge, which is
+        // mapped to 1..0 by lerp_values, since it maps -1..1 to low..high.
+        along_x
+            .abs()
+            .max(along_z.abs())
+            .lerp_values(1.0 + 1.0 / thickness, 1.0 - 1.0 / thickness)
+            .clamp(0.0, 1.0)
     }
 
-    /// Take the minimum of the two noises
-    pub fn min(mut self, mut other: Self) -> Self {
-        self.pipeline.append(&mut other.pipeline);
-        self.pipeline.push(NoiseSettings::Min);
+    /// Set the seed of the random number generator.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise_left = Noise::simplex(0.01).seed(1);
+    /// let noise_right = Noise::simplex(0.01).seed(2);
+    /// // In cases like this the seed is always taken from the noise the method is called on,
+    /// // so the seed here will be 1.
+    /// let noise = noise_left.add(noise_right);
+    /// ```
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed as u64;
         self
     }
 
-    /// Linearly interpolate between high and low using the input noise.
-    /// <div class="warning">The 'self' noise is required to be in the -1..1 range.</div>
-    pub fn lerp(mut self, mut low: Self, mut high: Self) -> Self {
-        // XXX: Append order is important for result order
-        self.pipeline.append(&mut high.pipeline);
-        self.pipeline.append(&mut low.pipeline);
-        self.pipeline.push(NoiseSettings::Lerp);
+    /// Set the seed of the random number generator from a string, e.g. a world name typed in by
+    /// a player.
+    ///
+    /// The string is hashed with 64-bit FNV-1a over its UTF-8 bytes. The hash is stable, the same
+    /// string will always produce the same noise, also across versions of this crate.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::simplex(0.01).seed_str("my world name");
+    /// ```
+    pub fn seed_str(mut self, seed: &str) -> Self {
+        self.seed = fnv1a(seed);
         self
     }
 
-    /// Interpolate between the high and low noise. When the input noise is above 'high' it's
-    /// clamped to the high noise, and below 'low' to the low noise. When in-between, use the input
-    /// noise to linearly interpolate between them.
-    pub fn range(mut self, low: f32, high: f32, mut low_noise: Self, mut high_noise: Self) -> Self {
-        // XXX: Append order is important for result order
-        self.pipeline.append(&mut high_noise.pipeline);
-        self.pipeline.append(&mut low_noise.pipeline);
-        self.pipeline.push(NoiseSettings::Range { low, high });
-        self
+    /// Derives the seed of another noise from the seed of this one and a label, so that
+    /// everything generated from a world seed stays deterministic without the noises sharing
+    /// seeds. Different labels give independent seeds, and the same seed and label always give
+    /// the same seed, also across versions of this crate.
+    ///
+    /// The label is hashed like [Noise::seed_str] and mixed into the seed with the hash of the
+    /// crate's random number generator.
+    ///
+    /// # Example
+    /// ```rust
+    /// let terrain = Noise::simplex(0.01).seed_str("my world name");
+    /// let caves = Noise::simplex(0.05).seed(terrain.derive_seed("caves"));
+    /// let ores = Noise::simplex(0.1).seed(terrain.derive_seed("ores"));
+    /// ```
+    pub fn derive_seed(&self, label: &str) -> u32 {
+        mix(self.seed, fnv1a(label)) as u32
     }
 
-    /// Square the noise, noise²
-    pub fn square(mut self) -> Self {
-        self.pipeline.push(NoiseSettings::Square);
+    /// Pin the version of the algorithms used to generate the noise. New noises use
+    /// [Algorithm::V1], and that default never changes, so a world keeps generating the same
+    /// terrain across versions of this crate. Like the seed, the algorithm of a combined noise is
+    /// taken from the noise the method is called on.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::simplex(0.01).algorithm(Algorithm::V1);
+    /// ```
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
         self
     }
 
-    /// Generates a line of noise. It also returns the min and max values generated.
+    /// Computes `octaves` layers of noise and adds them together, normalizing the result. Each
+    /// consecutive octave has its frequency multiplied by `lacunarity` and its amplitude
+    /// multiplied by `gain`. The lacunarity can be different along each axis, see [Lacunarity].
     ///
     /// # Example
+    /// ```rust
+    /// // Common values for gain and lacunarity. Each octave has half the amplitude and twice
+    /// // the frequency, addding finer detail to the noise.
+    /// let noise = Noise::simplex(0.01).fbm(5, 0.5, 2.0);
     /// ```
-    /// let width = 16;
-    /// let (noise, _min, _max) = Noise::perlin(0.01).generate_1d(0.0, width);
-    /// for x in 0..width {
-    ///     let value = noise[x];
-    /// }
-    /// ```
-    pub fn generate_1d(&self, x: f32, width: usize) -> (Vec<f32>, f32, f32) {
-        unsafe { (generate_1d())(self, x, width) }
+    pub fn fbm(self, octaves: u32, gain: f32, lacunarity: impl Into<Lacunarity>) -> Self {
+        self.fbm_octaves(octaves, gain, lacunarity.into(), None)
     }
 
-    /// Generates a plane of noise. It also returns the min and max values generated.
+    /// Same as [Noise::fbm], but each octave is shaped by `op` before the octaves are added
+    /// together.
     ///
     /// # Example
+    /// ```rust
+    /// // Ridged multifractal style mountains
+    /// let noise = Noise::simplex(0.01).fbm_with(5, 0.5, 2.0, OctaveOp::Ridge);
     /// ```
-    /// let width = 16;
-    /// let height = 16;
-    /// let (noise, _min, _max) = Noise::perlin(0.01).generate_2d(0.0, 0.0, width, height);
-    /// for x in 0..width {
-    ///     for y in 0..height {
-    ///         // This is how you should index the generated values
-    ///         let index = x * height + y;
-    ///         let value = noise[index];
-    ///         // If width == height and it's a power of 2 you can also index like this
-    ///         // 16 is 2^4 so we use 4 to shift. At 32 it would be 5, and 64, 6 etc...
-    ///         let index = x << 4 | y;
-    ///         let value = noise[index];
-    ///     }
-    /// }
+    pub fn fbm_with(
+        self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: impl Into<Lacunarity>,
+        op: OctaveOp,
+    ) -> Self {
+        self.fbm_octaves(octaves, gain, lacunarity.into(), Some(op))
+    }
+
+    /// Ridged multifractal noise, octaves like those of [Noise::fbm] turned into sharp ridges
+    /// where the noise crosses 0, as 1 - |n| squared. Unlike [OctaveOp::Ridge], each octave is
+    /// weighted by the one before it, so the finer octaves add detail along the ridges and leave
+    /// the valleys between them smooth, like eroded mountains. The values are 0..1, 1 on the
+    /// highest ridges.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mountains = Noise::simplex(0.005).ridged(6, 0.5, 2.0).lerp_values(40.0, 200.0);
     /// ```
-    pub fn generate_2d(&self, x: f32, y: f32, width: usize, height: usize) -> (Vec<f32>, f32, f32) {
-        unsafe { (generate_2d())(self, x, y, width, height) }
+    pub fn ridged(self, octaves: u32, gain: f32, lacunarity: impl Into<Lacunarity>) -> Self {
+        // The octaves are the same as those of fbm, only the node adding them up differs.
+        let mut noise = self.fbm_octaves(octaves, gain, lacunarity.into(), None);
+        *noise.pipeline.to_mut().last_mut().unwrap() = NoiseSettings::Ridged {
+            octaves,
+            gain,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        };
+        noise
     }
 
-    /// Generates a cube of noise. It also returns the min and max values generated.
+    /// Ping-pong fractal noise, like the PingPong fractal of FastNoiseLite. Each octave is
+    /// folded through a triangle wave before the octaves are added together, (n + 1) *
+    /// `strength` bounces back and forth between 0 and 1, so a higher strength gives more bands.
+    /// It gives banded, terraced patterns like layers of rock. The values are -1..1.
     ///
     /// # Example
+    /// ```rust
+    /// let strata = Noise::simplex(0.01).ping_pong(3, 0.5, 2.0, 2.0);
     /// ```
-    /// let width = 16;
-    /// let height = 16;
-    /// let depth = 16;
-    /// let (noise, _min, _max) = Noise::perlin(0.01).generate_3d(0.0, 0.0, 0.0, width, height, depth);
-    /// for x in 0..width {
-    ///     for z in 0..depth {
-    ///         for y in 0..height {
-    ///             // This is how you should index the generated values
-    ///             let index = x * depth * height + z * height + y;
-    ///             let value = noise[index];
-    ///             // If width == height == depth and it's a power of 2 you can also index like this
-    ///             // 16 is 2^4 so we use 4/8 to shift. At 32 it would be 5/10, and 64, 6/12 etc...
-    ///             let index = x << 8 | z << 4 | y;
-    ///             let value = noise[index];
-    ///         }
-    ///     }
-    /// }
+    pub fn ping_pong(
+        self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: impl Into<Lacunarity>,
+        strength: f32,
+    ) -> Self {
+        // The octaves are the same as those of fbm, only the node adding them up differs.
+        let mut noise = self.fbm_octaves(octaves, gain, lacunarity.into(), None);
+        *noise.pipeline.to_mut().last_mut().unwrap() = NoiseSettings::PingPong {
+            octaves,
+            gain,
+            strength,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        };
+        noise
+    }
+
+    fn fbm_octaves(
+        mut self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: Lacunarity,
+        op: Option<OctaveOp>,
+    ) -> Self {
+        assert!(octaves > 0, "There must be 1 or more octaves");
+
+        // Add the octaves so that they go from most lacunarized to least since they are added in
+        // reverse order during computation.
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        for i in (0..octaves).rev() {
+            let lacunarity = Lacunarity {
+                x: powi(lacunarity.x, i),
+                y: powi(lacunarity.y, i),
+                z: powi(lacunarity.z, i),
+            };
+            for settings in initial_pipeline.iter() {
+                let mut settings = *settings;
+                settings.scale_frequency_axes(lacunarity);
+                pipeline.push(settings);
+            }
+
+            match op {
+                Some(OctaveOp::Abs) => pipeline.push(NoiseSettings::Abs),
+                Some(OctaveOp::Square) => pipeline.push(NoiseSettings::Square),
+                // 1 - |n|, mapped by lerp_values
+                Some(OctaveOp::Ridge) => {
+                    pipeline.push(NoiseSettings::Abs);
+                    pipeline.push(NoiseSettings::LerpValues {
+                        low: 2.0,
+                        high: 0.0,
+                    });
+                }
+                None => (),
+            }
+        }
+
+        pipeline.push(NoiseSettings::Fbm {
+            octaves,
+            gain,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        });
+        self
+    }
+
+    /// Adds up copies of the noise with the frequency, amplitude and seed of each set by hand,
+    /// one for each of the `octaves`. Where [Noise::fbm] follows the gain and lacunarity from
+    /// one octave to the next, this can boost, weaken or move a single octave. The result is the
+    /// sum of the octaves times their amplitudes, it isn't normalized.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Like fbm(4, 0.5, 2.0), but with the third octave boosted and given another pattern
+    /// let octave = |frequency_mul, amplitude, seed_offset| OctaveParams {
+    ///     frequency_mul,
+    ///     amplitude,
+    ///     seed_offset,
+    /// };
+    /// let noise = Noise::simplex(0.01).fbm_custom(&[
+    ///     octave(1.0, 0.5, 0),
+    ///     octave(2.0, 0.25, 0),
+    ///     octave(4.0, 0.3, 7),
+    ///     octave(8.0, 0.0625, 0),
+    /// ]);
     /// ```
-    pub fn generate_3d(
-        &self,
-        x: f32,
-        y: f32,
-        z: f32,
-        width: usize,
-        height: usize,
-        depth: usize,
-    ) -> (Vec<f32>, f32, f32) {
-        unsafe { (generate_3d())(self, x, y, z, width, height, depth) }
+    pub fn fbm_custom(mut self, octaves: &[OctaveParams]) -> Self {
+        assert!(!octaves.is_empty(), "There must be 1 or more octaves");
+
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        for (i, octave) in octaves.iter().enumerate() {
+            // The offset is undone after the octave, so the other octaves keep their seeds.
+            let offset = octave.seed_offset as u64;
+            if offset != 0 {
+                pipeline.push(NoiseSettings::SeedOffset { offset });
+            }
+            for settings in initial_pipeline.iter() {
+                let mut settings = *settings;
+                settings.scale_frequency(octave.frequency_mul);
+                pipeline.push(settings);
+            }
+            if offset != 0 {
+                pipeline.push(NoiseSettings::SeedOffset {
+                    offset: offset.wrapping_neg(),
+                });
+            }
+
+            pipeline.push(NoiseSettings::Constant {
+                value: octave.amplitude,
+            });
+            pipeline.push(NoiseSettings::Mul);
+            if i > 0 {
+                pipeline.push(NoiseSettings::Add);
+            }
+        }
+        self
     }
-}
 
-/// The frequencies of a noise.
-///
-/// # Example
-/// ```
-/// // A convenient way to set the frequencies, as you usually want them to be the same.
-/// // If you intend to generate 1d/2d noise it doesn't matter if the remaining axis are
-/// // set.
-/// let noise = Noise::perlin(0.01);
-/// // Is equivalent to
-/// let noise = Noise::perlin(Frequency {
-///     x: 0.01,
-///     y: 0.01,
-///     z: 0.01
-/// });
-/// ```
-#[derive(Clone, Copy, Debug)]
-pub struct Frequency {
-    /// First dimension
-    pub x: f32,
-    /// Third dimension
-    pub y: f32,
-    /// Second dimension
-    pub z: f32,
-}
+    /// Wraps the coordinates so that the noise repeats every `period` along each axis, e.g. for
+    /// worlds shaped like a torus. A period of 0 leaves the axis unwrapped. For 2d noise
+    /// `period_z` is used for the second dimension and `period_y` should be 0.
+    ///
+    /// The noise is sampled at the wrapped coordinates and one period earlier, and the two are
+    /// blended so there is no seam at the wrap. This means the noise is evaluated twice for
+    /// each wrapped axis, and that values in the middle of the period are slightly flatter.
+    ///
+    /// # Example
+    /// ```rust
+    /// // A world that repeats every 4096 blocks horizontally
+    /// let noise = Noise::simplex(0.01)
+    ///     .fbm(4, 0.5, 2.0)
+    ///     .wrap_domain(4096.0, 0.0, 4096.0);
+    /// ```
+    pub fn wrap_domain(mut self, period_x: f32, period_y: f32, period_z: f32) -> Self {
+        let period = [period_x, period_y, period_z];
+        assert!(
+            period.iter().all(|period| *period >= 0.0),
+            "The periods can't be negative"
+        );
 
-impl From<f32> for Frequency {
-    fn from(value: f32) -> Self {
-        Self {
-            x: value,
-            y: value,
-            z: value,
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        let corners = NoiseSettings::wrap_corners(period);
+        for i in 0..corners {
+            pipeline.push(NoiseSettings::WrapDomain {
+                period,
+                corner: NoiseSettings::wrap_corner(period, i),
+            });
+            pipeline.extend_from_slice(&initial_pipeline);
+            pipeline.push(NoiseSettings::RestoreCoordinates {
+                rewind: i + 1 < corners,
+            });
         }
+        pipeline.push(NoiseSettings::WrapBlend { period });
+        self
     }
-}
 
-#[derive(Clone, Debug)]
-enum NoiseSettings {
-    Simplex {
-        frequency: Frequency,
-    },
-    Perlin {
-        frequency: Frequency,
-    },
-    Constant {
-        value: f32,
-    },
-    Fbm {
-        // Total number of octaves
-        // The number of octaves control the amount of detail in the noise function.
-        // Adding more octaves increases the detail, with the drawback of increasing the calculation time.
-        octaves: u32,
-        // Gain is a multiplier on the amplitude of each successive octave.
-        // i.e. A gain of 2.0 will cause each octave to be twice as impactful on the result as the
-        // previous one.
-        gain: f32,
-        // Derived scaled amplitude for normalizing the result.
-        first_octave_amplitude: f32,
-    },
-    Abs,
-    Add,
-    Mul,
-    Clamp {
-        min: f32,
-        max: f32,
-    },
-    Max,
-    Min,
-    Lerp,
-    Range {
-        low: f32,
-        high: f32,
-    },
-    Square,
+    /// Warps the noise, it is sampled at coordinates moved by the value of `offset` times
+    /// `amplitude` along each axis. The offset noise is sampled at the coordinates before they
+    /// are moved, once for each axis with other seeds, so it is evaluated 3 times. Warping
+    /// twists the regular shapes of the noise into swirls and folds, which looks more like
+    /// terrain shaped by wind and water.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Terrain moved around by up to 40 blocks
+    /// let terrain = Noise::perlin(0.01)
+    ///     .fbm(4, 0.5, 2.0)
+    ///     .warp(Noise::simplex(0.005), 40.0);
+    /// ```
+    pub fn warp(mut self, offset: Self, amplitude: f32) -> Self {
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        for _ in 0..3 {
+            self.append(&offset);
+        }
+        let pipeline = self.pipeline.to_mut();
+        pipeline.push(NoiseSettings::Warp { amplitude });
+        pipeline.extend_from_slice(&initial_pipeline);
+        pipeline.push(NoiseSettings::RestoreCoordinates { rewind: false });
+        self
+    }
+
+    /// The steepness of the noise, how much it changes per unit of distance. It's 0 where the
+    /// noise is flat, e.g. a heightmap noise can be turned into a mask of its cliffs.
+    ///
+    /// When the noise can be generated with derivatives, see
+    /// [Noise::generate_2d_with_derivatives], the slope is the exact length of its gradient in
+    /// 2d and 3d, and the noise is evaluated once. Otherwise, and in 1d and 4d, it is estimated
+    /// from copies of the noise sampled a step before and after along each axis, with a step of
+    /// a hundredth of the shortest wavelength in the noise, and the noise is evaluated twice for
+    /// each axis.
+    ///
+    /// # Example
+    /// ```rust
+    /// let height = Noise::simplex(0.01).fbm(4, 0.5, 2.0).lerp_values(0.0, 100.0);
+    /// // Rock where the terrain rises more than 1 block per block
+    /// let cliffs = height.slope().range(0.9, 1.1, Noise::constant(0.0), Noise::constant(1.0));
+    /// ```
+    pub fn slope(mut self) -> Self {
+        let step = NoiseSettings::slope_step(&self.pipeline);
+        let offsets = NoiseSettings::slope_offsets(step);
+
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        for (i, offset) in offsets.into_iter().enumerate() {
+            pipeline.push(NoiseSettings::OffsetDomain { offset });
+            pipeline.extend_from_slice(&initial_pipeline);
+            pipeline.push(NoiseSettings::RestoreCoordinates {
+                rewind: i + 1 < offsets.len(),
+            });
+        }
+        pipeline.push(NoiseSettings::Slope { step });
+        self
+    }
+
+    /// Samples the noise as 2d noise at (x, z) when it is generated in 3d, so it is the same
+    /// at every height like a heightmap. Heightmap noises can then be combined with 3d noises,
+    /// e.g. caves, in a single 3d generation. On its own the noise has the same values as when
+    /// generated with [Noise::generate_2d]. In 1d and 2d it has no effect.
+    ///
+    /// [Noise::generate_3d] computes the noise once per column of values and reuses it along y,
+    /// so an expensive surface noise costs about as much as it does in 2d.
+    ///
+    /// # Example
+    /// ```rust
+    /// let terrain = Noise::simplex(0.005).fbm(5, 0.5, 2.0).extrude_2d();
+    /// let caves = Noise::simplex(0.02).fbm(2, 0.5, 2.0).abs();
+    /// let density = terrain.min(caves);
+    /// let chunk = density.generate_3d::<f32>(0.0, 0.0, 0.0, 32, 32, 32);
+    /// ```
+    pub fn extrude_2d(mut self) -> Self {
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        pipeline.push(NoiseSettings::Extrude2d);
+        pipeline.extend_from_slice(&initial_pipeline);
+        pipeline.push(NoiseSettings::RestoreCoordinates { rewind: false });
+        self
+    }
+
+    /// Evaluates the noise only at the corners of a lattice with cells of `spacing` along every
+    /// axis, and interpolates linearly between them. The values at the corners of recently used
+    /// cells are kept, so a noise that changes little over a cell, like the continents under
+    /// the detail of a terrain, only costs a fraction of evaluating it everywhere.
+    ///
+    /// The result is only an approximation of the noise. Features smaller than a few cells are
+    /// lost, and the slope of the noise changes abruptly at the edges of the cells, so it is
+    /// for noises with a wavelength many times the spacing, i.e. a frequency well below
+    /// `1 / spacing`. The noise can't be translated to shader code.
+    ///
+    /// # Example
+    /// ```rust
+    /// // The continents change over thousands of blocks, the detail over tens.
+    /// let continents = Noise::simplex(0.0005).fbm(3, 0.5, 2.0).interpolated(16.0);
+    /// let detail = Noise::simplex(0.02).fbm(4, 0.5, 2.0);
+    /// let terrain = continents.add(detail.mul(Noise::constant(0.2)));
+    /// let chunk = terrain.generate_3d::<f32>(0.0, 0.0, 0.0, 32, 32, 32);
+    /// ```
+    pub fn interpolated(mut self, spacing: f32) -> Self {
+        assert!(spacing > 0.0, "The spacing must be larger than 0");
+        let initial_pipeline = std::mem::take(&mut self.pipeline);
+        let pipeline = self.pipeline.to_mut();
+        pipeline.push(NoiseSettings::Interpolate { spacing });
+        pipeline.extend_from_slice(&initial_pipeline);
+        pipeline.push(NoiseSettings::RestoreCoordinates { rewind: false });
+        self
+    }
+
+    /// Computes the absolute value of the noise
+    pub fn abs(mut self) -> Self {
+        self.pipeline.to_mut().push(NoiseSettings::Abs);
+        self
+    }
+
+    // Appends the nodes of the other noise, and the results it samples.
+    fn append(&mut self, other: &Noise) {
+        self.pipeline.to_mut().extend_from_slice(&other.pipeline);
+        for buffer in &other.buffers {
+            if !self.buffers.contains(buffer) {
+                self.buffers.push(buffer.clone());
+            }
+        }
+    }
+
+    /// Add two noises, the result is not normalized.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Add);
+        self
+    }
+
+    /// Subtract the other noise from this one, the result is not normalized.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Sub);
+        self
+    }
+
+    /// The absolute difference between two noises, |a - b|. It is 0 where the noises are equal,
+    /// e.g. rivers can be placed where it is below a threshold.
+    pub fn abs_diff(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::AbsDiff);
+        self
+    }
+
+    /// The average of two noises, unlike [Noise::add] the result stays in the range of the
+    /// noises.
+    pub fn avg(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline
+            .to_mut()
+            .push(NoiseSettings::Average { count: 2 });
+        self
+    }
+
+    /// The average of any number of noises. The seed of the first noise is used.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::avg_n(vec![
+    ///     Noise::simplex(0.01),
+    ///     Noise::perlin(0.02),
+    ///     Noise::simplex(0.05).abs(),
+    /// ]);
+    /// ```
+    pub fn avg_n(noises: Vec<Noise>) -> Self {
+        assert!(
+            !noises.is_empty(),
+            "There must be 1 or more noises to average"
+        );
+        let count = noises.len() as u32;
+        let mut noises = noises.into_iter();
+        let mut noise = noises.next().unwrap();
+        for other in noises {
+            noise.append(&other);
+        }
+        noise
+            .pipeline
+            .to_mut()
+            .push(NoiseSettings::Average { count });
+        noise
+    }
+
+    /// Multiply two noises, the result is not normalized.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Mul);
+        self
+    }
+
+    /// Divide the noise by the other noise. The result is infinite where the other noise is 0,
+    /// so it should be a noise that stays away from 0.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Varies from half to twice as strong
+    /// let strength = Noise::simplex(0.002).lerp_values(0.5, 2.0);
+    /// let noise = Noise::simplex(0.01).div(strength);
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Div);
+        self
+    }
+
+    /// Clamp the noise between min and max
+    pub fn clamp(mut self, min: f32, max: f32) -> Self {
+        assert!(min <= max, "The min can't be larger than the max");
+        self.pipeline
+            .to_mut()
+            .push(NoiseSettings::Clamp { min, max });
+        self
+    }
+
+    /// Take the maximum of the two noises
+    pub fn max(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Max);
+        self
+    }
+
+    /// Take the minimum of the two noises
+    pub fn min(mut self, other: Self) -> Self {
+        self.append(&other);
+        self.pipeline.to_mut().push(NoiseSettings::Min);
+        self
+    }
+
+    /// Linearly interpolate between high and low using the input noise.
+    /// <div class="warning">The 'self' noise is required to be in the -1..1 range, use
+    /// <code>lerp_with_range</code> for other ranges.</div>
+    pub fn lerp(self, low: Self, high: Self) -> Self {
+        self.lerp_with_range(-1.0, 1.0, low, high)
+    }
+
+    /// Linearly interpolate between high and low using the input noise, where the input noise is
+    /// known to be in the `selector_min..selector_max` range. Values at `selector_min` give the
+    /// low noise and values at `selector_max` the high noise.
+    ///
+    /// # Example
+    /// ```rust
+    /// // The absolute value of the noise is in the 0..1 range
+    /// let selector = Noise::simplex(0.01).fbm(3, 0.5, 2.0).abs();
+    /// let noise = selector.lerp_with_range(0.0, 1.0, Noise::constant(-1.0), Noise::constant(1.0));
+    /// ```
+    pub fn lerp_with_range(
+        mut self,
+        selector_min: f32,
+        selector_max: f32,
+        low: Self,
+        high: Self,
+    ) -> Self {
+        // XXX: Append order is important for result order
+        self.append(&high);
+        self.append(&low);
+        self.pipeline.to_mut().push(NoiseSettings::Lerp {
+            selector_min,
+            selector_max,
+        });
+        self
+    }
+
+    /// Linearly interpolate between two values using the input noise, -1 gives `low` and 1 gives
+    /// `high`. Same as [Noise::lerp] with two constant noises, but done in a single step.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Terrain height in blocks
+    /// let height = Noise::simplex(0.01).fbm(4, 0.5, 2.0).lerp_values(40.0, 120.0);
+    /// ```
+    pub fn lerp_values(mut self, low: f32, high: f32) -> Self {
+        self.pipeline
+            .to_mut()
+            .push(NoiseSettings::LerpValues { low, high });
+        self
+    }
+
+    /// Interpolate between the high and low noise. When the input noise is above 'high' it's
+    /// clamped to the high noise, and below 'low' to the low noise. When in-between, use the input
+    /// noise to linearly interpolate between them.
+    pub fn range(mut self, low: f32, high: f32, low_noise: Self, high_noise: Self) -> Self {
+        // XXX: Append order is important for result order
+        self.append(&high_noise);
+        self.append(&low_noise);
+        self.pipeline
+            .to_mut()
+            .push(NoiseSettings::Range { low, high });
+        self
+    }
+
+    /// Square the noise, noise²
+    pub fn square(mut self) -> Self {
+        self.pipeline.to_mut().push(NoiseSettings::Square);
+        self
+    }
+
+    /// Reduce the noise to `levels` evenly spaced values from min to max, values outside the
+    /// range are clamped to it. Instead of rounding each value to the nearest level, which shows
+    /// as contour bands, the values are rounded up or down following an ordered dithering
+    /// pattern so that the average over a small area keeps the value of the noise.
+    ///
+    /// The pattern repeats every 8 units of the horizontal coordinates, x and y in 2d and x and
+    /// z in 3d, and is meant for noise sampled once per unit.
+    ///
+    /// # Example
+    /// ```rust
+    /// // Heights that map exactly to the 256 values of an 8-bit heightmap
+    /// let noise = Noise::simplex(0.01).fbm(4, 0.5, 2.0).dither(-1.0, 1.0, 256);
+    /// let heights: Vec<u8> = noise
+    ///     .generate_2d::<f32>(0.0, 0.0, 256, 256)
+    ///     .iter()
+    ///     .map(|height| ((height + 1.0) * 127.5).round() as u8)
+    ///     .collect();
+    /// ```
+    pub fn dither(mut self, min: f32, max: f32, levels: u32) -> Self {
+        assert!(levels >= 2, "There must be 2 or more levels");
+        self.pipeline
+            .to_mut()
+            .push(NoiseSettings::Dither { min, max, levels });
+        self
+    }
+
+    /// Encodes the noise into a compact binary format, e.g. to send it over the network. The
+    /// format is versioned, bytes encoded by one version of the library can be decoded by any
+    /// later version. The [Algorithm] is stored with the noise, so decoded noises generate the
+    /// same values as when they were encoded. The results sampled by [Noise::sample] are encoded
+    /// along with the nodes.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::simplex(0.01).fbm(4, 0.5, 2.0).seed_str("world");
+    /// let bytes = noise.to_bytes();
+    /// assert_eq!(Noise::from_bytes(&bytes).unwrap(), noise);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytes::encode(self)
+    }
+
+    /// Decodes a noise encoded with [Noise::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        bytes::decode(bytes)
+    }
+
+    /// The nodes the noise is built from, see [NoiseSettings].
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::simplex(0.01).fbm(2, 0.5, 2.0);
+    /// let frequencies: Vec<f32> = noise
+    ///     .settings()
+    ///     .iter()
+    ///     .filter_map(|settings| match settings {
+    ///         NoiseSettings::Simplex { frequency } => Some(frequency.x),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(frequencies, [0.02, 0.01]);
+    /// ```
+    pub fn settings(&self) -> &[NoiseSettings] {
+        &self.pipeline
+    }
+
+    /// The seed of the noise, as set by [Noise::seed] or [Noise::seed_str].
+    pub fn raw_seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Whether the noises are the same apart from the values of their parameters, so they can
+    // be generated by the same pipeline.
+    fn same_nodes(&self, other: &Noise) -> bool {
+        self.seed == other.seed
+            && self.algorithm == other.algorithm
+            && self.pipeline.len() == other.pipeline.len()
+            && self
+                .pipeline
+                .iter()
+                .zip(other.pipeline.iter())
+                .all(|(a, b)| match (a, b) {
+                    (
+                        NoiseSettings::Parameter { name: a, .. },
+                        NoiseSettings::Parameter { name: b, .. },
+                    ) => a == b,
+                    (a, b) => a == b,
+                })
+    }
+
+    /// Builds a noise from its nodes, e.g. nodes read with [Noise::settings] and transformed.
+    /// The `first_octave_amplitude` of fbm nodes is derived from their octaves and gain. The
+    /// noise uses [Algorithm::V1], set it with [Noise::algorithm]. The nodes can't include
+    /// [NoiseSettings::Sample], the results they sample aren't part of the nodes.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::simplex(0.01).add(Noise::constant(0.5)).seed(3);
+    /// // Double the frequency of every simplex node
+    /// let mut settings = noise.settings().to_vec();
+    /// for settings in settings.iter_mut() {
+    ///     if let NoiseSettings::Simplex { frequency } = settings {
+    ///         frequency.x *= 2.0;
+    ///         frequency.y *= 2.0;
+    ///         frequency.z *= 2.0;
+    ///     }
+    /// }
+    /// let noise = Noise::from_settings(settings, noise.raw_seed()).unwrap();
+    /// assert_eq!(noise, Noise::simplex(0.02).add(Noise::constant(0.5)).seed(3));
+    /// ```
+    pub fn from_settings(
+        mut settings: Vec<NoiseSettings>,
+        seed: u64,
+    ) -> Result<Self, InvalidPipeline> {
+        if !NoiseSettings::validate(&settings)
+            || settings
+                .iter()
+                .any(|settings| matches!(settings, NoiseSettings::Sample { .. }))
+        {
+            return Err(InvalidPipeline);
+        }
+
+        for settings in settings.iter_mut() {
+            if let NoiseSettings::Fbm {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            }
+            | NoiseSettings::Ridged {
+                octaves,
+                gain,
+                first_octave_amplitude,
+            }
+            | NoiseSettings::PingPong {
+                octaves,
+                gain,
+                first_octave_amplitude,
+                ..
+            } = settings
+            {
+                *first_octave_amplitude = NoiseSettings::first_octave_amplitude(*octaves, *gain);
+            }
+        }
+
+        Ok(Self {
+            seed,
+            algorithm: Algorithm::V1,
+            pipeline: Cow::Owned(settings),
+            buffers: Vec::new(),
+        })
+    }
+
+    /// Translates the noise into WGSL functions named `{name}_1d`, `{name}_2d` and `{name}_3d`,
+    /// which compute the same values as the `generate_*` functions for the given coordinates.
+    /// The functions depend on [WGSL_LIBRARY], which must be included once in the shader.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::perlin(0.01).fbm(4, 0.5, 2.0);
+    /// let shader = format!(
+    ///     "{}\n{}\n{}",
+    ///     fmc_noise::WGSL_LIBRARY,
+    ///     noise.to_wgsl("terrain").unwrap(),
+    ///     r#"
+    ///     @group(0) @binding(0) var<storage, read_write> output: array<f32>;
+    ///
+    ///     @compute @workgroup_size(64)
+    ///     fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    ///         output[id.x] = terrain_2d(f32(id.x / 16u), f32(id.x % 16u));
+    ///     }
+    ///     "#
+    /// );
+    /// ```
+    pub fn to_wgsl(&self, name: &str) -> Result<String, UnsupportedNode> {
+        shader::generate(self, name, shader::Language::Wgsl)
+    }
+
+    /// Translates the noise into GLSL functions named `{name}_1d`, `{name}_2d` and `{name}_3d`,
+    /// like [Noise::to_wgsl]. The functions depend on [GLSL_LIBRARY], which must be included
+    /// before them. Requires GLSL 4.00 or later.
+    ///
+    /// # Example
+    /// ```rust
+    /// let noise = Noise::perlin(0.01).fbm(4, 0.5, 2.0);
+    /// let shader = format!(
+    ///     "#version 450\n{}\n{}\n{}",
+    ///     fmc_noise::GLSL_LIBRARY,
+    ///     noise.to_glsl("terrain").unwrap(),
+    ///     r#"
+    ///     layout(local_size_x = 64) in;
+    ///     layout(std430, binding = 0) buffer Output { float values[]; };
+    ///
+    ///     void main() {
+    ///         uint id = gl_GlobalInvocationID.x;
+    ///         values[id] = terrain_2d(float(id / 16u), float(id % 16u));
+    ///     }
+    ///     "#
+    /// );
+    /// ```
+    pub fn to_glsl(&self, name: &str) -> Result<String, UnsupportedNode> {
+        shader::generate(self, name, shader::Language::Glsl)
+    }
+
+    /// Compiles the noise into a SPIR-V compute module that can be dispatched without any
+    /// shader compilation. The module has three entry points, `main_1d`, `main_2d` and
+    /// `main_3d`, each with a workgroup size of 64 and producing one value per invocation.
+    ///
+    /// Bindings, both in group 0:
+    /// - `0`: uniform buffer, the origin as 3 f32 at offset 0, followed by the width, height
+    ///   and depth as 3 u32 at offset 16. 32 bytes in total.
+    /// - `1`: storage buffer of f32, written to in the same order as the `generate_*` functions
+    ///   order their values.
+    ///
+    /// Dispatch `ceil(width * height * depth / 64)` workgroups, leaving out the dimensions the
+    /// entry point doesn't use. When that is more than the device allows along x, dispatch a
+    /// grid with more than one workgroup along y instead, invocations past the end are skipped.
+    ///
+    /// Requires the `spirv` feature.
+    #[cfg(feature = "spirv")]
+    pub fn to_spirv(&self) -> Result<Vec<u32>, UnsupportedNode> {
+        shader::spirv(self)
+    }
+
+    /// Generates a line of noise on the gpu, see [Noise::generate_3d_gpu].
+    ///
+    /// Requires the `gpu` feature.
+    #[cfg(feature = "gpu")]
+    pub fn generate_1d_gpu(
+        &self,
+        x: impl Into<f64>,
+        width: usize,
+    ) -> Result<NoiseResult, GpuError> {
+        gpu::generate(self, Dimensions::X, [x.into(), 0.0, 0.0], [width, 1, 1])
+    }
+
+    /// Generates a plane of noise on the gpu, see [Noise::generate_3d_gpu].
+    ///
+    /// Requires the `gpu` feature.
+    #[cfg(feature = "gpu")]
+    pub fn generate_2d_gpu(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> Result<NoiseResult, GpuError> {
+        gpu::generate(
+            self,
+            Dimensions::XY,
+            [x.into(), y.into(), 0.0],
+            [width, height, 1],
+        )
+    }
+
+    /// Generates a cube of noise on the gpu, with the same layout and values as
+    /// [Noise::generate_3d] apart from rounding. The noise is translated to a compute shader
+    /// like [Noise::to_wgsl], compiled the first time it is used and kept for the next calls.
+    /// The gpu is set up on the first call, which can take a while.
+    ///
+    /// The coordinates are f32 on the gpu, so far from the origin the values are less precise
+    /// than those generated on the cpu.
+    ///
+    /// Requires the `gpu` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01).fbm(6, 0.5, 2.0);
+    /// let preview = match noise.generate_3d_gpu(0.0, 0.0, 0.0, 512, 256, 512) {
+    ///     Ok(result) => result,
+    ///     // No gpu, fall back to the cpu
+    ///     Err(_) => noise.generate_3d(0.0, 0.0, 0.0, 512, 256, 512),
+    /// };
+    /// ```
+    #[cfg(feature = "gpu")]
+    pub fn generate_3d_gpu(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> Result<NoiseResult, GpuError> {
+        gpu::generate(
+            self,
+            Dimensions::XYZ,
+            [x.into(), y.into(), z.into()],
+            [width, height, depth],
+        )
+    }
+
+    /// Generates a line of noise. The result also holds the min and max values generated.
+    ///
+    /// The values are generated as `T`, `f32` or `f64`, see [Float].
+    ///
+    /// # Example
+    /// ```
+    /// let width = 16;
+    /// let noise = Noise::perlin(0.01).generate_1d::<f32>(0.0, width);
+    /// for x in 0..width {
+    ///     let value = noise[x];
+    /// }
+    /// ```
+    pub fn generate_1d<T: Float>(&self, x: impl Into<f64>, width: usize) -> NoiseResult<T> {
+        let values = T::generate_1d(self, x.into(), width);
+        NoiseResult::new(values, width, 1, 1)
+    }
+
+    /// Same as [Noise::generate_1d], but the values are written to `out` instead of a new
+    /// vector, as many as it holds. Returns the min and max values generated.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::perlin(0.01);
+    /// let mut line = vec![0.0f32; 256];
+    /// for start in 0..4 {
+    ///     let (min, max) = noise.generate_1d_into(start as f64 * 256.0, &mut line);
+    /// }
+    /// ```
+    pub fn generate_1d_into<T: Float>(&self, x: impl Into<f64>, out: &mut [T]) -> (T, T) {
+        T::generate_1d_into(self, x.into(), out)
+    }
+
+    /// Generates `length` units of noise sampled `rate` times per unit, e.g. a sound from the
+    /// sample rate and duration in seconds, or an animation from the frame rate. The frequencies
+    /// of the noise are then in cycles per unit, hertz for audio, independent of the rate.
+    ///
+    /// # Example
+    /// ```
+    /// // Half a second of 440hz hum with a pink noise hiss, at 48khz
+    /// let rate = 48000.0;
+    /// let hum = Noise::simplex(440.0).generate_1d_at_rate(0.0, rate, 0.5);
+    /// let hiss = Noise::pink(rate).generate_1d_at_rate(0.0, rate, 0.5);
+    /// let samples: Vec<f32> = hum.iter().zip(hiss.iter()).map(|(a, b)| a + b * 0.1).collect();
+    /// ```
+    pub fn generate_1d_at_rate(
+        &self,
+        start: impl Into<f64>,
+        rate: f32,
+        length: f32,
+    ) -> NoiseResult {
+        assert!(rate > 0.0, "The rate must be larger than 0");
+        let width = (length as f64 * rate as f64).round().max(0.0) as usize;
+        let values = single::generate::generate_1d(self, start.into(), 1.0 / rate as f64, width);
+        NoiseResult::new(values, width, 1, 1)
+    }
+
+    /// Generates a line of noise that loops, the value after the last one is the first one
+    /// again. The line is sampled around a circle of circumference `length` in the 2d noise, so
+    /// features are the same size as with [Noise::generate_1d].
+    ///
+    /// # Example
+    /// ```
+    /// // A sway animation that repeats every 240 frames.
+    /// let sway = Noise::simplex(0.05).generate_1d_periodic(240);
+    /// let frame = 1000;
+    /// let angle = sway[frame % 240] * 0.1;
+    /// ```
+    pub fn generate_1d_periodic(&self, length: usize) -> NoiseResult {
+        let values = unsafe { (generate_1d_periodic())(self, length) };
+        NoiseResult::new(values, length, 1, 1)
+    }
+
+    /// Generates a plane of noise. The result also holds the min and max values generated.
+    ///
+    /// The values are generated as `T`, `f32` or `f64`, see [Float].
+    ///
+    /// # Example
+    /// ```
+    /// let width = 16;
+    /// let height = 16;
+    /// let noise = Noise::perlin(0.01).generate_2d::<f32>(0.0, 0.0, width, height);
+    /// for x in 0..width {
+    ///     for y in 0..height {
+    ///         // This is how you should index the generated values
+    ///         let index = x * height + y;
+    ///         let value = noise[index];
+    ///         // If width == height and it's a power of 2 you can also index like this
+    ///         // 16 is 2^4 so we use 4 to shift. At 32 it would be 5, and 64, 6 etc...
+    ///         let index = x << 4 | y;
+    ///         let value = noise[index];
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_2d<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> NoiseResult<T> {
+        let values = T::generate_2d(self, x.into(), y.into(), width, height);
+        NoiseResult::new(values, width, height, 1)
+    }
+
+    /// Same as [Noise::generate_2d], but the values are written to `out` instead of a new
+    /// vector, in the same order. Returns the min and max values generated.
+    ///
+    /// Panics if `out` doesn't hold `width * height` values.
+    pub fn generate_2d_into<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+        out: &mut [T],
+    ) -> (T, T) {
+        assert!(
+            out.len() == width * height,
+            "The output must hold width * height values"
+        );
+        T::generate_2d_into(self, x.into(), y.into(), width, height, out)
+    }
+
+    /// Generates a plane of noise that repeats every `period_x` values along x and `period_y`
+    /// along y, laid out like [Noise::generate_2d]. With the periods the same as the size the
+    /// result tiles seamlessly, e.g. for textures.
+    ///
+    /// Each axis is sampled around a circle of circumference `period` like
+    /// [Noise::generate_1d_periodic], together they make a torus in 4d noise, see
+    /// [Noise::generate_4d]. The first axis goes around x and w and the second around z and y,
+    /// so the frequencies of the noise along x and z should be the same as along w and y.
+    /// Nodes without a 4d kernel don't change along w and are mirrored in each period.
+    ///
+    /// # Example
+    /// ```
+    /// // A 256x256 texture that tiles
+    /// let texture = Noise::simplex(0.02)
+    ///     .fbm(5, 0.5, 2.0)
+    ///     .generate_2d_tileable(256, 256, 256.0, 256.0);
+    /// ```
+    pub fn generate_2d_tileable(
+        &self,
+        width: usize,
+        height: usize,
+        period_x: f32,
+        period_y: f32,
+    ) -> NoiseResult {
+        assert!(
+            period_x > 0.0 && period_y > 0.0,
+            "The periods must be larger than 0"
+        );
+        let values = unsafe {
+            (generate_2d_tileable())(self, width, height, [period_x as f64, period_y as f64])
+        };
+        NoiseResult::new(values, width, height, 1)
+    }
+
+    /// Generates a cube of noise. The result also holds the min and max values generated.
+    ///
+    /// The values are generated as `T`, `f32` or `f64`, see [Float].
+    ///
+    /// # Example
+    /// ```
+    /// let width = 16;
+    /// let height = 16;
+    /// let depth = 16;
+    /// let noise = Noise::perlin(0.01).generate_3d::<f32>(0.0, 0.0, 0.0, width, height, depth);
+    /// for x in 0..width {
+    ///     for z in 0..depth {
+    ///         for y in 0..height {
+    ///             // This is how you should index the generated values
+    ///             let index = x * depth * height + z * height + y;
+    ///             let value = noise[index];
+    ///             // If width == height == depth and it's a power of 2 you can also index like this
+    ///             // 16 is 2^4 so we use 4/8 to shift. At 32 it would be 5/10, and 64, 6/12 etc...
+    ///             let index = x << 8 | z << 4 | y;
+    ///             let value = noise[index];
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_3d<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> NoiseResult<T> {
+        let origin = [x.into(), y.into(), z.into()];
+        let values = T::generate_3d(self, origin, [width, height, depth]);
+        NoiseResult::new(values, width, height, depth)
+    }
+
+    /// Same as [Noise::generate_3d], but the values are written to `out` instead of a new
+    /// vector, in the same order. Returns the min and max values generated.
+    ///
+    /// Panics if `out` doesn't hold `width * height * depth` values.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01);
+    /// // A buffer reused for every chunk
+    /// let mut density = vec![0.0f32; 32 * 32 * 32];
+    /// for chunk_x in 0..4 {
+    ///     let x = chunk_x as f64 * 32.0;
+    ///     let (min, max) = noise.generate_3d_into(x, 0.0, 0.0, 32, 32, 32, &mut density);
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_into<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        out: &mut [T],
+    ) -> (T, T) {
+        assert!(
+            out.len() == width * height * depth,
+            "The output must hold width * height * depth values"
+        );
+        let origin = [x.into(), y.into(), z.into()];
+        T::generate_3d_into(self, origin, [width, height, depth], out)
+    }
+
+    /// Same as [Noise::generate_2d], but the result also holds the partial derivatives of every
+    /// value along x and y, see [NoiseResult::gradient]. They are computed analytically along
+    /// with the values, which is exact and cheaper than sampling the noise around every value.
+    /// The derivatives are per unit of the coordinates, e.g. a derivative of 0.1 along x means
+    /// the value rises by about 0.1 from one value to the next along x.
+    ///
+    /// At the kinks of nodes like [Noise::abs] and [Noise::clamp] the derivative of one side is
+    /// used. The derivatives of a [Noise::slope] are estimated from its value a step before and
+    /// after along each axis. Returns an error if the noise has a node without an analytical derivative, e.g.
+    /// cellular or value noise.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).fbm(5, 0.5, 2.0);
+    /// let height = height.generate_2d_with_derivatives::<f32>(0.0, 0.0, 256, 256).unwrap();
+    /// let gradient = height.gradient().unwrap();
+    /// // Erosion only where the terrain is steep
+    /// let steep: Vec<bool> = (0..height.len())
+    ///     .map(|i| gradient.x[i].hypot(gradient.y[i]) > 0.01)
+    ///     .collect();
+    /// ```
+    pub fn generate_2d_with_derivatives<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> Result<NoiseResult<T>, NotDifferentiable> {
+        derivatives::check(self)?;
+        let origin = [x.into(), y.into(), 0.0];
+        let (values, min, max, gradient) =
+            T::generate_derivatives(self, origin, [width, height, 1], Dimensions::XY);
+        let mut result = NoiseResult::new((values, min, max), width, height, 1);
+        result.gradient = Some(Box::new(gradient));
+        Ok(result)
+    }
+
+    /// Same as [Noise::generate_3d], but the result also holds the partial derivatives of every
+    /// value along x, y and z, like [Noise::generate_2d_with_derivatives].
+    ///
+    /// # Example
+    /// ```
+    /// let density = Noise::perlin(0.02).fbm(3, 0.5, 2.0);
+    /// let density = density
+    ///     .generate_3d_with_derivatives::<f32>(0.0, 0.0, 0.0, 32, 32, 32)
+    ///     .unwrap();
+    /// let gradient = density.gradient().unwrap();
+    /// // The surface normal of the voxel at (1, 2, 3) points down the gradient
+    /// let i = 1 * 32 * 32 + 3 * 32 + 2;
+    /// let normal = [-gradient.x[i], -gradient.y[i], -gradient.z[i]];
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_with_derivatives<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> Result<NoiseResult<T>, NotDifferentiable> {
+        derivatives::check(self)?;
+        let origin = [x.into(), y.into(), z.into()];
+        let (values, min, max, gradient) =
+            T::generate_derivatives(self, origin, [width, height, depth], Dimensions::XYZ);
+        let mut result = NoiseResult::new((values, min, max), width, height, depth);
+        result.gradient = Some(Box::new(gradient));
+        Ok(result)
+    }
+
+    /// Generates a cube of noise at `w` along a fourth dimension, laid out like
+    /// [Noise::generate_3d]. Moving `w` a little changes the cube a little, e.g. to animate a
+    /// volume over time without it drifting along one of its axes.
+    ///
+    /// Simplex and perlin noise have 4d kernels, the fourth dimension is scaled by their
+    /// frequency along x. OpenSimplex2 uses the 4d simplex kernel. The other nodes are the same
+    /// as in 3d and don't change along `w`.
+    ///
+    /// # Example
+    /// ```
+    /// let clouds = Noise::simplex(0.02).fbm(4, 0.5, 2.0);
+    /// for frame in 0..10 {
+    ///     let time = frame as f64 * 0.5;
+    ///     let density = clouds.generate_4d::<f32>(0.0, 0.0, 0.0, time, 32, 16, 32);
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_4d<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        w: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> NoiseResult<T> {
+        let origin = [x.into(), y.into(), z.into(), w.into()];
+        let values = T::generate_4d(self, origin, [width, height, depth]);
+        NoiseResult::new(values, width, height, depth)
+    }
+
+    /// Same as [Noise::generate_3d], but the result also holds the min and max values of every
+    /// `block_size`³ block of values, see [NoiseResult::blocks]. They are recorded while the
+    /// values are generated, so it's cheaper than going through the values again afterwards.
+    ///
+    /// # Example
+    /// ```
+    /// // Find the sub-chunks of a 64³ region that are entirely air.
+    /// let density = Noise::simplex(0.01).generate_3d_blocks(0.0, 0.0, 0.0, 64, 64, 64, 16);
+    /// let blocks = density.blocks().unwrap();
+    /// for x in 0..blocks.width() {
+    ///     for z in 0..blocks.depth() {
+    ///         for y in 0..blocks.height() {
+    ///             let (min, max) = blocks.get(x, y, z);
+    ///             let empty = max < 0.0;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_blocks(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        block_size: usize,
+    ) -> NoiseResult {
+        assert!(block_size > 0, "The block size must be 1 or more");
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let mut blocks = BlockRanges::new(block_size, width, height, depth);
+        let values = single::generate::generate_3d(
+            self,
+            x,
+            y,
+            z,
+            width,
+            height,
+            depth,
+            Some(&mut blocks),
+            None,
+        );
+        let mut result = NoiseResult::new(values, width, height, depth);
+        result.blocks = Some(blocks);
+        result
+    }
+
+    /// Same as [Noise::generate_2d], with the origin and size as vectors. The origin can be any
+    /// vector that converts to a `DVec2`, e.g. `Vec2` or `IVec2`.
+    ///
+    /// Requires the `glam` feature.
+    #[cfg(feature = "glam")]
+    pub fn generate_2d_at<T: Float>(
+        &self,
+        origin: impl Into<glam::DVec2>,
+        size: glam::UVec2,
+    ) -> NoiseResult<T> {
+        let origin = origin.into();
+        self.generate_2d(origin.x, origin.y, size.x as usize, size.y as usize)
+    }
+
+    /// Same as [Noise::generate_3d], with the origin and size as vectors. The origin can be any
+    /// vector that converts to a `DVec3`, e.g. `Vec3` or the `IVec3` position of a chunk.
+    ///
+    /// Requires the `glam` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use glam::{IVec3, UVec3};
+    ///
+    /// let chunk = IVec3::new(2, -1, 5);
+    /// let size = UVec3::splat(32);
+    /// let density = Noise::simplex(0.01).generate_3d_at::<f32>(chunk * 32, size);
+    /// ```
+    #[cfg(feature = "glam")]
+    pub fn generate_3d_at<T: Float>(
+        &self,
+        origin: impl Into<glam::DVec3>,
+        size: glam::UVec3,
+    ) -> NoiseResult<T> {
+        let origin = origin.into();
+        self.generate_3d(
+            origin.x,
+            origin.y,
+            origin.z,
+            size.x as usize,
+            size.y as usize,
+            size.z as usize,
+        )
+    }
+
+    /// Same as [Noise::generate_3d_blocks], with the origin and size as vectors like
+    /// [Noise::generate_3d_at].
+    ///
+    /// Requires the `glam` feature.
+    #[cfg(feature = "glam")]
+    pub fn generate_3d_blocks_at(
+        &self,
+        origin: impl Into<glam::DVec3>,
+        size: glam::UVec3,
+        block_size: usize,
+    ) -> NoiseResult {
+        let origin = origin.into();
+        self.generate_3d_blocks(
+            origin.x,
+            origin.y,
+            origin.z,
+            size.x as usize,
+            size.y as usize,
+            size.z as usize,
+            block_size,
+        )
+    }
+
+    /// Same as [Noise::generate_2d], but the result also holds the mean, variance and a
+    /// histogram with `bins` bins over `range` of the values, see [NoiseResult::statistics].
+    /// They are accumulated while the values are generated, so it's cheaper than going through
+    /// the values again afterwards.
+    ///
+    /// # Example
+    /// ```
+    /// // Auto-exposure of a preview
+    /// let preview = Noise::simplex(0.01).generate_2d_statistics(0.0, 0.0, 256, 256, 64, -1.0..1.0);
+    /// let statistics = preview.statistics().unwrap();
+    /// let low = statistics.mean() - 2.0 * statistics.standard_deviation();
+    /// let high = statistics.mean() + 2.0 * statistics.standard_deviation();
+    /// ```
+    pub fn generate_2d_statistics(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+        bins: usize,
+        range: std::ops::Range<f32>,
+    ) -> NoiseResult {
+        let mut statistics = Statistics::new(bins, range);
+        let values = single::generate::generate_2d(
+            self,
+            x.into(),
+            y.into(),
+            width,
+            height,
+            Some(&mut statistics),
+        );
+        let mut result = NoiseResult::new(values, width, height, 1);
+        result.statistics = Some(statistics);
+        result
+    }
+
+    /// Same as [Noise::generate_3d], with statistics like [Noise::generate_2d_statistics].
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_statistics(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        bins: usize,
+        range: std::ops::Range<f32>,
+    ) -> NoiseResult {
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let mut statistics = Statistics::new(bins, range);
+        let values = single::generate::generate_3d(
+            self,
+            x,
+            y,
+            z,
+            width,
+            height,
+            depth,
+            None,
+            Some(&mut statistics),
+        );
+        let mut result = NoiseResult::new(values, width, height, depth);
+        result.statistics = Some(statistics);
+        result
+    }
+
+    /// Generates a cube of noise like [Noise::generate_3d], with the octaves of the fbm split
+    /// between up to `threads` threads that each generate the whole cube. Meant for single large
+    /// volumes that can't be split into smaller ones, the values are the same as with
+    /// [Noise::generate_3d] apart from rounding. Noises that don't end in [Noise::fbm] are
+    /// generated on the current thread.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01).fbm(8, 0.5, 2.0);
+    /// let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    /// let volume = noise.generate_3d_parallel_octaves(0.0, 0.0, 0.0, 256, 256, 256, threads);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_parallel_octaves(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        threads: usize,
+    ) -> NoiseResult {
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        parallel::generate_3d(self, x, y, z, width, height, depth, threads)
+    }
+
+    /// Generates a cube of noise like [Noise::generate_3d], split into slabs along x that are
+    /// generated on the rayon thread pool. The values are the same as with
+    /// [Noise::generate_3d], each thread runs its own copy of the noise.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01).fbm(6, 0.5, 2.0);
+    /// let volume = noise.generate_3d_parallel::<f32>(0.0, 0.0, 0.0, 256, 256, 256);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn generate_3d_parallel<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> NoiseResult<T> {
+        let origin = [x.into(), y.into(), z.into()];
+        parallel::generate_3d_slabs(self, origin, [width, height, depth])
+    }
+
+    /// Generates a plane of noise on a grid rotated `angle` radians about its first sample at
+    /// (x, y), turning from the first dimension towards the second. The values are laid out the
+    /// same as with [Noise::generate_2d], an angle of 0 produces the same values.
+    ///
+    /// # Example
+    /// ```
+    /// let angle = std::f32::consts::FRAC_PI_4;
+    /// let noise = Noise::perlin(0.01).generate_2d_rotated(0.0, 0.0, 16, 16, angle);
+    /// ```
+    pub fn generate_2d_rotated(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+        angle: f32,
+    ) -> NoiseResult {
+        let (x, y) = (x.into(), y.into());
+        let values = unsafe { (generate_2d_rotated())(self, x, y, width, height, angle) };
+        NoiseResult::new(values, width, height, 1)
+    }
+
+    /// Generates a cube of noise on a grid rotated about its first sample at (x, y, z). The
+    /// values are laid out the same as with [Noise::generate_3d].
+    ///
+    /// # Example
+    /// ```
+    /// // A chunk of a ship that has turned 30 degrees and is slightly tilted.
+    /// let rotation = Rotation::y(30f32.to_radians()).then(Rotation::x(0.1));
+    /// let noise = Noise::perlin(0.01).generate_3d_rotated(0.0, 0.0, 0.0, 16, 16, 16, rotation);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_rotated(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        rotation: Rotation,
+    ) -> NoiseResult {
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let values =
+            unsafe { (generate_3d_rotated())(self, x, y, z, width, height, depth, rotation) };
+        NoiseResult::new(values, width, height, depth)
+    }
+
+    /// Samples the noise at each of the `points`, given as `[x, y, z]` with y the height like in
+    /// [Noise::generate_3d]. The points don't have to lie on a grid, they are sampled a vector
+    /// at a time in the order given, and the value of each point is at the same index.
+    ///
+    /// # Example
+    /// ```
+    /// // The density along a path between two positions
+    /// let (start, end) = ([0.0, 10.0, 0.0], [100.0, 40.0, -30.0]);
+    /// let path: Vec<[f32; 3]> = (0..64)
+    ///     .map(|i| {
+    ///         let t = i as f32 / 63.0;
+    ///         std::array::from_fn(|axis| start[axis] + (end[axis] - start[axis]) * t)
+    ///     })
+    ///     .collect();
+    /// let density = Noise::simplex(0.01).generate_points(&path);
+    /// ```
+    pub fn generate_points(&self, points: &[[f32; 3]]) -> Vec<f32> {
+        unsafe { (generate_points())(self, points) }
+    }
+
+    /// Generates a cube of noise like [Noise::generate_3d], but only keeps whether each value is
+    /// above the `threshold`, one bit per value. The values are compared as they are generated
+    /// and never stored, which is all that is needed to know which voxels are solid.
+    ///
+    /// # Example
+    /// ```
+    /// let density = Noise::simplex(0.02).fbm(3, 0.5, 2.0);
+    /// let solid = density.generate_3d_threshold(0.0, 0.0, 0.0, 32, 32, 32, 0.0);
+    /// if solid.get(4, 20, 7) {
+    ///     // place a block
+    /// }
+    /// let air = solid.len() - solid.count();
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_threshold(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        threshold: f32,
+    ) -> Occupancy {
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let words =
+            unsafe { (generate_3d_threshold())(self, x, y, z, width, height, depth, threshold) };
+        Occupancy::new(words, width, height, depth)
+    }
+
+    /// Generates a cube of noise like [Noise::generate_3d], but encodes each column along the y
+    /// axis as runs of materials. The material of a value is the number of `thresholds` it is
+    /// above, so with the thresholds `[-0.2, 0.3]` values up to -0.2 are material 0, values up to
+    /// 0.3 material 1 and the rest material 2. The values are never stored, the runs are built as
+    /// they are generated.
+    ///
+    /// The thresholds must be in ascending order, and there can be at most 255.
+    ///
+    /// # Example
+    /// ```
+    /// let density = Noise::simplex(0.02).fbm(3, 0.5, 2.0);
+    /// // air, dirt and stone
+    /// let chunk = density.generate_3d_runs(0.0, 0.0, 0.0, 32, 256, 32, &[0.0, 0.4]);
+    /// for run in chunk.column(3, 5) {
+    ///     println!("{} blocks of material {}", run.length, run.material);
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_runs(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        thresholds: &[f32],
+    ) -> ColumnRuns {
+        assert!(
+            thresholds.len() <= 255,
+            "There can be at most 255 thresholds"
+        );
+        assert!(
+            thresholds.windows(2).all(|pair| pair[0] <= pair[1]),
+            "The thresholds must be in ascending order"
+        );
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let (runs, starts) =
+            unsafe { (generate_3d_runs())(self, x, y, z, width, height, depth, thresholds) };
+        ColumnRuns::new(runs, starts, width, height, depth)
+    }
+
+    /// Generates a cube of noise split into bricks of `size` along every dimension, where only
+    /// the bricks that may cross the `threshold` are generated. The rest are only known to be
+    /// above or below it, like the sky and the solid underground of a terrain.
+    ///
+    /// First the noise is generated at the corners of the bricks. A brick is skipped when the
+    /// values at all its corners are more than `margin` above the threshold, or more than
+    /// `margin` below it. The margin is how far the values inside a brick can stray from the
+    /// values at its corners. The noise changes more over a brick when the frequency or the
+    /// brick is larger, too small a margin misses small pockets inside the skipped bricks, too
+    /// large a margin generates bricks that didn't need to be.
+    ///
+    /// # Example
+    /// ```
+    /// // Caves where the noise is above 0.6, most of the volume is solid.
+    /// let caves = Noise::simplex(0.01).fbm(3, 0.5, 2.0);
+    /// let bricks = caves.generate_3d_bricks(0.0, 0.0, 0.0, 128, 128, 128, 8, 0.6, 0.2);
+    /// println!("{} of {} bricks generated", bricks.generated(), bricks.bricks().len());
+    /// let air = bricks.is_above(10, 70, 20);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_bricks(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        size: usize,
+        threshold: f32,
+        margin: f32,
+    ) -> Bricks {
+        assert!(size > 0, "The brick size must be 1 or more");
+        assert!(margin >= 0.0, "The margin can't be negative");
+        let (x, y, z) = (x.into(), y.into(), z.into());
+        let bricks = unsafe {
+            (generate_3d_bricks())(self, x, y, z, width, height, depth, size, threshold, margin)
+        };
+        Bricks::new(
+            size,
+            width.div_ceil(size),
+            height.div_ceil(size),
+            depth.div_ceil(size),
+            threshold,
+            bricks,
+        )
+    }
+
+    /// Generates a rectangle of noise like [Noise::generate_2d], starting with every
+    /// `coarse_stride`th value and halving the stride until every value is generated. After each
+    /// level `callback` gets the result so far and the stride of the level, the values that
+    /// aren't generated yet are copies of the generated value before them. It is how an editor
+    /// can show a rough preview right away that sharpens as the noise is generated.
+    ///
+    /// Every value is only generated once, so all the levels together cost about the same as
+    /// generating the full result. The last level has a stride of 1 and is the same as
+    /// [Noise::generate_2d], which is also what is returned.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01).fbm(6, 0.5, 2.0);
+    /// let result = noise.generate_2d_progressive(0.0, 0.0, 512, 512, 16, |preview, stride| {
+    ///     println!("stride {stride}, range {:?}", preview.min..preview.max);
+    /// });
+    /// ```
+    pub fn generate_2d_progressive(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+        coarse_stride: usize,
+        mut callback: impl FnMut(&NoiseResult, usize),
+    ) -> NoiseResult {
+        assert!(
+            coarse_stride.is_power_of_two(),
+            "The coarse stride must be a power of 2"
+        );
+        let (x, y) = (x.into(), y.into());
+        let values = unsafe {
+            (generate_2d_progressive())(self, x, y, width, height, coarse_stride, &mut callback)
+        };
+        NoiseResult::new(values, width, height, 1)
+    }
+
+    /// Generates the values of a sub-rectangle of a result again, e.g. after a parameter of the
+    /// noise changed or an edit only affects part of a heightmap. The result must have been
+    /// generated by [Noise::generate_2d] at (`x`, `y`), the values in `region`, counted in values
+    /// from the first value of the result, are the same as when generating the whole result
+    /// with this noise.
+    ///
+    /// The min and max and the [block ranges](NoiseResult::blocks) are updated, the
+    /// [statistics](NoiseResult::statistics) and [gradient](NoiseResult::gradient) no longer
+    /// describe the values and are removed.
+    ///
+    /// # Example
+    /// ```
+    /// let mut noise = CompiledNoise::new(Noise::simplex(0.01).mul(Noise::param("height", 1.0)));
+    /// let mut terrain = noise.generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// noise.set_param("height", 2.0);
+    /// noise.regenerate_2d_region(&mut terrain, 0.0, 0.0, [64..128, 0..32]);
+    /// ```
+    pub fn regenerate_2d_region(
+        &self,
+        result: &mut NoiseResult,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        region: [std::ops::Range<usize>; 2],
+    ) {
+        let [x_range, y_range] = region;
+        self.regenerate_region(
+            result,
+            Dimensions::XY,
+            [x.into(), y.into(), 0.0],
+            [x_range, y_range, 0..1],
+        );
+    }
+
+    /// Same as [Noise::regenerate_2d_region] for a result generated by [Noise::generate_3d] at
+    /// (`x`, `y`, `z`).
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.02);
+    /// let mut chunk = noise.generate_3d_blocks(0.0, 0.0, 0.0, 32, 32, 32, 8);
+    /// // The values were edited near a corner and are restored.
+    /// chunk.values[0] = 1.0;
+    /// noise.regenerate_3d_region(&mut chunk, 0.0, 0.0, 0.0, [0..4, 0..4, 0..4]);
+    /// ```
+    pub fn regenerate_3d_region(
+        &self,
+        result: &mut NoiseResult,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        region: [std::ops::Range<usize>; 3],
+    ) {
+        self.regenerate_region(
+            result,
+            Dimensions::XYZ,
+            [x.into(), y.into(), z.into()],
+            region,
+        );
+    }
+
+    fn regenerate_region(
+        &self,
+        result: &mut NoiseResult,
+        dimensions: Dimensions,
+        origin: [f64; 3],
+        region: [std::ops::Range<usize>; 3],
+    ) {
+        let size = [result.width(), result.height(), result.depth()];
+        for (range, size) in region.iter().zip(size) {
+            assert!(
+                range.start <= range.end && range.end <= size,
+                "The region must be within the result"
+            );
+        }
+        if region.iter().any(|range| range.is_empty()) {
+            return;
+        }
+
+        unsafe {
+            (regenerate_region())(self, &mut result.values, dimensions, origin, size, &region)
+        };
+        result.min = result.values.iter().copied().fold(f32::MAX, f32::min);
+        result.max = result.values.iter().copied().fold(f32::MIN, f32::max);
+        if let Some(blocks) = &mut result.blocks {
+            blocks.update(&result.values, size, &region);
+        }
+        result.statistics = None;
+        result.gradient = None;
+    }
+
+    /// Times this noise with each lane width up to the widest the cpu supports and uses the
+    /// fastest for all generation from then on. The widest is used by default, but it's not
+    /// always the fastest, wide vectors can lower the clock speed of some cpus. Returns the
+    /// chosen width, see [simd_width].
+    ///
+    /// The width is global, it applies to every noise, in f32 and f64, on every thread, and is
+    /// what [simd_width] and [ExecutionPlan::lane_width] report afterwards. The widths are timed
+    /// on the calling thread only, other threads keep generating with the previous width until
+    /// the fastest is chosen.
+    ///
+    /// It takes a few milliseconds, call it once at startup with a representative noise.
+    ///
+    /// # Example
+    /// ```
+    /// let terrain = Noise::simplex(0.01).fbm(5, 0.5, 2.0);
+    /// let width = terrain.calibrate();
+    /// ```
+    pub fn calibrate(&self) -> usize {
+        let widest = unsafe { (simd_width_dispatch())() };
+
+        let previous = LANE_WIDTH_OVERRIDE.get();
+        let mut fastest = (widest, std::time::Duration::MAX);
+        for width in LANE_WIDTHS.into_iter().filter(|&width| width <= widest) {
+            LANE_WIDTH_OVERRIDE.set(width);
+            // The first run builds the pipeline and warms up the caches, the best of the
+            // following runs is kept to be less affected by interruptions.
+            self.generate_3d::<f32>(0.0, 0.0, 0.0, 32, 32, 32);
+            for _ in 0..3 {
+                let start = std::time::Instant::now();
+                std::hint::black_box(self.generate_3d::<f32>(0.0, 0.0, 0.0, 32, 32, 32));
+                let elapsed = start.elapsed();
+                if elapsed < fastest.1 {
+                    fastest = (width, elapsed);
+                }
+            }
+        }
+
+        LANE_WIDTH_OVERRIDE.set(previous);
+        LANE_WIDTH.store(fastest.0, Ordering::Relaxed);
+        fastest.0
+    }
+}
+
+impl std::ops::Add for Noise {
+    type Output = Noise;
+
+    /// See [Noise::add]
+    fn add(self, other: Noise) -> Noise {
+        Noise::add(self, other)
+    }
+}
+
+impl std::ops::Add<f32> for Noise {
+    type Output = Noise;
+
+    fn add(self, value: f32) -> Noise {
+        Noise::add(self, Noise::constant(value))
+    }
+}
+
+impl std::ops::Add<Noise> for f32 {
+    type Output = Noise;
+
+    fn add(self, noise: Noise) -> Noise {
+        noise + self
+    }
+}
+
+impl std::ops::Sub for Noise {
+    type Output = Noise;
+
+    /// See [Noise::sub]
+    fn sub(self, other: Noise) -> Noise {
+        Noise::sub(self, other)
+    }
+}
+
+impl std::ops::Sub<f32> for Noise {
+    type Output = Noise;
+
+    fn sub(self, value: f32) -> Noise {
+        self + -value
+    }
+}
+
+impl std::ops::Sub<Noise> for f32 {
+    type Output = Noise;
+
+    fn sub(self, noise: Noise) -> Noise {
+        -noise + self
+    }
+}
+
+impl std::ops::Mul for Noise {
+    type Output = Noise;
+
+    /// See [Noise::mul]
+    fn mul(self, other: Noise) -> Noise {
+        Noise::mul(self, other)
+    }
+}
+
+impl std::ops::Mul<f32> for Noise {
+    type Output = Noise;
+
+    fn mul(self, value: f32) -> Noise {
+        Noise::mul(self, Noise::constant(value))
+    }
+}
+
+impl std::ops::Mul<Noise> for f32 {
+    type Output = Noise;
+
+    fn mul(self, noise: Noise) -> Noise {
+        noise * self
+    }
+}
+
+impl std::ops::Div for Noise {
+    type Output = Noise;
+
+    /// See [Noise::div]
+    fn div(self, other: Noise) -> Noise {
+        Noise::div(self, other)
+    }
+}
+
+impl std::ops::Div<f32> for Noise {
+    type Output = Noise;
+
+    /// The noise times 1 / `value`
+    fn div(self, value: f32) -> Noise {
+        Noise::mul(self, Noise::constant(value.recip()))
+    }
+}
+
+impl std::ops::Neg for Noise {
+    type Output = Noise;
+
+    /// The noise times -1
+    fn neg(self) -> Noise {
+        self * -1.0
+    }
+}
+
+/// Version of the algorithms used to generate a noise, see [Noise::algorithm].
+///
+/// A version never changes once released. When the hashing or the gradients are changed, e.g. to
+/// fix an artifact, the change is added as a new version, and noises that use the earlier ones
+/// keep producing the same values. The version is stored by [Noise::to_bytes].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// The first version
+    #[default]
+    V1,
+}
+
+/// Returned by [Noise::from_settings] when the nodes don't form a complete noise, e.g. an
+/// operation is missing one of its inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidPipeline;
+
+impl std::fmt::Display for InvalidPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the nodes do not form a valid noise")
+    }
+}
+
+impl std::error::Error for InvalidPipeline {}
+
+/// Shaping applied to each octave by [Noise::fbm_with]. They all produce values in the 0..1
+/// range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OctaveOp {
+    /// The absolute value, |n|. Gives billowy noise with creases where the noise crosses 0.
+    Abs,
+    /// The square, n². Flattens the noise around 0.
+    Square,
+    /// The inverted absolute value, 1 - |n|. Gives sharp ridges where the noise crosses 0.
+    Ridge,
+}
+
+/// How [Noise::cellular_with] measures the distance to the points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellularDistance {
+    /// The length of the straight line to the point, gives round cells.
+    Euclidean,
+    /// The sum of the distances along each axis, gives cells with straight diagonal edges.
+    Manhattan,
+}
+
+/// The value of [Noise::cellular_with]. F1 is the distance to the closest point and F2 the
+/// distance to the second closest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellularReturn {
+    /// 0 at the points and growing towards the borders between the cells.
+    F1,
+    /// Largest at the points and lowest at the borders.
+    F2,
+    /// 0 at the borders between the cells and growing towards the points, for walls and cracks.
+    F2MinusF1,
+    /// A random value within -1..1 for each cell, the same in the whole cell.
+    CellValue,
+    /// The distance to the closest border of the cell, measured straight to the plane halfway
+    /// between the closest point and each of the other points. Unlike [CellularReturn::F2MinusF1]
+    /// it grows evenly away from the borders, and is 0 on them. The borders are the ones of
+    /// [CellularDistance::Euclidean] cells whatever the distance.
+    EdgeDistance,
+}
+
+/// One octave of [Noise::fbm_custom]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OctaveParams {
+    /// Factor on the frequencies of the noise
+    pub frequency_mul: f32,
+    /// Factor on the values of the octave
+    pub amplitude: f32,
+    /// Seeds skipped before the octave, a different offset gives the octave another pattern.
+    pub seed_offset: u32,
+}
+
+/// A shape of [Noise::sdf]. Positions are in 3d with y as the height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SdfShape {
+    Sphere {
+        center: [f32; 3],
+        radius: f32,
+    },
+    /// A box aligned with the axes
+    Box {
+        center: [f32; 3],
+        /// Half the width of the box along each axis
+        half_size: [f32; 3],
+    },
+    /// Everything behind the plane, the side opposite of the normal, is inside.
+    Plane {
+        /// The direction the plane faces, it doesn't need to have a length of 1.
+        normal: [f32; 3],
+        /// Distance of the plane from the origin along the normal
+        offset: f32,
+    },
+    /// The points within `radius` of the line segment from `start` to `end`
+    Capsule {
+        start: [f32; 3],
+        end: [f32; 3],
+        radius: f32,
+    },
+}
+
+impl SdfShape {
+    // The parameters of the shape as bits, for comparing and hashing them like the other nodes.
+    fn bits(&self) -> [u32; 7] {
+        let mut bits = [0; 7];
+        let mut write = |offset: usize, values: &[f32]| {
+            for (bits, value) in bits[offset..].iter_mut().zip(values) {
+                *bits = value.to_bits();
+            }
+        };
+        match self {
+            SdfShape::Sphere { center, radius } => {
+                write(0, center);
+                write(3, &[*radius]);
+            }
+            SdfShape::Box { center, half_size } => {
+                write(0, center);
+                write(3, half_size);
+            }
+            SdfShape::Plane { normal, offset } => {
+                write(0, normal);
+                write(3, &[*offset]);
+            }
+            SdfShape::Capsule { start, end, radius } => {
+                write(0, start);
+                write(3, end);
+                write(6, &[*radius]);
+            }
+        }
+        bits
+    }
+}
+
+/// A rotation of the sampling grid used by [Noise::generate_3d_rotated]. Angles are in radians
+/// and follow the right hand rule.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rotation {
+    // The directions of a step along the x, y and z axis of the grid.
+    basis: [[f32; 3]; 3],
+}
+
+impl Rotation {
+    /// Rotation about the x axis
+    pub fn x(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            basis: [[1.0, 0.0, 0.0], [0.0, cos, sin], [0.0, -sin, cos]],
+        }
+    }
+
+    /// Rotation about the y axis, the height of a 3d noise
+    pub fn y(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            basis: [[cos, 0.0, -sin], [0.0, 1.0, 0.0], [sin, 0.0, cos]],
+        }
+    }
+
+    /// Rotation about the z axis
+    pub fn z(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            basis: [[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    /// Combines the rotations, `other` is applied after this rotation.
+    pub fn then(self, other: Rotation) -> Self {
+        let rotate = |v: [f32; 3]| {
+            let mut result = [0.0; 3];
+            for (axis, step) in v.iter().zip(other.basis.iter()) {
+                for i in 0..3 {
+                    result[i] += axis * step[i];
+                }
+            }
+            result
+        };
+        Self {
+            basis: self.basis.map(rotate),
+        }
+    }
+}
+
+/// The frequencies of a noise.
+///
+/// # Example
+/// ```
+/// // A convenient way to set the frequencies, as you usually want them to be the same.
+/// // If you intend to generate 1d/2d noise it doesn't matter if the remaining axis are
+/// // set.
+/// let noise = Noise::perlin(0.01);
+/// // Is equivalent to
+/// let noise = Noise::perlin(Frequency {
+///     x: 0.01,
+///     y: 0.01,
+///     z: 0.01
+/// });
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Frequency {
+    /// First dimension
+    pub x: f32,
+    /// Third dimension
+    pub y: f32,
+    /// Second dimension
+    pub z: f32,
+}
+
+// Compared bitwise so that equality agrees with the hash, f32's PartialEq would make 0.0 and -0.0
+// equal while hashing them differently.
+impl PartialEq for Frequency {
+    fn eq(&self, other: &Self) -> bool {
+        self.x.to_bits() == other.x.to_bits()
+            && self.y.to_bits() == other.y.to_bits()
+            && self.z.to_bits() == other.z.to_bits()
+    }
+}
+
+impl Eq for Frequency {}
+
+impl Hash for Frequency {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.x.to_bits().hash(state);
+        self.y.to_bits().hash(state);
+        self.z.to_bits().hash(state);
+    }
+}
+
+impl Frequency {
+    /// The same frequency along all axes, like `Frequency::from` but usable in `const`.
+    pub const fn splat(value: f32) -> Self {
+        Self {
+            x: value,
+            y: value,
+            z: value,
+        }
+    }
+}
+
+impl From<f32> for Frequency {
+    fn from(value: f32) -> Self {
+        Self::splat(value)
+    }
+}
+
+/// The lacunarity of [Noise::fbm], the factor on the frequencies from one octave to the next.
+/// The axes match those of [Frequency], different factors stretch the finer octaves along some
+/// axes more than the others.
+///
+/// # Example
+/// ```
+/// // The same along all axes
+/// let hills = Noise::simplex(0.01).fbm(5, 0.5, 2.0);
+/// // Strata, the finer octaves are squashed vertically
+/// let rock = Noise::simplex(0.01).fbm(
+///     5,
+///     0.5,
+///     Lacunarity {
+///         x: 1.5,
+///         y: 3.0,
+///         z: 1.5,
+///     },
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lacunarity {
+    /// First dimension
+    pub x: f32,
+    /// Third dimension
+    pub y: f32,
+    /// Second dimension
+    pub z: f32,
+}
+
+impl Lacunarity {
+    /// The same lacunarity along all axes, like `Lacunarity::from` but usable in `const`.
+    pub const fn splat(value: f32) -> Self {
+        Self {
+            x: value,
+            y: value,
+            z: value,
+        }
+    }
+}
+
+impl From<f32> for Lacunarity {
+    fn from(value: f32) -> Self {
+        Self::splat(value)
+    }
+}
+
+/// A node of a noise, the low level form of the builder methods of [Noise]. Tools like node
+/// graph editors can read the nodes of a noise with [Noise::settings], transform them, and build
+/// a noise from them again with [Noise::from_settings].
+///
+/// The nodes are in postfix order. Each node takes its inputs from the results of the nodes
+/// before it and adds one result, and the noise is the single result left at the end. Sources
+/// take no inputs, the other nodes take the results of the sub-noises before them, in the order
+/// their builder method appends them, e.g. `a.add(b)` is `[a.., b.., Add]` and
+/// `selector.lerp(low, high)` is `[selector.., high.., low.., Lerp]`.
+///
+/// [NoiseSettings::WrapDomain], [NoiseSettings::OffsetDomain], [NoiseSettings::Extrude2d] and
+/// [NoiseSettings::Interpolate] start a sub-noise that is sampled at other coordinates, it ends
+/// with a [NoiseSettings::RestoreCoordinates] after the one result of the sub-noise.
+/// [NoiseSettings::Warp] takes 3 results before starting one, `offset.., offset.., offset..,
+/// Warp, noise.., RestoreCoordinates`.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum NoiseSettings {
+    /// Simplex noise, see [Noise::simplex]
+    Simplex { frequency: Frequency },
+    /// Perlin noise, see [Noise::perlin]
+    Perlin { frequency: Frequency },
+    /// OpenSimplex2 noise, see [Noise::opensimplex2]
+    OpenSimplex2 { frequency: Frequency },
+    /// Value noise, see [Noise::value]
+    Value { frequency: Frequency },
+    /// Cubic value noise, see [Noise::value_cubic]
+    ValueCubic { frequency: Frequency },
+    /// Cellular noise, see [Noise::cellular_with]
+    Cellular {
+        frequency: Frequency,
+        distance: CellularDistance,
+        return_type: CellularReturn,
+    },
+    /// White noise, see [Noise::white]
+    White,
+    /// A constant, see [Noise::constant]
+    Constant { value: f32 },
+    /// A constant that can be changed without building the pipeline again, see [Noise::param]
+    Parameter {
+        /// The name hashed with 64-bit FNV-1a, like [Noise::seed_str]
+        name: u64,
+        value: f32,
+    },
+    /// Octaves of interpolated random values along x, with a spectrum of 1/f^exponent. See
+    /// [Noise::pink] and [Noise::brown].
+    Colored {
+        /// Random values per unit of distance of the fastest octave
+        frequency: f32,
+        /// 1 for pink noise and 2 for brown noise
+        exponent: f32,
+    },
+    /// The sum of the last `octaves` results, see [Noise::fbm]. The octaves are copies of a
+    /// sub-noise with their frequencies scaled by the lacunarity, from the highest frequency to
+    /// the lowest.
+    Fbm {
+        /// Total number of octaves
+        /// The number of octaves control the amount of detail in the noise function.
+        /// Adding more octaves increases the detail, with the drawback of increasing the calculation time.
+        octaves: u32,
+        /// Gain is a multiplier on the amplitude of each successive octave.
+        /// i.e. A gain of 2.0 will cause each octave to be twice as impactful on the result as the
+        /// previous one.
+        gain: f32,
+        /// Derived scaled amplitude for normalizing the result. [Noise::from_settings] derives it
+        /// from the other two.
+        first_octave_amplitude: f32,
+    },
+    /// The ridged sum of the last `octaves` results, see [Noise::ridged]. The octaves are the
+    /// same as those of [NoiseSettings::Fbm].
+    Ridged {
+        octaves: u32,
+        gain: f32,
+        /// Derived like that of [NoiseSettings::Fbm]
+        first_octave_amplitude: f32,
+    },
+    /// The sum of the last `octaves` results folded into bands, see [Noise::ping_pong]. The
+    /// octaves are the same as those of [NoiseSettings::Fbm].
+    PingPong {
+        octaves: u32,
+        gain: f32,
+        strength: f32,
+        /// Derived like that of [NoiseSettings::Fbm]
+        first_octave_amplitude: f32,
+    },
+    /// See [Noise::spectral]
+    Spectral {
+        lowest_frequency: f32,
+        highest_frequency: f32,
+        beta: f32,
+        waves: u32,
+    },
+    /// See [Noise::dots]
+    Dots {
+        frequency: Frequency,
+        /// Probability of a cell having a dot, 0..=1
+        density: f32,
+        /// Radius of the dots in cells, 0..=1
+        radius: f32,
+        /// How much weaker than 1 a dot can randomly be, 0..=1
+        amplitude_jitter: f32,
+    },
+    /// See [Noise::abs]
+    Abs,
+    /// See [Noise::abs_diff]
+    AbsDiff,
+    /// See [Noise::add]
+    Add,
+    /// See [Noise::sub]
+    Sub,
+    /// The average of the last `count` results, see [Noise::avg_n]
+    Average { count: u32 },
+    /// See [Noise::mul]
+    Mul,
+    /// See [Noise::div]
+    Div,
+    /// See [Noise::clamp]
+    Clamp { min: f32, max: f32 },
+    /// See [Noise::max]
+    Max,
+    /// See [Noise::min]
+    Min,
+    /// See [Noise::lerp_with_range]
+    Lerp {
+        /// The range of the selector noise, it is remapped to 0..1 before interpolating.
+        selector_min: f32,
+        selector_max: f32,
+    },
+    /// See [Noise::lerp_values]
+    LerpValues { low: f32, high: f32 },
+    /// See [Noise::range]
+    Range { low: f32, high: f32 },
+    /// See [Noise::square]
+    Square,
+    /// See [Noise::dither]
+    Dither { min: f32, max: f32, levels: u32 },
+    /// See [Noise::falloff_radial]
+    FalloffRadial {
+        center_x: f32,
+        center_z: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// The signed distance to a shape, see [Noise::sdf]
+    Sdf { shape: SdfShape },
+    /// A result generated earlier, see [Noise::sample]
+    Sample {
+        /// Hash of the size and values of the result, the result itself is held by the noise.
+        buffer: u64,
+        origin: [f32; 3],
+        spacing: f32,
+    },
+    /// Start of one copy of a sub-noise wrapped by [Noise::wrap_domain]. The coordinates are
+    /// wrapped into the period, and moved back one period along the axes set in `corner`, 1 for
+    /// x, 2 for y and 4 for z. A period of 0 means the axis isn't wrapped.
+    WrapDomain { period: [f32; 3], corner: u8 },
+    /// Blends the copies of a wrapped sub-noise, one for each combination of the wrapped axes.
+    WrapBlend { period: [f32; 3] },
+    /// Start of a sub-noise sampled at coordinates moved by `offset`
+    OffsetDomain { offset: [f32; 3] },
+    /// Start of a sub-noise sampled at coordinates moved by the last 3 results, the offsets
+    /// along x, y and z, times `amplitude`. See [Noise::warp].
+    Warp { amplitude: f32 },
+    /// Start of a sub-noise sampled as 2d noise, see [Noise::extrude_2d].
+    Extrude2d,
+    /// Start of a sub-noise sampled on a lattice and interpolated, see [Noise::interpolated].
+    Interpolate { spacing: f32 },
+    /// Skips `offset` seeds, wrapping around, so the sources after it get other seeds. See
+    /// [Noise::fbm_custom].
+    SeedOffset { offset: u64 },
+    /// Length of the gradient estimated from 6 copies of a sub-noise, offset by `step` forwards
+    /// and backwards along each axis. Only the copies along the axes that are generated are run,
+    /// and only the first when the sub-noise has derivatives. The copies come right before it,
+    /// each an [NoiseSettings::OffsetDomain], the sub-noise and a
+    /// [NoiseSettings::RestoreCoordinates]. See [Noise::slope].
+    Slope { step: f32 },
+    /// Ends a sub-noise that changed the coordinates. With `rewind` the seeds are also restored
+    /// so that the next copy of the sub-noise uses the same seeds.
+    RestoreCoordinates { rewind: bool },
+}
+
+impl NoiseSettings {
+    // Number of values a node takes from the results and the number it adds.
+    pub(crate) fn stack_effect(&self) -> (usize, usize) {
+        match self {
+            NoiseSettings::Simplex { .. }
+            | NoiseSettings::Perlin { .. }
+            | NoiseSettings::OpenSimplex2 { .. }
+            | NoiseSettings::Value { .. }
+            | NoiseSettings::ValueCubic { .. }
+            | NoiseSettings::Cellular { .. }
+            | NoiseSettings::White
+            | NoiseSettings::Constant { .. }
+            | NoiseSettings::Parameter { .. }
+            | NoiseSettings::Colored { .. }
+            | NoiseSettings::Spectral { .. }
+            | NoiseSettings::Dots { .. }
+            | NoiseSettings::FalloffRadial { .. }
+            | NoiseSettings::Sdf { .. }
+            | NoiseSettings::Sample { .. } => (0, 1),
+            NoiseSettings::Abs
+            | NoiseSettings::Square
+            | NoiseSettings::Clamp { .. }
+            | NoiseSettings::Dither { .. }
+            | NoiseSettings::LerpValues { .. } => (1, 1),
+            NoiseSettings::AbsDiff
+            | NoiseSettings::Add
+            | NoiseSettings::Sub
+            | NoiseSettings::Mul
+            | NoiseSettings::Div
+            | NoiseSettings::Max
+            | NoiseSettings::Min => (2, 1),
+            NoiseSettings::Lerp { .. } | NoiseSettings::Range { .. } => (3, 1),
+            NoiseSettings::Slope { .. } => (6, 1),
+            NoiseSettings::Fbm { octaves, .. }
+            | NoiseSettings::Ridged { octaves, .. }
+            | NoiseSettings::PingPong { octaves, .. } => (*octaves as usize, 1),
+            NoiseSettings::Average { count } => (*count as usize, 1),
+            NoiseSettings::WrapBlend { period } => (NoiseSettings::wrap_corners(*period), 1),
+            NoiseSettings::Warp { .. } => (3, 0),
+            NoiseSettings::WrapDomain { .. }
+            | NoiseSettings::OffsetDomain { .. }
+            | NoiseSettings::Extrude2d
+            | NoiseSettings::Interpolate { .. }
+            | NoiseSettings::SeedOffset { .. }
+            | NoiseSettings::RestoreCoordinates { .. } => (0, 0),
+        }
+    }
+
+    // Whether the nodes form a complete noise. Every node must have all its inputs available,
+    // every sub-noise that changes the coordinates must end with one more result, and every
+    // Slope must follow the copies of its sub-noise.
+    pub(crate) fn validate(pipeline: &[NoiseSettings]) -> bool {
+        let mut depth: usize = 0;
+        // The depth and the index of the node at the start of each sub-noise
+        let mut sub_noises = Vec::new();
+        // The node that starts the sub-noise ended by each RestoreCoordinates
+        let mut starts = vec![0; pipeline.len()];
+        for (index, settings) in pipeline.iter().enumerate() {
+            match settings {
+                NoiseSettings::Fbm { octaves: 0, .. }
+                | NoiseSettings::Ridged { octaves: 0, .. }
+                | NoiseSettings::PingPong { octaves: 0, .. }
+                | NoiseSettings::Spectral { waves: 0, .. }
+                | NoiseSettings::Average { count: 0 }
+                | NoiseSettings::Dither { levels: 0..2, .. }
+                | NoiseSettings::Sdf {
+                    shape:
+                        SdfShape::Plane {
+                            normal: [0.0, 0.0, 0.0],
+                            ..
+                        },
+                } => {
+                    return false;
+                }
+                // Dots further than a cell away aren't looked for
+                NoiseSettings::Dots { radius, .. } if !(*radius > 0.0 && *radius <= 1.0) => {
+                    return false;
+                }
+                NoiseSettings::Clamp { min, max } if min.is_nan() || max.is_nan() || min > max => {
+                    return false;
+                }
+                // The cells of the lattice must have a size
+                NoiseSettings::Interpolate { spacing } if spacing.is_nan() || *spacing <= 0.0 => {
+                    return false;
+                }
+                NoiseSettings::WrapDomain { .. }
+                | NoiseSettings::OffsetDomain { .. }
+                | NoiseSettings::Extrude2d
+                | NoiseSettings::Interpolate { .. } => {
+                    sub_noises.push((depth, index));
+                }
+                // The offsets are taken before the sub-noise starts
+                NoiseSettings::Warp { .. } => {
+                    let Some(remaining) = depth.checked_sub(3) else {
+                        return false;
+                    };
+                    depth = remaining;
+                    sub_noises.push((depth, index));
+                }
+                NoiseSettings::RestoreCoordinates { .. } => match sub_noises.pop() {
+                    Some((start_depth, start)) if start_depth == depth.wrapping_sub(1) => {
+                        starts[index] = start;
+                    }
+                    _ => return false,
+                },
+                NoiseSettings::Slope { .. } if !Self::follows_copies(pipeline, &starts, index) => {
+                    return false;
+                }
+                _ => {
+                    let (inputs, outputs) = settings.stack_effect();
+                    let Some(remaining) = depth.checked_sub(inputs) else {
+                        return false;
+                    };
+                    depth = remaining + outputs;
+                }
+            }
+        }
+        depth == 1 && sub_noises.is_empty()
+    }
+
+    // Whether the Slope at `index` follows 6 copies of the same sub-noise, each started by an
+    // OffsetDomain and ended by a RestoreCoordinates, see `NoiseSettings::slope_nodes`. `starts`
+    // holds the start of each sub-noise that has ended.
+    fn follows_copies(pipeline: &[NoiseSettings], starts: &[usize], index: usize) -> bool {
+        let mut copies = Vec::with_capacity(6);
+        let mut next = index;
+        for _ in 0..6 {
+            let Some(end) = next.checked_sub(1) else {
+                return false;
+            };
+            let start = starts[end];
+            if !matches!(pipeline[end], NoiseSettings::RestoreCoordinates { .. })
+                || !matches!(pipeline[start], NoiseSettings::OffsetDomain { .. })
+            {
+                return false;
+            }
+            copies.push(&pipeline[start + 1..end]);
+            next = start;
+        }
+        copies.iter().all(|copy| *copy == copies[0])
+    }
+
+    // Multiplies the frequency of the node, if it has one.
+    const fn scale_frequency(&mut self, factor: f32) {
+        self.scale_frequency_axes(Lacunarity::splat(factor));
+    }
+
+    // Multiplies the frequency of the node along each axis, colored and spectral noise only
+    // have x.
+    const fn scale_frequency_axes(&mut self, factor: Lacunarity) {
+        match self {
+            NoiseSettings::Simplex { frequency }
+            | NoiseSettings::Perlin { frequency }
+            | NoiseSettings::OpenSimplex2 { frequency }
+            | NoiseSettings::Value { frequency }
+            | NoiseSettings::ValueCubic { frequency }
+            | NoiseSettings::Cellular { frequency, .. }
+            | NoiseSettings::Dots { frequency, .. } => {
+                frequency.x *= factor.x;
+                frequency.y *= factor.y;
+                frequency.z *= factor.z;
+            }
+            NoiseSettings::Colored { frequency, .. } => *frequency *= factor.x,
+            NoiseSettings::Spectral {
+                lowest_frequency,
+                highest_frequency,
+                ..
+            } => {
+                *lowest_frequency *= factor.x;
+                *highest_frequency *= factor.x;
+            }
+            _ => (),
+        }
+    }
+
+    // The waves of spectral noise: the frequency of the first and the ratio between successive
+    // frequencies, and the same for the amplitudes. The frequencies are at the centers of equal
+    // steps on a log scale from the lowest to the highest frequency. Each wave stands for the
+    // annulus of frequencies around it, which in 2d has an area of f² per step, so the amplitude
+    // is sqrt(f^-beta * f²). The amplitudes are normalized to a standard deviation of 0.25, a
+    // wave of amplitude a has a variance of a² / 2.
+    pub(crate) fn spectral_waves(
+        lowest_frequency: f32,
+        highest_frequency: f32,
+        beta: f32,
+        waves: u32,
+    ) -> (f64, f64, f64, f64) {
+        let frequency_ratio =
+            (highest_frequency as f64 / lowest_frequency as f64).powf(1.0 / waves as f64);
+        let amplitude_ratio = frequency_ratio.powf(1.0 - beta as f64 * 0.5);
+        let mut amplitude = 1.0;
+        let mut variance = 0.0;
+        for _ in 0..waves {
+            variance += amplitude * amplitude * 0.5;
+            amplitude *= amplitude_ratio;
+        }
+        (
+            lowest_frequency as f64 * frequency_ratio.sqrt(),
+            frequency_ratio,
+            0.25 / variance.sqrt(),
+            amplitude_ratio,
+        )
+    }
+
+    // Number of octaves summed by colored noise. The slowest octave changes every 2^15 periods
+    // of the fastest, at 44.1khz that is below 2hz.
+    pub(crate) const COLORED_OCTAVES: u32 = 16;
+
+    // Voss-McCartney: octave k holds a random value for every 2^k periods. An octave has the
+    // power of the band around its frequency, so for a spectrum of 1/f^exponent the power of
+    // each octave is 2^(exponent - 1) that of the octave above it, and the amplitude the square
+    // root of that. Returns the amplitude of the fastest octave, normalized so they sum to 1, and
+    // the ratio between successive octaves.
+    pub(crate) fn colored_amplitudes(exponent: f32) -> (f32, f32) {
+        let ratio = 2f32.powf((exponent - 1.0) * 0.5);
+        let mut amplitude = 1.0;
+        let mut total_amplitude = 0.0;
+        for _ in 0..Self::COLORED_OCTAVES {
+            total_amplitude += amplitude;
+            amplitude *= ratio;
+        }
+        (1.0 / total_amplitude, ratio)
+    }
+
+    // The amplitude gets pre-scaled so that we can skip normalizing the result.
+    // e.g. if the gain is 0.5 and there are 2 octaves, the amplitude would be 1 + 0.5 = 1.5
+    // when both octaves are combined normally. Instead, we set the initial amplitude to be
+    // 1/1.5 == 2/3, the second octave's amplitude becomes 2/3 * 0.5 = 1/3 and we end up with a
+    // normalized result naturally.
+    pub(crate) const fn first_octave_amplitude(octaves: u32, gain: f32) -> f32 {
+        let mut amp = gain;
+        let mut total_amplitude = 1.0;
+        let mut octave = 1;
+        while octave < octaves {
+            total_amplitude += amp;
+            amp *= gain;
+            octave += 1;
+        }
+        1.0 / total_amplitude
+    }
+
+    // Number of copies of the sub-noise of a WrapDomain, one for each combination of the wrapped
+    // axes.
+    pub(crate) const fn wrap_corners(period: [f32; 3]) -> usize {
+        let mut corners = 1;
+        let mut axis = 0;
+        while axis < 3 {
+            if period[axis] > 0.0 {
+                corners *= 2;
+            }
+            axis += 1;
+        }
+        corners
+    }
+
+    // The axes moved back one period in copy `i`. Bit n of `i` is the n-th wrapped axis, so the
+    // copies are in the order the blend expects them.
+    pub(crate) const fn wrap_corner(period: [f32; 3], i: usize) -> u8 {
+        let mut corner = 0;
+        let mut bit = 0;
+        let mut axis = 0;
+        while axis < 3 {
+            if period[axis] > 0.0 {
+                if i >> bit & 1 == 1 {
+                    corner |= 1 << axis;
+                }
+                bit += 1;
+            }
+            axis += 1;
+        }
+        corner
+    }
+
+    // The step of a Slope, a hundredth of the shortest wavelength in the sub-noise, or 1 if it has
+    // no frequency.
+    pub(crate) const fn slope_step(pipeline: &[NoiseSettings]) -> f32 {
+        let mut frequency: f32 = 0.0;
+        let mut i = 0;
+        while i < pipeline.len() {
+            match pipeline[i] {
+                NoiseSettings::Simplex { frequency: f }
+                | NoiseSettings::Perlin { frequency: f }
+                | NoiseSettings::OpenSimplex2 { frequency: f }
+                | NoiseSettings::Value { frequency: f }
+                | NoiseSettings::ValueCubic { frequency: f }
+                | NoiseSettings::Dots { frequency: f, .. } => {
+                    frequency = frequency.max(f.x.max(f.y).max(f.z));
+                }
+                // The distances and the edge distance change over the width of a cell, the cell
+                // value only at the borders, which are just as far apart.
+                NoiseSettings::Cellular {
+                    frequency: f,
+                    return_type:
+                        CellularReturn::F1
+                        | CellularReturn::F2
+                        | CellularReturn::F2MinusF1
+                        | CellularReturn::CellValue
+                        | CellularReturn::EdgeDistance,
+                    ..
+                } => {
+                    frequency = frequency.max(f.x.max(f.y).max(f.z));
+                }
+                NoiseSettings::Colored { frequency: f, .. }
+                | NoiseSettings::Spectral {
+                    highest_frequency: f,
+                    ..
+                } => frequency = frequency.max(f),
+                _ => (),
+            }
+            i += 1;
+        }
+        if frequency > 0.0 {
+            0.01 / frequency
+        } else {
+            1.0
+        }
+    }
+
+    // The offsets of the copies of the sub-noise of a Slope, forwards and backwards along each
+    // axis.
+    pub(crate) const fn slope_offsets(step: f32) -> [[f32; 3]; 6] {
+        [
+            [step, 0.0, 0.0],
+            [-step, 0.0, 0.0],
+            [0.0, step, 0.0],
+            [0.0, -step, 0.0],
+            [0.0, 0.0, step],
+            [0.0, 0.0, -step],
+        ]
+    }
+
+    // The nodes of each Slope in the pipeline, found from the nodes that end its copies.
+    pub(crate) fn slope_nodes(pipeline: &[NoiseSettings]) -> Vec<SlopeNodes> {
+        // The node that starts the sub-noise ended by each RestoreCoordinates
+        let mut starts = vec![0; pipeline.len()];
+        let mut outer = Vec::new();
+        let mut slopes = Vec::new();
+        for (index, settings) in pipeline.iter().enumerate() {
+            match settings {
+                NoiseSettings::WrapDomain { .. }
+                | NoiseSettings::OffsetDomain { .. }
+                | NoiseSettings::Warp { .. }
+                | NoiseSettings::Interpolate { .. }
+                | NoiseSettings::Extrude2d => outer.push(index),
+                NoiseSettings::RestoreCoordinates { .. } => starts[index] = outer.pop().unwrap(),
+                NoiseSettings::Slope { .. } => {
+                    let mut copies = [(0, 0); 6];
+                    let mut end = index - 1;
+                    for copy in copies.iter_mut().rev() {
+                        *copy = (starts[end], end);
+                        end = starts[end].wrapping_sub(1);
+                    }
+                    slopes.push(SlopeNodes {
+                        copies,
+                        slope: index,
+                    });
+                }
+                _ => (),
+            }
+        }
+        slopes
+    }
+}
+
+impl PartialEq for NoiseSettings {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Simplex { frequency: a }, Self::Simplex { frequency: b }) => a == b,
+            (Self::Perlin { frequency: a }, Self::Perlin { frequency: b }) => a == b,
+            (Self::OpenSimplex2 { frequency: a }, Self::OpenSimplex2 { frequency: b }) => a == b,
+            (Self::Value { frequency: a }, Self::Value { frequency: b }) => a == b,
+            (Self::ValueCubic { frequency: a }, Self::ValueCubic { frequency: b }) => a == b,
+            (
+                Self::Cellular {
+                    frequency: a_frequency,
+                    distance: a_distance,
+                    return_type: a_return,
+                },
+                Self::Cellular {
+                    frequency: b_frequency,
+                    distance: b_distance,
+                    return_type: b_return,
+                },
+            ) => a_frequency == b_frequency && a_distance == b_distance && a_return == b_return,
+            (Self::Constant { value: a }, Self::Constant { value: b }) => {
+                a.to_bits() == b.to_bits()
+            }
+            (
+                Self::Parameter {
+                    name: a_name,
+                    value: a_value,
+                },
+                Self::Parameter {
+                    name: b_name,
+                    value: b_value,
+                },
+            ) => a_name == b_name && a_value.to_bits() == b_value.to_bits(),
+            (
+                Self::Colored {
+                    frequency: a_frequency,
+                    exponent: a_exponent,
+                },
+                Self::Colored {
+                    frequency: b_frequency,
+                    exponent: b_exponent,
+                },
+            ) => {
+                a_frequency.to_bits() == b_frequency.to_bits()
+                    && a_exponent.to_bits() == b_exponent.to_bits()
+            }
+            (
+                Self::Spectral {
+                    lowest_frequency: a_lowest,
+                    highest_frequency: a_highest,
+                    beta: a_beta,
+                    waves: a_waves,
+                },
+                Self::Spectral {
+                    lowest_frequency: b_lowest,
+                    highest_frequency: b_highest,
+                    beta: b_beta,
+                    waves: b_waves,
+                },
+            ) => {
+                a_lowest.to_bits() == b_lowest.to_bits()
+                    && a_highest.to_bits() == b_highest.to_bits()
+                    && a_beta.to_bits() == b_beta.to_bits()
+                    && a_waves == b_waves
+            }
+            (
+                Self::Dots {
+                    frequency: a_frequency,
+                    density: a_density,
+                    radius: a_radius,
+                    amplitude_jitter: a_jitter,
+                },
+                Self::Dots {
+                    frequency: b_frequency,
+                    density: b_density,
+                    radius: b_radius,
+                    amplitude_jitter: b_jitter,
+                },
+            ) => {
+                a_frequency == b_frequency
+                    && a_density.to_bits() == b_density.to_bits()
+                    && a_radius.to_bits() == b_radius.to_bits()
+                    && a_jitter.to_bits() == b_jitter.to_bits()
+            }
+            (
+                Self::Fbm {
+                    octaves: a_octaves,
+                    gain: a_gain,
+                    ..
+                },
+                Self::Fbm {
+                    octaves: b_octaves,
+                    gain: b_gain,
+                    ..
+                },
+            ) => a_octaves == b_octaves && a_gain.to_bits() == b_gain.to_bits(),
+            (
+                Self::Ridged {
+                    octaves: a_octaves,
+                    gain: a_gain,
+                    ..
+                },
+                Self::Ridged {
+                    octaves: b_octaves,
+                    gain: b_gain,
+                    ..
+                },
+            ) => a_octaves == b_octaves && a_gain.to_bits() == b_gain.to_bits(),
+            (
+                Self::PingPong {
+                    octaves: a_octaves,
+                    gain: a_gain,
+                    strength: a_strength,
+                    ..
+                },
+                Self::PingPong {
+                    octaves: b_octaves,
+                    gain: b_gain,
+                    strength: b_strength,
+                    ..
+                },
+            ) => {
+                a_octaves == b_octaves
+                    && a_gain.to_bits() == b_gain.to_bits()
+                    && a_strength.to_bits() == b_strength.to_bits()
+            }
+            (
+                Self::Clamp {
+                    min: a_min,
+                    max: a_max,
+                },
+                Self::Clamp {
+                    min: b_min,
+                    max: b_max,
+                },
+            ) => a_min.to_bits() == b_min.to_bits() && a_max.to_bits() == b_max.to_bits(),
+            (
+                Self::Lerp {
+                    selector_min: a_min,
+                    selector_max: a_max,
+                },
+                Self::Lerp {
+                    selector_min: b_min,
+                    selector_max: b_max,
+                },
+            ) => a_min.to_bits() == b_min.to_bits() && a_max.to_bits() == b_max.to_bits(),
+            (
+                Self::LerpValues {
+                    low: a_low,
+                    high: a_high,
+                },
+                Self::LerpValues {
+                    low: b_low,
+                    high: b_high,
+                },
+            ) => a_low.to_bits() == b_low.to_bits() && a_high.to_bits() == b_high.to_bits(),
+            (
+                Self::Range {
+                    low: a_low,
+                    high: a_high,
+                },
+                Self::Range {
+                    low: b_low,
+                    high: b_high,
+                },
+            ) => a_low.to_bits() == b_low.to_bits() && a_high.to_bits() == b_high.to_bits(),
+            (
+                Self::Dither {
+                    min: a_min,
+                    max: a_max,
+                    levels: a_levels,
+                },
+                Self::Dither {
+                    min: b_min,
+                    max: b_max,
+                    levels: b_levels,
+                },
+            ) => {
+                a_min.to_bits() == b_min.to_bits()
+                    && a_max.to_bits() == b_max.to_bits()
+                    && a_levels == b_levels
+            }
+            (
+                Self::FalloffRadial {
+                    center_x: a_x,
+                    center_z: a_z,
+                    inner_radius: a_inner,
+                    outer_radius: a_outer,
+                },
+                Self::FalloffRadial {
+                    center_x: b_x,
+                    center_z: b_z,
+                    inner_radius: b_inner,
+                    outer_radius: b_outer,
+                },
+            ) => {
+                a_x.to_bits() == b_x.to_bits()
+                    && a_z.to_bits() == b_z.to_bits()
+                    && a_inner.to_bits() == b_inner.to_bits()
+                    && a_outer.to_bits() == b_outer.to_bits()
+            }
+            (Self::Sdf { shape: a }, Self::Sdf { shape: b }) => {
+                std::mem::discriminant(a) == std::mem::discriminant(b) && a.bits() == b.bits()
+            }
+            (
+                Self::Sample {
+                    buffer: a_buffer,
+                    origin: a_origin,
+                    spacing: a_spacing,
+                },
+                Self::Sample {
+                    buffer: b_buffer,
+                    origin: b_origin,
+                    spacing: b_spacing,
+                },
+            ) => {
+                a_buffer == b_buffer
+                    && a_origin.map(f32::to_bits) == b_origin.map(f32::to_bits)
+                    && a_spacing.to_bits() == b_spacing.to_bits()
+            }
+            (
+                Self::WrapDomain {
+                    period: a_period,
+                    corner: a_corner,
+                },
+                Self::WrapDomain {
+                    period: b_period,
+                    corner: b_corner,
+                },
+            ) => a_period.map(f32::to_bits) == b_period.map(f32::to_bits) && a_corner == b_corner,
+            (Self::WrapBlend { period: a }, Self::WrapBlend { period: b }) => {
+                a.map(f32::to_bits) == b.map(f32::to_bits)
+            }
+            (Self::OffsetDomain { offset: a }, Self::OffsetDomain { offset: b }) => {
+                a.map(f32::to_bits) == b.map(f32::to_bits)
+            }
+            (Self::Warp { amplitude: a }, Self::Warp { amplitude: b }) => {
+                a.to_bits() == b.to_bits()
+            }
+            (Self::Slope { step: a }, Self::Slope { step: b }) => a.to_bits() == b.to_bits(),
+            (Self::Interpolate { spacing: a }, Self::Interpolate { spacing: b }) => {
+                a.to_bits() == b.to_bits()
+            }
+            (Self::SeedOffset { offset: a }, Self::SeedOffset { offset: b }) => a == b,
+            (Self::Average { count: a }, Self::Average { count: b }) => a == b,
+            (Self::RestoreCoordinates { rewind: a }, Self::RestoreCoordinates { rewind: b }) => {
+                a == b
+            }
+            // Variants without parameters
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl Eq for NoiseSettings {}
+
+impl Hash for NoiseSettings {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Simplex { frequency } => frequency.hash(state),
+            Self::Perlin { frequency } => frequency.hash(state),
+            Self::OpenSimplex2 { frequency } => frequency.hash(state),
+            Self::Value { frequency } => frequency.hash(state),
+            Self::ValueCubic { frequency } => frequency.hash(state),
+            Self::Cellular {
+                frequency,
+                distance,
+                return_type,
+            } => {
+                frequency.hash(state);
+                distance.hash(state);
+                return_type.hash(state);
+            }
+            Self::Constant { value } => value.to_bits().hash(state),
+            Self::Parameter { name, value } => {
+                name.hash(state);
+                value.to_bits().hash(state);
+            }
+            Self::Colored {
+                frequency,
+                exponent,
+            } => {
+                frequency.to_bits().hash(state);
+                exponent.to_bits().hash(state);
+            }
+            Self::Spectral {
+                lowest_frequency,
+                highest_frequency,
+                beta,
+                waves,
+            } => {
+                lowest_frequency.to_bits().hash(state);
+                highest_frequency.to_bits().hash(state);
+                beta.to_bits().hash(state);
+                waves.hash(state);
+            }
+            Self::Dots {
+                frequency,
+                density,
+                radius,
+                amplitude_jitter,
+            } => {
+                frequency.hash(state);
+                density.to_bits().hash(state);
+                radius.to_bits().hash(state);
+                amplitude_jitter.to_bits().hash(state);
+            }
+            // The amplitude is derived from the other two
+            Self::Fbm { octaves, gain, .. } | Self::Ridged { octaves, gain, .. } => {
+                octaves.hash(state);
+                gain.to_bits().hash(state);
+            }
+            Self::PingPong {
+                octaves,
+                gain,
+                strength,
+                ..
+            } => {
+                octaves.hash(state);
+                gain.to_bits().hash(state);
+                strength.to_bits().hash(state);
+            }
+            Self::Clamp { min, max } => {
+                min.to_bits().hash(state);
+                max.to_bits().hash(state);
+            }
+            Self::Lerp {
+                selector_min,
+                selector_max,
+            } => {
+                selector_min.to_bits().hash(state);
+                selector_max.to_bits().hash(state);
+            }
+            Self::LerpValues { low, high } => {
+                low.to_bits().hash(state);
+                high.to_bits().hash(state);
+            }
+            Self::Range { low, high } => {
+                low.to_bits().hash(state);
+                high.to_bits().hash(state);
+            }
+            Self::Dither { min, max, levels } => {
+                min.to_bits().hash(state);
+                max.to_bits().hash(state);
+                levels.hash(state);
+            }
+            Self::FalloffRadial {
+                center_x,
+                center_z,
+                inner_radius,
+                outer_radius,
+            } => {
+                center_x.to_bits().hash(state);
+                center_z.to_bits().hash(state);
+                inner_radius.to_bits().hash(state);
+                outer_radius.to_bits().hash(state);
+            }
+            Self::Sdf { shape } => {
+                std::mem::discriminant(shape).hash(state);
+                shape.bits().hash(state);
+            }
+            Self::Sample {
+                buffer,
+                origin,
+                spacing,
+            } => {
+                buffer.hash(state);
+                origin.map(f32::to_bits).hash(state);
+                spacing.to_bits().hash(state);
+            }
+            Self::WrapDomain { period, corner } => {
+                period.map(f32::to_bits).hash(state);
+                corner.hash(state);
+            }
+            Self::WrapBlend { period } => period.map(f32::to_bits).hash(state),
+            Self::OffsetDomain { offset } => offset.map(f32::to_bits).hash(state),
+            Self::Warp { amplitude } => amplitude.to_bits().hash(state),
+            Self::Slope { step } => step.to_bits().hash(state),
+            Self::Interpolate { spacing } => spacing.to_bits().hash(state),
+            Self::SeedOffset { offset } => offset.hash(state),
+            Self::Average { count } => count.hash(state),
+            Self::RestoreCoordinates { rewind } => rewind.hash(state),
+            _ => (),
+        }
+    }
+}
+
+/// A noise built for evaluating `N` coordinates at a time, to embed the noise in your own SIMD
+/// loops, e.g. interleaved with meshing, without the buffer the `generate_*` functions fill.
+///
+/// The pipeline is built for the dimensions it is created for and the cpu, like the pipelines
+/// of the `generate_*` functions, and gives the same values as them for the same coordinates.
+/// Building it is the expensive part, keep it around and call [NoisePipeline::set_coords] and
+/// [NoisePipeline::execute] for each batch of coordinates.
+///
+/// The coordinates and values are `f32`, or `f64` for a `NoisePipeline<N, f64>`, see [Float].
+///
+/// # Example
+/// ```rust
+/// #![feature(portable_simd)]
+/// use std::simd::Simd;
+///
+/// let noise = Noise::simplex(0.01).fbm(4, 0.5, 2.0);
+/// let mut pipeline = NoisePipeline::<8>::new_2d(&noise);
+/// let y = Simd::from_array([0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+/// for x in 0..16 {
+///     pipeline.set_coords(Simd::splat(x as f32), y, Simd::splat(0.0));
+///     let heights = pipeline.execute();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NoisePipeline<const N: usize, T: SimdElement = f32>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    rng: Rng,
+    index: usize,
+    // The function of each node. The settings of the nodes are kept apart in `noise`, so that
+    // calling the functions only goes through the pointers.
+    functions: Vec<unsafe fn(pipeline: &mut NoisePipeline<N, T>)>,
+    results: Vec<Simd<T, N>>,
+    x: Simd<T, N>,
+    y: Simd<T, N>,
+    z: Simd<T, N>,
+    w: Simd<T, N>,
+    // Coordinates saved by the nodes that change them for a sub-noise, along with the state of
+    // the rng so that the sub-noise can be repeated with the same seeds.
+    saved: Vec<SavedCoordinates<N, T>>,
+    // The last result of each sub-noise extruded from 2d in a 3d pipeline
+    columns: Vec<Column<N, T>>,
+    // The values at the corners of recent cells of each interpolated sub-noise
+    lattices: Vec<Lattice<N, T>>,
+    // The nodes of each Slope
+    slopes: Vec<SlopeNodes>,
+    // What the pipeline was built from, to find it in the cache. It also holds the settings of
+    // each node.
+    noise: Noise,
+    dimensions: Dimensions,
+    // The sampler the pipeline was taken from, it is put back there instead of the cache
+    sampler: Option<SamplerPipelines>,
+}
+
+// The function of a node, or of a fused run of nodes
+type Kernel<const N: usize, T> = unsafe fn(pipeline: &mut NoisePipeline<N, T>);
+
+// Maximum number of pipelines in the cache of a thread.
+const PIPELINE_CACHE_SIZE: usize = 64;
+
+thread_local! {
+    // Pipelines that have already been built, so that generating the same noise again doesn't
+    // have to build it again. They are stored as `NoisePipeline<N>`, the most recently used
+    // last. Comparing noises stops at the first difference, so searching is faster than hashing
+    // the noise would be.
+    static PIPELINES: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Debug)]
+struct SavedCoordinates<const N: usize, T: SimdElement>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    x: Simd<T, N>,
+    y: Simd<T, N>,
+    z: Simd<T, N>,
+    w: Simd<T, N>,
+    rng_seed: u64,
+}
+
+// A sub-noise extruded from 2d doesn't change along y, and the 3d drivers put y in the lanes, so
+// its result is the same for every vector of a column. The result is kept with the (x, z) it was
+// computed at, and reused while the coordinates stay the same.
+#[derive(Debug)]
+struct Column<const N: usize, T: SimdElement>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // Index of the node that starts the sub-noise and of the one that ends it
+    start: usize,
+    end: usize,
+    valid: bool,
+    x: Simd<T, N>,
+    z: Simd<T, N>,
+    result: Simd<T, N>,
+    // The state of the rng after the sub-noise, so that the nodes after it get the same seeds
+    rng_seed: u64,
+}
+
+// A Slope is made of 6 copies of its sub-noise, each started by an OffsetDomain and ended by a
+// RestoreCoordinates, followed by the Slope node. When the sub-noise has derivatives only the
+// first copy is run, with dual numbers, and copies along axes the pipeline doesn't have are
+// skipped.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SlopeNodes {
+    // Index of the node that starts and of the one that ends each copy, in the order of
+    // `NoiseSettings::slope_offsets`
+    copies: [(usize, usize); 6],
+    // Index of the Slope node
+    slope: usize,
+}
+
+// Number of cells kept by each interpolated sub-noise
+const LATTICE_CELLS: usize = 64;
+
+// A sub-noise interpolated on a lattice, see `Noise::interpolated`. The start of the sub-noise
+// runs it at the corners of the cells that aren't kept yet, with a corner in each lane, and the
+// end returns to the start with the result instead of continuing the pipeline.
+#[derive(Debug)]
+struct Lattice<const N: usize, T: SimdElement>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // Index of the node that starts the sub-noise and of the one that ends it
+    start: usize,
+    end: usize,
+    // The result of the last run of the sub-noise
+    result: Simd<T, N>,
+    // The state of the rng after the sub-noise, so that the nodes after it get the same seeds
+    rng_seed: u64,
+    // The corners of recently used cells, at an index hashed from the cell
+    cells: Vec<LatticeCell<T>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LatticeCell<T> {
+    valid: bool,
+    // The cell along x, y and z, and the bits of w. The sub-noise isn't interpolated along w, the
+    // cells are kept for each w.
+    cell: [i64; 4],
+    // The value at each corner, bit n of the index is the n-th axis
+    corners: [T; 8],
+}
+
+impl<const N: usize, T: Float> NoisePipeline<N, T>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline(always)]
+    #[track_caller]
+    fn current_settings(&self) -> &NoiseSettings {
+        &self.noise.pipeline[self.index]
+    }
+
+    #[inline(always)]
+    fn next(&mut self) {
+        self.index += 1;
+        if self.index == self.functions.len() {
+            return;
+        }
+        unsafe { (self.functions[self.index])(self) };
+    }
+
+    // The settings of the second node of a fused kernel
+    #[inline(always)]
+    fn fused_settings(&self) -> &NoiseSettings {
+        &self.noise.pipeline[self.index + 1]
+    }
+
+    // The settings of the `offset`th node after the first of a fused kernel
+    #[inline(always)]
+    fn fused_settings_at(&self, offset: usize) -> &NoiseSettings {
+        &self.noise.pipeline[self.index + offset]
+    }
+
+    // Continues after the second node of a fused kernel
+    #[inline(always)]
+    fn next_fused(&mut self) {
+        self.index += 1;
+        self.next();
+    }
+
+    // Continues after the last of the `count` nodes of a fused kernel
+    #[inline(always)]
+    fn next_fused_run(&mut self, count: usize) {
+        self.index += count - 1;
+        self.next();
+    }
+
+    #[inline(always)]
+    fn save_coordinates(&mut self) {
+        self.saved.push(SavedCoordinates {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            w: self.w,
+            rng_seed: self.rng.current_seed,
+        });
+    }
+
+    #[inline(always)]
+    fn restore_coordinates(&mut self, rewind_rng: bool) {
+        let saved = self.saved.pop().unwrap();
+        self.x = saved.x;
+        self.y = saved.y;
+        self.z = saved.z;
+        self.w = saved.w;
+        if rewind_rng {
+            self.rng.current_seed = saved.rng_seed;
+        }
+    }
+
+    /// Builds the noise for 1d coordinates, only `x` is used.
+    pub fn new_1d(noise: &Noise) -> Self {
+        Self::acquire(noise, Dimensions::X)
+    }
+
+    /// Builds the noise for 2d coordinates, `x` and `y` are used.
+    pub fn new_2d(noise: &Noise) -> Self {
+        Self::acquire(noise, Dimensions::XY)
+    }
+
+    /// Builds the noise for 3d coordinates, `y` is the height like in
+    /// [Noise::generate_3d].
+    pub fn new_3d(noise: &Noise) -> Self {
+        Self::acquire(noise, Dimensions::XYZ)
+    }
+
+    /// Builds the noise for 4d coordinates, the coordinates are set with
+    /// [NoisePipeline::set_coords_4d]. See [Noise::generate_4d] for what the nodes do with `w`.
+    pub fn new_4d(noise: &Noise) -> Self {
+        Self::acquire(noise, Dimensions::XYZW)
+    }
+
+    /// Sets the coordinates of the next [NoisePipeline::execute], one for each lane.
+    /// Coordinates of the dimensions the pipeline wasn't built for are ignored.
+    #[inline(always)]
+    pub fn set_coords(&mut self, x: Simd<T, N>, y: Simd<T, N>, z: Simd<T, N>) {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+    }
+
+    /// Same as [NoisePipeline::set_coords], with the fourth coordinate of a pipeline built by
+    /// [NoisePipeline::new_4d].
+    #[inline(always)]
+    pub fn set_coords_4d(&mut self, x: Simd<T, N>, y: Simd<T, N>, z: Simd<T, N>, w: Simd<T, N>) {
+        self.set_coords(x, y, z);
+        self.w = w;
+    }
+
+    /// Evaluates the noise at the coordinates set by [NoisePipeline::set_coords].
+    #[inline(always)]
+    pub fn execute(&mut self) -> Simd<T, N> {
+        self.index = 0;
+        self.rng.reset();
+
+        unsafe { (self.functions[0])(self) };
+        return self.results.pop().unwrap();
+    }
+
+    // Takes the pipeline for the noise out of the cache, or builds it if there isn't one. It is
+    // put back by `release` when done.
+    fn acquire(noise: &Noise, dimensions: Dimensions) -> Self {
+        // A sampler only holds pipelines of its own noise, so only the dimensions are compared
+        if let Some(sampler) = sampler_pipelines(noise) {
+            let taken = {
+                let mut pipelines = sampler.lock().unwrap_or_else(PoisonError::into_inner);
+                let position = pipelines.iter().position(|pipeline| {
+                    pipeline
+                        .downcast_ref::<Self>()
+                        .is_some_and(|pipeline| pipeline.dimensions == dimensions)
+                });
+                position.and_then(|position| pipelines.swap_remove(position).downcast().ok())
+            };
+            let mut pipeline = taken.map_or_else(|| Self::build(noise, dimensions), |p| *p);
+            pipeline.sampler = Some(sampler);
+            return pipeline;
+        }
+
+        let cached = PIPELINES.with_borrow_mut(|pipelines| {
+            let position = pipelines.iter().rposition(|pipeline| {
+                pipeline.downcast_ref::<Self>().is_some_and(|pipeline| {
+                    pipeline.dimensions == dimensions && pipeline.noise.same_nodes(noise)
+                })
+            })?;
+            pipelines.remove(position).downcast::<Self>().ok()
+        });
+
+        match cached {
+            Some(mut pipeline) => {
+                // Only the values of the parameters can differ, the nodes read them from here.
+                if pipeline.noise != *noise {
+                    pipeline
+                        .noise
+                        .pipeline
+                        .to_mut()
+                        .copy_from_slice(&noise.pipeline);
+                    for column in &mut pipeline.columns {
+                        column.valid = false;
+                    }
+                    for lattice in &mut pipeline.lattices {
+                        for cell in &mut lattice.cells {
+                            cell.valid = false;
+                        }
+                    }
+                }
+                *pipeline
+            }
+            None => Self::build(noise, dimensions),
+        }
+    }
+
+    fn release(mut self) {
+        if let Some(sampler) = self.sampler.take() {
+            let mut pipelines = sampler.lock().unwrap_or_else(PoisonError::into_inner);
+            pipelines.push(Box::new(self));
+            return;
+        }
+
+        PIPELINES.with_borrow_mut(|pipelines| {
+            if pipelines.len() == PIPELINE_CACHE_SIZE {
+                pipelines.remove(0);
+            }
+            pipelines.push(Box::new(self));
+        });
+    }
+
+    // Runs of nodes that often follow each other are done by a single kernel, saving a call
+    // for each node after the first. Constant operands of adds and multiplications are applied
+    // directly instead of taking a result, and adds of 0 and multiplications by 1 are skipped.
+    // Returns the index of the first node of each run, its number of nodes and its kernel.
+    fn fused_runs(pipeline: &[NoiseSettings]) -> Vec<(usize, usize, Kernel<N, T>)> {
+        let mut runs = Vec::new();
+        let mut i = 0;
+        while i + 1 < pipeline.len() {
+            let Some((fused, count)) = T::fused(&pipeline[i..]) else {
+                i += 1;
+                continue;
+            };
+            runs.push((i, count, fused));
+            i += count;
+        }
+        runs
+    }
+
+    fn build(noise: &Noise, dimensions: Dimensions) -> Self {
+        let mut functions = Vec::with_capacity(noise.pipeline.len());
+
+        // The nodes of a sub-noise extruded from 2d are built for 2d. The dimensions outside of
+        // each sub-noise, and the index of the node that starts it, are kept until it ends.
+        let mut current = dimensions;
+        let mut outer = Vec::new();
+        let mut columns = Vec::new();
+        let mut lattices = Vec::new();
+        let mut node_dimensions = Vec::with_capacity(noise.pipeline.len());
+        for (index, settings) in noise.pipeline.iter().enumerate() {
+            functions.push(T::function(settings, current));
+            node_dimensions.push(current);
+            match settings {
+                NoiseSettings::WrapDomain { .. }
+                | NoiseSettings::OffsetDomain { .. }
+                | NoiseSettings::Warp { .. }
+                | NoiseSettings::Interpolate { .. } => {
+                    outer.push((current, index));
+                }
+                NoiseSettings::Extrude2d => {
+                    outer.push((current, index));
+                    if matches!(current, Dimensions::XYZ | Dimensions::XYZW) {
+                        current = Dimensions::XY;
+                    }
+                }
+                NoiseSettings::RestoreCoordinates { .. } => {
+                    let start;
+                    (current, start) = outer.pop().unwrap();
+                    if matches!(current, Dimensions::XYZ | Dimensions::XYZW)
+                        && matches!(noise.pipeline[start], NoiseSettings::Extrude2d)
+                    {
+                        functions[index] = T::extrude_2d_end();
+                        columns.push(Column {
+                            start,
+                            end: index,
+                            valid: false,
+                            x: Simd::default(),
+                            z: Simd::default(),
+                            result: Simd::default(),
+                            rng_seed: 0,
+                        });
+                    } else if matches!(noise.pipeline[start], NoiseSettings::Interpolate { .. }) {
+                        functions[index] = T::interpolate_end();
+                        lattices.push(Lattice {
+                            start,
+                            end: index,
+                            result: Simd::default(),
+                            rng_seed: 0,
+                            cells: vec![
+                                LatticeCell {
+                                    valid: false,
+                                    cell: [0; 4],
+                                    corners: [T::default(); 8],
+                                };
+                                LATTICE_CELLS
+                            ],
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // A Slope of a sub-noise with derivatives is computed from the first copy. Otherwise the
+        // copies along axes the dimensions don't have are skipped, the noise doesn't change along
+        // them. The second dimension of 2d noise is the z axis of the offsets.
+        let slopes = NoiseSettings::slope_nodes(&noise.pipeline);
+        for slope in &slopes {
+            let (start, end) = slope.copies[0];
+            let dimensions = node_dimensions[start];
+            if matches!(dimensions, Dimensions::XY | Dimensions::XYZ)
+                && derivatives::check_nodes(&noise.pipeline[start + 1..end]).is_ok()
+            {
+                functions[start] = T::slope_derivatives(dimensions);
+                continue;
+            }
+
+            let axes: &[usize] = match dimensions {
+                Dimensions::X => &[0],
+                Dimensions::XY => &[0, 2],
+                Dimensions::XYZ | Dimensions::XYZW => &[0, 1, 2],
+            };
+            let mut last_run = 0;
+            for (copy, &(start, _)) in slope.copies.iter().enumerate() {
+                if axes.contains(&(copy / 2)) {
+                    last_run = copy;
+                } else {
+                    functions[start] = T::slope_skip_copy();
+                }
+            }
+            // Only the last copy keeps the seeds drawn by the sub-noise
+            if last_run + 1 < slope.copies.len() {
+                functions[slope.copies[last_run].1] = T::slope_last_copy_end();
+            }
+        }
+
+        // The other nodes of a fused run are kept so the indices don't change.
+        for (i, _, fused) in Self::fused_runs(&noise.pipeline) {
+            functions[i] = fused;
+        }
+
+        NoisePipeline {
+            rng: Rng::new(noise.seed),
+            index: 0,
+            functions,
+            results: Vec::new(),
+            x: Simd::default(),
+            y: Simd::default(),
+            z: Simd::default(),
+            w: Simd::default(),
+            saved: Vec::new(),
+            columns,
+            lattices,
+            slopes,
+            noise: noise.clone(),
+            dimensions,
+            sampler: None,
+        }
+    }
+}
+
+/// The number of values computed at a time by the generate functions on this cpu. Buffers sized
+/// in multiples of it line up with the vectors used to generate the noise.
+///
+/// This is the widest the cpu supports, unless [Noise::calibrate] found a narrower width to be
+/// faster. The width chosen by [Noise::calibrate] is global, once it returns this is the same
+/// for every thread and every noise.
+pub fn simd_width() -> usize {
+    match lane_width() {
+        0 => unsafe { (simd_width_dispatch())() },
+        width => width,
+    }
+}
+
+// Lane widths that can be chosen at runtime.
+const LANE_WIDTHS: [usize; 3] = [4, 8, 16];
+
+// Width chosen by `Noise::calibrate`, 0 until calibrated, in which case the widest the target
+// supports is used.
+static LANE_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Width `Noise::calibrate` is timing on this thread, used instead of `LANE_WIDTH` so that the
+    // other threads aren't affected until it's done. 0 when not calibrating.
+    static LANE_WIDTH_OVERRIDE: Cell<usize> = const { Cell::new(0) };
+}
+
+// The lane width used by the current thread, 0 for the widest the target supports.
+fn lane_width() -> usize {
+    match LANE_WIDTH_OVERRIDE.get() {
+        0 => LANE_WIDTH.load(Ordering::Relaxed),
+        width => width,
+    }
+}
+
+// Calls the generic version of a driver with the calibrated lane width, or the given default.
+// The generic functions are always inlined so they are compiled for the target of the caller.
+macro_rules! with_lane_width {
+    ($default:expr, $function:ident($($arg:expr),*)) => {
+        match crate::lane_width() {
+            4 => $function::<4>($($arg),*),
+            8 => $function::<8>($($arg),*),
+            16 => $function::<16>($($arg),*),
+            _ => $function::<{ $default }>($($arg),*),
+        }
+    };
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn simd_width_dispatch() -> usize {
+    simd_width!(f32)
+}
+
+// The target the kernels are dispatched to on this cpu, e.g. "x86_64+avx2+fma", or only the
+// architecture if the cpu has none of the features the kernels are compiled for.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn simd_target_dispatch() -> String {
+    const FEATURES: [&[&str]; 4] = [&["avx2", "fma"], &["sse4.2"], &["sse2"], &["neon"]];
+    let target = selected_target!();
+    let features = FEATURES.into_iter().find(|features| {
+        features.iter().all(|feature| {
+            target_features::Feature::new(target.architecture(), feature)
+                .is_ok_and(|feature| target.supports_feature(feature))
+        })
+    });
+    match features {
+        Some(features) => format!("{}+{}", std::env::consts::ARCH, features.join("+")),
+        None => String::from(std::env::consts::ARCH),
+    }
+}
+
+// Coordinates of `N` consecutive samples `step` apart. They are computed in f64 and converted
+// after, far from the origin f32 can't represent every coordinate and adding up the steps would
+// drift.
+#[inline(always)]
+fn lane_coordinates<const N: usize, T: Float>(start: f64, step: f64) -> Simd<T, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let lanes = Simd::<f64, N>::from_array(std::array::from_fn(|lane| lane as f64 * step));
+    (Simd::splat(start) + lanes).cast()
+}
+
+// The kernels and the drivers of the generate functions, compiled for each float type.
+#[allow(clippy::duplicate_mod)]
+mod single {
+    type Float = f32;
+    // Integers of the same size as `Float`, for masks and bits
+    type Int = i32;
+    type Bits = u32;
+    include!("kernels.rs");
+}
+
+#[allow(clippy::duplicate_mod)]
+mod double {
+    type Float = f64;
+    type Int = i64;
+    type Bits = u64;
+    include!("kernels.rs");
+}
+
+/// The float types the values of a noise can be generated as, `f32` and `f64`.
+///
+/// The kernels are compiled for both, so the type is chosen for each call, e.g.
+/// `noise.generate_3d::<f64>(..)`. Both give the same noise apart from rounding. `f64` takes
+/// about twice as long, but the coordinates and values keep their precision far from the origin,
+/// where `f32` can no longer tell neighbouring coordinates apart. There is no feature to switch
+/// the whole crate to `f64`, large worlds can generate everything as `f64` and smaller noises as
+/// `f32` in the same build.
+///
+/// # Example
+/// ```rust
+/// let noise = Noise::simplex(0.01);
+/// let near = noise.generate_2d::<f32>(0.0, 0.0, 16, 16);
+/// let far = noise.generate_2d::<f64>(1.0e7, 1.0e7, 16, 16);
+/// ```
+pub trait Float: sealed::Sealed {}
+
+impl Float for f32 {}
+impl Float for f64 {}
+
+mod sealed {
+    use std::simd::{LaneCount, SimdCast, SimdElement, SupportedLaneCount};
+
+    use crate::{Gradient, Kernel, Noise, NoisePipeline, NoiseSettings};
+
+    // The dimensions a pipeline is built for. It's here to be usable in `Sealed`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Dimensions {
+        X,
+        XY,
+        XYZ,
+        XYZW,
+    }
+
+    // Implemented by `kernels.rs` for each float type.
+    pub trait Sealed:
+        SimdElement + SimdCast + Default + PartialOrd + Send + Sync + std::fmt::Debug + 'static
+    {
+        // The kernel of a node
+        fn function<const N: usize>(
+            settings: &NoiseSettings,
+            dimensions: Dimensions,
+        ) -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel that does the work of the first few of `nodes`, and how many nodes it
+        // does, if there is one.
+        fn fused<const N: usize>(nodes: &[NoiseSettings]) -> Option<(Kernel<N, Self>, usize)>
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel of the node that ends a sub-noise extruded from 2d in a 3d pipeline
+        fn extrude_2d_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel of the node that ends an interpolated sub-noise
+        fn interpolate_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel of the node that starts the first copy of the sub-noise of a Slope, when the
+        // sub-noise has derivatives in `dimensions`
+        fn slope_derivatives<const N: usize>(
+            dimensions: Dimensions,
+        ) -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel of the node that starts a copy of the sub-noise of a Slope that is skipped
+        fn slope_skip_copy<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        // The kernel of the node that ends the last copy of the sub-noise of a Slope that is run,
+        // when the copies after it are skipped
+        fn slope_last_copy_end<const N: usize>() -> unsafe fn(&mut NoisePipeline<N, Self>)
+        where
+            LaneCount<N>: SupportedLaneCount;
+
+        fn generate_1d(noise: &Noise, x: f64, width: usize) -> (Vec<Self>, Self, Self);
+
+        fn generate_2d(
+            noise: &Noise,
+            x: f64,
+            y: f64,
+            width: usize,
+            height: usize,
+        ) -> (Vec<Self>, Self, Self);
+
+        fn generate_3d(
+            noise: &Noise,
+            origin: [f64; 3],
+            size: [usize; 3],
+        ) -> (Vec<Self>, Self, Self);
+
+        fn generate_4d(
+            noise: &Noise,
+            origin: [f64; 4],
+            size: [usize; 3],
+        ) -> (Vec<Self>, Self, Self);
+
+        fn generate_1d_into(noise: &Noise, x: f64, out: &mut [Self]) -> (Self, Self);
+
+        fn generate_2d_into(
+            noise: &Noise,
+            x: f64,
+            y: f64,
+            width: usize,
+            height: usize,
+            out: &mut [Self],
+        ) -> (Self, Self);
+
+        fn generate_3d_into(
+            noise: &Noise,
+            origin: [f64; 3],
+            size: [usize; 3],
+            out: &mut [Self],
+        ) -> (Self, Self);
+
+        // A 2d or 3d grid with the derivatives of its values, the depth of 2d grids is 1.
+        fn generate_derivatives(
+            noise: &Noise,
+            origin: [f64; 3],
+            size: [usize; 3],
+            dimensions: Dimensions,
+        ) -> (Vec<Self>, Self, Self, Gradient<Self>);
+    }
+}
+
+// The rotated grids can't step the coordinates along a single axis, so each vector is computed
+// from the offset of its lanes along the vectorized axis. Like the other grids, it's done in f64.
+// Periodic lines are sampled on a circle in the 2d noise, the angle of each lane is computed
+// from its index so the circle closes exactly.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn generate_1d_periodic(noise: &Noise, length: usize) -> (Vec<f32>, f32, f32) {
+    with_lane_width!(simd_width!(f32), generate_1d_periodic_lanes(noise, length))
+}
+
+#[inline(always)]
+fn generate_1d_periodic_lanes<const N: usize>(noise: &Noise, length: usize) -> (Vec<f32>, f32, f32)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let step = std::f64::consts::TAU / length as f64;
+    let radius = length as f64 / std::f64::consts::TAU;
+
+    let mut min_s = Simd::splat(f32::MAX);
+    let mut max_s = Simd::splat(f32::MIN);
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+
+    let mut result = vec![0.0; length];
+
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XY);
+
+    for i in (0..length).step_by(N) {
+        let point = |f: fn(f64) -> f64| {
+            let coordinates = std::array::from_fn(|lane| f((i + lane) as f64 * step) * radius);
+            Simd::<f64, N>::from_array(coordinates).cast()
+        };
+        pipeline.x = point(f64::cos);
+        pipeline.y = point(f64::sin);
+        let f = pipeline.execute();
+
+        let count = (length - i).min(N);
+        if count == N {
+            max_s = max_s.simd_max(f);
+            min_s = min_s.simd_min(f);
+            f.copy_to_slice(&mut result[i..]);
+        } else {
+            for (j, &n) in f.as_array()[..count].iter().enumerate() {
+                result[i + j] = n;
+                min = min.min(n);
+                max = max.max(n);
+            }
+        }
+    }
+
+    pipeline.release();
+    (
+        result,
+        min.min(min_s.reduce_min()),
+        max.max(max_s.reduce_max()),
+    )
 }
 
-#[derive(Debug)]
-struct NoisePipeline<const N: usize>
-where
-    LaneCount<N>: SupportedLaneCount,
-{
-    rng: Rng,
-    index: usize,
-    pipeline: Vec<NoiseNode<N>>,
-    results: Vec<Simd<f32, N>>,
-    x: Simd<f32, N>,
-    y: Simd<f32, N>,
-    z: Simd<f32, N>,
+// Tileable planes are sampled on a torus in the 4d noise, the angles are computed from the index
+// of each value like for periodic lines.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn generate_2d_tileable(
+    noise: &Noise,
+    width: usize,
+    height: usize,
+    period: [f64; 2],
+) -> (Vec<f32>, f32, f32) {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_2d_tileable_lanes(noise, width, height, period)
+    )
 }
 
-impl<const N: usize> NoisePipeline<N>
+#[inline(always)]
+fn generate_2d_tileable_lanes<const N: usize>(
+    noise: &Noise,
+    width: usize,
+    height: usize,
+    period: [f64; 2],
+) -> (Vec<f32>, f32, f32)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    #[inline(always)]
-    #[track_caller]
-    fn current_node(&self) -> &NoiseNode<N> {
-        &self.pipeline[self.index]
-    }
+    let step = period.map(|period| std::f64::consts::TAU / period);
+    let radius = period.map(|period| period / std::f64::consts::TAU);
 
-    #[inline(always)]
-    fn next(&mut self) {
-        self.index += 1;
-        if self.index == self.pipeline.len() {
-            return;
-        }
-        unsafe { (&self.pipeline[self.index].function)(self) };
-    }
+    let mut min_s = Simd::splat(f32::MAX);
+    let mut max_s = Simd::splat(f32::MIN);
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
 
-    #[inline(always)]
-    fn execute(&mut self) -> Simd<f32, N> {
-        self.index = 0;
-        self.rng.reset();
+    let mut result = vec![0.0; width * height];
 
-        unsafe { (self.pipeline[0].function)(self) };
-        return self.results.pop().unwrap();
-    }
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZW);
 
-    fn build(noise: &Noise, dimensions: Dimensions) -> Self {
-        let mut pipeline = Vec::with_capacity(noise.pipeline.len());
-
-        for settings in noise.pipeline.iter().cloned() {
-            let function = match settings {
-                NoiseSettings::Simplex { .. } => match dimensions {
-                    Dimensions::X => crate::simplex::simplex_1d(),
-                    Dimensions::XY => crate::simplex::simplex_2d(),
-                    Dimensions::XYZ => crate::simplex::simplex_3d(),
-                },
-                NoiseSettings::Perlin { .. } => match dimensions {
-                    Dimensions::X => crate::simplex::simplex_1d(),
-                    Dimensions::XY => crate::perlin::perlin_2d(),
-                    Dimensions::XYZ => crate::perlin::perlin_3d(),
-                },
-                NoiseSettings::Constant { .. } => crate::constant::constant(),
-                NoiseSettings::Fbm { .. } => crate::fbm::fbm(),
-                NoiseSettings::Abs { .. } => crate::abs::abs(),
-                NoiseSettings::Add { .. } => crate::add::add(),
-                NoiseSettings::Mul { .. } => crate::mul::mul(),
-                NoiseSettings::Clamp { .. } => crate::clamp::clamp(),
-                NoiseSettings::Max { .. } => crate::min_and_max::max(),
-                NoiseSettings::Min { .. } => crate::min_and_max::min(),
-                NoiseSettings::Lerp { .. } => crate::lerp::lerp(),
-                NoiseSettings::Range { .. } => crate::range::range(),
-                NoiseSettings::Square { .. } => crate::square::square(),
+    let mut i = 0;
+    for xi in 0..width {
+        let angle = xi as f64 * step[0];
+        pipeline.x = Simd::splat((angle.cos() * radius[0]) as f32);
+        pipeline.w = Simd::splat((angle.sin() * radius[0]) as f32);
+        for yi in (0..height).step_by(N) {
+            let point = |f: fn(f64) -> f64| {
+                let coordinates =
+                    std::array::from_fn(|lane| f((yi + lane) as f64 * step[1]) * radius[1]);
+                Simd::<f64, N>::from_array(coordinates).cast()
             };
-            let noise_node = NoiseNode { settings, function };
-
-            pipeline.push(noise_node)
-        }
+            pipeline.z = point(f64::cos);
+            pipeline.y = point(f64::sin);
+            let f = pipeline.execute();
 
-        NoisePipeline {
-            rng: Rng::new(noise.seed),
-            index: 0,
-            pipeline,
-            results: Vec::new(),
-            x: Simd::splat(0.0),
-            y: Simd::splat(0.0),
-            z: Simd::splat(0.0),
+            let count = (height - yi).min(N);
+            if count == N {
+                max_s = max_s.simd_max(f);
+                min_s = min_s.simd_min(f);
+                f.copy_to_slice(&mut result[i..]);
+            } else {
+                for (j, &n) in f.as_array()[..count].iter().enumerate() {
+                    result[i + j] = n;
+                    min = min.min(n);
+                    max = max.max(n);
+                }
+            }
+            i += count;
         }
     }
+
+    pipeline.release();
+    (
+        result,
+        min.min(min_s.reduce_min()),
+        max.max(max_s.reduce_max()),
+    )
 }
 
-enum Dimensions {
-    X,
-    XY,
-    XYZ,
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn generate_2d_rotated(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    angle: f32,
+) -> (Vec<f32>, f32, f32) {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_2d_rotated_lanes(noise, x, y, width, height, angle)
+    )
 }
 
-#[derive(Debug)]
-struct NoiseNode<const N: usize>
+#[inline(always)]
+fn generate_2d_rotated_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    angle: f32,
+) -> (Vec<f32>, f32, f32)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    pub settings: NoiseSettings,
-    pub function: unsafe fn(pipeline: &mut NoisePipeline<N>),
-}
-
-#[multiversion(targets = "simd")]
-fn generate_1d(noise: &Noise, x: f32, width: usize) -> (Vec<f32>, f32, f32) {
-    const N: usize = if let Some(size) = selected_target!().suggested_simd_width::<f32>() {
-        size
-    } else {
-        1
-    };
+    let (sin, cos) = (angle as f64).sin_cos();
 
     let mut min_s = Simd::splat(f32::MAX);
     let mut max_s = Simd::splat(f32::MIN);
     let mut min = f32::MAX;
     let mut max = f32::MIN;
 
-    let mut result = Vec::with_capacity(width);
-    unsafe {
-        result.set_len(width);
-    }
-    let vector_width = N;
-    let remainder = width % vector_width;
-    let mut x_arr = Vec::with_capacity(vector_width);
-    unsafe {
-        x_arr.set_len(vector_width);
-    }
-    for i in (0..vector_width).rev() {
-        x_arr[i] = x + i as f32;
-    }
+    let mut result = vec![0.0; width * height];
+    let lanes = Simd::<f64, N>::from_array(std::array::from_fn(|lane| lane as f64));
 
-    let mut pipeline = NoisePipeline::<N>::build(noise, Dimensions::X);
-    pipeline.x = Simd::from_slice(&x_arr);
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XY);
 
     let mut i = 0;
-    for _ in 0..width / vector_width {
-        let f = pipeline.execute();
-        max_s = max_s.simd_max(f);
-        min_s = min_s.simd_min(f);
-        f.copy_to_slice(&mut result[i..]);
-        i += vector_width;
-        pipeline.x = pipeline.x + Simd::splat(vector_width as f32);
-    }
-    if remainder != 0 {
-        let f = pipeline.execute();
-        for j in 0..remainder {
-            let n = f[j];
-            unsafe {
-                *result.get_unchecked_mut(i) = n;
-            }
-            if n < min {
-                min = n;
-            }
-            if n > max {
-                max = n;
+    for xi in 0..width {
+        let start_x = Simd::splat(x + xi as f64 * cos);
+        let start_y = Simd::splat(y + xi as f64 * sin);
+        for yi in (0..height).step_by(N) {
+            let offset = lanes + Simd::splat(yi as f64);
+            pipeline.x = offset.mul_add(Simd::splat(-sin), start_x).cast();
+            pipeline.y = offset.mul_add(Simd::splat(cos), start_y).cast();
+            let f = pipeline.execute();
+
+            let count = (height - yi).min(N);
+            if count == N {
+                max_s = max_s.simd_max(f);
+                min_s = min_s.simd_min(f);
+                f.copy_to_slice(&mut result[i..]);
+            } else {
+                for (j, &n) in f.as_array()[..count].iter().enumerate() {
+                    result[i + j] = n;
+                    min = min.min(n);
+                    max = max.max(n);
+                }
             }
-            i += 1;
-        }
-    }
-    for i in 0..vector_width {
-        if min_s[i] < min {
-            min = min_s[i];
-        }
-        if max_s[i] > max {
-            max = max_s[i];
+            i += count;
         }
     }
-    (result, min, max)
+
+    pipeline.release();
+    (
+        result,
+        min.min(min_s.reduce_min()),
+        max.max(max_s.reduce_max()),
+    )
 }
 
-#[multiversion(targets = "simd")]
-fn generate_2d(noise: &Noise, x: f32, y: f32, width: usize, height: usize) -> (Vec<f32>, f32, f32) {
-    const N: usize = if let Some(size) = selected_target!().suggested_simd_width::<f32>() {
-        size
-    } else {
-        1
-    };
+#[multiversion(targets = "simd", dispatcher = "cached")]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_rotated(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    rotation: Rotation,
+) -> (Vec<f32>, f32, f32) {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_3d_rotated_lanes(noise, x, y, z, width, height, depth, rotation)
+    )
+}
+
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_rotated_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    rotation: Rotation,
+) -> (Vec<f32>, f32, f32)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let [step_x, step_y, step_z] = rotation.basis.map(|step| step.map(f64::from));
 
     let mut min_s = Simd::splat(f32::MAX);
     let mut max_s = Simd::splat(f32::MIN);
     let mut min = f32::MAX;
     let mut max = f32::MIN;
 
-    let mut result = Vec::with_capacity(width * height);
-    unsafe {
-        result.set_len(width * height);
-    }
+    let mut result = vec![0.0; width * height * depth];
+    let lanes = Simd::<f64, N>::from_array(std::array::from_fn(|lane| lane as f64));
+
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZ);
+
+    let mut i = 0;
+    for xi in 0..width {
+        for zi in 0..depth {
+            let start = |axis: usize, origin: f64| {
+                Simd::splat(origin + xi as f64 * step_x[axis] + zi as f64 * step_z[axis])
+            };
+            let (start_x, start_y, start_z) = (start(0, x), start(1, y), start(2, z));
+            for yi in (0..height).step_by(N) {
+                let offset = lanes + Simd::splat(yi as f64);
+                pipeline.x = offset.mul_add(Simd::splat(step_y[0]), start_x).cast();
+                pipeline.y = offset.mul_add(Simd::splat(step_y[1]), start_y).cast();
+                pipeline.z = offset.mul_add(Simd::splat(step_y[2]), start_z).cast();
+                let f = pipeline.execute();
 
-    let vector_width = N;
-    let remainder = width % vector_width;
-    let mut y_arr = Vec::with_capacity(vector_width);
-    unsafe {
-        y_arr.set_len(vector_width);
+                let count = (height - yi).min(N);
+                if count == N {
+                    max_s = max_s.simd_max(f);
+                    min_s = min_s.simd_min(f);
+                    f.copy_to_slice(&mut result[i..]);
+                } else {
+                    for (j, &n) in f.as_array()[..count].iter().enumerate() {
+                        result[i + j] = n;
+                        min = min.min(n);
+                        max = max.max(n);
+                    }
+                }
+                i += count;
+            }
+        }
     }
-    for i in (0..vector_width).rev() {
-        y_arr[i] = y + i as f32;
+
+    pipeline.release();
+    (
+        result,
+        min.min(min_s.reduce_min()),
+        max.max(max_s.reduce_max()),
+    )
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn generate_points(noise: &Noise, points: &[[f32; 3]]) -> Vec<f32> {
+    with_lane_width!(simd_width!(f32), generate_points_lanes(noise, points))
+}
+
+#[inline(always)]
+fn generate_points_lanes<const N: usize>(noise: &Noise, points: &[[f32; 3]]) -> Vec<f32>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut result = Vec::with_capacity(points.len());
+
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZ);
+    for chunk in points.chunks(N) {
+        // The lanes past the last point repeat it
+        let coordinate = |axis: usize| {
+            Simd::from_array(std::array::from_fn(|lane| {
+                chunk[lane.min(chunk.len() - 1)][axis]
+            }))
+        };
+        pipeline.x = coordinate(0);
+        pipeline.y = coordinate(1);
+        pipeline.z = coordinate(2);
+        let f = pipeline.execute();
+        result.extend_from_slice(&f.as_array()[..chunk.len()]);
     }
+    pipeline.release();
+
+    result
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_threshold(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    threshold: f32,
+) -> Vec<u64> {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_3d_threshold_lanes(noise, x, y, z, width, height, depth, threshold)
+    )
+}
+
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_threshold_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    threshold: f32,
+) -> Vec<u64>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut words = vec![0u64; (width * height * depth).div_ceil(64)];
+    let threshold = Simd::splat(threshold);
 
-    let mut pipeline = NoisePipeline::<N>::build(noise, Dimensions::XY);
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZ);
 
-    pipeline.x = Simd::splat(x);
     let mut i = 0;
-    for _ in 0..height {
-        pipeline.y = Simd::from_slice(&y_arr);
-        for _ in 0..width / vector_width {
-            let f = pipeline.execute();
-            max_s = max_s.simd_max(f);
-            min_s = min_s.simd_min(f);
-            f.copy_to_slice(&mut result[i..]);
-            i += vector_width;
-            pipeline.y = pipeline.y + Simd::splat(vector_width as f32);
+    for xi in 0..width {
+        pipeline.x = Simd::splat((x + xi as f64) as f32);
+        for zi in 0..depth {
+            pipeline.z = Simd::splat((z + zi as f64) as f32);
+            for yi in (0..height).step_by(N) {
+                pipeline.y = lane_coordinates(y + yi as f64, 1.0);
+                let count = (height - yi).min(N);
+                let mut bits = pipeline.execute().simd_gt(threshold).to_bitmask();
+                if count < N {
+                    bits &= (1 << count) - 1;
+                }
+                // The bits of a vector can be split between two words.
+                let (word, bit) = (i / 64, i % 64);
+                words[word] |= bits << bit;
+                if bit + count > 64 {
+                    words[word + 1] |= bits >> (64 - bit);
+                }
+                i += count;
+            }
         }
-        if remainder != 0 {
-            let f = pipeline.execute();
-            for j in 0..remainder {
-                let n = f[j];
-                unsafe {
-                    *result.get_unchecked_mut(i) = n;
+    }
+
+    pipeline.release();
+    words
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_runs(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    thresholds: &[f32],
+) -> (Vec<Run>, Vec<usize>) {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_3d_runs_lanes(noise, x, y, z, width, height, depth, thresholds)
+    )
+}
+
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_runs_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    thresholds: &[f32],
+) -> (Vec<Run>, Vec<usize>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut runs: Vec<Run> = Vec::new();
+    let mut starts = Vec::with_capacity(width * depth + 1);
+
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZ);
+
+    for xi in 0..width {
+        pipeline.x = Simd::splat((x + xi as f64) as f32);
+        for zi in 0..depth {
+            pipeline.z = Simd::splat((z + zi as f64) as f32);
+            let start = runs.len();
+            starts.push(start);
+            for yi in (0..height).step_by(N) {
+                pipeline.y = lane_coordinates(y + yi as f64, 1.0);
+                let values = pipeline.execute();
+                let mut materials = Simd::<i32, N>::splat(0);
+                for &threshold in thresholds {
+                    let above = values.simd_gt(Simd::splat(threshold));
+                    materials += above.select(Simd::splat(1), Simd::splat(0));
                 }
-                if n < min {
-                    min = n;
+                let count = (height - yi).min(N);
+                let materials = &materials.as_array()[..count];
+                // Mostly the whole vector is inside one run
+                if let Some(run) = runs[start..].last_mut() {
+                    if materials
+                        .iter()
+                        .all(|&material| material as u8 == run.material)
+                    {
+                        run.length += count as u32;
+                        continue;
+                    }
                 }
-                if n > max {
-                    max = n;
+                for &material in materials {
+                    let material = material as u8;
+                    match runs[start..].last_mut() {
+                        Some(run) if run.material == material => run.length += 1,
+                        _ => runs.push(Run {
+                            material,
+                            length: 1,
+                        }),
+                    }
                 }
-                i += 1;
             }
         }
-        pipeline.x = pipeline.x + Simd::splat(1.0);
     }
-    for i in 0..vector_width {
-        if min_s[i] < min {
-            min = min_s[i];
+    starts.push(runs.len());
+
+    pipeline.release();
+    (runs, starts)
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_bricks(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    size: usize,
+    threshold: f32,
+    margin: f32,
+) -> Vec<Brick> {
+    with_lane_width!(
+        simd_width!(f32),
+        generate_3d_bricks_lanes(noise, x, y, z, width, height, depth, size, threshold, margin)
+    )
+}
+
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+fn generate_3d_bricks_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    size: usize,
+    threshold: f32,
+    margin: f32,
+) -> Vec<Brick>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (bricks_width, bricks_height, bricks_depth) = (
+        width.div_ceil(size),
+        height.div_ceil(size),
+        depth.div_ceil(size),
+    );
+    if bricks_width * bricks_height * bricks_depth == 0 {
+        return Vec::new();
+    }
+
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XYZ);
+
+    // The corners of the bricks along an axis, the last one is at the last value.
+    let corners = |len: usize| {
+        (0..=len.div_ceil(size))
+            .map(|brick| (brick * size).min(len - 1))
+            .collect::<Vec<_>>()
+    };
+    let (corners_x, corners_y, corners_z) = (corners(width), corners(height), corners(depth));
+    let mut at_corners = Vec::with_capacity(corners_x.len() * corners_y.len() * corners_z.len());
+    for &xi in &corners_x {
+        pipeline.x = Simd::splat((x + xi as f64) as f32);
+        for &zi in &corners_z {
+            pipeline.z = Simd::splat((z + zi as f64) as f32);
+            for chunk in corners_y.chunks(N) {
+                pipeline.y = Simd::from_array(std::array::from_fn(|lane| {
+                    (y + chunk[lane.min(chunk.len() - 1)] as f64) as f32
+                }));
+                let values = pipeline.execute();
+                at_corners.extend_from_slice(&values.as_array()[..chunk.len()]);
+            }
         }
-        if max_s[i] > max {
-            max = max_s[i];
+    }
+
+    let mut bricks = Vec::with_capacity(bricks_width * bricks_height * bricks_depth);
+    for bx in 0..bricks_width {
+        for bz in 0..bricks_depth {
+            for by in 0..bricks_height {
+                let (mut min, mut max) = (f32::MAX, f32::MIN);
+                for (cx, cz, cy) in (0..8).map(|i| (bx + (i & 1), bz + (i >> 1 & 1), by + (i >> 2)))
+                {
+                    let value = at_corners[(cx * corners_z.len() + cz) * corners_y.len() + cy];
+                    min = min.min(value);
+                    max = max.max(value);
+                }
+                if min - margin > threshold {
+                    bricks.push(Brick::Above);
+                    continue;
+                } else if max + margin <= threshold {
+                    bricks.push(Brick::Below);
+                    continue;
+                }
+
+                let (brick_width, brick_height, brick_depth) = (
+                    size.min(width - bx * size),
+                    size.min(height - by * size),
+                    size.min(depth - bz * size),
+                );
+                let mut values = Vec::with_capacity(brick_width * brick_height * brick_depth);
+                let (mut min, mut max) = (f32::MAX, f32::MIN);
+                for xi in bx * size..bx * size + brick_width {
+                    pipeline.x = Simd::splat((x + xi as f64) as f32);
+                    for zi in bz * size..bz * size + brick_depth {
+                        pipeline.z = Simd::splat((z + zi as f64) as f32);
+                        for yi in (0..brick_height).step_by(N) {
+                            pipeline.y = lane_coordinates(y + (by * size + yi) as f64, 1.0);
+                            let count = (brick_height - yi).min(N);
+                            let result = pipeline.execute();
+                            for &value in &result.as_array()[..count] {
+                                min = min.min(value);
+                                max = max.max(value);
+                                values.push(value);
+                            }
+                        }
+                    }
+                }
+                bricks.push(Brick::Values(NoiseResult::new(
+                    (values, min, max),
+                    brick_width,
+                    brick_height,
+                    brick_depth,
+                )));
+            }
         }
     }
-    (result, min, max)
+
+    pipeline.release();
+    bricks
 }
 
-#[multiversion(targets = "simd")]
-fn generate_3d(
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn generate_2d_progressive(
     noise: &Noise,
-    x: f32,
-    y: f32,
-    z: f32,
+    x: f64,
+    y: f64,
     width: usize,
     height: usize,
-    depth: usize,
+    coarse_stride: usize,
+    callback: &mut dyn FnMut(&NoiseResult, usize),
 ) -> (Vec<f32>, f32, f32) {
-    const N: usize = if let Some(size) = selected_target!().suggested_simd_width::<f32>() {
-        size
-    } else {
-        1
-    };
+    with_lane_width!(
+        simd_width!(f32),
+        generate_2d_progressive_lanes(noise, x, y, width, height, coarse_stride, callback)
+    )
+}
 
-    let mut min_s = Simd::splat(f32::MAX);
-    let mut max_s = Simd::splat(f32::MIN);
-    let mut min = f32::MAX;
-    let mut max = f32::MIN;
+#[inline(always)]
+fn generate_2d_progressive_lanes<const N: usize>(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+    coarse_stride: usize,
+    callback: &mut dyn FnMut(&NoiseResult, usize),
+) -> (Vec<f32>, f32, f32)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut values = vec![0.0; width * height];
+    let (mut min, mut max) = (f32::MAX, f32::MIN);
 
-    let mut result = Vec::with_capacity(width * height * depth);
-    unsafe {
-        result.set_len(width * height * depth);
-    }
-    let vector_width = N;
-    let remainder = height % vector_width;
-    let mut y_arr = Vec::with_capacity(vector_width);
-    unsafe {
-        y_arr.set_len(vector_width);
-    }
-    for i in (0..vector_width).rev() {
-        y_arr[i] = y + i as f32;
-    }
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, Dimensions::XY);
 
-    let mut pipeline = NoisePipeline::<N>::build(noise, Dimensions::XYZ);
+    let mut stride = coarse_stride;
+    loop {
+        // The values on the grid of the previous level are already generated, on those columns
+        // only every other value is new.
+        let previous = stride * 2;
+        for xi in (0..width).step_by(stride) {
+            pipeline.x = Simd::splat((x + xi as f64) as f32);
+            let (start, step) = if stride < coarse_stride && xi % previous == 0 {
+                (stride, previous)
+            } else {
+                (0, stride)
+            };
+            for yi in (start..height).step_by(step * N) {
+                pipeline.y = lane_coordinates(y + yi as f64, step as f64);
+                let count = (height - yi).div_ceil(step).min(N);
+                let result = pipeline.execute();
+                for (lane, &value) in result.as_array()[..count].iter().enumerate() {
+                    values[xi * height + yi + lane * step] = value;
+                    min = min.min(value);
+                    max = max.max(value);
+                }
+            }
+        }
 
-    // TODO: This loop in loop system is maybe not good? Try a flat design where "overflowing"
-    // values of the first axis is transfered to the second, and same for second to third every
-    // iteration.
-    pipeline.x = Simd::splat(x);
-    let mut i = 0;
-    for _ in 0..width {
-        pipeline.z = Simd::splat(z);
-        for _ in 0..depth {
-            pipeline.y = Simd::from_slice(&y_arr);
-            for _ in 0..height / vector_width {
-                let f = pipeline.execute();
-                max_s = max_s.simd_max(f);
-                min_s = min_s.simd_min(f);
-                f.copy_to_slice(&mut result[i..]);
-                i += vector_width;
-                pipeline.y = pipeline.y + Simd::splat(vector_width as f32);
+        if stride > 1 {
+            // Fill in the values between the grid points with the grid point before them, along
+            // the columns of the grid and then the columns between them.
+            for xi in (0..width).step_by(stride) {
+                let column = &mut values[xi * height..(xi + 1) * height];
+                for yi in 0..height {
+                    column[yi] = column[yi - yi % stride];
+                }
             }
-            if remainder != 0 {
-                let f = pipeline.execute();
-                for j in 0..remainder {
-                    let n = f[j];
-                    unsafe {
-                        *result.get_unchecked_mut(i) = n;
-                    }
-                    if n < min {
-                        min = n;
-                    }
-                    if n > max {
-                        max = n;
-                    }
-                    i += 1;
+            for xi in 0..width {
+                if xi % stride != 0 {
+                    let column = (xi - xi % stride) * height;
+                    values.copy_within(column..column + height, xi * height);
                 }
             }
-            pipeline.z = pipeline.z + Simd::splat(1.0);
         }
-        pipeline.x = pipeline.x + Simd::splat(1.0);
+
+        let result = NoiseResult::new((values, min, max), width, height, 1);
+        callback(&result, stride);
+        values = result.into_vec();
+
+        if stride == 1 {
+            break;
+        }
+        stride /= 2;
+    }
+
+    pipeline.release();
+    (values, min, max)
+}
+
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn regenerate_region(
+    noise: &Noise,
+    values: &mut [f32],
+    dimensions: Dimensions,
+    origin: [f64; 3],
+    size: [usize; 3],
+    region: &[std::ops::Range<usize>; 3],
+) {
+    with_lane_width!(
+        simd_width!(f32),
+        regenerate_region_lanes(noise, values, dimensions, origin, size, region)
+    )
+}
+
+#[inline(always)]
+fn regenerate_region_lanes<const N: usize>(
+    noise: &Noise,
+    values: &mut [f32],
+    dimensions: Dimensions,
+    [x, y, z]: [f64; 3],
+    [_, height, depth]: [usize; 3],
+    [x_range, y_range, z_range]: &[std::ops::Range<usize>; 3],
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut pipeline = NoisePipeline::<N>::acquire(noise, dimensions);
+
+    // The vectors start at the same multiples of the lane width as when the whole result is
+    // generated, so that the coordinates are computed the same way.
+    let start = y_range.start - y_range.start % N;
+    for xi in x_range.clone() {
+        pipeline.x = Simd::splat((x + xi as f64) as f32);
+        for zi in z_range.clone() {
+            if dimensions == Dimensions::XYZ {
+                pipeline.z = Simd::splat((z + zi as f64) as f32);
+            }
+            let column = &mut values[(xi * depth + zi) * height..][..height];
+            for yi in (start..y_range.end).step_by(N) {
+                pipeline.y = lane_coordinates(y + yi as f64, 1.0);
+                let result = pipeline.execute();
+                let lanes = yi.max(y_range.start)..(yi + N).min(y_range.end);
+                column[lanes.clone()].copy_from_slice(&result[lanes.start - yi..lanes.end - yi]);
+            }
+        }
+    }
+
+    pipeline.release();
+}
+
+// 64-bit FNV-1a of the UTF-8 bytes
+const fn fnv1a(string: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = string.as_bytes();
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
     }
-    for i in 0..vector_width {
-        if min_s[i] < min {
-            min = min_s[i];
+    hash
+}
+
+// Same as f32::powi, which isn't const. Squares like the compiler's powi so that the result is the
+// same at compile time and at runtime.
+const fn powi(mut base: f32, mut exponent: u32) -> f32 {
+    let mut result = 1.0;
+    loop {
+        if exponent & 1 == 1 {
+            result *= base;
         }
-        if max_s[i] > max {
-            max = max_s[i];
+        exponent >>= 1;
+        if exponent == 0 {
+            return result;
         }
+        base *= base;
     }
-    (result, min, max)
+}
+
+// The multiply and fold of wyhash, the hash the rng is built on
+fn mix(a: u64, b: u64) -> u64 {
+    let t = u128::from(a ^ 0xa076_1d64_78bd_642f) * u128::from(b ^ 0xe703_7ed1_a0b4_28db);
+    (t as u64) ^ (t >> 64) as u64
 }
 
 // See WyRand https://github.com/wangyi-fudan/wyhash/blob/master/wyhash.h#L151
@@ -728,6 +5285,8 @@ struct Rng {
 }
 
 impl Rng {
+    const INCREMENT: u64 = 0x2d35_8dcc_aa6c_78a5;
+
     fn new(seed: u64) -> Self {
         Self {
             seed,
@@ -736,13 +5295,24 @@ impl Rng {
     }
 
     fn next(&mut self) -> i32 {
-        let seed = self.current_seed.wrapping_add(0x2d35_8dcc_aa6c_78a5);
+        self.next_u64() as i32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let seed = self.current_seed.wrapping_add(Self::INCREMENT);
         self.current_seed = seed;
         let t = u128::from(seed) * u128::from(seed ^ 0x8bb8_4b93_962e_acc9);
-        ((t as u64) ^ (t >> 64) as u64) as i32
+        (t as u64) ^ (t >> 64) as u64
     }
 
     fn reset(&mut self) {
         self.current_seed = self.seed;
     }
+
+    // Same as drawing `count` seeds, the state moves by the same increment for every draw.
+    fn skip(&mut self, count: u64) {
+        self.current_seed = self
+            .current_seed
+            .wrapping_add(count.wrapping_mul(Self::INCREMENT));
+    }
 }
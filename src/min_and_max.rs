@@ -2,10 +2,11 @@ use std::simd::{prelude::*, LaneCount, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::NoisePipeline;
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn max<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn max<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -17,7 +18,7 @@ where
 }
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn min<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn min<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -2,10 +2,11 @@ use std::simd::{LaneCount, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::NoisePipeline;
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn mul<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn mul<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -14,3 +15,15 @@ where
     pipeline.results.push(left * right);
     pipeline.next();
 }
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn div<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The other noise was added last, its result is on top.
+    let other = pipeline.results.pop().unwrap();
+    let noise = pipeline.results.pop().unwrap();
+    pipeline.results.push(noise / other);
+    pipeline.next();
+}
@@ -0,0 +1,176 @@
+use std::io::{self, Write};
+
+use crate::{Float, NoiseResult};
+
+impl<T: Float> NoiseResult<T> {
+    /// Writes the values in the NumPy `.npy` format, to be loaded with `numpy.load`.
+    ///
+    /// The shape of the array follows the layout of the values, `(width,)` for 1d results,
+    /// `(width, height)` for 2d results and `(width, depth, height)` for 3d results, so
+    /// `array[x, z, y]` is the value at (x, y, z).
+    ///
+    /// Requires the `npy` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let file = std::fs::File::create("height.npy").unwrap();
+    /// height.write_npy(std::io::BufWriter::new(file)).unwrap();
+    /// ```
+    pub fn write_npy(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(&self.npy_header())?;
+        writer.write_all(self.npy_data())
+    }
+
+    /// Writes several results to one NumPy `.npz` archive, each stored as an array with the
+    /// given name, like [NoiseResult::write_npy]. The archive isn't compressed.
+    ///
+    /// Requires the `npy` feature.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let moisture = Noise::simplex(0.02).seed(1).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let file = std::fs::File::create("climate.npz").unwrap();
+    /// NoiseResult::write_npz(file, &[("height", &height), ("moisture", &moisture)]).unwrap();
+    /// ```
+    pub fn write_npz(mut writer: impl Write, arrays: &[(&str, &NoiseResult<T>)]) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "The archive is too large");
+
+        // A zip archive of stored files, each file is a local header followed by the file,
+        // and a central directory at the end lists them.
+        let mut offset = 0u32;
+        let mut directory = Vec::new();
+        for (name, result) in arrays {
+            let name = format!("{name}.npy");
+            let header = result.npy_header();
+            let data = result.npy_data();
+            let crc = crc32(crc32(0, &header), data);
+            let size = u32::try_from(header.len() + data.len()).map_err(|_| too_large())?;
+
+            let mut local = Vec::with_capacity(30 + name.len());
+            local.extend(0x04034b50u32.to_le_bytes());
+            local.extend(zip_entry(crc, size, name.len()));
+            local.extend(name.as_bytes());
+            writer.write_all(&local)?;
+            writer.write_all(&header)?;
+            writer.write_all(data)?;
+
+            directory.extend(0x02014b50u32.to_le_bytes());
+            // Version made by
+            directory.extend(20u16.to_le_bytes());
+            directory.extend(zip_entry(crc, size, name.len()));
+            // Comment length, disk number, internal and external attributes
+            directory.extend([0; 10]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name.as_bytes());
+
+            offset = u32::try_from(local.len())
+                .ok()
+                .and_then(|len| offset.checked_add(len)?.checked_add(size))
+                .ok_or_else(too_large)?;
+        }
+
+        let count = u16::try_from(arrays.len()).map_err(|_| too_large())?;
+        let directory_size = u32::try_from(directory.len()).map_err(|_| too_large())?;
+        directory.extend(0x06054b50u32.to_le_bytes());
+        // Number of this disk and the disk the directory starts on
+        directory.extend([0; 4]);
+        directory.extend(count.to_le_bytes());
+        directory.extend(count.to_le_bytes());
+        directory.extend(directory_size.to_le_bytes());
+        directory.extend(offset.to_le_bytes());
+        // Comment length
+        directory.extend([0; 2]);
+        writer.write_all(&directory)
+    }
+
+    fn npy_header(&self) -> Vec<u8> {
+        let shape = if self.depth() > 1 {
+            format!("({}, {}, {})", self.width(), self.depth(), self.height())
+        } else if self.height() > 1 {
+            format!("({}, {})", self.width(), self.height())
+        } else {
+            format!("({},)", self.width())
+        };
+        let endian = if cfg!(target_endian = "little") {
+            '<'
+        } else {
+            '>'
+        };
+        let descr = format!("{endian}f{}", std::mem::size_of::<T>());
+        let mut dict =
+            format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+
+        // The magic string, version and header length take 10 bytes, the header is padded with
+        // spaces and ends with a newline so the data starts at a multiple of 64.
+        let len = (10 + dict.len() + 1).next_multiple_of(64) - 10;
+        dict.extend(std::iter::repeat_n(' ', len - dict.len() - 1));
+        dict.push('\n');
+
+        let mut header = Vec::with_capacity(10 + len);
+        header.extend(b"\x93NUMPY\x01\x00");
+        header.extend((len as u16).to_le_bytes());
+        header.extend(dict.as_bytes());
+        header
+    }
+
+    // The values as they are in memory, the byte order is in the header.
+    fn npy_data(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self.values.as_ptr() as *const u8,
+                std::mem::size_of_val(self.values.as_slice()),
+            )
+        }
+    }
+}
+
+// The part of a file entry shared by the local header and the central directory, from the
+// version needed to extract to the length of the extra field.
+fn zip_entry(crc: u32, size: u32, name_len: usize) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(26);
+    // Version needed, flags and method, stored without compression
+    entry.extend(20u16.to_le_bytes());
+    entry.extend([0; 4]);
+    // Modification time and date, 1980-01-01
+    entry.extend(0u16.to_le_bytes());
+    entry.extend(0x21u16.to_le_bytes());
+    entry.extend(crc.to_le_bytes());
+    // Compressed and uncompressed size
+    entry.extend(size.to_le_bytes());
+    entry.extend(size.to_le_bytes());
+    entry.extend((name_len as u16).to_le_bytes());
+    // Extra field length
+    entry.extend([0; 2]);
+    entry
+}
+
+// CRC-32 as used by zip, continued from a previous `crc`
+fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    0xedb8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    let mut crc = !crc;
+    for &byte in bytes {
+        crc = TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
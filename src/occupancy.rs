@@ -0,0 +1,82 @@
+/// Whether each value of a cube of noise is above a threshold, one bit per value, produced by
+/// [Noise::generate_3d_threshold](crate::Noise::generate_3d_threshold).
+///
+/// The bits are in the same order as the values of a [NoiseResult](crate::NoiseResult), the bit
+/// of (x, y, z) is bit `i % 64` of word `i / 64` where `i` is `x * depth * height + z * height +
+/// y`. The bits after the last value are 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Occupancy {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl Occupancy {
+    pub(crate) fn new(words: Vec<u64>, width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            words,
+            width,
+            height,
+            depth,
+        }
+    }
+
+    /// Size of the first dimension
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Size of the second dimension
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of the third dimension
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of values
+    pub fn len(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+
+    /// True if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the value at (x, y, z) is above the threshold
+    pub fn get(&self, x: usize, y: usize, z: usize) -> bool {
+        assert!(
+            x < self.width && y < self.height && z < self.depth,
+            "The position is outside of the cube"
+        );
+        let i = (x * self.depth + z) * self.height + y;
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// The number of values above the threshold
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether each value is above the threshold, in the order of the values
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len()).map(|i| self.words[i / 64] >> (i % 64) & 1 == 1)
+    }
+
+    /// The bits packed into words, see [Occupancy].
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// The bits packed into words, see [Occupancy].
+    pub fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+}
@@ -0,0 +1,234 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::gradient::{grad3d_dot, hash2d, hash3d};
+use super::simplex::{X_PRIME, Y_PRIME, Z_PRIME};
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// 24 unit gradients 15 degrees apart, starting at 7.5 degrees so none of them lines up with the
+// axes. The x components are followed by the y components.
+const GRADIENTS_2D: [Float; 48] = {
+    const COSINES: [Float; 12] = [
+        0.9914448613738104,
+        0.9238795325112867,
+        0.7933533402912352,
+        0.6087614290087207,
+        0.3826834323650898,
+        0.1305261922200516,
+        -0.1305261922200516,
+        -0.3826834323650898,
+        -0.6087614290087207,
+        -0.7933533402912352,
+        -0.9238795325112867,
+        -0.9914448613738104,
+    ];
+    // The cosines of the first half of the circle, the second half is the same negated. The sines
+    // are the cosines of the angles a quarter turn earlier.
+    let mut table = [0.0; 48];
+    let mut k = 0;
+    while k < 12 {
+        table[k] = COSINES[k];
+        table[k + 12] = -COSINES[k];
+        k += 1;
+    }
+    while k < 36 {
+        table[k + 12] = table[(k + 6) % 24];
+        k += 1;
+    }
+    table
+};
+
+// Dot product of the gradient picked by the hash with [x, y]
+#[inline(always)]
+fn grad2_dot<const N: usize>(
+    hash: Simd<i32, N>,
+    x: Simd<Float, N>,
+    y: Simd<Float, N>,
+) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // 16 bits of the hash scaled down to 0..24
+    let index =
+        (((hash >> Simd::splat(8)) & Simd::splat(0xffff)) * Simd::splat(24)) >> Simd::splat(16);
+    let index = index.cast::<usize>();
+    let gx = Simd::gather_or_default(&GRADIENTS_2D[..24], index);
+    let gy = Simd::gather_or_default(&GRADIENTS_2D[24..], index);
+    gx.mul_add(x, gy * y)
+}
+
+/// Samples 2-dimensional OpenSimplex2 noise
+///
+/// Produces a value -1 ≤ n ≤ 1.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn opensimplex2_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const SQRT3: Float = 1.7320508075688772935274463415059;
+    const F2: Float = 0.5 * (SQRT3 - 1.0);
+    const G2: Float = (3.0 - SQRT3) / 6.0;
+
+    let settings = pipeline.current_settings();
+    let NoiseSettings::OpenSimplex2 { frequency } = *settings else {
+        unreachable!()
+    };
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    // The second dimension of 2d noise is stored in y
+    let x = pipeline.x * Simd::splat(frequency.x as Float);
+    let y = pipeline.y * Simd::splat(frequency.z as Float);
+
+    // Skewed onto the simplex lattice
+    let t = (x + y) * Simd::splat(F2);
+    let (x, y) = (x + t, y + t);
+
+    let (xf, yf) = (x.floor(), y.floor());
+    let i = xf.cast::<i32>() * Simd::splat(X_PRIME);
+    let j = yf.cast::<i32>() * Simd::splat(Y_PRIME);
+    let (xi, yi) = (x - xf, y - yf);
+
+    let t = (xi + yi) * Simd::splat(G2);
+    let x0 = xi - t;
+    let y0 = yi - t;
+
+    let zero = Simd::splat(0.0);
+    let a = Simd::splat(0.5) - x0 * x0 - y0 * y0;
+    // The falloff of the far corner, derived from the unclamped falloff of the near one
+    let c = Simd::splat(2.0 * (1.0 - 2.0 * G2) * (1.0 / G2 - 2.0)).mul_add(
+        t,
+        Simd::splat(-2.0 * (1.0 - 2.0 * G2) * (1.0 - 2.0 * G2)) + a,
+    );
+
+    let a = a.simd_max(zero);
+    let n0 = (a * a) * (a * a) * grad2_dot(hash2d(seed, i, j), x0, y0);
+
+    let c = c.simd_max(zero);
+    let x2 = x0 + Simd::splat(2.0 * G2 - 1.0);
+    let y2 = y0 + Simd::splat(2.0 * G2 - 1.0);
+    let n2 = (c * c)
+        * (c * c)
+        * grad2_dot(
+            hash2d(seed, i + Simd::splat(X_PRIME), j + Simd::splat(Y_PRIME)),
+            x2,
+            y2,
+        );
+
+    // The middle corner is a step along y or x, whichever the point is closer to.
+    let along_y = y0.simd_gt(x0);
+    let x1 = x0 + along_y.select(Simd::splat(G2), Simd::splat(G2 - 1.0));
+    let y1 = y0 + along_y.select(Simd::splat(G2 - 1.0), Simd::splat(G2));
+    let along_y = along_y.cast::<i32>();
+    let i1 = along_y.select(i, i + Simd::splat(X_PRIME));
+    let j1 = along_y.select(j + Simd::splat(Y_PRIME), j);
+    let b = (Simd::splat(0.5) - x1 * x1 - y1 * y1).simd_max(zero);
+    let n1 = (b * b) * (b * b) * grad2_dot(hash2d(seed, i1, j1), x1, y1);
+
+    let result = (n0 + n1 + n2) * Simd::splat(99.83685446303647);
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+/// Samples 3-dimensional OpenSimplex2 noise
+///
+/// Produces a value -1 ≤ n ≤ 1.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn opensimplex2_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::OpenSimplex2 { frequency } = *settings else {
+        unreachable!()
+    };
+
+    let mut seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    let x = pipeline.x * Simd::splat(frequency.x as Float);
+    let y = pipeline.y * Simd::splat(frequency.y as Float);
+    let z = pipeline.z * Simd::splat(frequency.z as Float);
+
+    // Rotated onto the body centered cubic lattice, two offset cubic lattices
+    let r = (x + y + z) * Simd::splat(2.0 / 3.0);
+    let (x, y, z) = (r - x, r - y, r - z);
+
+    let (xr, yr, zr) = (x.round(), y.round(), z.round());
+    let mut i = xr.cast::<i32>() * Simd::splat(X_PRIME);
+    let mut j = yr.cast::<i32>() * Simd::splat(Y_PRIME);
+    let mut k = zr.cast::<i32>() * Simd::splat(Z_PRIME);
+    let (mut x0, mut y0, mut z0) = (x - xr, y - yr, z - zr);
+
+    // -1 where the point is past the lattice point along the axis, 1 otherwise
+    let sign = |v: Simd<Float, N>| {
+        v.simd_ge(Simd::splat(0.0))
+            .select(Simd::splat(-1.0), Simd::splat(1.0))
+    };
+    let (mut x_sign, mut y_sign, mut z_sign) = (sign(x0), sign(y0), sign(z0));
+    let (mut ax0, mut ay0, mut az0) = (x0.abs(), y0.abs(), z0.abs());
+
+    let zero = Simd::splat(0.0);
+    let mut value = Simd::splat(0.0);
+    let mut a = Simd::splat(0.6) - x0 * x0 - (y0 * y0 + z0 * z0);
+    for lattice in 0..2 {
+        let falloff = a.simd_max(zero);
+        value += (falloff * falloff)
+            * (falloff * falloff)
+            * grad3d_dot(hash3d(seed, i, j, k), x0, y0, z0);
+
+        // The second closest point of the lattice is one step along the axis the point is
+        // furthest from the closest one.
+        let along_x = ax0.simd_ge(ay0) & ax0.simd_ge(az0);
+        let along_y = !along_x & ay0.simd_gt(ax0) & ay0.simd_ge(az0);
+        let along_z = !along_x & !along_y;
+        let distance = along_x.select(ax0, along_y.select(ay0, az0));
+        let b = (a + distance + distance - Simd::splat(1.0)).simd_max(zero);
+
+        let dx = along_x.select(x_sign, zero);
+        let dy = along_y.select(y_sign, zero);
+        let dz = along_z.select(z_sign, zero);
+        let prime_step = |d: Simd<Float, N>, prime: i32| d.cast::<i32>() * Simd::splat(prime);
+        let hash = hash3d(
+            seed,
+            i - prime_step(dx, X_PRIME),
+            j - prime_step(dy, Y_PRIME),
+            k - prime_step(dz, Z_PRIME),
+        );
+        value += (b * b) * (b * b) * grad3d_dot(hash, x0 + dx, y0 + dy, z0 + dz);
+
+        if lattice == 1 {
+            break;
+        }
+
+        // Over to the closest point of the other cubic lattice
+        ax0 = Simd::splat(0.5) - ax0;
+        ay0 = Simd::splat(0.5) - ay0;
+        az0 = Simd::splat(0.5) - az0;
+        x0 = x_sign * ax0;
+        y0 = y_sign * ay0;
+        z0 = z_sign * az0;
+        a += (Simd::splat(0.75) - ax0) - (ay0 + az0);
+
+        i = x_sign
+            .simd_lt(zero)
+            .cast::<i32>()
+            .select(i + Simd::splat(X_PRIME), i);
+        j = y_sign
+            .simd_lt(zero)
+            .cast::<i32>()
+            .select(j + Simd::splat(Y_PRIME), j);
+        k = z_sign
+            .simd_lt(zero)
+            .cast::<i32>()
+            .select(k + Simd::splat(Z_PRIME), k);
+        x_sign = -x_sign;
+        y_sign = -y_sign;
+        z_sign = -z_sign;
+        seed = !seed;
+    }
+
+    let result = value * Simd::splat(32.69428253173828125);
+    pipeline.results.push(result);
+    pipeline.next();
+}
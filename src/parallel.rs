@@ -0,0 +1,212 @@
+#[cfg(feature = "rayon")]
+use crate::Float;
+use crate::{Noise, NoiseResult, NoiseSettings, Rng};
+
+// Splits a noise that ends in fbm into at most `parts` noises that each sum a consecutive run of
+// the octaves, with the amplitudes they have in the full noise. The seeds are moved to where the
+// full noise is at the start of each run, so the parts add up to the full noise.
+fn split_octaves(noise: &Noise, parts: usize) -> Option<Vec<Noise>> {
+    let Some(&NoiseSettings::Fbm {
+        octaves,
+        gain,
+        first_octave_amplitude,
+    }) = noise.pipeline.last()
+    else {
+        return None;
+    };
+    let parts = parts.min(octaves as usize);
+    if parts < 2 {
+        return None;
+    }
+
+    // Walk back from the fbm to find where each octave starts. The octaves are in the pipeline
+    // from the last to the first.
+    let mut starts = Vec::with_capacity(octaves as usize);
+    let mut end = noise.pipeline.len() - 1;
+    for _ in 0..octaves {
+        let mut needed = 1;
+        while needed > 0 {
+            end -= 1;
+            let (inputs, outputs) = noise.pipeline[end].stack_effect();
+            needed = needed + inputs - outputs;
+            // Nodes that change the coordinates for a sub-noise come before it, and a warp comes
+            // after the offsets it takes.
+            while needed == 0
+                && end > 0
+                && matches!(
+                    noise.pipeline[end - 1],
+                    NoiseSettings::WrapDomain { .. }
+                        | NoiseSettings::OffsetDomain { .. }
+                        | NoiseSettings::Warp { .. }
+                        | NoiseSettings::Extrude2d
+                        | NoiseSettings::Interpolate { .. }
+                )
+            {
+                end -= 1;
+                needed = noise.pipeline[end].stack_effect().0;
+            }
+        }
+        starts.push(end);
+    }
+    starts.reverse();
+
+    // State of the rng at the start of each octave, mirrors how the pipeline uses it.
+    let mut seeds = Vec::with_capacity(octaves as usize);
+    let mut rng = Rng::new(noise.seed);
+    let mut saved = Vec::new();
+    for (index, settings) in noise.pipeline.iter().enumerate() {
+        if starts.get(seeds.len()) == Some(&index) {
+            seeds.push(rng.current_seed);
+        }
+        match settings {
+            NoiseSettings::Simplex { .. }
+            | NoiseSettings::Perlin { .. }
+            | NoiseSettings::Colored { .. }
+            | NoiseSettings::Spectral { .. }
+            | NoiseSettings::Dots { .. }
+            | NoiseSettings::Cellular { .. }
+            | NoiseSettings::White
+            | NoiseSettings::OpenSimplex2 { .. }
+            | NoiseSettings::Value { .. }
+            | NoiseSettings::ValueCubic { .. } => {
+                rng.next();
+            }
+            NoiseSettings::WrapDomain { .. }
+            | NoiseSettings::OffsetDomain { .. }
+            | NoiseSettings::Warp { .. }
+            | NoiseSettings::Extrude2d
+            | NoiseSettings::Interpolate { .. } => {
+                saved.push(rng.clone());
+            }
+            NoiseSettings::SeedOffset { offset } => rng.skip(*offset),
+            NoiseSettings::RestoreCoordinates { rewind } => {
+                let saved = saved.pop().unwrap();
+                if *rewind {
+                    rng = saved;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Runs of octaves in pipeline order, the first run has the most octaves when they can't be
+    // split evenly.
+    let mut noises = Vec::with_capacity(parts);
+    let mut first = 0;
+    for part in 0..parts {
+        let count = (octaves as usize - first).div_ceil(parts - part);
+        let last = first + count;
+        let end = starts
+            .get(last)
+            .copied()
+            .unwrap_or(noise.pipeline.len() - 1);
+
+        // The lowest octave of the run is the last one in the pipeline.
+        let lowest_octave = octaves as usize - last;
+        let mut amplitude = first_octave_amplitude;
+        for _ in 0..lowest_octave {
+            amplitude *= gain;
+        }
+
+        let mut pipeline = noise.pipeline[starts[first]..end].to_vec();
+        pipeline.push(NoiseSettings::Fbm {
+            octaves: count as u32,
+            gain,
+            first_octave_amplitude: amplitude,
+        });
+        noises.push(Noise {
+            seed: seeds[first],
+            algorithm: noise.algorithm,
+            pipeline: pipeline.into(),
+            buffers: noise.buffers.clone(),
+        });
+
+        first = last;
+    }
+
+    Some(noises)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn generate_3d(
+    noise: &Noise,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: usize,
+    height: usize,
+    depth: usize,
+    threads: usize,
+) -> NoiseResult {
+    let Some(parts) = split_octaves(noise, threads) else {
+        return noise.generate_3d(x, y, z, width, height, depth);
+    };
+
+    let mut results = std::thread::scope(|scope| {
+        let handles: Vec<_> = parts
+            .iter()
+            .map(|part| scope.spawn(move || part.generate_3d(x, y, z, width, height, depth)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // Sum the parts from the lowest octaves up, like the fbm does.
+    let mut values = results.pop().unwrap().into_vec();
+    for part in results.iter().rev() {
+        for (value, part) in values.iter_mut().zip(part.iter()) {
+            *value += part;
+        }
+    }
+
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+    for &value in values.iter() {
+        min = min.min(value);
+        max = max.max(value);
+    }
+
+    NoiseResult::new((values, min, max), width, height, depth)
+}
+
+// The volume is split into slabs along x, each of them is a contiguous part of the result. There
+// are a few slabs per thread so that the threads that finish early take over the rest.
+#[cfg(feature = "rayon")]
+pub(crate) fn generate_3d_slabs<T: Float>(
+    noise: &Noise,
+    [x, y, z]: [f64; 3],
+    [width, height, depth]: [usize; 3],
+) -> NoiseResult<T> {
+    use rayon::prelude::*;
+
+    let plane = height * depth;
+    if width * plane == 0 {
+        return noise.generate_3d(x, y, z, width, height, depth);
+    }
+    let slab_width = width.div_ceil(rayon::current_num_threads() * 4);
+
+    let mut values = vec![T::default(); width * plane];
+    let ranges: Vec<(T, T)> = values
+        .par_chunks_mut(slab_width * plane)
+        .enumerate()
+        .map(|(slab, values)| {
+            let origin = [x + (slab * slab_width) as f64, y, z];
+            let size = [values.len() / plane, height, depth];
+            T::generate_3d_into(noise, origin, size, values)
+        })
+        .collect();
+
+    let (mut min, mut max) = ranges[0];
+    for (slab_min, slab_max) in ranges {
+        if slab_min < min {
+            min = slab_min;
+        }
+        if slab_max > max {
+            max = slab_max;
+        }
+    }
+
+    NoiseResult::new((values, min, max), width, height, depth)
+}
@@ -3,10 +3,13 @@ use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
 
 use multiversion::multiversion;
 
-use crate::gradient::grad3d_dot;
-use crate::gradient::hash2d;
-use crate::gradient::hash3d;
-use crate::gradient::{grad1, grad2};
+use super::gradient::grad3d_dot;
+use super::gradient::hash2d;
+use super::gradient::hash3d;
+use super::gradient::{grad1, grad2};
+use super::gradient::{grad4d_dot, hash4d};
+use super::simplex::W_PRIME;
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
 
 pub const X_PRIME: i32 = 501125321;
@@ -14,20 +17,20 @@ pub const Y_PRIME: i32 = 1136930381;
 pub const Z_PRIME: i32 = 1720413743;
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn perlin_2d<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn perlin_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Perlin { frequency } = node.settings else {
+    let NoiseSettings::Perlin { frequency } = *settings else {
         unreachable!()
     };
 
     let seed = Simd::splat(pipeline.rng.next());
 
-    let x = pipeline.x * Simd::splat(frequency.x);
-    let y = pipeline.y * Simd::splat(frequency.z);
+    let x = pipeline.x * Simd::splat(frequency.x as Float);
+    let y = pipeline.y * Simd::splat(frequency.z as Float);
 
     let mut xs = x.floor();
     let mut ys = y.floor();
@@ -66,21 +69,21 @@ where
     pipeline.next();
 }
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn perlin_3d<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn perlin_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Perlin { frequency } = node.settings else {
+    let NoiseSettings::Perlin { frequency } = *settings else {
         unreachable!()
     };
 
     let seed = Simd::splat(pipeline.rng.next());
 
-    let x = pipeline.x * Simd::splat(frequency.x);
-    let y = pipeline.y * Simd::splat(frequency.y);
-    let z = pipeline.z * Simd::splat(frequency.z);
+    let x = pipeline.x * Simd::splat(frequency.x as Float);
+    let y = pipeline.y * Simd::splat(frequency.y as Float);
+    let z = pipeline.z * Simd::splat(frequency.z as Float);
 
     let mut xs = x.floor();
     let mut ys = y.floor();
@@ -140,8 +143,67 @@ where
     pipeline.next();
 }
 
+/// Samples 4-dimensional perlin noise. The fourth dimension has no frequency of its own, it is
+/// scaled by the frequency along x.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn perlin_4d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const PRIMES: [i32; 4] = [X_PRIME, Y_PRIME, Z_PRIME, W_PRIME];
+
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::Perlin { frequency } = *settings else {
+        unreachable!()
+    };
+
+    let seed = Simd::splat(pipeline.rng.next());
+
+    let point = [
+        pipeline.x * Simd::splat(frequency.x as Float),
+        pipeline.y * Simd::splat(frequency.y as Float),
+        pipeline.z * Simd::splat(frequency.z as Float),
+        pipeline.w * Simd::splat(frequency.x as Float),
+    ];
+
+    let cell = point.map(|coordinate| coordinate.floor());
+    let hashes: [Simd<i32, N>; 4] = std::array::from_fn(|axis| unsafe {
+        cell[axis].to_int_unchecked() * Simd::splat(PRIMES[axis])
+    });
+    let fraction: [Simd<Float, N>; 4] = std::array::from_fn(|axis| point[axis] - cell[axis]);
+
+    // The gradients at the 16 corners of the cell, bit n of the index is the n-th axis
+    let mut corners = [Simd::splat(0.0); 16];
+    for (index, corner) in corners.iter_mut().enumerate() {
+        let mut hash = hashes;
+        let mut offset = fraction;
+        for axis in 0..4 {
+            if index >> axis & 1 == 1 {
+                hash[axis] += Simd::splat(PRIMES[axis]);
+                offset[axis] -= Simd::splat(1.0);
+            }
+        }
+        let [x, y, z, w] = offset;
+        *corner = grad4d_dot(hash4d(seed, hash[0], hash[1], hash[2], hash[3]), x, y, z, w);
+    }
+
+    // Interpolated along each axis in turn, halving the corners each time
+    let mut len = 16;
+    for t in fraction.map(interpolate_quintic) {
+        len /= 2;
+        for i in 0..len {
+            corners[i] = lerp(corners[2 * i], corners[2 * i + 1], t);
+        }
+    }
+
+    let result = Simd::splat(0.82) * corners[0];
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
 #[inline(always)]
-fn lerp<const N: usize>(a: Simd<f32, N>, b: Simd<f32, N>, t: Simd<f32, N>) -> Simd<f32, N>
+fn lerp<const N: usize>(a: Simd<Float, N>, b: Simd<Float, N>, t: Simd<Float, N>) -> Simd<Float, N>
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -149,7 +211,7 @@ where
 }
 
 #[inline(always)]
-fn interpolate_quintic<const N: usize>(v: Simd<f32, N>) -> Simd<f32, N>
+fn interpolate_quintic<const N: usize>(v: Simd<Float, N>) -> Simd<Float, N>
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -2,10 +2,11 @@ use std::simd::{prelude::*, LaneCount, Simd, StdFloat, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn range<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn range<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
@@ -13,14 +14,14 @@ where
     let high_noise = pipeline.results.pop().unwrap();
     let selector_noise = pipeline.results.pop().unwrap();
 
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Range { low, high } = node.settings else {
+    let NoiseSettings::Range { low, high } = *settings else {
         unreachable!()
     };
 
-    let low = Simd::splat(low);
-    let high = Simd::splat(high);
+    let low = Simd::splat(low as Float);
+    let high = Simd::splat(high as Float);
 
     let low_clipped = selector_noise.simd_lt(low);
     let high_clipped = selector_noise.simd_gt(high);
@@ -0,0 +1,818 @@
+use std::ops::Range;
+use std::simd::prelude::*;
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use multiversion::{multiversion, simd_width};
+
+/// Values produced by the `generate_*` functions of [Noise](crate::Noise).
+///
+/// The values are ordered with height as the innermost axis, then depth and then width, i.e.
+/// the value at (x, y, z) is at `x * depth * height + z * height + y`. 1d results have a height
+/// and depth of 1, and 2d results a depth of 1.
+///
+/// The result dereferences to a slice of the values, so it can be indexed directly.
+///
+/// The values are `f32` unless generated as `f64`, see [Float](crate::Float). The methods that
+/// process the values further are only on `f32` results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseResult<T = f32> {
+    /// The generated values
+    pub values: Vec<T>,
+    /// The smallest generated value
+    pub min: T,
+    /// The largest generated value
+    pub max: T,
+    width: usize,
+    height: usize,
+    depth: usize,
+    pub(crate) blocks: Option<BlockRanges>,
+    pub(crate) statistics: Option<Statistics>,
+    pub(crate) gradient: Option<Box<Gradient<T>>>,
+}
+
+/// The partial derivatives of the values of a [NoiseResult], generated by
+/// [Noise::generate_2d_with_derivatives](crate::Noise::generate_2d_with_derivatives) and
+/// [Noise::generate_3d_with_derivatives](crate::Noise::generate_3d_with_derivatives). They are
+/// ordered the same way as the values, per unit of the coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient<T = f32> {
+    /// Derivatives along the first dimension
+    pub x: Vec<T>,
+    /// Derivatives along the vertical dimension, the second dimension of 2d results
+    pub y: Vec<T>,
+    /// Derivatives along the third dimension, empty for 2d results
+    pub z: Vec<T>,
+}
+
+/// The min and max values of each block of a [NoiseResult], produced by
+/// [Noise::generate_3d_blocks](crate::Noise::generate_3d_blocks).
+///
+/// The blocks are positioned and ordered the same way as the values, the block at (x, y, z)
+/// covers the values from `x * size` up to `(x + 1) * size` along the first dimension and so on.
+/// The last blocks are cut off where the size of the result isn't a multiple of the block size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockRanges {
+    size: usize,
+    width: usize,
+    height: usize,
+    depth: usize,
+    // (min, max) of each block
+    ranges: Vec<(f32, f32)>,
+}
+
+/// Mean, variance and histogram of the values of a [NoiseResult], accumulated while the values
+/// are generated by [Noise::generate_2d_statistics](crate::Noise::generate_2d_statistics) and
+/// [Noise::generate_3d_statistics](crate::Noise::generate_3d_statistics).
+///
+/// The histogram splits its range into bins of equal size, values outside the range are counted
+/// in the first or last bin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statistics {
+    range: Range<f32>,
+    count: u64,
+    sum: f64,
+    sum_of_squares: f64,
+    histogram: Vec<u64>,
+}
+
+/// Filter used when resampling a [NoiseResult].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Linear interpolation between the 4 closest values.
+    Bilinear,
+    /// Catmull-Rom interpolation between the 16 closest values. Smoother than bilinear, but the
+    /// values may overshoot the range of the original values slightly.
+    Bicubic,
+}
+
+/// How the values are combined when downsampling a [NoiseResult].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pooling {
+    /// The average of the values
+    Average,
+    /// The largest of the values
+    Max,
+}
+
+impl<T> NoiseResult<T> {
+    pub(crate) fn new(
+        (values, min, max): (Vec<T>, T, T),
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> Self {
+        Self {
+            values,
+            min,
+            max,
+            width,
+            height,
+            depth,
+            blocks: None,
+            statistics: None,
+            gradient: None,
+        }
+    }
+
+    /// Size of the first dimension
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Size of the vertical dimension, the second dimension of 2d results.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of the third dimension
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The min and max values of each block, if the result was generated with blocks.
+    pub fn blocks(&self) -> Option<&BlockRanges> {
+        self.blocks.as_ref()
+    }
+
+    /// Mean, variance and histogram of the values, if the result was generated with statistics.
+    pub fn statistics(&self) -> Option<&Statistics> {
+        self.statistics.as_ref()
+    }
+
+    /// The derivatives of the values, if the result was generated with derivatives.
+    pub fn gradient(&self) -> Option<&Gradient<T>> {
+        self.gradient.as_deref()
+    }
+
+    /// Takes the values out of the result.
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl NoiseResult {
+    /// Resamples a 1d or 2d result to a new size. The corners of the new grid line up with the
+    /// corners of the old one, so a coarse result generated at every n-th coordinate can be
+    /// resampled to approximate the full resolution result.
+    ///
+    /// # Example
+    /// ```
+    /// // Preview at a quarter of the resolution, every 4th coordinate covers the same area.
+    /// let noise = Noise::simplex(0.01 * 4.0);
+    /// let coarse = noise.generate_2d::<f32>(0.0, 0.0, 33, 33);
+    /// let preview = coarse.resample(129, 129, Filter::Bicubic);
+    /// ```
+    pub fn resample(&self, new_width: usize, new_height: usize, filter: Filter) -> NoiseResult {
+        assert!(self.depth == 1, "Only 1d and 2d results can be resampled");
+        assert!(
+            self.width > 0 && self.height > 0,
+            "Can't resample an empty result"
+        );
+
+        let scale = |old: usize, new: usize| {
+            if new > 1 {
+                (old - 1) as f32 / (new - 1) as f32
+            } else {
+                0.0
+            }
+        };
+        let x_scale = scale(self.width, new_width);
+        let y_scale = scale(self.height, new_height);
+
+        let mut values = Vec::with_capacity(new_width * new_height);
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for x in 0..new_width {
+            for y in 0..new_height {
+                let value = self.sample(x as f32 * x_scale, y as f32 * y_scale, filter);
+                min = min.min(value);
+                max = max.max(value);
+                values.push(value);
+            }
+        }
+
+        NoiseResult::new((values, min, max), new_width, new_height, 1)
+    }
+
+    /// Amplifies a coarse 2d heightmap, e.g. a hand drawn world map, to `factor` times the
+    /// resolution. The heightmap is resampled like [NoiseResult::resample] and the `detail`
+    /// noise is added, scaled by how rough the heightmap is around each sample, so mountains
+    /// get rugged and plains stay flat. Scale the detail noise to make it stronger or weaker.
+    ///
+    /// The result is `(width - 1) * factor + 1` wide and the same for the height, with the
+    /// corners on the corners of the heightmap. The detail noise is generated from (x, y) with
+    /// one sample per value of the result.
+    ///
+    /// # Example
+    /// ```
+    /// // Every value of the map covers 16 blocks
+    /// let map = Noise::simplex(0.02).generate_2d::<f32>(0.0, 0.0, 33, 33);
+    /// let detail = Noise::simplex(0.05).fbm(4, 0.5, 2.0).mul(Noise::constant(2.0));
+    /// let terrain = map.amplify(16, &detail, 0.0, 0.0);
+    /// ```
+    pub fn amplify(
+        &self,
+        factor: usize,
+        detail: &crate::Noise,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+    ) -> NoiseResult {
+        assert!(self.depth == 1, "Only 1d and 2d results can be amplified");
+        assert!(factor > 0, "The factor must be 1 or more");
+        assert!(
+            self.width > 0 && self.height > 0,
+            "Can't amplify an empty result"
+        );
+
+        let new_width = (self.width - 1) * factor + 1;
+        let new_height = (self.height - 1) * factor + 1;
+
+        // The roughness is the standard deviation of the 3x3 samples around each sample.
+        let mut roughness = Vec::with_capacity(self.len());
+        for x in 0..self.width as isize {
+            for y in 0..self.height as isize {
+                let mut sum = 0.0;
+                let mut sum_of_squares = 0.0;
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        let value = self.get(x + dx, y + dy);
+                        sum += value;
+                        sum_of_squares += value * value;
+                    }
+                }
+                let mean = sum / 9.0;
+                roughness.push((sum_of_squares / 9.0 - mean * mean).max(0.0).sqrt());
+            }
+        }
+        let roughness = NoiseResult::new((roughness, 0.0, 0.0), self.width, self.height, 1)
+            .resample(new_width, new_height, Filter::Bilinear);
+
+        let mut values = self
+            .resample(new_width, new_height, Filter::Bicubic)
+            .into_vec();
+        let detail = detail.generate_2d::<f32>(x, y, new_width, new_height);
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for ((value, detail), roughness) in
+            values.iter_mut().zip(detail.iter()).zip(roughness.iter())
+        {
+            *value = detail.mul_add(*roughness, *value);
+            min = min.min(*value);
+            max = max.max(*value);
+        }
+
+        NoiseResult::new((values, min, max), new_width, new_height, 1)
+    }
+
+    /// Shrinks the result by combining each `factor`³ block of values into one, or `factor`² for
+    /// 2d results. Blocks at the edges that are cut off combine the values that are there.
+    ///
+    /// # Example
+    /// ```
+    /// // Mip chain of a heightmap
+    /// let mut mips = vec![Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 64, 64)];
+    /// while mips.last().unwrap().width() > 1 {
+    ///     let next = mips.last().unwrap().downsample(2, Pooling::Average);
+    ///     mips.push(next);
+    /// }
+    /// ```
+    pub fn downsample(&self, factor: usize, pooling: Pooling) -> NoiseResult {
+        assert!(factor > 0, "The factor must be 1 or more");
+        unsafe { (downsample())(self, factor, pooling) }
+    }
+
+    /// Reshapes the values so they follow the distribution of the [Reshape](crate::Reshape).
+    /// The min, max and block ranges are reshaped along with them, the statistics and gradient
+    /// no longer describe the values and are removed.
+    ///
+    /// # Example
+    /// ```
+    /// let noise = Noise::simplex(0.01);
+    /// let sample = noise.generate_2d::<f32>(0.0, 0.0, 1024, 1024);
+    /// let normal = Distribution::Normal {
+    ///     mean: 0.0,
+    ///     standard_deviation: 1.0,
+    /// };
+    /// let normal = Reshape::new(&sample, normal);
+    /// let values = noise.generate_2d::<f32>(0.0, 0.0, 64, 64).reshape(&normal);
+    /// ```
+    pub fn reshape(mut self, reshape: &crate::Reshape) -> NoiseResult {
+        reshape.apply(&mut self.values);
+        self.min = reshape.get(self.min);
+        self.max = reshape.get(self.max);
+        if let Some(blocks) = &mut self.blocks {
+            for (min, max) in &mut blocks.ranges {
+                *min = reshape.get(*min);
+                *max = reshape.get(*max);
+            }
+        }
+        self.statistics = None;
+        self.gradient = None;
+        self
+    }
+
+    /// Traces the lines where the values of a 2d result cross the `threshold`, e.g. the
+    /// coastlines at sea level. The points of the lines are positions in the grid, [1.5, 0.0] is
+    /// halfway between the second and third value along the first dimension. Lines that close
+    /// end with their first point, the others start and end at the edges of the grid.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.01).generate_2d::<f32>(0.0, 0.0, 128, 128);
+    /// for coastline in height.contours(0.0) {
+    ///     let closed = coastline.first() == coastline.last();
+    /// }
+    /// ```
+    pub fn contours(&self, threshold: f32) -> Vec<Vec<[f32; 2]>> {
+        assert!(
+            self.depth == 1,
+            "Contours can only be traced in 1d and 2d results"
+        );
+        crate::contour::contours(self, threshold)
+    }
+
+    /// Traces the rivers of a 2d heightmap. Every value drains into its steepest downhill
+    /// neighbour, and where the water of at least `threshold` values has gathered there is a
+    /// river. The rivers are also carved into a copy of the heightmap, up to `depth` deep where
+    /// they have gathered the most water.
+    ///
+    /// Water that reaches a pit without a lower neighbour stops there, so rivers can end inland.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.005).fbm(5, 0.5, 2.0).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let rivers = height.rivers(200, 0.05);
+    /// for river in rivers.lines.iter() {
+    ///     let source = river.first().unwrap();
+    /// }
+    /// let terrain = rivers.carved;
+    /// ```
+    pub fn rivers(&self, threshold: u32, depth: f32) -> crate::Rivers {
+        assert!(
+            self.depth == 1,
+            "Rivers can only be traced in 1d and 2d results"
+        );
+        crate::river::rivers(self, threshold, depth)
+    }
+
+    /// Makes a 1d or 2d result tile seamlessly, for noises that can't be made periodic with
+    /// [Noise::wrap_domain](crate::Noise::wrap_domain). The values within `border` of the edges
+    /// are cross-faded with the values from the other side of the tile, and all the values are
+    /// then matched back to the distribution of the original values, so the result has exactly
+    /// the same values, min and max, only moved around. The statistics are kept, the block
+    /// ranges and gradient are removed.
+    ///
+    /// The border must be less than half the width, and half the height of 2d results.
+    ///
+    /// # Example
+    /// ```
+    /// let tile = Noise::simplex(0.02)
+    ///     .fbm(4, 0.5, 2.0)
+    ///     .generate_2d::<f32>(0.0, 0.0, 256, 256)
+    ///     .make_seamless(32);
+    /// ```
+    pub fn make_seamless(&self, border: usize) -> NoiseResult {
+        assert!(
+            self.depth == 1,
+            "Only 1d and 2d results can be made seamless"
+        );
+        assert!(border > 0, "The border must be 1 or more");
+        assert!(
+            border < self.width / 2 && (self.height == 1 || border < self.height / 2),
+            "The border must be less than half the size of the result"
+        );
+        crate::seamless::make_seamless(self, border)
+    }
+
+    /// Encodes a 2d heightmap as an RGB normal map, 3 bytes per pixel. The pixels are in rows
+    /// along the first dimension, the way images are laid out, so pixel (x, y) starts at byte
+    /// `(y * width + x) * 3`. Red and green hold the normal along the first and second dimension
+    /// and blue points up out of the surface, each mapped from -1..1 to 0..255.
+    ///
+    /// The slope is measured between the neighbouring values, one unit apart, and multiplied by
+    /// `strength` before the normal is computed. Heightmaps with values in blocks have a
+    /// strength of 1, a heightmap in -1..1 needs something like its height in blocks. Results
+    /// generated with [derivatives](crate::Noise::generate_2d_with_derivatives) use the exact
+    /// slope instead.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.02).fbm(4, 0.5, 2.0).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// // Terrain that goes 40 blocks up and down
+    /// let normals = height.normal_map(40.0);
+    /// assert_eq!(normals.len(), 256 * 256 * 3);
+    /// ```
+    pub fn normal_map(&self, strength: f32) -> Vec<u8> {
+        assert!(self.depth == 1, "Only 1d and 2d results have a normal map");
+
+        let encode = |component: f32| ((component * 0.5 + 0.5) * 255.0).round() as u8;
+        // Central differences, and one sided at the edges where the neighbour is clamped.
+        let scale = |before: isize, after: isize, size: usize| {
+            let before = before.max(0);
+            let after = after.min(size as isize - 1);
+            strength / (after - before).max(1) as f32
+        };
+
+        let mut pixels = vec![0; self.len() * 3];
+        for x in 0..self.width as isize {
+            for y in 0..self.height as isize {
+                let (dx, dy) = match &self.gradient {
+                    Some(gradient) => {
+                        let index = x as usize * self.height + y as usize;
+                        (gradient.x[index] * strength, gradient.y[index] * strength)
+                    }
+                    None => (
+                        (self.get(x + 1, y) - self.get(x - 1, y)) * scale(x - 1, x + 1, self.width),
+                        (self.get(x, y + 1) - self.get(x, y - 1))
+                            * scale(y - 1, y + 1, self.height),
+                    ),
+                };
+                let length = dx.mul_add(dx, dy.mul_add(dy, 1.0)).sqrt();
+                let pixel = (y as usize * self.width + x as usize) * 3;
+                pixels[pixel] = encode(-dx / length);
+                pixels[pixel + 1] = encode(-dy / length);
+                pixels[pixel + 2] = encode(1.0 / length);
+            }
+        }
+        pixels
+    }
+
+    /// The radially averaged power spectrum of a 2d result, to check which frequencies a noise
+    /// is made of. Bin `i` is the average power of the frequencies around `i / size` cycles per
+    /// value, where `size` is the shorter side of the result, up to 0.5 in the last bin. Bin 0
+    /// is the mean squared.
+    ///
+    /// The result is faded out towards its edges before it is transformed, so the edges don't
+    /// show up as power in every frequency. The power is scaled so that white noise with a
+    /// variance of 1 has a power of 1 in every bin.
+    ///
+    /// Requires the `fft` feature.
+    ///
+    /// # Example
+    /// ```
+    /// // A single octave has most of its power a little below its frequency, 1/32 is bin 8 of a
+    /// // result 256 values wide.
+    /// let result = Noise::simplex(1.0 / 32.0).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// let spectrum = result.power_spectrum();
+    /// let peak = (1..spectrum.len())
+    ///     .max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b]))
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "fft")]
+    pub fn power_spectrum(&self) -> Vec<f32> {
+        assert!(
+            self.depth == 1,
+            "The power spectrum can only be computed for 1d and 2d results"
+        );
+        crate::spectrum::power_spectrum(self)
+    }
+
+    /// Prints a 1d or 2d result to the terminal as shaded characters, from ' ' at the smallest
+    /// value to '@' at the largest, for a quick look at a noise without writing an image, e.g.
+    /// over ssh. The result is resampled to `width` characters along the first dimension and
+    /// `height` lines along the second. Characters are about twice as tall as they are wide, so
+    /// a height of half the width keeps a square result square.
+    ///
+    /// # Example
+    /// ```
+    /// let height = Noise::simplex(0.02).fbm(4, 0.5, 2.0).generate_2d::<f32>(0.0, 0.0, 256, 256);
+    /// height.print_ascii(64, 32);
+    /// ```
+    pub fn print_ascii(&self, width: usize, height: usize) {
+        const SHADES: &[u8] = b" .:-=+*#%@";
+
+        let mut output = String::new();
+        for line in self.preview(width, height) {
+            output.extend(line.into_iter().map(|shade| {
+                char::from(SHADES[(shade * (SHADES.len() - 1) as f32).round() as usize])
+            }));
+            output.push('\n');
+        }
+        print!("{output}");
+    }
+
+    /// Same as [NoiseResult::print_ascii], but prints blocks in the 24 shades of gray of the
+    /// 256 color ANSI palette, which most terminals support.
+    pub fn print_ansi(&self, width: usize, height: usize) {
+        // The grays of the palette go from 232 to 255
+        const GRAYS: f32 = 23.0;
+
+        let mut output = String::new();
+        for line in self.preview(width, height) {
+            for shade in line {
+                let color = 232 + (shade * GRAYS).round() as u8;
+                output.push_str(&format!("\x1b[38;5;{color}m█"));
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        print!("{output}");
+    }
+
+    // The values resampled to `width` x `height` and mapped from the range of the result to 0..1,
+    // in lines along the first dimension.
+    fn preview(&self, width: usize, height: usize) -> Vec<Vec<f32>> {
+        assert!(self.depth == 1, "Only 1d and 2d results can be printed");
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let resampled = self.resample(width, height, Filter::Bilinear);
+        let range = self.max - self.min;
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let value = resampled[x * height + y];
+                        if range > 0.0 {
+                            ((value - self.min) / range).clamp(0.0, 1.0)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Value at a grid position, clamped to the edges.
+    fn get(&self, x: isize, y: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.values[x * self.height + y]
+    }
+
+    fn sample(&self, x: f32, y: f32, filter: Filter) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        match filter {
+            Filter::Bilinear => {
+                let lerp = |a: f32, b: f32, t: f32| t.mul_add(b - a, a);
+                let row = |x| lerp(self.get(x, y0), self.get(x, y0 + 1), ty);
+                lerp(row(x0), row(x0 + 1), tx)
+            }
+            Filter::Bicubic => {
+                let row = |x| catmull_rom([-1, 0, 1, 2].map(|offset| self.get(x, y0 + offset)), ty);
+                catmull_rom([-1, 0, 1, 2].map(|offset| row(x0 + offset)), tx)
+            }
+        }
+    }
+}
+
+impl BlockRanges {
+    pub(crate) fn new(size: usize, width: usize, height: usize, depth: usize) -> Self {
+        let (width, height, depth) = (
+            width.div_ceil(size),
+            height.div_ceil(size),
+            depth.div_ceil(size),
+        );
+        Self {
+            size,
+            width,
+            height,
+            depth,
+            ranges: vec![(f32::MAX, f32::MIN); width * height * depth],
+        }
+    }
+
+    /// Size of the blocks along every dimension
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of blocks along the first dimension
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of blocks along the vertical dimension
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of blocks along the third dimension
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The (min, max) of the values in the block at (x, y, z), counted in blocks.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> (f32, f32) {
+        self.ranges[(x * self.depth + z) * self.height + y]
+    }
+
+    // Adds consecutive values along the height, starting at the position (x, y, z) of the first
+    // value, to the ranges of the blocks they are in.
+    pub(crate) fn record(&mut self, x: usize, mut y: usize, z: usize, mut values: &[f32]) {
+        let (x, z) = (x / self.size, z / self.size);
+        while !values.is_empty() {
+            let len = (self.size - y % self.size).min(values.len());
+            let (block, rest) = values.split_at(len);
+
+            let index = (x * self.depth + z) * self.height + y / self.size;
+            let (min, max) = &mut self.ranges[index];
+            for &value in block {
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+
+            y += len;
+            values = rest;
+        }
+    }
+
+    // Recomputes the ranges of the blocks that overlap the region from all their values, the
+    // values around the region in those blocks are part of the range too.
+    pub(crate) fn update(
+        &mut self,
+        values: &[f32],
+        [width, height, depth]: [usize; 3],
+        region: &[Range<usize>; 3],
+    ) {
+        let [x_blocks, y_blocks, z_blocks] = region
+            .clone()
+            .map(|range| range.start / self.size..range.end.div_ceil(self.size));
+        for x in x_blocks.clone() {
+            for z in z_blocks.clone() {
+                for y in y_blocks.clone() {
+                    self.ranges[(x * self.depth + z) * self.height + y] = (f32::MAX, f32::MIN);
+                }
+            }
+        }
+
+        let values_in = |blocks: &Range<usize>, size: usize| {
+            blocks.start * self.size..(blocks.end * self.size).min(size)
+        };
+        let (ys, zs) = (values_in(&y_blocks, height), values_in(&z_blocks, depth));
+        for xi in values_in(&x_blocks, width) {
+            for zi in zs.clone() {
+                let column = (xi * depth + zi) * height;
+                self.record(
+                    xi,
+                    ys.start,
+                    zi,
+                    &values[column + ys.start..column + ys.end],
+                );
+            }
+        }
+    }
+}
+
+impl Statistics {
+    pub(crate) fn new(bins: usize, range: Range<f32>) -> Self {
+        assert!(bins > 0, "The histogram must have 1 or more bins");
+        assert!(
+            range.start < range.end,
+            "The start of the histogram range must be less than the end"
+        );
+        Self {
+            range,
+            count: 0,
+            sum: 0.0,
+            sum_of_squares: 0.0,
+            histogram: vec![0; bins],
+        }
+    }
+
+    /// The mean of the values
+    pub fn mean(&self) -> f32 {
+        (self.sum / self.count as f64) as f32
+    }
+
+    /// The variance of the values
+    pub fn variance(&self) -> f32 {
+        let mean = self.sum / self.count as f64;
+        (self.sum_of_squares / self.count as f64 - mean * mean).max(0.0) as f32
+    }
+
+    /// The standard deviation of the values
+    pub fn standard_deviation(&self) -> f32 {
+        self.variance().sqrt()
+    }
+
+    /// The number of values in each bin of the histogram, from the start of the range to the
+    /// end.
+    pub fn histogram(&self) -> &[u64] {
+        &self.histogram
+    }
+
+    /// The range covered by the histogram
+    pub fn range(&self) -> Range<f32> {
+        self.range.clone()
+    }
+
+    // Adds the first `lanes` values of a vector.
+    #[inline(always)]
+    pub(crate) fn record<const N: usize>(&mut self, values: Simd<f32, N>, lanes: usize)
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        let last_bin = (self.histogram.len() - 1) as f32;
+        let scale = self.histogram.len() as f32 / (self.range.end - self.range.start);
+        let bins = ((values - Simd::splat(self.range.start)) * Simd::splat(scale))
+            .simd_clamp(Simd::splat(0.0), Simd::splat(last_bin))
+            .cast::<usize>();
+
+        if lanes == N {
+            let values = values.cast::<f64>();
+            self.sum += values.reduce_sum();
+            self.sum_of_squares += (values * values).reduce_sum();
+        } else {
+            for &value in &values.as_array()[..lanes] {
+                self.sum += value as f64;
+                self.sum_of_squares += value as f64 * value as f64;
+            }
+        }
+        for &bin in &bins.as_array()[..lanes] {
+            self.histogram[bin] += 1;
+        }
+        self.count += lanes as u64;
+    }
+}
+
+// Combines all the rows of a block along the height axis first, where the values are contiguous,
+// then reduces each block of the combined row.
+#[multiversion(targets = "simd", dispatcher = "cached")]
+fn downsample(result: &NoiseResult, factor: usize, pooling: Pooling) -> NoiseResult {
+    const N: usize = simd_width!(f32);
+
+    let (width, height, depth) = (result.width, result.height, result.depth);
+    let new_width = width.div_ceil(factor);
+    let new_height = height.div_ceil(factor);
+    let new_depth = depth.div_ceil(factor);
+
+    let initial = match pooling {
+        Pooling::Average => 0.0,
+        Pooling::Max => f32::MIN,
+    };
+
+    let mut values = Vec::with_capacity(new_width * new_height * new_depth);
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+    let mut row = vec![0.0; height];
+    for block_x in 0..new_width {
+        for block_z in 0..new_depth {
+            row.fill(initial);
+            let mut rows = 0;
+            for x in block_x * factor..((block_x + 1) * factor).min(width) {
+                for z in block_z * factor..((block_z + 1) * factor).min(depth) {
+                    let start = (x * depth + z) * height;
+                    let source = &result.values[start..start + height];
+
+                    let mut row_chunks = row.chunks_exact_mut(N);
+                    let mut source_chunks = source.chunks_exact(N);
+                    for (row, source) in (&mut row_chunks).zip(&mut source_chunks) {
+                        let a = Simd::<f32, N>::from_slice(row);
+                        let b = Simd::from_slice(source);
+                        let combined = match pooling {
+                            Pooling::Average => a + b,
+                            Pooling::Max => a.simd_max(b),
+                        };
+                        combined.copy_to_slice(row);
+                    }
+                    let row_remainder = row_chunks.into_remainder();
+                    for (row, source) in row_remainder.iter_mut().zip(source_chunks.remainder()) {
+                        *row = match pooling {
+                            Pooling::Average => *row + source,
+                            Pooling::Max => row.max(*source),
+                        };
+                    }
+                    rows += 1;
+                }
+            }
+
+            for block in row.chunks(factor) {
+                let value = match pooling {
+                    Pooling::Average => block.iter().sum::<f32>() / (block.len() * rows) as f32,
+                    Pooling::Max => block.iter().fold(f32::MIN, |max, &v| max.max(v)),
+                };
+                min = min.min(value);
+                max = max.max(value);
+                values.push(value);
+            }
+        }
+    }
+
+    NoiseResult::new((values, min, max), new_width, new_height, new_depth)
+}
+
+fn catmull_rom([p0, p1, p2, p3]: [f32; 4], t: f32) -> f32 {
+    let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+    let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c = -0.5 * p0 + 0.5 * p2;
+    ((a * t + b) * t + c) * t + p1
+}
+
+impl<T> std::ops::Deref for NoiseResult<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<T> std::ops::DerefMut for NoiseResult<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.values
+    }
+}
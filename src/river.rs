@@ -0,0 +1,127 @@
+use crate::NoiseResult;
+
+/// Rivers traced over a heightmap by [NoiseResult::rivers].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rivers {
+    /// The rivers as lines of grid positions, the same positions as used by
+    /// [NoiseResult::contours]. Each line flows from its first point to its last. A tributary
+    /// ends with the point where it joins the river it flows into.
+    pub lines: Vec<Vec<[f32; 2]>>,
+    /// The heightmap with the rivers carved into it
+    pub carved: NoiseResult,
+    /// The number of samples that drain through each sample, including itself. Indexed the same
+    /// way as the heightmap.
+    pub accumulation: Vec<u32>,
+}
+
+// The 8 neighbours and the distance to them
+const NEIGHBOURS: [(isize, isize, f32); 8] = [
+    (-1, -1, std::f32::consts::SQRT_2),
+    (-1, 0, 1.0),
+    (-1, 1, std::f32::consts::SQRT_2),
+    (0, -1, 1.0),
+    (0, 1, 1.0),
+    (1, -1, std::f32::consts::SQRT_2),
+    (1, 0, 1.0),
+    (1, 1, std::f32::consts::SQRT_2),
+];
+
+// D8 flow routing. Every sample drains into the neighbour with the steepest slope down from it,
+// samples with no lower neighbour are sinks. The accumulation is passed downstream from the
+// highest sample to the lowest, so every sample has received all of its inflow before it passes
+// it on.
+pub(crate) fn rivers(result: &NoiseResult, threshold: u32, depth: f32) -> Rivers {
+    let (width, height) = (result.width(), result.height());
+
+    let mut downstream = vec![None; result.len()];
+    for x in 0..width {
+        for y in 0..height {
+            let index = x * height + y;
+            let mut steepest = 0.0;
+            for (dx, dy, distance) in NEIGHBOURS {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if nx >= width || ny >= height {
+                    continue;
+                }
+                let neighbour = nx * height + ny;
+                let slope = (result[index] - result[neighbour]) / distance;
+                if slope > steepest {
+                    steepest = slope;
+                    downstream[index] = Some(neighbour);
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..result.len()).collect();
+    order.sort_unstable_by(|a, b| result[*b].total_cmp(&result[*a]));
+    let mut accumulation = vec![1u32; result.len()];
+    for &index in order.iter() {
+        if let Some(next) = downstream[index] {
+            accumulation[next] += accumulation[index];
+        }
+    }
+
+    let is_river = |index: usize| accumulation[index] >= threshold;
+
+    // Rivers start at the river samples that no other river sample drains into.
+    let mut has_source = vec![false; result.len()];
+    for (index, next) in downstream.iter().enumerate() {
+        if let Some(next) = *next {
+            if is_river(index) {
+                has_source[next] = true;
+            }
+        }
+    }
+
+    let position = |index: usize| [(index / height) as f32, (index % height) as f32];
+    let mut visited = vec![false; result.len()];
+    let mut lines = Vec::new();
+    // From the highest sources, so the main stem is traced before the tributaries that join it.
+    for &start in order.iter() {
+        if !is_river(start) || has_source[start] {
+            continue;
+        }
+
+        let mut line = Vec::new();
+        let mut current = Some(start);
+        while let Some(index) = current {
+            line.push(position(index));
+            if visited[index] {
+                break;
+            }
+            visited[index] = true;
+            current = downstream[index];
+        }
+        if line.len() > 1 {
+            lines.push(line);
+        }
+    }
+
+    // Rivers are carved deeper the more they have accumulated, from nothing at their source to
+    // `depth` far downstream.
+    let mut carved = result.clone();
+    carved.blocks = None;
+    carved.statistics = None;
+    carved.gradient = None;
+    carved.min = f32::MAX;
+    carved.max = f32::MIN;
+    for (index, value) in carved.values.iter_mut().enumerate() {
+        if is_river(index) {
+            *value -= depth * (1.0 - threshold as f32 / accumulation[index] as f32);
+        }
+    }
+    for &value in carved.values.iter() {
+        carved.min = carved.min.min(value);
+        carved.max = carved.max.max(value);
+    }
+
+    Rivers {
+        lines,
+        carved,
+        accumulation,
+    }
+}
@@ -0,0 +1,77 @@
+use rand_core::{impls, RngCore, SeedableRng};
+
+use crate::{Noise, Rng};
+
+/// The random number generator noises draw the seeds of their sources from, usable through
+/// [rand_core].
+///
+/// It is seeded with a plain u64, [NoiseRng::seed_from_u64] gives the same sequence as the rng
+/// of a noise with the same seed, see [Noise::rng].
+///
+/// Requires the `rand` feature.
+#[derive(Debug, Clone)]
+pub struct NoiseRng(Rng);
+
+impl RngCore for NoiseRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+impl SeedableRng for NoiseRng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::seed_from_u64(u64::from_le_bytes(seed))
+    }
+
+    // The default implementation scrambles the seed, the noise uses it as it is.
+    fn seed_from_u64(state: u64) -> Self {
+        Self(Rng::new(state))
+    }
+}
+
+impl Noise {
+    /// Set the seed of the random number generator from another random number generator, so
+    /// the noise can be part of the seed hierarchy of a world that already uses `rand`.
+    ///
+    /// Requires the `rand` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rand::SeedableRng;
+    ///
+    /// let mut world = rand::rngs::StdRng::seed_from_u64(42);
+    /// let terrain = Noise::simplex(0.01).seed_from_rng(&mut world);
+    /// let caves = Noise::simplex(0.05).seed_from_rng(&mut world);
+    /// ```
+    pub fn seed_from_rng<R: RngCore + ?Sized>(mut self, rng: &mut R) -> Self {
+        self.seed = rng.next_u64();
+        self
+    }
+
+    /// The random number generator of the noise, seeded with the seed of the noise. Other
+    /// randomness that should follow the noise, e.g. where trees are placed on the terrain, can
+    /// be drawn from it.
+    ///
+    /// Requires the `rand` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rand::Rng;
+    ///
+    /// let terrain = Noise::simplex(0.01).seed(42);
+    /// let tree_count = terrain.rng().random_range(10..20);
+    /// ```
+    pub fn rng(&self) -> NoiseRng {
+        NoiseRng::seed_from_u64(self.seed)
+    }
+}
@@ -0,0 +1,92 @@
+/// A run of values with the same material in a column of a [ColumnRuns]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Run {
+    /// The number of thresholds the values are above
+    pub material: u8,
+    /// The number of values in the run
+    pub length: u32,
+}
+
+/// The columns of a cube of noise encoded as runs of materials, produced by
+/// [Noise::generate_3d_runs](crate::Noise::generate_3d_runs).
+///
+/// A column is the values along the y axis at one (x, z), its runs go from y = 0 upwards. The
+/// columns are in the same order as in a [NoiseResult](crate::NoiseResult), the column at (x, z)
+/// is column `x * depth + z`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnRuns {
+    runs: Vec<Run>,
+    // The index of the first run of each column, and the number of runs at the end
+    starts: Vec<usize>,
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl ColumnRuns {
+    pub(crate) fn new(
+        runs: Vec<Run>,
+        starts: Vec<usize>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> Self {
+        Self {
+            runs,
+            starts,
+            width,
+            height,
+            depth,
+        }
+    }
+
+    /// Size of the first dimension
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Size of the second dimension, the length of the columns
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of the third dimension
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The runs of the column at (x, z)
+    pub fn column(&self, x: usize, z: usize) -> &[Run] {
+        assert!(
+            x < self.width && z < self.depth,
+            "The column is outside of the cube"
+        );
+        let column = x * self.depth + z;
+        &self.runs[self.starts[column]..self.starts[column + 1]]
+    }
+
+    /// The material of the value at (x, y, z)
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        assert!(y < self.height, "The position is outside of the cube");
+        let mut end = 0;
+        for run in self.column(x, z) {
+            end += run.length as usize;
+            if y < end {
+                return run.material;
+            }
+        }
+        unreachable!()
+    }
+
+    /// The runs of all the columns one after the other
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    /// The runs of each column, in the order of the columns
+    pub fn columns(&self) -> impl Iterator<Item = &[Run]> + '_ {
+        self.starts
+            .windows(2)
+            .map(|pair| &self.runs[pair[0]..pair[1]])
+    }
+}
@@ -0,0 +1,105 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// Interpolates the sampled result linearly along the first `AXES` of x, y and z. Axes the
+// result or the noise doesn't have are at the first value.
+#[inline(always)]
+fn sample<const N: usize, const AXES: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Sample {
+        buffer,
+        origin,
+        spacing,
+    } = *settings
+    else {
+        unreachable!()
+    };
+    let result = &pipeline
+        .noise
+        .buffers
+        .iter()
+        .find(|other| other.id == buffer)
+        .unwrap()
+        .result;
+
+    let size = [result.width(), result.height(), result.depth()];
+    // Same order as the values, x is the outermost axis and y the innermost.
+    let strides = [size[1] * size[2], 1, size[1]];
+    let coordinates = [pipeline.x, pipeline.y, pipeline.z];
+
+    // The index of the first of the values around the coordinates, and the weight of the
+    // second value along each axis.
+    let mut index = Simd::<usize, N>::splat(0);
+    let mut weights = [Simd::<Float, N>::splat(0.0); 3];
+    let mut axes = 0;
+    for axis in 0..AXES {
+        if size[axis] < 2 {
+            continue;
+        }
+        let last = (size[axis] - 1) as Float;
+        let position = ((coordinates[axis] - Simd::splat(origin[axis] as Float))
+            * Simd::splat(1.0 / spacing as Float))
+        .simd_clamp(Simd::splat(0.0), Simd::splat(last));
+        let lower = position.floor().simd_min(Simd::splat(last - 1.0));
+        weights[axis] = position - lower;
+        index += lower.cast::<usize>() * Simd::splat(strides[axis]);
+        axes |= 1 << axis;
+    }
+
+    let mut value = Simd::splat(0.0);
+    for corner in 0..8 {
+        if corner & !axes != 0 {
+            continue;
+        }
+        let mut offset = index;
+        let mut weight = Simd::<Float, N>::splat(1.0);
+        for axis in 0..3 {
+            if axes >> axis & 1 == 0 {
+                continue;
+            }
+            if corner >> axis & 1 == 1 {
+                offset += Simd::splat(strides[axis]);
+                weight *= weights[axis];
+            } else {
+                weight *= Simd::splat(1.0) - weights[axis];
+            }
+        }
+        let values = Simd::<f32, N>::gather_or_default(result, offset);
+        value = weight.mul_add(values.cast(), value);
+    }
+
+    pipeline.results.push(value);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sample_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    sample::<N, 1>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sample_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    sample::<N, 2>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sample_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    sample::<N, 3>(pipeline);
+}
@@ -0,0 +1,140 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+use crate::{Float, Noise, NoiseResult};
+
+// The pipelines built for a sampler, stored as `NoisePipeline<N, T>`.
+pub(crate) type SamplerPipelines = Arc<Mutex<Vec<Box<dyn Any + Send>>>>;
+
+thread_local! {
+    // The noise of the sampler generating on this thread, and its pipelines. The pipelines
+    // acquired for that noise are taken from the sampler instead of the cache of the thread.
+    static SAMPLING: RefCell<Option<(*const Noise, SamplerPipelines)>> =
+        const { RefCell::new(None) };
+}
+
+/// A [Noise] whose pipelines are built once and shared by every thread generating it, e.g. the
+/// workers of a world generator that generate chunks of the same noise thousands of times per
+/// second.
+///
+/// Generating a [Noise] looks its pipeline up in the ones kept by the thread, and builds it
+/// again once it has been pushed out by other noises. The sampler keeps its own pipelines
+/// instead, one for each dimension and thread generating at the same time, so after the first
+/// chunks it neither builds nor searches for them.
+///
+/// # Example
+/// ```rust
+/// let sampler = NoiseSampler::new(Noise::simplex(0.01).fbm(4, 0.5, 2.0));
+/// std::thread::scope(|scope| {
+///     for chunk_x in 0..4 {
+///         let sampler = &sampler;
+///         scope.spawn(move || sampler.generate_2d::<f32>(chunk_x as f64 * 32.0, 0.0, 32, 32));
+///     }
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct NoiseSampler {
+    noise: Noise,
+    pipelines: SamplerPipelines,
+}
+
+impl NoiseSampler {
+    pub fn new(noise: Noise) -> Self {
+        Self {
+            noise,
+            pipelines: SamplerPipelines::default(),
+        }
+    }
+
+    /// The noise generated by the sampler.
+    pub fn noise(&self) -> &Noise {
+        &self.noise
+    }
+
+    /// Same as [Noise::generate_1d].
+    pub fn generate_1d<T: Float>(&self, x: impl Into<f64>, width: usize) -> NoiseResult<T> {
+        self.sample(|noise| noise.generate_1d(x, width))
+    }
+
+    /// Same as [Noise::generate_1d_into].
+    pub fn generate_1d_into<T: Float>(&self, x: impl Into<f64>, out: &mut [T]) -> (T, T) {
+        self.sample(|noise| noise.generate_1d_into(x, out))
+    }
+
+    /// Same as [Noise::generate_2d].
+    pub fn generate_2d<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> NoiseResult<T> {
+        self.sample(|noise| noise.generate_2d(x, y, width, height))
+    }
+
+    /// Same as [Noise::generate_2d_into].
+    pub fn generate_2d_into<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+        out: &mut [T],
+    ) -> (T, T) {
+        self.sample(|noise| noise.generate_2d_into(x, y, width, height, out))
+    }
+
+    /// Same as [Noise::generate_3d].
+    pub fn generate_3d<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> NoiseResult<T> {
+        self.sample(|noise| noise.generate_3d(x, y, z, width, height, depth))
+    }
+
+    /// Same as [Noise::generate_3d_into].
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_3d_into<T: Float>(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        z: impl Into<f64>,
+        width: usize,
+        height: usize,
+        depth: usize,
+        out: &mut [T],
+    ) -> (T, T) {
+        self.sample(|noise| noise.generate_3d_into(x, y, z, width, height, depth, out))
+    }
+
+    // Generates the noise with the pipelines of the sampler. They are only used for the noise
+    // of the sampler itself, the address tells it apart from any other noise generated while
+    // it runs.
+    fn sample<R>(&self, generate: impl FnOnce(&Noise) -> R) -> R {
+        // Puts back what was sampling before, also when generating panics
+        struct Restore(Option<(*const Noise, SamplerPipelines)>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SAMPLING.set(self.0.take());
+            }
+        }
+
+        let sampling = Some((&self.noise as *const Noise, self.pipelines.clone()));
+        let _restore = Restore(SAMPLING.replace(sampling));
+        generate(&self.noise)
+    }
+}
+
+// The pipelines of the sampler generating `noise` on this thread, if there is one.
+pub(crate) fn sampler_pipelines(noise: &Noise) -> Option<SamplerPipelines> {
+    SAMPLING.with_borrow(|sampling| {
+        let (sampled, pipelines) = sampling.as_ref()?;
+        std::ptr::eq(*sampled, noise).then(|| pipelines.clone())
+    })
+}
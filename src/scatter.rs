@@ -0,0 +1,256 @@
+//! Blue noise placement of points, e.g. for trees, ores or structures.
+//!
+//! Points are at least a minimum distance apart and otherwise evenly spread, without the clumps
+//! and gaps of uniformly random points. A [Scatter] only depends on its seed and settings, the
+//! points of a region are the same no matter how the world is split into regions, so chunks can
+//! be scattered one at a time and agree on the points along their edges.
+//!
+//! # Example
+//! ```rust
+//! use fmc_noise::{scatter::Scatter, Noise};
+//!
+//! // Trees at least 5 blocks apart, denser where the forest noise is high.
+//! let trees = Scatter::new(5.0)
+//!     .seed(42)
+//!     .density(Noise::simplex(0.01).lerp_values(0.0, 1.0));
+//!
+//! for point in trees.generate_2d(0.0, 0.0, 16, 16) {
+//!     let [x, z] = point.position;
+//!     let variant = point.hash % 3;
+//! }
+//! ```
+
+use crate::{mix, Noise};
+
+/// Settings of a point distribution. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Scatter {
+    seed: u64,
+    radius: f32,
+    candidates: u32,
+    density: Option<Noise>,
+}
+
+/// A point placed by [Scatter::generate_2d].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScatterPoint {
+    /// Position in the same coordinates as the values of [Noise::generate_2d]
+    pub position: [f32; 2],
+    /// Random value unique to the point, e.g. to pick a variant of what is placed there.
+    pub hash: u64,
+}
+
+// Cells within this distance of a cell can have candidates within the radius.
+const REACH: usize = 2;
+// Rounds of placing candidates, see `Scatter::generate_2d`
+const ROUNDS: usize = 3;
+
+// A possible point, the one with the highest hash is placed when two are too close.
+#[derive(Clone, Copy)]
+struct Candidate {
+    position: [f32; 2],
+    hash: u64,
+    state: State,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Undecided,
+    Placed,
+    Removed,
+}
+
+impl Scatter {
+    /// Points that are at least `radius` apart.
+    pub fn new(radius: f32) -> Self {
+        assert!(radius > 0.0, "The radius must be larger than 0");
+        Self {
+            seed: 0,
+            radius,
+            candidates: 4,
+            density: None,
+        }
+    }
+
+    /// Set the seed of the placement.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed as u64;
+        self
+    }
+
+    /// Number of candidate points tried in each cell of the grid the points are placed in. More
+    /// candidates pack the points closer to the radius, at the cost of speed. Defaults to 4.
+    pub fn candidates(mut self, candidates: u32) -> Self {
+        assert!(candidates > 0, "There must be 1 or more candidates");
+        self.candidates = candidates;
+        self
+    }
+
+    /// Thins out the points by the values of a noise. Where the noise is 1 or more the points
+    /// are as dense as the radius allows, where it is 0 or less there are none.
+    pub fn density(mut self, noise: Noise) -> Self {
+        self.density = Some(noise);
+        self
+    }
+
+    /// Places the points in the region from (x, y) to (x + width, y + height).
+    pub fn generate_2d(
+        &self,
+        x: impl Into<f64>,
+        y: impl Into<f64>,
+        width: usize,
+        height: usize,
+    ) -> Vec<ScatterPoint> {
+        let (x, y) = (x.into(), y.into());
+
+        // The cells are small enough that a cell can hold only one point, and candidates further
+        // than `REACH` cells away can't be within the radius.
+        let cell = self.radius as f64 / std::f64::consts::SQRT_2;
+
+        let first_x = (x / cell).floor() as i64;
+        let first_y = (y / cell).floor() as i64;
+        let last_x = ((x + width as f64) / cell).floor() as i64;
+        let last_y = ((y + height as f64) / cell).floor() as i64;
+
+        // Each round can be decided by the candidates `REACH` cells further out than the round
+        // before, so the region needs a margin of candidates around it that covers every round.
+        let margin = 2 * REACH * ROUNDS;
+        let (grid_x, grid_y) = (first_x - margin as i64, first_y - margin as i64);
+        let mut grid = Grid {
+            width: (last_x - first_x) as usize + 1 + 2 * margin,
+            height: (last_y - first_y) as usize + 1 + 2 * margin,
+            candidates: self.candidates as usize,
+            cells: Vec::new(),
+        };
+        for cell_x in grid_x..grid_x + grid.width as i64 {
+            for cell_y in grid_y..grid_y + grid.height as i64 {
+                for index in 0..self.candidates {
+                    let hash = hash(self.seed, cell_x, cell_y, index);
+                    let offset = mix(hash, 0x9e37_79b9_7f4a_7c15);
+                    let position = [
+                        ((cell_x as f64 + unit(offset)) * cell) as f32,
+                        ((cell_y as f64 + unit(offset >> 32)) * cell) as f32,
+                    ];
+                    grid.cells.push(Candidate {
+                        position,
+                        hash,
+                        state: State::Undecided,
+                    });
+                }
+            }
+        }
+
+        if let Some(noise) = &self.density {
+            let origin_x = (grid_x as f64 * cell).floor();
+            let origin_y = (grid_y as f64 * cell).floor();
+            let density_width = (grid.width as f64 * cell).ceil() as usize + 1;
+            let density_height = (grid.height as f64 * cell).ceil() as usize + 1;
+            let density = noise.generate_2d(origin_x, origin_y, density_width, density_height);
+            for candidate in grid.cells.iter_mut() {
+                let [position_x, position_y] = candidate.position;
+                let density_x = ((position_x as f64 - origin_x) as usize).min(density_width - 1);
+                let density_y = ((position_y as f64 - origin_y) as usize).min(density_height - 1);
+                let value = density[density_x * density_height + density_y];
+                let threshold = unit(mix(candidate.hash, 0xc2b2_ae3d_27d4_eb4f)) as f32;
+                if threshold >= value {
+                    candidate.state = State::Removed;
+                }
+            }
+        }
+
+        // The candidates are placed in order of their hash, but instead of going through them
+        // one by one, every candidate with the highest hash of the undecided ones around it is
+        // placed at once and the candidates too close to them are removed. After a few rounds
+        // nearly all are decided, the rest are left out.
+        let radius_squared = self.radius * self.radius;
+        for _ in 0..ROUNDS {
+            let mut placed = Vec::new();
+            for (index, candidate) in grid.cells.iter().enumerate() {
+                if candidate.state == State::Undecided
+                    && !grid.any_within(index, radius_squared, |other| {
+                        other.state == State::Undecided && other.hash > candidate.hash
+                    })
+                {
+                    placed.push(index);
+                }
+            }
+            for &index in placed.iter() {
+                grid.cells[index].state = State::Placed;
+            }
+
+            let mut removed = Vec::new();
+            for (index, candidate) in grid.cells.iter().enumerate() {
+                if candidate.state == State::Undecided
+                    && grid.any_within(index, radius_squared, |other| other.state == State::Placed)
+                {
+                    removed.push(index);
+                }
+            }
+            for &index in removed.iter() {
+                grid.cells[index].state = State::Removed;
+            }
+        }
+
+        grid.cells
+            .iter()
+            .filter(|candidate| {
+                let [position_x, position_y] = candidate.position.map(|p| p as f64);
+                candidate.state == State::Placed
+                    && (x..x + width as f64).contains(&position_x)
+                    && (y..y + height as f64).contains(&position_y)
+            })
+            .map(|candidate| ScatterPoint {
+                position: candidate.position,
+                hash: candidate.hash,
+            })
+            .collect()
+    }
+}
+
+// Candidates of a region and the margin around it.
+struct Grid {
+    width: usize,
+    height: usize,
+    // Candidates per cell
+    candidates: usize,
+    cells: Vec<Candidate>,
+}
+
+impl Grid {
+    // Whether any of the candidates within the radius of the candidate at `index` matches.
+    fn any_within(
+        &self,
+        index: usize,
+        radius_squared: f32,
+        matches: impl Fn(&Candidate) -> bool,
+    ) -> bool {
+        let cell = index / self.candidates;
+        let (cell_x, cell_y) = (cell / self.height, cell % self.height);
+        let [position_x, position_y] = self.cells[index].position;
+
+        for neighbour_x in cell_x.saturating_sub(REACH)..(cell_x + REACH + 1).min(self.width) {
+            for neighbour_y in cell_y.saturating_sub(REACH)..(cell_y + REACH + 1).min(self.height) {
+                let start = (neighbour_x * self.height + neighbour_y) * self.candidates;
+                for other in &self.cells[start..start + self.candidates] {
+                    let dx = other.position[0] - position_x;
+                    let dy = other.position[1] - position_y;
+                    if dx * dx + dy * dy < radius_squared && matches(other) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+fn hash(seed: u64, x: i64, y: i64, index: u32) -> u64 {
+    let hash = mix(seed, x as u64);
+    let hash = mix(hash, y as u64);
+    mix(hash, index as u64)
+}
+
+// 0..1 from the low 24 bits
+fn unit(hash: u64) -> f64 {
+    (hash & 0xff_ffff) as f64 / (1 << 24) as f64
+}
@@ -0,0 +1,101 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings, SdfShape};
+
+// The signed distance from the point to the shape
+#[inline(always)]
+fn distance<const N: usize>(shape: SdfShape, point: [Simd<Float, N>; 3]) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let splat = |value: f32| Simd::splat(value as Float);
+    let length = |[x, y, z]: [Simd<Float, N>; 3]| x.mul_add(x, y.mul_add(y, z * z)).sqrt();
+    let zero = Simd::splat(0.0);
+
+    match shape {
+        SdfShape::Sphere { center, radius } => {
+            let offset = std::array::from_fn(|axis| point[axis] - splat(center[axis]));
+            length(offset) - splat(radius)
+        }
+        // The distance to the box outside of it, and the distance to the closest side as a
+        // negative number inside.
+        SdfShape::Box { center, half_size } => {
+            let q: [Simd<Float, N>; 3] = std::array::from_fn(|axis| {
+                (point[axis] - splat(center[axis])).abs() - splat(half_size[axis])
+            });
+            let outside = length(q.map(|q| q.simd_max(zero)));
+            let inside = q[0].simd_max(q[1]).simd_max(q[2]).simd_min(zero);
+            outside + inside
+        }
+        SdfShape::Plane { normal, offset } => {
+            let normal_length = normal.iter().map(|v| v * v).sum::<f32>().sqrt();
+            let normal = normal.map(|v| splat(v / normal_length));
+            let [x, y, z] = point;
+            x.mul_add(normal[0], y.mul_add(normal[1], z * normal[2])) - splat(offset)
+        }
+        // The distance to the closest point of the segment between the ends
+        SdfShape::Capsule { start, end, radius } => {
+            let segment: [f32; 3] = std::array::from_fn(|axis| end[axis] - start[axis]);
+            let squared_length = segment.iter().map(|v| v * v).sum::<f32>();
+            let to_point: [Simd<Float, N>; 3] =
+                std::array::from_fn(|axis| point[axis] - splat(start[axis]));
+            let t = if squared_length > 0.0 {
+                let dot = to_point[0].mul_add(
+                    splat(segment[0]),
+                    to_point[1].mul_add(splat(segment[1]), to_point[2] * splat(segment[2])),
+                );
+                (dot * splat(1.0 / squared_length)).simd_clamp(zero, Simd::splat(1.0))
+            } else {
+                zero
+            };
+            let offset =
+                std::array::from_fn(|axis| t.mul_add(-splat(segment[axis]), to_point[axis]));
+            length(offset) - splat(radius)
+        }
+    }
+}
+
+#[inline(always)]
+fn sdf<const N: usize>(pipeline: &mut NoisePipeline<N, Float>, point: [Simd<Float, N>; 3])
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Sdf { shape } = *settings else {
+        unreachable!()
+    };
+
+    let result = distance(shape, point);
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sdf_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let zero = Simd::splat(0.0);
+    sdf(pipeline, [pipeline.x, zero, zero]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sdf_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y, it is the z axis of the shapes.
+    sdf(pipeline, [pipeline.x, Simd::splat(0.0), pipeline.y]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn sdf_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    sdf(pipeline, [pipeline.x, pipeline.y, pipeline.z]);
+}
@@ -0,0 +1,63 @@
+use crate::NoiseResult;
+
+// Each axis is cross-faded with a copy of the values moved by half the axis. The copy is
+// continuous across the edges of the tile, where it is used, and its seam is in the middle,
+// where the original values are used. The cross-fade averages unrelated values, which flattens
+// the contrast, so the values are matched back to the original ones by rank.
+pub(crate) fn make_seamless(result: &NoiseResult, border: usize) -> NoiseResult {
+    let (width, height) = (result.width(), result.height());
+    let mut values = result.values.clone();
+
+    // The values of (x, y) are at x * height + y
+    cross_fade(&mut values, 0..height, width, height, border);
+    if height > 1 {
+        cross_fade(
+            &mut values,
+            (0..width).map(|x| x * height),
+            height,
+            1,
+            border,
+        );
+    }
+
+    let mut original = result.values.clone();
+    original.sort_unstable_by(f32::total_cmp);
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by(|&a, &b| values[a].total_cmp(&values[b]));
+    for (index, value) in order.into_iter().zip(original) {
+        values[index] = value;
+    }
+
+    let mut seamless = NoiseResult::new((values, result.min, result.max), width, height, 1);
+    // The values are the same, only moved around.
+    seamless.statistics = result.statistics.clone();
+    seamless
+}
+
+// Cross-fades the lines of `len` values `stride` apart that begin at `starts`.
+fn cross_fade(
+    values: &mut [f32],
+    starts: impl Iterator<Item = usize>,
+    len: usize,
+    stride: usize,
+    border: usize,
+) {
+    // The weight of the original values, 0 at the edges and 1 from `border` values in.
+    let weights: Vec<f32> = (0..len)
+        .map(|i| {
+            let t = (i.min(len - 1 - i) as f32 / border as f32).min(1.0);
+            t * t * (3.0 - 2.0 * t)
+        })
+        .collect();
+
+    let mut line = vec![0.0; len];
+    for start in starts {
+        for (i, value) in line.iter_mut().enumerate() {
+            *value = values[start + i * stride];
+        }
+        for (i, weight) in weights.iter().enumerate() {
+            let moved = line[(i + len / 2) % len];
+            values[start + i * stride] = (line[i] - moved).mul_add(*weight, moved);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn seed_offset<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+
+    let NoiseSettings::SeedOffset { offset } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.rng.skip(offset);
+    pipeline.next();
+}
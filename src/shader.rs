@@ -0,0 +1,675 @@
+use std::fmt::Write;
+
+use crate::{Dimensions, Noise, NoiseSettings, Rng, SdfShape};
+
+/// Shader implementations of the noise kernels used by the functions generated by
+/// [Noise::to_wgsl]. It must be included once in the shader module.
+pub const WGSL_LIBRARY: &str = include_str!("shader/library.wgsl");
+
+/// Shader implementations of the noise kernels used by the functions generated by
+/// [Noise::to_glsl]. It must be included once in the shader, before the generated functions.
+pub const GLSL_LIBRARY: &str = include_str!("shader/library.glsl");
+
+#[cfg(any(feature = "spirv", feature = "gpu"))]
+const COMPUTE_ENTRY_POINTS: &str = include_str!("shader/compute.wgsl");
+
+/// Returned when a noise contains an operation that can't be translated to shader code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedNode {
+    /// Name of the operation
+    pub node: &'static str,
+}
+
+impl std::fmt::Display for UnsupportedNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' can not be translated to shader code", self.node)
+    }
+}
+
+impl std::error::Error for UnsupportedNode {}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Language {
+    Wgsl,
+    Glsl,
+}
+
+impl Language {
+    fn function_header(self, name: &str, dimensions: &Dimensions) -> String {
+        let (suffix, coordinates): (_, &[&str]) = match dimensions {
+            Dimensions::X => ("1d", &["x"]),
+            Dimensions::XY => ("2d", &["x", "y"]),
+            Dimensions::XYZ => ("3d", &["x", "y", "z"]),
+            Dimensions::XYZW => unreachable!(),
+        };
+        match self {
+            Language::Wgsl => {
+                let args = coordinates
+                    .iter()
+                    .map(|c| format!("{c}: f32"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("fn {name}_{suffix}({args}) -> f32 {{\n")
+            }
+            Language::Glsl => {
+                let args = coordinates
+                    .iter()
+                    .map(|c| format!("float {c}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("float {name}_{suffix}({args}) {{\n")
+            }
+        }
+    }
+
+    fn declaration(self, variable: &str, expression: &str) -> String {
+        match self {
+            Language::Wgsl => format!("    let {variable} = {expression};\n"),
+            Language::Glsl => format!("    float {variable} = {expression};\n"),
+        }
+    }
+
+    // Equivalent of Mask::select
+    fn select(self, condition: &str, if_true: &str, if_false: &str) -> String {
+        match self {
+            Language::Wgsl => format!("select({if_false}, {if_true}, {condition})"),
+            Language::Glsl => format!("(({condition}) ? {if_true} : {if_false})"),
+        }
+    }
+
+    fn int(self, value: i32) -> String {
+        match self {
+            Language::Wgsl => format!("bitcast<i32>({:#x}u)", value as u32),
+            Language::Glsl => format!("int({:#x}u)", value as u32),
+        }
+    }
+
+    fn float(self, value: f32) -> String {
+        // Debug formatting is the shortest representation that parses back to the same f32
+        format!("{value:?}")
+    }
+}
+
+/// Translates the noise into one function per dimension, named `{name}_1d`, `{name}_2d` and
+/// `{name}_3d`. They take the coordinates of a sample, the same as the coordinates of the values
+/// produced by the `generate_*` functions.
+pub(crate) fn generate(
+    noise: &Noise,
+    name: &str,
+    language: Language,
+) -> Result<String, UnsupportedNode> {
+    let mut source = String::new();
+    // There are no 4d functions, `Noise::generate_4d` is only done on the cpu.
+    for dimensions in [Dimensions::X, Dimensions::XY, Dimensions::XYZ] {
+        source += &Function::new(noise, language, dimensions).generate(name)?;
+        source += "\n";
+    }
+    Ok(source)
+}
+
+/// WGSL module with the compute entry points `main_1d`, `main_2d` and `main_3d`, see
+/// [Noise::to_spirv] for how they are bound and dispatched.
+#[cfg(any(feature = "spirv", feature = "gpu"))]
+pub(crate) fn compute_module(noise: &Noise) -> Result<String, UnsupportedNode> {
+    Ok(format!(
+        "{}\n{}\n{}",
+        WGSL_LIBRARY,
+        generate(noise, "fmc_noise", Language::Wgsl)?,
+        COMPUTE_ENTRY_POINTS
+    ))
+}
+
+/// Compiles the noise into a SPIR-V module with the entry points `main_1d`, `main_2d` and
+/// `main_3d`. The WGSL generated for the noise goes through naga, so the values are the same as
+/// those produced by [Noise::to_wgsl].
+#[cfg(feature = "spirv")]
+pub(crate) fn spirv(noise: &Noise) -> Result<Vec<u32>, UnsupportedNode> {
+    let source = compute_module(noise)?;
+
+    // The source is entirely generated by us, failing here is a bug in the generator.
+    let module = naga::front::wgsl::parse_str(&source).expect("generated WGSL failed to parse");
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .expect("generated WGSL failed validation");
+
+    Ok(
+        naga::back::spv::write_vec(&module, &info, &naga::back::spv::Options::default(), None)
+            .expect("failed to write SPIR-V"),
+    )
+}
+
+// Equivalent of the wrapping in wrap.rs
+fn wrap(coordinate: &str, period: String) -> String {
+    format!("{coordinate} - floor({coordinate} / {period}) * {period}")
+}
+
+struct Function<'a> {
+    noise: &'a Noise,
+    language: Language,
+    dimensions: Dimensions,
+    // The seeds are handed out in the same order as the cpu pipeline does it.
+    rng: Rng,
+    body: String,
+    // Names of the variables holding the results, mirrors `NoisePipeline::results`.
+    results: Vec<String>,
+    variable_count: usize,
+    // Names of the variables holding the coordinates, mirrors `NoisePipeline::{x, y, z}`.
+    coordinates: [String; 3],
+    // Mirrors `NoisePipeline::saved`, along with the dimensions outside of the sub-noise
+    saved: Vec<([String; 3], Rng, Dimensions)>,
+}
+
+impl<'a> Function<'a> {
+    fn new(noise: &'a Noise, language: Language, dimensions: Dimensions) -> Self {
+        Self {
+            noise,
+            language,
+            dimensions,
+            rng: Rng::new(noise.seed),
+            body: String::new(),
+            results: Vec::new(),
+            variable_count: 0,
+            coordinates: ["x", "y", "z"].map(String::from),
+            saved: Vec::new(),
+        }
+    }
+
+    fn declare(&mut self, expression: String) -> String {
+        let variable = format!("v{}", self.variable_count);
+        self.variable_count += 1;
+        self.body += &self.language.declaration(&variable, &expression);
+        variable
+    }
+
+    fn push(&mut self, expression: String) {
+        let variable = self.declare(expression);
+        self.results.push(variable);
+    }
+
+    fn pop(&mut self) -> String {
+        self.results.pop().unwrap()
+    }
+
+    // Pairs each coordinate with the axis it is in 3d, the second coordinate of 2d noise is the
+    // z axis.
+    fn axes(&self) -> [(usize, Option<usize>); 3] {
+        match self.dimensions {
+            Dimensions::X => [(0, Some(0)), (1, None), (2, None)],
+            Dimensions::XY => [(0, Some(0)), (1, Some(2)), (2, None)],
+            Dimensions::XYZ => [(0, Some(0)), (1, Some(1)), (2, Some(2))],
+            Dimensions::XYZW => unreachable!(),
+        }
+    }
+
+    fn source(&mut self, kernel: &str, frequency: &crate::Frequency) -> String {
+        let seed = self.language.int(self.rng.next());
+        let f = |v| self.language.float(v);
+        let [x, y, z] = &self.coordinates;
+        match self.dimensions {
+            Dimensions::X => format!("fmc_simplex_1d({seed}, {x} * {})", f(frequency.x)),
+            Dimensions::XY => format!(
+                "fmc_{kernel}_2d({seed}, {x} * {}, {y} * {})",
+                f(frequency.x),
+                f(frequency.z)
+            ),
+            Dimensions::XYZ => format!(
+                "fmc_{kernel}_3d({seed}, {x} * {}, {y} * {}, {z} * {})",
+                f(frequency.x),
+                f(frequency.y),
+                f(frequency.z)
+            ),
+            Dimensions::XYZW => unreachable!(),
+        }
+    }
+
+    // Equivalent of `distance` in sdf.rs
+    fn sdf(&mut self, shape: &SdfShape) -> String {
+        let language = self.language;
+        let f = |v| language.float(v);
+        let length =
+            |[x, y, z]: &[String; 3]| format!("sqrt(fma({x}, {x}, fma({y}, {y}, {z} * {z})))");
+
+        // The point in 3d, the axes the noise doesn't have are 0.
+        let mut point = [(); 3].map(|_| String::from("0.0"));
+        for (coordinate, axis) in self.axes() {
+            if let Some(axis) = axis {
+                point[axis] = self.coordinates[coordinate].clone();
+            }
+        }
+        let mut offset_from = |origin: &[f32; 3]| {
+            std::array::from_fn(|axis| {
+                self.declare(format!("{} - {}", point[axis], f(origin[axis])))
+            })
+        };
+
+        match shape {
+            SdfShape::Sphere { center, radius } => {
+                let offset = offset_from(center);
+                format!("{} - {}", length(&offset), f(*radius))
+            }
+            SdfShape::Box { center, half_size } => {
+                let offset: [String; 3] = offset_from(center);
+                let q: [String; 3] = std::array::from_fn(|axis| {
+                    self.declare(format!("abs({}) - {}", offset[axis], f(half_size[axis])))
+                });
+                let outside = length(&q.clone().map(|q| format!("max({q}, 0.0)")));
+                let [qx, qy, qz] = &q;
+                format!("{outside} + min(max(max({qx}, {qy}), {qz}), 0.0)")
+            }
+            SdfShape::Plane { normal, offset } => {
+                let normal_length = normal.iter().map(|v| v * v).sum::<f32>().sqrt();
+                let [nx, ny, nz] = normal.map(|v| f(v / normal_length));
+                let [x, y, z] = &point;
+                format!(
+                    "fma({x}, {nx}, fma({y}, {ny}, {z} * {nz})) - {}",
+                    f(*offset)
+                )
+            }
+            SdfShape::Capsule { start, end, radius } => {
+                let segment: [f32; 3] = std::array::from_fn(|axis| end[axis] - start[axis]);
+                let squared_length = segment.iter().map(|v| v * v).sum::<f32>();
+                let to_point: [String; 3] = offset_from(start);
+                let t = if squared_length > 0.0 {
+                    let [x, y, z] = &to_point;
+                    let [sx, sy, sz] = segment.map(f);
+                    self.declare(format!(
+                        "clamp(fma({x}, {sx}, fma({y}, {sy}, {z} * {sz})) * {}, 0.0, 1.0)",
+                        f(1.0 / squared_length)
+                    ))
+                } else {
+                    String::from("0.0")
+                };
+                let offset = std::array::from_fn(|axis| {
+                    self.declare(format!(
+                        "fma({t}, {}, {})",
+                        f(-segment[axis]),
+                        to_point[axis]
+                    ))
+                });
+                format!("{} - {}", length(&offset), f(*radius))
+            }
+        }
+    }
+
+    fn generate(mut self, name: &str) -> Result<String, UnsupportedNode> {
+        let language = self.language;
+        let f = |v| language.float(v);
+
+        for settings in self.noise.pipeline.iter() {
+            match settings {
+                NoiseSettings::Simplex { frequency } => {
+                    let expression = self.source("simplex", frequency);
+                    self.push(expression);
+                }
+                NoiseSettings::Perlin { frequency } => {
+                    let expression = self.source("perlin", frequency);
+                    self.push(expression);
+                }
+                NoiseSettings::Constant { value } | NoiseSettings::Parameter { value, .. } => {
+                    self.push(f(*value))
+                }
+                NoiseSettings::Colored {
+                    frequency,
+                    exponent,
+                } => {
+                    let seed = language.int(self.rng.next());
+                    let (first_octave_amplitude, ratio) =
+                        NoiseSettings::colored_amplitudes(*exponent);
+                    self.push(format!(
+                        "fmc_colored({seed}, {} * {}, {}, {})",
+                        self.coordinates[0],
+                        f(*frequency),
+                        f(first_octave_amplitude),
+                        f(ratio)
+                    ));
+                }
+                NoiseSettings::Fbm {
+                    octaves,
+                    gain,
+                    first_octave_amplitude,
+                } => {
+                    let mut amplitude = *first_octave_amplitude;
+                    let mut expression = String::from("0.0");
+                    for _ in 0..*octaves {
+                        let noise = self.pop();
+                        write!(expression, " + {noise} * {}", f(amplitude)).unwrap();
+                        amplitude *= gain;
+                    }
+                    self.push(expression);
+                }
+                NoiseSettings::Ridged {
+                    octaves,
+                    gain,
+                    first_octave_amplitude,
+                } => {
+                    let mut amplitude = *first_octave_amplitude;
+                    let mut weight = f(1.0);
+                    let mut expression = String::from("0.0");
+                    for octave in 0..*octaves {
+                        let noise = self.pop();
+                        let ridge = self.declare(format!("{} - abs({noise})", f(1.0)));
+                        let signal = self.declare(format!("{ridge} * {ridge} * {weight}"));
+                        if octave + 1 < *octaves {
+                            weight = self.declare(format!(
+                                "clamp({signal} * {}, {}, {})",
+                                f(2.0),
+                                f(0.0),
+                                f(1.0)
+                            ));
+                        }
+                        write!(expression, " + {signal} * {}", f(amplitude)).unwrap();
+                        amplitude *= gain;
+                    }
+                    self.push(expression);
+                }
+                NoiseSettings::PingPong {
+                    octaves,
+                    gain,
+                    strength,
+                    first_octave_amplitude,
+                } => {
+                    let mut amplitude = *first_octave_amplitude;
+                    let mut expression = String::from("0.0");
+                    for _ in 0..*octaves {
+                        let noise = self.pop();
+                        let t = self.declare(format!("({noise} + {}) * {}", f(1.0), f(*strength)));
+                        let t = self.declare(format!("{t} - floor({t} * {}) * {}", f(0.5), f(2.0)));
+                        let folded = self.declare(format!("{} - abs({t} - {})", f(1.0), f(1.0)));
+                        write!(
+                            expression,
+                            " + fma({folded}, {}, {}) * {}",
+                            f(2.0),
+                            f(-1.0),
+                            f(amplitude)
+                        )
+                        .unwrap();
+                        amplitude *= gain;
+                    }
+                    self.push(expression);
+                }
+                NoiseSettings::Abs => {
+                    let noise = self.pop();
+                    self.push(format!("abs({noise})"));
+                }
+                NoiseSettings::AbsDiff => {
+                    let (left, right) = (self.pop(), self.pop());
+                    self.push(format!("abs({left} - {right})"));
+                }
+                NoiseSettings::Add => {
+                    let (left, right) = (self.pop(), self.pop());
+                    self.push(format!("{left} + {right}"));
+                }
+                NoiseSettings::Sub => {
+                    let (other, noise) = (self.pop(), self.pop());
+                    self.push(format!("{noise} - {other}"));
+                }
+                NoiseSettings::Average { count } => {
+                    let mut sum = self.pop();
+                    for _ in 1..*count {
+                        write!(sum, " + {}", self.pop()).unwrap();
+                    }
+                    self.push(format!("({sum}) * {}", f(1.0 / *count as f32)));
+                }
+                NoiseSettings::Mul => {
+                    let (left, right) = (self.pop(), self.pop());
+                    self.push(format!("{left} * {right}"));
+                }
+                NoiseSettings::Div => {
+                    let (other, noise) = (self.pop(), self.pop());
+                    self.push(format!("{noise} / {other}"));
+                }
+                NoiseSettings::Clamp { min, max } => {
+                    let noise = self.pop();
+                    self.push(format!("min(max({noise}, {}), {})", f(*min), f(*max)));
+                }
+                NoiseSettings::Max => {
+                    let (left, right) = (self.pop(), self.pop());
+                    self.push(format!("max({left}, {right})"));
+                }
+                NoiseSettings::Min => {
+                    let (left, right) = (self.pop(), self.pop());
+                    self.push(format!("min({left}, {right})"));
+                }
+                NoiseSettings::Lerp {
+                    selector_min,
+                    selector_max,
+                } => {
+                    let (low, high, selector) = (self.pop(), self.pop(), self.pop());
+                    let scale = 1.0 / (selector_max - selector_min);
+                    let offset = -selector_min * scale;
+                    self.push(format!(
+                        "fma({high} - {low}, fma({selector}, {}, {}), {low})",
+                        f(scale),
+                        f(offset)
+                    ));
+                }
+                NoiseSettings::LerpValues { low, high } => {
+                    let noise = self.pop();
+                    self.push(format!(
+                        "fma({noise}, {}, {})",
+                        f((high - low) * 0.5),
+                        f((high + low) * 0.5)
+                    ));
+                }
+                NoiseSettings::Range { low, high } => {
+                    let (low_noise, high_noise, selector) = (self.pop(), self.pop(), self.pop());
+                    let interpolation = format!(
+                        "fma({high_noise} - {low_noise}, ({selector} - {}) / {}, {low_noise})",
+                        f(*low),
+                        f(high - low)
+                    );
+                    let high_clipped = language.select(
+                        &format!("{selector} > {}", f(*high)),
+                        &high_noise,
+                        &interpolation,
+                    );
+                    self.push(language.select(
+                        &format!("{selector} < {}", f(*low)),
+                        &low_noise,
+                        &high_clipped,
+                    ));
+                }
+                NoiseSettings::Square => {
+                    let noise = self.pop();
+                    self.push(format!("{noise} * {noise}"));
+                }
+                NoiseSettings::Dither { min, max, levels } => {
+                    let noise = self.pop();
+                    let [x, y, z] = &self.coordinates;
+                    let index = match self.dimensions {
+                        Dimensions::X => format!("fmc_bayer_1d({x})"),
+                        Dimensions::XY => format!("fmc_bayer({x}, {y})"),
+                        Dimensions::XYZ => format!("fmc_bayer({x}, {z})"),
+                        Dimensions::XYZW => unreachable!(),
+                    };
+                    let step = (max - min) / (levels - 1) as f32;
+                    self.push(format!(
+                        "fmc_dither({noise}, {index}, {}, {}, {})",
+                        f(*min),
+                        f(step),
+                        f((levels - 1) as f32)
+                    ));
+                }
+                NoiseSettings::FalloffRadial {
+                    center_x,
+                    center_z,
+                    inner_radius,
+                    outer_radius,
+                } => {
+                    let [x, y, z] = &self.coordinates;
+                    let dx = format!("({x} - {})", f(*center_x));
+                    let planar = |second: &str| {
+                        let dz = format!("({second} - {})", f(*center_z));
+                        format!("sqrt(fma({dx}, {dx}, {dz} * {dz}))")
+                    };
+                    let distance = match self.dimensions {
+                        Dimensions::X => format!("abs{dx}"),
+                        // The second dimension is y in 2d
+                        Dimensions::XY => planar(y),
+                        Dimensions::XYZ => planar(z),
+                        Dimensions::XYZW => unreachable!(),
+                    };
+                    self.push(format!(
+                        "fmc_falloff({distance}, {}, {})",
+                        f(*inner_radius),
+                        f(1.0 / (outer_radius - inner_radius))
+                    ));
+                }
+                NoiseSettings::Sdf { shape } => {
+                    let expression = self.sdf(shape);
+                    self.push(expression);
+                }
+                NoiseSettings::WrapDomain { period, corner } => {
+                    self.saved
+                        .push((self.coordinates.clone(), self.rng.clone(), self.dimensions));
+                    for (coordinate, axis) in self.axes() {
+                        let Some(axis) = axis else {
+                            continue;
+                        };
+                        if period[axis] <= 0.0 {
+                            continue;
+                        }
+                        let mut expression = wrap(&self.coordinates[coordinate], f(period[axis]));
+                        if corner >> axis & 1 == 1 {
+                            write!(expression, " - {}", f(period[axis])).unwrap();
+                        }
+                        self.coordinates[coordinate] = self.declare(expression);
+                    }
+                }
+                NoiseSettings::WrapBlend { period } => {
+                    // Coordinate of each axis, if it exists in this dimension
+                    let mut coordinates = [None; 3];
+                    for (coordinate, axis) in self.axes() {
+                        if let Some(axis) = axis {
+                            coordinates[axis] = Some(coordinate);
+                        }
+                    }
+
+                    let mut fractions = Vec::new();
+                    for axis in 0..3 {
+                        if period[axis] <= 0.0 {
+                            continue;
+                        }
+                        let period = f(period[axis]);
+                        fractions.push(match coordinates[axis] {
+                            Some(coordinate) => {
+                                let wrapped = wrap(&self.coordinates[coordinate], period.clone());
+                                self.declare(format!("({wrapped}) / {period}"))
+                            }
+                            None => String::from("0.0"),
+                        });
+                    }
+
+                    let mut expression = String::from("0.0");
+                    for corner in (0..1usize << fractions.len()).rev() {
+                        let mut weight = String::from("1.0");
+                        for (axis, fraction) in fractions.iter().enumerate() {
+                            if corner >> axis & 1 == 1 {
+                                write!(weight, " * {fraction}").unwrap();
+                            } else {
+                                write!(weight, " * (1.0 - {fraction})").unwrap();
+                            }
+                        }
+                        let noise = self.pop();
+                        expression = format!("fma({noise}, {weight}, {expression})");
+                    }
+                    self.push(expression);
+                }
+                NoiseSettings::OffsetDomain { offset } => {
+                    self.saved
+                        .push((self.coordinates.clone(), self.rng.clone(), self.dimensions));
+                    for (coordinate, axis) in self.axes() {
+                        let Some(axis) = axis else {
+                            continue;
+                        };
+                        if offset[axis] == 0.0 {
+                            continue;
+                        }
+                        let expression =
+                            format!("{} + {}", self.coordinates[coordinate], f(offset[axis]));
+                        self.coordinates[coordinate] = self.declare(expression);
+                    }
+                }
+                NoiseSettings::Warp { amplitude } => {
+                    let (z, y) = (self.pop(), self.pop());
+                    let offsets = [self.pop(), y, z];
+                    self.saved
+                        .push((self.coordinates.clone(), self.rng.clone(), self.dimensions));
+                    for (coordinate, axis) in self.axes() {
+                        let Some(axis) = axis else {
+                            continue;
+                        };
+                        let expression = format!(
+                            "fma({}, {}, {})",
+                            offsets[axis],
+                            f(*amplitude),
+                            self.coordinates[coordinate]
+                        );
+                        self.coordinates[coordinate] = self.declare(expression);
+                    }
+                }
+                NoiseSettings::Slope { step } => {
+                    let mut differences = [String::new(), String::new(), String::new()];
+                    for difference in differences.iter_mut().rev() {
+                        let (before, after) = (self.pop(), self.pop());
+                        *difference = self.declare(format!("{after} - {before}"));
+                    }
+                    let [dx, dy, dz] = differences;
+                    self.push(format!(
+                        "sqrt(fma({dx}, {dx}, fma({dy}, {dy}, {dz} * {dz}))) * {}",
+                        f(0.5 / step)
+                    ));
+                }
+                NoiseSettings::SeedOffset { offset } => self.rng.skip(*offset),
+                NoiseSettings::Sample { .. } => return Err(UnsupportedNode { node: "sample" }),
+                NoiseSettings::Spectral { .. } => return Err(UnsupportedNode { node: "spectral" }),
+                NoiseSettings::Dots { .. } => return Err(UnsupportedNode { node: "dots" }),
+                NoiseSettings::Cellular { .. } => return Err(UnsupportedNode { node: "cellular" }),
+                NoiseSettings::White => return Err(UnsupportedNode { node: "white" }),
+                NoiseSettings::OpenSimplex2 { .. } => {
+                    return Err(UnsupportedNode {
+                        node: "opensimplex2",
+                    })
+                }
+                NoiseSettings::Value { .. } => return Err(UnsupportedNode { node: "value" }),
+                NoiseSettings::ValueCubic { .. } => {
+                    return Err(UnsupportedNode {
+                        node: "value_cubic",
+                    })
+                }
+                NoiseSettings::Interpolate { .. } => {
+                    return Err(UnsupportedNode {
+                        node: "interpolated",
+                    })
+                }
+                NoiseSettings::Extrude2d => {
+                    self.saved
+                        .push((self.coordinates.clone(), self.rng.clone(), self.dimensions));
+                    if self.dimensions == Dimensions::XYZ {
+                        // The second dimension of 2d noise is in y
+                        self.coordinates[1] = self.coordinates[2].clone();
+                        self.dimensions = Dimensions::XY;
+                    }
+                }
+                NoiseSettings::RestoreCoordinates { rewind } => {
+                    let (coordinates, rng, dimensions) = self.saved.pop().unwrap();
+                    self.coordinates = coordinates;
+                    self.dimensions = dimensions;
+                    if *rewind {
+                        self.rng = rng;
+                    }
+                }
+            }
+        }
+
+        let result = self.pop();
+        Ok(format!(
+            "{}{}    return {result};\n}}\n",
+            self.language.function_header(name, &self.dimensions),
+            self.body
+        ))
+    }
+}
@@ -0,0 +1,60 @@
+// Compute entry points wrapped around the functions generated for `Noise::to_spirv`. One
+// invocation produces one value, stored at the same index the `generate_*` functions use.
+// Workgroups can be dispatched along y as well when there are more than the limit along x, the
+// rows of workgroups continue where the previous one ended.
+
+struct FmcNoiseParams {
+    origin: vec3<f32>,
+    size: vec3<u32>,
+}
+
+@group(0) @binding(0) var<uniform> fmc_params: FmcNoiseParams;
+@group(0) @binding(1) var<storage, read_write> fmc_output: array<f32>;
+
+@compute @workgroup_size(64)
+fn main_1d(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let index = id.y * groups.x * 64u + id.x;
+    if index >= fmc_params.size.x {
+        return;
+    }
+
+    fmc_output[index] = fmc_noise_1d(fmc_params.origin.x + f32(index));
+}
+
+@compute @workgroup_size(64)
+fn main_2d(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let index = id.y * groups.x * 64u + id.x;
+    let height = fmc_params.size.y;
+    if index >= fmc_params.size.x * height {
+        return;
+    }
+
+    let x = fmc_params.origin.x + f32(index / height);
+    let y = fmc_params.origin.y + f32(index % height);
+    fmc_output[index] = fmc_noise_2d(x, y);
+}
+
+@compute @workgroup_size(64)
+fn main_3d(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let index = id.y * groups.x * 64u + id.x;
+    let height = fmc_params.size.y;
+    let depth = fmc_params.size.z;
+    if index >= fmc_params.size.x * height * depth {
+        return;
+    }
+
+    // Height is the innermost axis, followed by depth, then width.
+    let x = fmc_params.origin.x + f32(index / (height * depth));
+    let y = fmc_params.origin.y + f32(index % height);
+    let z = fmc_params.origin.z + f32((index / height) % depth);
+    fmc_output[index] = fmc_noise_3d(x, y, z);
+}
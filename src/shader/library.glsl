@@ -0,0 +1,383 @@
+// Shader implementations of the noise kernels, shared by every function generated with
+// `Noise::to_glsl`. Include it once in the shader, requires GLSL 4.00 or later for fma and the
+// float bit casts.
+//
+// Everything here mirrors the SIMD kernels in gradient.rs, perlin.rs, simplex.rs and colored.rs
+// operation for operation, so that the GPU produces the same values as the CPU. The float
+// constants are the f32 values the CPU ends up with, not the exact values they are derived from.
+
+const int FMC_X_PRIME = 501125321;
+const int FMC_Y_PRIME = 1136930381;
+const int FMC_Z_PRIME = 1720413743;
+
+const int FMC_PERM[256] = int[256](
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173, 186,
+    3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206, 59,
+    227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163, 70,
+    221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232, 178,
+    185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162, 241, 81,
+    51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204, 176, 115,
+    121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141, 128, 195,
+    78, 66, 215, 61, 156, 180
+);
+
+float fmc_grad1(int seed, int hash) {
+    int h = (seed ^ hash) & 15;
+    float v = float(h & 7);
+    return (h & 8) == 0 ? 0.0 - v : v;
+}
+
+float fmc_grad2(int hash, float x, float y) {
+    uint bit1 = uint(hash << 31);
+    uint bit2 = uint((hash >> 1) << 31);
+    bool bit4 = ((hash << 29) >> 31) != 0;
+
+    float xs = uintBitsToFloat(floatBitsToUint(x) ^ bit1);
+    float ys = uintBitsToFloat(floatBitsToUint(y) ^ bit2);
+
+    float a = bit4 ? ys : xs;
+    float b = bit4 ? xs : ys;
+
+    // 1 + sqrt(2)
+    return fma(2.4142137, a, b);
+}
+
+float fmc_grad3d_dot(int hash, float x, float y, float z) {
+    int hasha13 = hash & 13;
+
+    float u = hasha13 < 8 ? x : y;
+    float v = hasha13 == 12 ? x : z;
+    v = hasha13 < 2 ? y : v;
+
+    uint h1 = uint(hash << 31);
+    uint h2 = uint((hash & 2) << 30);
+    float a = uintBitsToFloat(floatBitsToUint(u) ^ h1);
+    float b = uintBitsToFloat(floatBitsToUint(v) ^ h2);
+    return a + b;
+}
+
+int fmc_hash2d(int seed, int i, int j) {
+    int hash = (seed ^ i ^ j) * 0x27d4eb2d;
+    return (hash >> 15) ^ hash;
+}
+
+int fmc_hash3d(int seed, int i, int j, int k) {
+    int hash = (seed ^ i ^ j ^ k) * 0x27d4eb2d;
+    return (hash >> 15) ^ hash;
+}
+
+float fmc_lerp(float a, float b, float t) {
+    return fma(t, b - a, a);
+}
+
+float fmc_interpolate_quintic(float v) {
+    return v * v * v * fma(v, fma(v, 6.0, -15.0), 10.0);
+}
+
+// Mirrors falloff.rs, `scale` is 1 / (outer radius - inner radius)
+float fmc_falloff(float distance, float inner, float scale) {
+    float t = clamp((distance - inner) * scale, 0.0, 1.0);
+    return 1.0 - t * t * (3.0 - 2.0 * t);
+}
+
+// Mirrors dither.rs, position of the cell in an 8x8 Bayer matrix
+int fmc_bayer(float x, float y) {
+    int b = int(floor(y));
+    int c = int(floor(x)) ^ b;
+    return ((c & 1) << 5) | ((b & 1) << 4) | ((c & 2) << 2) | ((b & 2) << 1)
+        | ((c & 4) >> 1) | ((b & 4) >> 2);
+}
+
+int fmc_bayer_1d(float x) {
+    int a = int(floor(x));
+    return ((a & 1) << 5) | ((a & 2) << 3) | ((a & 4) << 1);
+}
+
+// `last` is the highest level, levels - 1
+float fmc_dither(float value, int index, float low, float step, float last) {
+    float threshold = (float(index) + 0.5) * (1.0 / 64.0);
+    float level = clamp(floor((value - low) / step + threshold), 0.0, last);
+    return fma(level, step, low);
+}
+
+float fmc_colored_value(int seed, int cell, int octave) {
+    int hash = seed ^ (cell * FMC_X_PRIME) ^ (octave * FMC_Y_PRIME);
+    hash *= hash * hash * 60493;
+    return float(hash) * (1.0 / 2147483648.0);
+}
+// Mirrors colored.rs, `first_amplitude` is the amplitude of the fastest octave and `ratio` the
+// ratio between successive octaves.
+float fmc_colored(int seed, float x, float first_amplitude, float ratio) {
+    float amplitude = first_amplitude;
+    float result = 0.0;
+    float scale = 1.0;
+    for (int octave = 0; octave < 16; octave++) {
+        float position = x * scale;
+        float floor_position = floor(position);
+        float t = position - floor_position;
+        int cell = int(floor_position);
+        float a = fmc_colored_value(seed, cell, octave);
+        float b = fmc_colored_value(seed, cell + 1, octave);
+        result += fmc_lerp(a, b, t) * amplitude;
+        amplitude *= ratio;
+        scale *= 0.5;
+    }
+    return result;
+}
+
+float fmc_simplex_1d(int seed, float x) {
+    float ips = floor(x);
+    int i0 = int(ips);
+    int i1 = (i0 + 1) & 0xff;
+
+    float x0 = x - ips;
+    float x1 = x0 - 1.0;
+
+    i0 = i0 & 0xff;
+    int gi0 = FMC_PERM[i0];
+    int gi1 = FMC_PERM[i1];
+
+    float x20 = x0 * x0;
+    float t0 = 1.0 - x20;
+    float t20 = t0 * t0;
+    float t40 = t20 * t20;
+    float gx0 = fmc_grad1(seed, gi0);
+    float n0 = t40 * gx0 * x0;
+
+    float x21 = x1 * x1;
+    float t1 = 1.0 - x21;
+    float t21 = t1 * t1;
+    float t41 = t21 * t21;
+    float gx1 = fmc_grad1(seed, gi1);
+    float n1 = t41 * gx1 * x1;
+
+    // 256 / (81 * 7)
+    return (n0 + n1) * 0.4514991;
+}
+
+float fmc_simplex_2d(int seed, float x, float y) {
+    // (sqrt(3) - 1) / 2 and (3 - sqrt(3)) / 6
+    const float F2 = 0.3660254;
+    const float G2 = 0.21132487;
+
+    float f = F2 * (x + y);
+    float x0 = floor(x + f);
+    float y0 = floor(y + f);
+
+    int i = int(x0) * FMC_X_PRIME;
+    int j = int(y0) * FMC_Y_PRIME;
+
+    float g = G2 * (x0 + y0);
+    x0 = x - (x0 - g);
+    y0 = y - (y0 - g);
+
+    bool i1 = x0 > y0;
+
+    float x1 = (i1 ? x0 - 1.0 : x0) + G2;
+    float y1 = (i1 ? y0 : y0 - 1.0) + G2;
+
+    // G2 * 2 - 1
+    float x2 = x0 + -0.57735026;
+    float y2 = y0 + -0.57735026;
+
+    float t0 = fma(x0, -x0, fma(y0, -y0, 0.5));
+    float t1 = fma(x1, -x1, fma(y1, -y1, 0.5));
+    float t2 = fma(x2, -x2, fma(y2, -y2, 0.5));
+
+    t0 = max(t0, 0.0);
+    t1 = max(t1, 0.0);
+    t2 = max(t2, 0.0);
+
+    t0 *= t0;
+    t0 *= t0;
+    t1 *= t1;
+    t1 *= t1;
+    t2 *= t2;
+    t2 *= t2;
+
+    float n0 = fmc_grad2(fmc_hash2d(seed, i, j), x0, y0);
+    int j1 = i1 ? j : j + FMC_Y_PRIME;
+    int i1_hash = i1 ? i + FMC_X_PRIME : i;
+    float n1 = fmc_grad2(fmc_hash2d(seed, i1_hash, j1), x1, y1);
+    float n2 = fmc_grad2(fmc_hash2d(seed, i + FMC_X_PRIME, j + FMC_Y_PRIME), x2, y2);
+
+    return 38.283688 * fma(n0, t0, fma(n1, t1, n2 * t2));
+}
+
+float fmc_simplex_3d(int seed, float x, float y, float z) {
+    const float F3 = 0.33333334;
+    const float G3 = 0.5;
+
+    float s = F3 * (x + y + z);
+    x += s;
+    y += s;
+    z += s;
+
+    float x0 = floor(x);
+    float y0 = floor(y);
+    float z0 = floor(z);
+    float xi = x - x0;
+    float yi = y - y0;
+    float zi = z - z0;
+
+    int i = int(x0) * FMC_X_PRIME;
+    int j = int(y0) * FMC_Y_PRIME;
+    int k = int(z0) * FMC_Z_PRIME;
+
+    bool x_ge_y = xi >= yi;
+    bool y_ge_z = yi >= zi;
+    bool x_ge_z = xi >= zi;
+
+    float g = G3 * (xi + yi + zi);
+    x0 = xi - g;
+    y0 = yi - g;
+    z0 = zi - g;
+
+    bool i1 = x_ge_y && x_ge_z;
+    bool j1 = y_ge_z && !x_ge_y;
+    bool k1 = !x_ge_z && !y_ge_z;
+
+    bool i2 = x_ge_y || x_ge_z;
+    bool j2 = !x_ge_y || y_ge_z;
+    bool k2 = x_ge_z && y_ge_z;
+
+    float x1 = (i1 ? x0 - 1.0 : x0) + G3;
+    float y1 = (j1 ? y0 - 1.0 : y0) + G3;
+    float z1 = (k1 ? z0 - 1.0 : z0) + G3;
+    // G3 * 2
+    float x2 = (i2 ? x0 - 1.0 : x0) + 1.0;
+    float y2 = (j2 ? y0 - 1.0 : y0) + 1.0;
+    float z2 = (k2 ? z0 : z0 - 1.0) + 1.0;
+    // G3 * 3 - 1
+    float x3 = x0 + 0.5;
+    float y3 = y0 + 0.5;
+    float z3 = z0 + 0.5;
+
+    float t0 = fma(x0, -x0, fma(y0, -y0, fma(z0, -z0, 0.6)));
+    float t1 = fma(x1, -x1, fma(y1, -y1, fma(z1, -z1, 0.6)));
+    float t2 = fma(x2, -x2, fma(y2, -y2, fma(z2, -z2, 0.6)));
+    float t3 = fma(x3, -x3, fma(y3, -y3, fma(z3, -z3, 0.6)));
+
+    t0 = max(t0, 0.0);
+    t1 = max(t1, 0.0);
+    t2 = max(t2, 0.0);
+    t3 = max(t3, 0.0);
+
+    t0 *= t0;
+    t0 *= t0;
+    t1 *= t1;
+    t1 *= t1;
+    t2 *= t2;
+    t2 *= t2;
+    t3 *= t3;
+    t3 *= t3;
+
+    float n0 = fmc_grad3d_dot(fmc_hash3d(seed, i, j, k), x0, y0, z0);
+    int i1_hash = i1 ? i + FMC_X_PRIME : i;
+    int j1_hash = j1 ? j + FMC_Y_PRIME : j;
+    int k1_hash = k1 ? k + FMC_Z_PRIME : k;
+    float n1 = fmc_grad3d_dot(fmc_hash3d(seed, i1_hash, j1_hash, k1_hash), x1, y1, z1);
+    int i2_hash = i2 ? i + FMC_X_PRIME : i;
+    int j2_hash = j2 ? j + FMC_Y_PRIME : j;
+    int k2_hash = k2 ? k : k + FMC_Z_PRIME;
+    float n2 = fmc_grad3d_dot(fmc_hash3d(seed, i2_hash, j2_hash, k2_hash), x2, y2, z2);
+    int i3 = i + FMC_X_PRIME;
+    int j3 = j + FMC_Y_PRIME;
+    int k3 = k + FMC_Z_PRIME;
+    float n3 = fmc_grad3d_dot(fmc_hash3d(seed, i3, j3, k3), x3, y3, z3);
+
+    return 32.694283 * fma(n0, t0, fma(n1, t1, fma(n2, t2, n3 * t3)));
+}
+
+float fmc_perlin_2d(int seed, float x, float y) {
+    float xs = floor(x);
+    float ys = floor(y);
+
+    int x0 = int(xs) * FMC_X_PRIME;
+    int y0 = int(ys) * FMC_Y_PRIME;
+    int x1 = x0 + FMC_X_PRIME;
+    int y1 = y0 + FMC_Y_PRIME;
+
+    float xf0 = x - xs;
+    float yf0 = y - ys;
+
+    float xf1 = xf0 - 1.0;
+    float yf1 = yf0 - 1.0;
+
+    float u = fmc_interpolate_quintic(xf0);
+    float v = fmc_interpolate_quintic(yf0);
+
+    return 0.579107 * fmc_lerp(
+        fmc_lerp(
+            fmc_grad2(fmc_hash2d(seed, x0, y0), xf0, yf0),
+            fmc_grad2(fmc_hash2d(seed, x1, y0), xf1, yf0),
+            u
+        ),
+        fmc_lerp(
+            fmc_grad2(fmc_hash2d(seed, x0, y1), xf0, yf1),
+            fmc_grad2(fmc_hash2d(seed, x1, y1), xf1, yf1),
+            u
+        ),
+        v
+    );
+}
+
+float fmc_perlin_3d(int seed, float x, float y, float z) {
+    float xs = floor(x);
+    float ys = floor(y);
+    float zs = floor(z);
+
+    int x0 = int(xs) * FMC_X_PRIME;
+    int y0 = int(ys) * FMC_Y_PRIME;
+    int z0 = int(zs) * FMC_Z_PRIME;
+    int x1 = x0 + FMC_X_PRIME;
+    int y1 = y0 + FMC_Y_PRIME;
+    int z1 = z0 + FMC_Z_PRIME;
+
+    float xf0 = x - xs;
+    float yf0 = y - ys;
+    float zf0 = z - zs;
+
+    float xf1 = xf0 - 1.0;
+    float yf1 = yf0 - 1.0;
+    float zf1 = zf0 - 1.0;
+
+    float u = fmc_interpolate_quintic(xf0);
+    float v = fmc_interpolate_quintic(yf0);
+    float w = fmc_interpolate_quintic(zf0);
+
+    return 0.9649214 * fmc_lerp(
+        fmc_lerp(
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y0, z0), xf0, yf0, zf0),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y0, z0), xf1, yf0, zf0),
+                u
+            ),
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y1, z0), xf0, yf1, zf0),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y1, z0), xf1, yf1, zf0),
+                u
+            ),
+            v
+        ),
+        fmc_lerp(
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y0, z1), xf0, yf0, zf1),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y0, z1), xf1, yf0, zf1),
+                u
+            ),
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y1, z1), xf0, yf1, zf1),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y1, z1), xf1, yf1, zf1),
+                u
+            ),
+            v
+        ),
+        w
+    );
+}
@@ -0,0 +1,382 @@
+// Shader implementations of the noise kernels, shared by every function generated with
+// `Noise::to_wgsl`. Include it once in the shader module.
+//
+// Everything here mirrors the SIMD kernels in gradient.rs, perlin.rs, simplex.rs and colored.rs
+// operation for operation, so that the GPU produces the same values as the CPU. The float
+// constants are the f32 values the CPU ends up with, not the exact values they are derived from.
+
+const FMC_X_PRIME: i32 = 501125321;
+const FMC_Y_PRIME: i32 = 1136930381;
+const FMC_Z_PRIME: i32 = 1720413743;
+
+var<private> FMC_PERM: array<i32, 256> = array<i32, 256>(
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173, 186,
+    3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206, 59,
+    227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163, 70,
+    221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232, 178,
+    185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162, 241, 81,
+    51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204, 176, 115,
+    121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141, 128, 195,
+    78, 66, 215, 61, 156, 180,
+);
+
+fn fmc_grad1(seed: i32, hash: i32) -> f32 {
+    let h = (seed ^ hash) & 15;
+    let v = f32(h & 7);
+    return select(v, 0.0 - v, (h & 8) == 0);
+}
+
+fn fmc_grad2(hash: i32, x: f32, y: f32) -> f32 {
+    let bit1 = bitcast<u32>(hash << 31u);
+    let bit2 = bitcast<u32>((hash >> 1u) << 31u);
+    let bit4 = ((hash << 29u) >> 31u) != 0;
+
+    let xs = bitcast<f32>(bitcast<u32>(x) ^ bit1);
+    let ys = bitcast<f32>(bitcast<u32>(y) ^ bit2);
+
+    let a = select(xs, ys, bit4);
+    let b = select(ys, xs, bit4);
+
+    // 1 + sqrt(2)
+    return fma(2.4142137, a, b);
+}
+
+fn fmc_grad3d_dot(hash: i32, x: f32, y: f32, z: f32) -> f32 {
+    let hasha13 = hash & 13;
+
+    let u = select(y, x, hasha13 < 8);
+    var v = select(z, x, hasha13 == 12);
+    v = select(v, y, hasha13 < 2);
+
+    let h1 = bitcast<u32>(hash << 31u);
+    let h2 = bitcast<u32>((hash & 2) << 30u);
+    let a = bitcast<f32>(bitcast<u32>(u) ^ h1);
+    let b = bitcast<f32>(bitcast<u32>(v) ^ h2);
+    return a + b;
+}
+
+fn fmc_hash2d(seed: i32, i: i32, j: i32) -> i32 {
+    let hash = (seed ^ i ^ j) * 0x27d4eb2d;
+    return (hash >> 15u) ^ hash;
+}
+
+fn fmc_hash3d(seed: i32, i: i32, j: i32, k: i32) -> i32 {
+    let hash = (seed ^ i ^ j ^ k) * 0x27d4eb2d;
+    return (hash >> 15u) ^ hash;
+}
+
+fn fmc_lerp(a: f32, b: f32, t: f32) -> f32 {
+    return fma(t, b - a, a);
+}
+
+fn fmc_interpolate_quintic(v: f32) -> f32 {
+    return v * v * v * fma(v, fma(v, 6.0, -15.0), 10.0);
+}
+
+// Mirrors falloff.rs, `scale` is 1 / (outer radius - inner radius)
+fn fmc_falloff(distance: f32, inner: f32, scale: f32) -> f32 {
+    let t = clamp((distance - inner) * scale, 0.0, 1.0);
+    return 1.0 - t * t * (3.0 - 2.0 * t);
+}
+
+// Mirrors dither.rs, position of the cell in an 8x8 Bayer matrix
+fn fmc_bayer(x: f32, y: f32) -> i32 {
+    let b = i32(floor(y));
+    let c = i32(floor(x)) ^ b;
+    return ((c & 1) << 5u) | ((b & 1) << 4u) | ((c & 2) << 2u) | ((b & 2) << 1u)
+        | ((c & 4) >> 1u) | ((b & 4) >> 2u);
+}
+
+fn fmc_bayer_1d(x: f32) -> i32 {
+    let a = i32(floor(x));
+    return ((a & 1) << 5u) | ((a & 2) << 3u) | ((a & 4) << 1u);
+}
+
+// `last` is the highest level, levels - 1
+fn fmc_dither(value: f32, index: i32, low: f32, step: f32, last: f32) -> f32 {
+    let threshold = (f32(index) + 0.5) * (1.0 / 64.0);
+    let level = clamp(floor((value - low) / step + threshold), 0.0, last);
+    return fma(level, step, low);
+}
+
+fn fmc_colored_value(seed: i32, cell: i32, octave: i32) -> f32 {
+    var hash = seed ^ (cell * FMC_X_PRIME) ^ (octave * FMC_Y_PRIME);
+    hash *= hash * hash * 60493;
+    return f32(hash) * (1.0 / 2147483648.0);
+}
+// Mirrors colored.rs, `first_amplitude` is the amplitude of the fastest octave and `ratio` the
+// ratio between successive octaves.
+fn fmc_colored(seed: i32, x: f32, first_amplitude: f32, ratio: f32) -> f32 {
+    var amplitude = first_amplitude;
+    var result = 0.0;
+    var scale = 1.0;
+    for (var octave = 0; octave < 16; octave++) {
+        let position = x * scale;
+        let floor_position = floor(position);
+        let t = position - floor_position;
+        let cell = i32(floor_position);
+        let a = fmc_colored_value(seed, cell, octave);
+        let b = fmc_colored_value(seed, cell + 1, octave);
+        result += fmc_lerp(a, b, t) * amplitude;
+        amplitude *= ratio;
+        scale *= 0.5;
+    }
+    return result;
+}
+
+fn fmc_simplex_1d(seed: i32, x: f32) -> f32 {
+    let ips = floor(x);
+    var i0 = i32(ips);
+    let i1 = (i0 + 1) & 0xff;
+
+    let x0 = x - ips;
+    let x1 = x0 - 1.0;
+
+    i0 = i0 & 0xff;
+    let gi0 = FMC_PERM[i0];
+    let gi1 = FMC_PERM[i1];
+
+    let x20 = x0 * x0;
+    let t0 = 1.0 - x20;
+    let t20 = t0 * t0;
+    let t40 = t20 * t20;
+    let gx0 = fmc_grad1(seed, gi0);
+    let n0 = t40 * gx0 * x0;
+
+    let x21 = x1 * x1;
+    let t1 = 1.0 - x21;
+    let t21 = t1 * t1;
+    let t41 = t21 * t21;
+    let gx1 = fmc_grad1(seed, gi1);
+    let n1 = t41 * gx1 * x1;
+
+    // 256 / (81 * 7)
+    return (n0 + n1) * 0.4514991;
+}
+
+fn fmc_simplex_2d(seed: i32, x: f32, y: f32) -> f32 {
+    // (sqrt(3) - 1) / 2 and (3 - sqrt(3)) / 6
+    let F2 = 0.3660254;
+    let G2 = 0.21132487;
+
+    let f = F2 * (x + y);
+    var x0 = floor(x + f);
+    var y0 = floor(y + f);
+
+    let i = i32(x0) * FMC_X_PRIME;
+    let j = i32(y0) * FMC_Y_PRIME;
+
+    let g = G2 * (x0 + y0);
+    x0 = x - (x0 - g);
+    y0 = y - (y0 - g);
+
+    let i1 = x0 > y0;
+
+    let x1 = select(x0, x0 - 1.0, i1) + G2;
+    let y1 = select(y0 - 1.0, y0, i1) + G2;
+
+    // G2 * 2 - 1
+    let x2 = x0 + -0.57735026;
+    let y2 = y0 + -0.57735026;
+
+    var t0 = fma(x0, -x0, fma(y0, -y0, 0.5));
+    var t1 = fma(x1, -x1, fma(y1, -y1, 0.5));
+    var t2 = fma(x2, -x2, fma(y2, -y2, 0.5));
+
+    t0 = max(t0, 0.0);
+    t1 = max(t1, 0.0);
+    t2 = max(t2, 0.0);
+
+    t0 *= t0;
+    t0 *= t0;
+    t1 *= t1;
+    t1 *= t1;
+    t2 *= t2;
+    t2 *= t2;
+
+    let n0 = fmc_grad2(fmc_hash2d(seed, i, j), x0, y0);
+    let j1 = select(j + FMC_Y_PRIME, j, i1);
+    let i1_hash = select(i, i + FMC_X_PRIME, i1);
+    let n1 = fmc_grad2(fmc_hash2d(seed, i1_hash, j1), x1, y1);
+    let n2 = fmc_grad2(fmc_hash2d(seed, i + FMC_X_PRIME, j + FMC_Y_PRIME), x2, y2);
+
+    return 38.283688 * fma(n0, t0, fma(n1, t1, n2 * t2));
+}
+
+fn fmc_simplex_3d(seed: i32, x_in: f32, y_in: f32, z_in: f32) -> f32 {
+    let F3 = 0.33333334;
+    let G3 = 0.5;
+
+    let s = F3 * (x_in + y_in + z_in);
+    let x = x_in + s;
+    let y = y_in + s;
+    let z = z_in + s;
+
+    var x0 = floor(x);
+    var y0 = floor(y);
+    var z0 = floor(z);
+    let xi = x - x0;
+    let yi = y - y0;
+    let zi = z - z0;
+
+    let i = i32(x0) * FMC_X_PRIME;
+    let j = i32(y0) * FMC_Y_PRIME;
+    let k = i32(z0) * FMC_Z_PRIME;
+
+    let x_ge_y = xi >= yi;
+    let y_ge_z = yi >= zi;
+    let x_ge_z = xi >= zi;
+
+    let g = G3 * (xi + yi + zi);
+    x0 = xi - g;
+    y0 = yi - g;
+    z0 = zi - g;
+
+    let i1 = x_ge_y && x_ge_z;
+    let j1 = y_ge_z && !x_ge_y;
+    let k1 = !x_ge_z && !y_ge_z;
+
+    let i2 = x_ge_y || x_ge_z;
+    let j2 = !x_ge_y || y_ge_z;
+    let k2 = x_ge_z && y_ge_z;
+
+    let x1 = select(x0, x0 - 1.0, i1) + G3;
+    let y1 = select(y0, y0 - 1.0, j1) + G3;
+    let z1 = select(z0, z0 - 1.0, k1) + G3;
+    // G3 * 2
+    let x2 = select(x0, x0 - 1.0, i2) + 1.0;
+    let y2 = select(y0, y0 - 1.0, j2) + 1.0;
+    let z2 = select(z0 - 1.0, z0, k2) + 1.0;
+    // G3 * 3 - 1
+    let x3 = x0 + 0.5;
+    let y3 = y0 + 0.5;
+    let z3 = z0 + 0.5;
+
+    var t0 = fma(x0, -x0, fma(y0, -y0, fma(z0, -z0, 0.6)));
+    var t1 = fma(x1, -x1, fma(y1, -y1, fma(z1, -z1, 0.6)));
+    var t2 = fma(x2, -x2, fma(y2, -y2, fma(z2, -z2, 0.6)));
+    var t3 = fma(x3, -x3, fma(y3, -y3, fma(z3, -z3, 0.6)));
+
+    t0 = max(t0, 0.0);
+    t1 = max(t1, 0.0);
+    t2 = max(t2, 0.0);
+    t3 = max(t3, 0.0);
+
+    t0 *= t0;
+    t0 *= t0;
+    t1 *= t1;
+    t1 *= t1;
+    t2 *= t2;
+    t2 *= t2;
+    t3 *= t3;
+    t3 *= t3;
+
+    let n0 = fmc_grad3d_dot(fmc_hash3d(seed, i, j, k), x0, y0, z0);
+    let i1_hash = select(i, i + FMC_X_PRIME, i1);
+    let j1_hash = select(j, j + FMC_Y_PRIME, j1);
+    let k1_hash = select(k, k + FMC_Z_PRIME, k1);
+    let n1 = fmc_grad3d_dot(fmc_hash3d(seed, i1_hash, j1_hash, k1_hash), x1, y1, z1);
+    let i2_hash = select(i, i + FMC_X_PRIME, i2);
+    let j2_hash = select(j, j + FMC_Y_PRIME, j2);
+    let k2_hash = select(k + FMC_Z_PRIME, k, k2);
+    let n2 = fmc_grad3d_dot(fmc_hash3d(seed, i2_hash, j2_hash, k2_hash), x2, y2, z2);
+    let i3 = i + FMC_X_PRIME;
+    let j3 = j + FMC_Y_PRIME;
+    let k3 = k + FMC_Z_PRIME;
+    let n3 = fmc_grad3d_dot(fmc_hash3d(seed, i3, j3, k3), x3, y3, z3);
+
+    return 32.694283 * fma(n0, t0, fma(n1, t1, fma(n2, t2, n3 * t3)));
+}
+
+fn fmc_perlin_2d(seed: i32, x: f32, y: f32) -> f32 {
+    let xs = floor(x);
+    let ys = floor(y);
+
+    let x0 = i32(xs) * FMC_X_PRIME;
+    let y0 = i32(ys) * FMC_Y_PRIME;
+    let x1 = x0 + FMC_X_PRIME;
+    let y1 = y0 + FMC_Y_PRIME;
+
+    let xf0 = x - xs;
+    let yf0 = y - ys;
+
+    let xf1 = xf0 - 1.0;
+    let yf1 = yf0 - 1.0;
+
+    let u = fmc_interpolate_quintic(xf0);
+    let v = fmc_interpolate_quintic(yf0);
+
+    return 0.579107 * fmc_lerp(
+        fmc_lerp(
+            fmc_grad2(fmc_hash2d(seed, x0, y0), xf0, yf0),
+            fmc_grad2(fmc_hash2d(seed, x1, y0), xf1, yf0),
+            u,
+        ),
+        fmc_lerp(
+            fmc_grad2(fmc_hash2d(seed, x0, y1), xf0, yf1),
+            fmc_grad2(fmc_hash2d(seed, x1, y1), xf1, yf1),
+            u,
+        ),
+        v,
+    );
+}
+
+fn fmc_perlin_3d(seed: i32, x: f32, y: f32, z: f32) -> f32 {
+    let xs = floor(x);
+    let ys = floor(y);
+    let zs = floor(z);
+
+    let x0 = i32(xs) * FMC_X_PRIME;
+    let y0 = i32(ys) * FMC_Y_PRIME;
+    let z0 = i32(zs) * FMC_Z_PRIME;
+    let x1 = x0 + FMC_X_PRIME;
+    let y1 = y0 + FMC_Y_PRIME;
+    let z1 = z0 + FMC_Z_PRIME;
+
+    let xf0 = x - xs;
+    let yf0 = y - ys;
+    let zf0 = z - zs;
+
+    let xf1 = xf0 - 1.0;
+    let yf1 = yf0 - 1.0;
+    let zf1 = zf0 - 1.0;
+
+    let u = fmc_interpolate_quintic(xf0);
+    let v = fmc_interpolate_quintic(yf0);
+    let w = fmc_interpolate_quintic(zf0);
+
+    return 0.9649214 * fmc_lerp(
+        fmc_lerp(
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y0, z0), xf0, yf0, zf0),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y0, z0), xf1, yf0, zf0),
+                u,
+            ),
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y1, z0), xf0, yf1, zf0),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y1, z0), xf1, yf1, zf0),
+                u,
+            ),
+            v,
+        ),
+        fmc_lerp(
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y0, z1), xf0, yf0, zf1),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y0, z1), xf1, yf0, zf1),
+                u,
+            ),
+            fmc_lerp(
+                fmc_grad3d_dot(fmc_hash3d(seed, x0, y1, z1), xf0, yf1, zf1),
+                fmc_grad3d_dot(fmc_hash3d(seed, x1, y1, z1), xf1, yf1, zf1),
+                u,
+            ),
+            v,
+        ),
+        w,
+    );
+}
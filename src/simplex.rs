@@ -3,15 +3,23 @@ use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
 
 use multiversion::multiversion;
 
-use crate::gradient::grad3d_dot;
-use crate::gradient::hash2d;
-use crate::gradient::hash3d;
-use crate::gradient::{grad1, grad2};
+use super::gradient::grad3d_dot;
+use super::gradient::hash2d;
+use super::gradient::hash3d;
+use super::gradient::{grad1, grad2};
+use super::gradient::{grad4d_dot, hash4d};
+use super::Float;
 use crate::{NoisePipeline, NoiseSettings};
 
 pub const X_PRIME: i32 = 501125321;
 pub const Y_PRIME: i32 = 1136930381;
 pub const Z_PRIME: i32 = 1720413743;
+pub const W_PRIME: i32 = 1066037191;
+
+const SQRT3: Float = 1.7320508075688772935274463415059;
+// Skew and unskew factors of the 2d simplex lattice
+pub const F2: Float = 0.5 * (SQRT3 - 1.0);
+pub const G2: Float = (3.0 - SQRT3) / 6.0;
 
 const PERM: [i32; 512] = [
     151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
@@ -47,18 +55,22 @@ const PERM: [i32; 512] = [
 ///
 /// Produces a value -1 ≤ n ≤ 1.
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn simplex_1d<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn simplex_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Simplex { frequency } = node.settings else {
+    // Perlin and OpenSimplex2 noise are the same as simplex noise in 1d
+    let (NoiseSettings::Simplex { frequency }
+    | NoiseSettings::Perlin { frequency }
+    | NoiseSettings::OpenSimplex2 { frequency }) = *settings
+    else {
         unreachable!()
     };
 
     let seed = Simd::splat(pipeline.rng.next());
-    let freq = Simd::splat(frequency.x);
+    let freq = Simd::splat(frequency.x as Float);
     let x = pipeline.x * freq;
 
     // Gradients are selected deterministically based on the whole part of `x`
@@ -105,7 +117,7 @@ where
     // for 0 ≤ x0 < 1. This can be done by root-finding on the derivative, obtaining 81 / 256 when
     // x0 = 0.5, which we finally multiply by the maximum gradient to get the maximum value,
     // allowing us to scale into [-1, 1]
-    const SCALE: f32 = 256.0 / (81.0 * 7.0);
+    const SCALE: Float = 256.0 / (81.0 * 7.0);
 
     let value = (n0 + n1) * Simd::splat(SCALE);
     //let derivative = ((t20 * t0 * gx0 * x20 + t21 * t1 * gx1 * x21) * Simd::splat(-8.0)
@@ -120,23 +132,19 @@ where
 ///
 /// Produces a value -1 ≤ n ≤ 1.
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn simplex_2d<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn simplex_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    const SQRT3: f32 = 1.7320508075688772935274463415059;
-    const F2: f32 = 0.5 * (SQRT3 - 1.0);
-    const G2: f32 = (3.0 - SQRT3) / 6.0;
-
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Simplex { frequency } = node.settings else {
+    let NoiseSettings::Simplex { frequency } = *settings else {
         unreachable!()
     };
 
     let seed = Simd::splat(pipeline.rng.next());
-    let x = pipeline.x * Simd::splat(frequency.x);
-    let y = pipeline.y * Simd::splat(frequency.z);
+    let x = pipeline.x * Simd::splat(frequency.x as Float);
+    let y = pipeline.y * Simd::splat(frequency.z as Float);
 
     let f = Simd::splat(F2) * (x + y);
     let mut x0 = (x + f).floor();
@@ -187,24 +195,24 @@ where
 }
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn simplex_3d<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn simplex_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    const F3: f32 = 1.0 / 3.0;
-    const G3: f32 = 1.0 / 2.0;
+    const F3: Float = 1.0 / 3.0;
+    const G3: Float = 1.0 / 2.0;
 
-    let node = pipeline.current_node();
+    let settings = pipeline.current_settings();
 
-    let NoiseSettings::Simplex { frequency } = node.settings else {
+    let NoiseSettings::Simplex { frequency } = *settings else {
         unreachable!()
     };
 
     let seed = Simd::splat(pipeline.rng.next());
 
-    let mut x = pipeline.x * Simd::splat(frequency.x);
-    let mut y = pipeline.y * Simd::splat(frequency.y);
-    let mut z = pipeline.z * Simd::splat(frequency.z);
+    let mut x = pipeline.x * Simd::splat(frequency.x as Float);
+    let mut y = pipeline.y * Simd::splat(frequency.y as Float);
+    let mut z = pipeline.z * Simd::splat(frequency.z as Float);
 
     let s = Simd::splat(F3) * (x + y + z);
     x += s;
@@ -288,3 +296,90 @@ where
     pipeline.results.push(result);
     pipeline.next();
 }
+
+/// Samples 4-dimensional simplex noise, also used for OpenSimplex2 noise in 4d. The fourth
+/// dimension has no frequency of its own, it is scaled by the frequency along x.
+///
+/// Produces a value -1 ≤ n ≤ 1.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn simplex_4d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const SQRT5: Float = 2.2360679774997896964091736687313;
+    const F4: Float = (SQRT5 - 1.0) / 4.0;
+    const G4: Float = (5.0 - SQRT5) / 20.0;
+    const PRIMES: [i32; 4] = [X_PRIME, Y_PRIME, Z_PRIME, W_PRIME];
+
+    let settings = pipeline.current_settings();
+
+    let (NoiseSettings::Simplex { frequency } | NoiseSettings::OpenSimplex2 { frequency }) =
+        *settings
+    else {
+        unreachable!()
+    };
+
+    let seed = Simd::splat(pipeline.rng.next());
+
+    let point = [
+        pipeline.x * Simd::splat(frequency.x as Float),
+        pipeline.y * Simd::splat(frequency.y as Float),
+        pipeline.z * Simd::splat(frequency.z as Float),
+        pipeline.w * Simd::splat(frequency.x as Float),
+    ];
+
+    let s = Simd::splat(F4) * (point[0] + point[1] + point[2] + point[3]);
+    let skewed = point.map(|coordinate| coordinate + s);
+    let cell = skewed.map(|coordinate| coordinate.floor());
+    let hashes: [Simd<i32, N>; 4] = std::array::from_fn(|axis| unsafe {
+        cell[axis].to_int_unchecked() * Simd::splat(PRIMES[axis])
+    });
+
+    let g = Simd::splat(G4) * (cell[0] + cell[1] + cell[2] + cell[3]);
+    let origin: [Simd<Float, N>; 4] = std::array::from_fn(|axis| point[axis] - (cell[axis] - g));
+
+    // The simplex the point is in is found from the order of its coordinates within the cell,
+    // the rank of an axis is the number of axes it is larger than.
+    let one = Simd::splat(1.0);
+    let zero = Simd::splat(0.0);
+    let gt = |a: Simd<Float, N>, b: Simd<Float, N>| a.simd_gt(b).select(one, zero);
+    let [x0, y0, z0, w0] = origin;
+    let (xy, xz, xw) = (gt(x0, y0), gt(x0, z0), gt(x0, w0));
+    let (yz, yw, zw) = (gt(y0, z0), gt(y0, w0), gt(z0, w0));
+    let rank: [Simd<Float, N>; 4] = [
+        xy + xz + xw,
+        (one - xy) + yz + yw,
+        (one - xz) + (one - yz) + zw,
+        Simd::splat(3.0) - xw - yw - zw,
+    ];
+
+    let mut value = Simd::splat(0.0);
+    for corner in 0..5 {
+        // Each corner steps along one more axis than the last, from the highest rank down.
+        let threshold = Simd::splat(4.0 - corner as Float);
+        let unskew = Simd::splat(corner as Float * G4);
+        let mut offset = [zero; 4];
+        let mut hash = [Simd::splat(0); 4];
+        // Smaller than the 0.6 of 3d, the falloff would reach past the simplices next to the
+        // corner and the noise would jump at their faces.
+        let mut t = Simd::splat(0.5);
+        for axis in 0..4 {
+            let step = rank[axis].simd_ge(threshold);
+            offset[axis] = step.select(origin[axis] - one, origin[axis]) + unskew;
+            hash[axis] = step
+                .cast::<i32>()
+                .select(hashes[axis] + Simd::splat(PRIMES[axis]), hashes[axis]);
+            t = offset[axis].mul_add(-offset[axis], t);
+        }
+
+        let t = t.simd_max(zero);
+        let t = t * t;
+        let [x, y, z, w] = offset;
+        let gradient = grad4d_dot(hash4d(seed, hash[0], hash[1], hash[2], hash[3]), x, y, z, w);
+        value = (t * t).mul_add(gradient, value);
+    }
+
+    let result = Simd::splat(62.77) * value;
+    pipeline.results.push(result);
+    pipeline.next();
+}
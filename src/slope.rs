@@ -0,0 +1,150 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::dual;
+use super::Float;
+use crate::{Dimensions, NoisePipeline, NoiseSettings};
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn offset_domain_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::OffsetDomain { offset } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x += Simd::splat(offset[0] as Float);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn offset_domain_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::OffsetDomain { offset } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x += Simd::splat(offset[0] as Float);
+    // The second dimension of 2d noise is stored in y
+    pipeline.y += Simd::splat(offset[2] as Float);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn offset_domain_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::OffsetDomain { offset } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x += Simd::splat(offset[0] as Float);
+    pipeline.y += Simd::splat(offset[1] as Float);
+    pipeline.z += Simd::splat(offset[2] as Float);
+    pipeline.next();
+}
+
+/// Length of the gradient, from the sub-noise sampled one step before and after the coordinates
+/// along each axis. Axes that don't exist in the current dimension have a difference of 0.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn slope<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Slope { step } = *settings else {
+        unreachable!()
+    };
+
+    let mut differences = [Simd::splat(0.0); 3];
+    for difference in differences.iter_mut().rev() {
+        let before = pipeline.results.pop().unwrap();
+        let after = pipeline.results.pop().unwrap();
+        *difference = after - before;
+    }
+
+    let [dx, dy, dz] = differences;
+    let length = dx.mul_add(dx, dy.mul_add(dy, dz * dz)).sqrt();
+    let scale = Simd::splat(0.5 / step as Float);
+    pipeline.results.push(length * scale);
+    pipeline.next();
+}
+
+// The length of the gradient of the sub-noise of the Slope that starts at the current node, from
+// its derivatives. The copies of the sub-noise aren't run, the pipeline continues after the Slope.
+#[inline(always)]
+fn slope_derivatives<const N: usize>(pipeline: &mut NoisePipeline<N, Float>, dimensions: Dimensions)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let index = pipeline.index;
+    let slope = *pipeline
+        .slopes
+        .iter()
+        .find(|slope| slope.copies[0].0 == index)
+        .unwrap();
+    let length = dual::slope_length(pipeline, &slope, dimensions);
+    pipeline.results.push(length);
+    pipeline.index = slope.slope;
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn slope_derivatives_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    slope_derivatives(pipeline, Dimensions::XY);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn slope_derivatives_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    slope_derivatives(pipeline, Dimensions::XYZ);
+}
+
+/// Skips a copy of the sub-noise of a Slope along an axis the pipeline doesn't have. The noise
+/// doesn't change along it, both copies of the axis are skipped and their difference is 0.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn skip_copy<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let index = pipeline.index;
+    let end = pipeline
+        .slopes
+        .iter()
+        .flat_map(|slope| slope.copies)
+        .find(|&(start, _)| start == index)
+        .unwrap()
+        .1;
+    pipeline.results.push(Simd::splat(0.0));
+    pipeline.index = end;
+    pipeline.next();
+}
+
+/// Ends the last copy of the sub-noise of a Slope that is run when the copies after it are
+/// skipped. The seeds aren't rewound, the nodes after the Slope continue from the seeds after the
+/// sub-noise, like after the last copy.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn last_copy_end<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    pipeline.restore_coordinates(false);
+    pipeline.next();
+}
@@ -0,0 +1,133 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::simplex::{X_PRIME, Y_PRIME};
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// Random value 0..1 for a component of each wave
+#[inline(always)]
+fn random<const N: usize>(seed: Simd<i32, N>, wave: Simd<i32, N>, component: i32) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut hash =
+        seed ^ (wave * Simd::splat(X_PRIME)) ^ Simd::splat(component.wrapping_mul(Y_PRIME));
+    hash *= hash * hash * Simd::splat(60493);
+    hash.cast::<Float>()
+        .mul_add(Simd::splat(0.5 / 2147483648.0), Simd::splat(0.5))
+}
+
+// Cosine of an angle in turns. The angle is moved to within a quarter turn of 0 and the cosine
+// computed as the sine of the angle to a quarter turn, with its Taylor series up to v⁹. The
+// error is below 4e-6.
+#[inline(always)]
+fn cos_turns<const N: usize>(turns: Simd<Float, N>) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let distance = (turns - turns.round()).abs();
+    let v = (Simd::splat(0.25) - distance) * Simd::splat(std::f64::consts::TAU as Float);
+    let v2 = v * v;
+    let series = Simd::splat(1.0 / 362880.0)
+        .mul_add(v2, Simd::splat(-1.0 / 5040.0))
+        .mul_add(v2, Simd::splat(1.0 / 120.0))
+        .mul_add(v2, Simd::splat(-1.0 / 6.0))
+        .mul_add(v2, Simd::splat(1.0));
+    series * v
+}
+
+// Sums cosine waves with random directions and phases along the first `AXES` of x, y and z. The
+// directions and phases of `N` waves are drawn at a time, then the waves are added one by one.
+#[inline(always)]
+fn spectral<const N: usize, const AXES: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Spectral {
+        lowest_frequency,
+        highest_frequency,
+        beta,
+        waves,
+    } = *settings
+    else {
+        unreachable!()
+    };
+
+    let (mut frequency, frequency_ratio, mut amplitude, amplitude_ratio) =
+        NoiseSettings::spectral_waves(lowest_frequency, highest_frequency, beta, waves);
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    let (x, y, z) = (pipeline.x, pipeline.y, pipeline.z);
+
+    let mut result = Simd::splat(0.0);
+    for first in (0..waves as usize).step_by(N) {
+        let wave = Simd::<i32, N>::from_array(std::array::from_fn(|lane| (first + lane) as i32));
+        let phase = random(seed, wave, 0);
+        let angle = random(seed, wave, 1);
+        // Uniform on the circle in 2d, and on the sphere in 3d where the height of the direction
+        // is uniform.
+        let (dx, dy, dz) = match AXES {
+            1 => (Simd::splat(1.0), Simd::splat(0.0), Simd::splat(0.0)),
+            2 => (
+                cos_turns(angle),
+                cos_turns(angle - Simd::splat(0.25)),
+                Simd::splat(0.0),
+            ),
+            _ => {
+                let height = random(seed, wave, 2).mul_add(Simd::splat(2.0), Simd::splat(-1.0));
+                let radius = (Simd::splat(1.0) - height * height).sqrt();
+                (
+                    radius * cos_turns(angle),
+                    height,
+                    radius * cos_turns(angle - Simd::splat(0.25)),
+                )
+            }
+        };
+
+        for lane in 0..N.min(waves as usize - first) {
+            let mut position = x * Simd::splat(dx[lane]);
+            if AXES > 1 {
+                position = y.mul_add(Simd::splat(dy[lane]), position);
+            }
+            if AXES > 2 {
+                position = z.mul_add(Simd::splat(dz[lane]), position);
+            }
+            let turns = position.mul_add(Simd::splat(frequency as Float), Simd::splat(phase[lane]));
+            result = cos_turns(turns).mul_add(Simd::splat(amplitude as Float), result);
+            frequency *= frequency_ratio;
+            amplitude *= amplitude_ratio;
+        }
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn spectral_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    spectral::<N, 1>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn spectral_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    spectral::<N, 2>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn spectral_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    spectral::<N, 3>(pipeline);
+}
@@ -0,0 +1,81 @@
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::NoiseResult;
+
+// Radially averaged power spectrum of a 2d result, see `NoiseResult::power_spectrum`.
+pub(crate) fn power_spectrum(result: &NoiseResult) -> Vec<f32> {
+    let (width, height) = (result.width(), result.height());
+
+    // A Hann window fades the edges to 0, without it the jump between opposite edges of the
+    // result leaks power into every frequency.
+    let window = |i: usize, size: usize| {
+        if size > 1 {
+            0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / (size - 1) as f32).cos()
+        } else {
+            1.0
+        }
+    };
+    let mut window_energy = 0.0;
+    let mut buffer = Vec::with_capacity(result.len());
+    for x in 0..width {
+        for y in 0..height {
+            let weight = window(x, width) * window(y, height);
+            window_energy += weight * weight;
+            buffer.push(Complex::new(result[x * height + y] * weight, 0.0));
+        }
+    }
+
+    // Transform along the second dimension, which is contiguous, then along the first.
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_forward(height).process(&mut buffer);
+    let mut column = vec![Complex::new(0.0, 0.0); width];
+    let fft = planner.plan_fft_forward(width);
+    for y in 0..height {
+        for (x, value) in column.iter_mut().enumerate() {
+            *value = buffer[x * height + y];
+        }
+        fft.process(&mut column);
+        for (x, value) in column.iter().enumerate() {
+            buffer[x * height + y] = *value;
+        }
+    }
+
+    // Bins are 1 / shortest side apart, up to the highest frequency both sides can represent.
+    let size = width.min(height);
+    let bins = size / 2 + 1;
+    let mut power = vec![0.0f64; bins];
+    let mut counts = vec![0u32; bins];
+    // Signed frequency of an index of the transform, in cycles per value
+    let frequency = |i: usize, n: usize| {
+        let i = if i > n / 2 {
+            i as f32 - n as f32
+        } else {
+            i as f32
+        };
+        i / n as f32
+    };
+    for x in 0..width {
+        for y in 0..height {
+            let fx = frequency(x, width);
+            let fy = frequency(y, height);
+            let bin = ((fx * fx + fy * fy).sqrt() * size as f32).round() as usize;
+            if bin < bins {
+                power[bin] += buffer[x * height + y].norm_sqr() as f64;
+                counts[bin] += 1;
+            }
+        }
+    }
+
+    power
+        .iter()
+        .zip(counts.iter())
+        .map(|(power, count)| {
+            if *count == 0 {
+                0.0
+            } else {
+                (*power / *count as f64 / window_energy as f64) as f32
+            }
+        })
+        .collect()
+}
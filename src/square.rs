@@ -2,10 +2,11 @@ use std::simd::{LaneCount, SupportedLaneCount};
 
 use multiversion::multiversion;
 
+use super::Float;
 use crate::NoisePipeline;
 
 #[multiversion(targets = "simd", dispatcher = "pointer")]
-pub fn square<const N: usize>(pipeline: &mut NoisePipeline<N>)
+pub fn square<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
 where
     LaneCount<N>: SupportedLaneCount,
 {
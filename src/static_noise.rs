@@ -0,0 +1,611 @@
+use std::borrow::Cow;
+
+use crate::{fnv1a, powi};
+use crate::{
+    Algorithm, CellularDistance, CellularReturn, Frequency, Lacunarity, Noise, NoiseSettings,
+    OctaveOp, OctaveParams, SdfShape,
+};
+
+/// A [Noise] built at compile time, for noises that never change, e.g. the terrain of a game.
+///
+/// It has the same builder methods as [Noise], but they are `const`, and the operations are
+/// stored inline, up to `CAPACITY` of them. Every source and operation takes one slot, fbm takes
+/// the slots of the noise once for each octave plus one, [StaticNoise::wrap_domain] two per
+/// copy of the noise plus one and [StaticNoise::slope] two per copy plus one. Building a
+/// noise that doesn't fit is a compile error.
+///
+/// [StaticNoise::noise] turns a `StaticNoise` stored in a static into a [Noise] that borrows its
+/// operations, without allocating, so the noise can itself be stored in a static. It generates
+/// the same values and compares equal to the same noise built at runtime.
+///
+/// # Example
+/// ```rust
+/// static TERRAIN: StaticNoise<16> = StaticNoise::simplex(Frequency::splat(0.01))
+///     .fbm(4, 0.5, Lacunarity::splat(2.0))
+///     .lerp_values(40.0, 120.0)
+///     .seed(42);
+/// static TERRAIN_NOISE: Noise = TERRAIN.noise();
+///
+/// let height = TERRAIN_NOISE.generate_2d::<f32>(0.0, 0.0, 16, 16);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct StaticNoise<const CAPACITY: usize> {
+    seed: u64,
+    algorithm: Algorithm,
+    len: usize,
+    pipeline: [NoiseSettings; CAPACITY],
+}
+
+impl<const CAPACITY: usize> StaticNoise<CAPACITY> {
+    const fn new(settings: NoiseSettings) -> Self {
+        Self {
+            seed: 0,
+            algorithm: Algorithm::V1,
+            // Slots past `len` are never read
+            len: 0,
+            pipeline: [NoiseSettings::Abs; CAPACITY],
+        }
+        .push(settings)
+    }
+
+    const fn push(mut self, settings: NoiseSettings) -> Self {
+        assert!(
+            self.len < CAPACITY,
+            "The noise doesn't fit in the capacity of the StaticNoise"
+        );
+        self.pipeline[self.len] = settings;
+        self.len += 1;
+        self
+    }
+
+    const fn append(mut self, pipeline: &[NoiseSettings]) -> Self {
+        let mut i = 0;
+        while i < pipeline.len() {
+            self = self.push(pipeline[i]);
+            i += 1;
+        }
+        self
+    }
+
+    const fn settings(&self) -> &[NoiseSettings] {
+        self.pipeline.split_at(self.len).0
+    }
+
+    /// The noise, it borrows the operations so nothing is allocated.
+    ///
+    /// # Example
+    /// ```rust
+    /// static CAVES: StaticNoise<8> = StaticNoise::simplex(Frequency::splat(0.05)).abs();
+    /// static CAVES_NOISE: Noise = CAVES.noise();
+    /// ```
+    pub const fn noise(&'static self) -> Noise {
+        Noise {
+            seed: self.seed,
+            algorithm: self.algorithm,
+            pipeline: Cow::Borrowed(self.settings()),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// See [Noise::simplex]
+    pub const fn simplex(frequency: Frequency) -> Self {
+        Self::new(NoiseSettings::Simplex { frequency })
+    }
+
+    /// See [Noise::perlin]
+    pub const fn perlin(frequency: Frequency) -> Self {
+        Self::new(NoiseSettings::Perlin { frequency })
+    }
+
+    /// See [Noise::opensimplex2]
+    pub const fn opensimplex2(frequency: Frequency) -> Self {
+        Self::new(NoiseSettings::OpenSimplex2 { frequency })
+    }
+
+    /// See [Noise::value]
+    pub const fn value(frequency: Frequency) -> Self {
+        Self::new(NoiseSettings::Value { frequency })
+    }
+
+    /// See [Noise::value_cubic]
+    pub const fn value_cubic(frequency: Frequency) -> Self {
+        Self::new(NoiseSettings::ValueCubic { frequency })
+    }
+
+    /// See [Noise::cellular]
+    pub const fn cellular(frequency: Frequency) -> Self {
+        Self::cellular_with(frequency, CellularDistance::Euclidean, CellularReturn::F1)
+    }
+
+    /// See [Noise::cellular_with]
+    pub const fn cellular_with(
+        frequency: Frequency,
+        distance: CellularDistance,
+        return_type: CellularReturn,
+    ) -> Self {
+        Self::new(NoiseSettings::Cellular {
+            frequency,
+            distance,
+            return_type,
+        })
+    }
+
+    /// See [Noise::white]
+    pub const fn white() -> Self {
+        Self::new(NoiseSettings::White)
+    }
+
+    /// See [Noise::pink]
+    pub const fn pink(frequency: f32) -> Self {
+        Self::colored(frequency, 1.0)
+    }
+
+    /// See [Noise::brown]
+    pub const fn brown(frequency: f32) -> Self {
+        Self::colored(frequency, 2.0)
+    }
+
+    const fn colored(frequency: f32, exponent: f32) -> Self {
+        assert!(frequency > 0.0, "The frequency must be larger than 0");
+        Self::new(NoiseSettings::Colored {
+            frequency,
+            exponent,
+        })
+    }
+
+    /// See [Noise::spectral]
+    pub const fn spectral(
+        lowest_frequency: f32,
+        highest_frequency: f32,
+        beta: f32,
+        waves: u32,
+    ) -> Self {
+        assert!(
+            lowest_frequency > 0.0,
+            "The lowest frequency must be larger than 0"
+        );
+        assert!(
+            highest_frequency >= lowest_frequency,
+            "The highest frequency can't be below the lowest"
+        );
+        assert!(waves > 0, "There must be 1 or more waves");
+        Self::new(NoiseSettings::Spectral {
+            lowest_frequency,
+            highest_frequency,
+            beta,
+            waves,
+        })
+    }
+
+    /// See [Noise::dots]
+    pub const fn dots(
+        frequency: Frequency,
+        density: f32,
+        radius: f32,
+        amplitude_jitter: f32,
+    ) -> Self {
+        assert!(
+            density >= 0.0 && density <= 1.0,
+            "The density must be within 0..=1"
+        );
+        assert!(
+            radius > 0.0 && radius <= 1.0,
+            "The radius must be larger than 0 and at most 1"
+        );
+        assert!(
+            amplitude_jitter >= 0.0 && amplitude_jitter <= 1.0,
+            "The amplitude jitter must be within 0..=1"
+        );
+        Self::new(NoiseSettings::Dots {
+            frequency,
+            density,
+            radius,
+            amplitude_jitter,
+        })
+    }
+
+    /// See [Noise::constant]
+    pub const fn constant(value: f32) -> Self {
+        Self::new(NoiseSettings::Constant { value })
+    }
+
+    /// See [Noise::param]
+    pub const fn param(name: &str, value: f32) -> Self {
+        Self::new(NoiseSettings::Parameter {
+            name: fnv1a(name),
+            value,
+        })
+    }
+
+    /// See [Noise::falloff_radial]
+    pub const fn falloff_radial(
+        center_x: f32,
+        center_z: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    ) -> Self {
+        assert!(
+            outer_radius > inner_radius,
+            "The outer radius must be larger than the inner radius"
+        );
+        Self::new(NoiseSettings::FalloffRadial {
+            center_x,
+            center_z,
+            inner_radius,
+            outer_radius,
+        })
+    }
+
+    /// See [Noise::sdf]
+    pub const fn sdf(shape: SdfShape) -> Self {
+        match shape {
+            SdfShape::Sphere { radius, .. } | SdfShape::Capsule { radius, .. } => {
+                assert!(radius >= 0.0, "The radius can't be negative");
+            }
+            SdfShape::Box { half_size, .. } => {
+                assert!(
+                    half_size[0] >= 0.0 && half_size[1] >= 0.0 && half_size[2] >= 0.0,
+                    "The size of the box can't be negative"
+                );
+            }
+            SdfShape::Plane { normal, .. } => {
+                assert!(
+                    normal[0] != 0.0 || normal[1] != 0.0 || normal[2] != 0.0,
+                    "The normal of the plane can't be 0"
+                );
+            }
+        }
+        Self::new(NoiseSettings::Sdf { shape })
+    }
+
+    /// See [Noise::veins], takes 7 slots.
+    pub const fn veins(frequency: f32, thickness: f32, elongation: f32) -> Self {
+        assert!(thickness > 0.0, "The thickness must be larger than 0");
+        assert!(elongation >= 1.0, "The elongation must be 1 or more");
+
+        let stretched = frequency / elongation;
+        let along_x = Self::simplex(Frequency {
+            x: stretched,
+            y: frequency,
+            z: frequency,
+        });
+        let along_z = Self::simplex(Frequency {
+            x: frequency,
+            y: frequency,
+            z: stretched,
+        });
+
+        along_x
+            .abs()
+            .max(along_z.abs())
+            .lerp_values(1.0 + 1.0 / thickness, 1.0 - 1.0 / thickness)
+            .clamp(0.0, 1.0)
+    }
+
+    /// See [Noise::seed]
+    pub const fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed as u64;
+        self
+    }
+
+    /// See [Noise::seed_str]
+    pub const fn seed_str(mut self, seed: &str) -> Self {
+        self.seed = fnv1a(seed);
+        self
+    }
+
+    /// See [Noise::algorithm]
+    pub const fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// See [Noise::fbm]
+    pub const fn fbm(self, octaves: u32, gain: f32, lacunarity: Lacunarity) -> Self {
+        self.fbm_octaves(octaves, gain, lacunarity, None)
+    }
+
+    /// See [Noise::fbm_with]
+    pub const fn fbm_with(
+        self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: Lacunarity,
+        op: OctaveOp,
+    ) -> Self {
+        self.fbm_octaves(octaves, gain, lacunarity, Some(op))
+    }
+
+    /// See [Noise::ridged]
+    pub const fn ridged(self, octaves: u32, gain: f32, lacunarity: Lacunarity) -> Self {
+        let mut noise = self.fbm_octaves(octaves, gain, lacunarity, None);
+        noise.pipeline[noise.len - 1] = NoiseSettings::Ridged {
+            octaves,
+            gain,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        };
+        noise
+    }
+
+    /// See [Noise::ping_pong]
+    pub const fn ping_pong(
+        self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: Lacunarity,
+        strength: f32,
+    ) -> Self {
+        let mut noise = self.fbm_octaves(octaves, gain, lacunarity, None);
+        noise.pipeline[noise.len - 1] = NoiseSettings::PingPong {
+            octaves,
+            gain,
+            strength,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        };
+        noise
+    }
+
+    // Same as Noise::fbm_octaves
+    const fn fbm_octaves(
+        mut self,
+        octaves: u32,
+        gain: f32,
+        lacunarity: Lacunarity,
+        op: Option<OctaveOp>,
+    ) -> Self {
+        assert!(octaves > 0, "There must be 1 or more octaves");
+
+        let initial = self;
+        self.len = 0;
+        let mut i = octaves;
+        while i > 0 {
+            i -= 1;
+            let lacunarity = Lacunarity {
+                x: powi(lacunarity.x, i),
+                y: powi(lacunarity.y, i),
+                z: powi(lacunarity.z, i),
+            };
+            let mut j = 0;
+            while j < initial.len {
+                let mut settings = initial.pipeline[j];
+                settings.scale_frequency_axes(lacunarity);
+                self = self.push(settings);
+                j += 1;
+            }
+
+            self = match op {
+                Some(OctaveOp::Abs) => self.push(NoiseSettings::Abs),
+                Some(OctaveOp::Square) => self.push(NoiseSettings::Square),
+                Some(OctaveOp::Ridge) => {
+                    self.push(NoiseSettings::Abs)
+                        .push(NoiseSettings::LerpValues {
+                            low: 2.0,
+                            high: 0.0,
+                        })
+                }
+                None => self,
+            };
+        }
+
+        self.push(NoiseSettings::Fbm {
+            octaves,
+            gain,
+            first_octave_amplitude: NoiseSettings::first_octave_amplitude(octaves, gain),
+        })
+    }
+
+    /// See [Noise::fbm_custom]
+    pub const fn fbm_custom(mut self, octaves: &[OctaveParams]) -> Self {
+        assert!(!octaves.is_empty(), "There must be 1 or more octaves");
+
+        let initial = self;
+        self.len = 0;
+        let mut i = 0;
+        while i < octaves.len() {
+            let octave = octaves[i];
+            let offset = octave.seed_offset as u64;
+            if offset != 0 {
+                self = self.push(NoiseSettings::SeedOffset { offset });
+            }
+            let mut j = 0;
+            while j < initial.len {
+                let mut settings = initial.pipeline[j];
+                settings.scale_frequency(octave.frequency_mul);
+                self = self.push(settings);
+                j += 1;
+            }
+            if offset != 0 {
+                self = self.push(NoiseSettings::SeedOffset {
+                    offset: offset.wrapping_neg(),
+                });
+            }
+
+            self = self
+                .push(NoiseSettings::Constant {
+                    value: octave.amplitude,
+                })
+                .push(NoiseSettings::Mul);
+            if i > 0 {
+                self = self.push(NoiseSettings::Add);
+            }
+            i += 1;
+        }
+        self
+    }
+
+    /// See [Noise::wrap_domain]
+    pub const fn wrap_domain(mut self, period_x: f32, period_y: f32, period_z: f32) -> Self {
+        let period = [period_x, period_y, period_z];
+        assert!(
+            period_x >= 0.0 && period_y >= 0.0 && period_z >= 0.0,
+            "The periods can't be negative"
+        );
+
+        let initial = self;
+        self.len = 0;
+        let corners = NoiseSettings::wrap_corners(period);
+        let mut i = 0;
+        while i < corners {
+            self = self
+                .push(NoiseSettings::WrapDomain {
+                    period,
+                    corner: NoiseSettings::wrap_corner(period, i),
+                })
+                .append(initial.settings())
+                .push(NoiseSettings::RestoreCoordinates {
+                    rewind: i + 1 < corners,
+                });
+            i += 1;
+        }
+        self.push(NoiseSettings::WrapBlend { period })
+    }
+
+    /// See [Noise::warp]
+    pub const fn warp(mut self, offset: Self, amplitude: f32) -> Self {
+        let initial = self;
+        self.len = 0;
+        self.append(offset.settings())
+            .append(offset.settings())
+            .append(offset.settings())
+            .push(NoiseSettings::Warp { amplitude })
+            .append(initial.settings())
+            .push(NoiseSettings::RestoreCoordinates { rewind: false })
+    }
+
+    /// See [Noise::slope]
+    pub const fn slope(mut self) -> Self {
+        let step = NoiseSettings::slope_step(self.settings());
+        let offsets = NoiseSettings::slope_offsets(step);
+
+        let initial = self;
+        self.len = 0;
+        let mut i = 0;
+        while i < offsets.len() {
+            self = self
+                .push(NoiseSettings::OffsetDomain { offset: offsets[i] })
+                .append(initial.settings())
+                .push(NoiseSettings::RestoreCoordinates {
+                    rewind: i + 1 < offsets.len(),
+                });
+            i += 1;
+        }
+        self.push(NoiseSettings::Slope { step })
+    }
+
+    /// See [Noise::extrude_2d]
+    pub const fn extrude_2d(mut self) -> Self {
+        let initial = self;
+        self.len = 0;
+        self.push(NoiseSettings::Extrude2d)
+            .append(initial.settings())
+            .push(NoiseSettings::RestoreCoordinates { rewind: false })
+    }
+
+    /// See [Noise::interpolated]
+    pub const fn interpolated(mut self, spacing: f32) -> Self {
+        assert!(spacing > 0.0, "The spacing must be larger than 0");
+        let initial = self;
+        self.len = 0;
+        self.push(NoiseSettings::Interpolate { spacing })
+            .append(initial.settings())
+            .push(NoiseSettings::RestoreCoordinates { rewind: false })
+    }
+
+    /// See [Noise::abs]
+    pub const fn abs(self) -> Self {
+        self.push(NoiseSettings::Abs)
+    }
+
+    /// See [Noise::add]
+    pub const fn add(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Add)
+    }
+
+    /// See [Noise::sub]
+    pub const fn sub(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Sub)
+    }
+
+    /// See [Noise::abs_diff]
+    pub const fn abs_diff(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::AbsDiff)
+    }
+
+    /// See [Noise::avg]
+    pub const fn avg(self, other: Self) -> Self {
+        self.append(other.settings())
+            .push(NoiseSettings::Average { count: 2 })
+    }
+
+    /// See [Noise::mul]
+    pub const fn mul(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Mul)
+    }
+
+    /// See [Noise::div]
+    pub const fn div(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Div)
+    }
+
+    /// See [Noise::clamp]
+    pub const fn clamp(self, min: f32, max: f32) -> Self {
+        assert!(min <= max, "The min can't be larger than the max");
+        self.push(NoiseSettings::Clamp { min, max })
+    }
+
+    /// See [Noise::max]
+    pub const fn max(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Max)
+    }
+
+    /// See [Noise::min]
+    pub const fn min(self, other: Self) -> Self {
+        self.append(other.settings()).push(NoiseSettings::Min)
+    }
+
+    /// See [Noise::lerp]
+    pub const fn lerp(self, low: Self, high: Self) -> Self {
+        self.lerp_with_range(-1.0, 1.0, low, high)
+    }
+
+    /// See [Noise::lerp_with_range]
+    pub const fn lerp_with_range(
+        self,
+        selector_min: f32,
+        selector_max: f32,
+        low: Self,
+        high: Self,
+    ) -> Self {
+        // XXX: Append order is important for result order
+        self.append(high.settings())
+            .append(low.settings())
+            .push(NoiseSettings::Lerp {
+                selector_min,
+                selector_max,
+            })
+    }
+
+    /// See [Noise::lerp_values]
+    pub const fn lerp_values(self, low: f32, high: f32) -> Self {
+        self.push(NoiseSettings::LerpValues { low, high })
+    }
+
+    /// See [Noise::range]
+    pub const fn range(self, low: f32, high: f32, low_noise: Self, high_noise: Self) -> Self {
+        // XXX: Append order is important for result order
+        self.append(high_noise.settings())
+            .append(low_noise.settings())
+            .push(NoiseSettings::Range { low, high })
+    }
+
+    /// See [Noise::square]
+    pub const fn square(self) -> Self {
+        self.push(NoiseSettings::Square)
+    }
+
+    /// See [Noise::dither]
+    pub const fn dither(self, min: f32, max: f32, levels: u32) -> Self {
+        assert!(levels >= 2, "There must be 2 or more levels");
+        self.push(NoiseSettings::Dither { min, max, levels })
+    }
+}
@@ -0,0 +1,185 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::cellular::{cell_hash, random};
+use super::Float;
+use crate::{Frequency, NoisePipeline, NoiseSettings};
+
+// The coordinates scaled by the frequency, the second dimension of 2d noise is stored in y and
+// it is the z axis of the frequency.
+#[inline(always)]
+fn scale<const N: usize, const AXES: usize>(
+    pipeline: &NoisePipeline<N, Float>,
+    frequency: Frequency,
+) -> [Simd<Float, N>; 3]
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let frequency = match AXES {
+        2 => [frequency.x, frequency.z, 0.0],
+        _ => [frequency.x, frequency.y, frequency.z],
+    };
+    let point = [pipeline.x, pipeline.y, pipeline.z];
+    std::array::from_fn(|axis| {
+        if axis < AXES {
+            point[axis] * Simd::splat(frequency[axis] as Float)
+        } else {
+            Simd::splat(0.0)
+        }
+    })
+}
+
+// Random value -1..1 at a lattice point
+#[inline(always)]
+fn lattice<const N: usize>(seed: Simd<i32, N>, point: [Simd<Float, N>; 3]) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    random(cell_hash(seed, point), 0).mul_add(Simd::splat(2.0), Simd::splat(-1.0))
+}
+
+// Random values at the corners of the lattice cell, interpolated with the quintic curve so that
+// the noise has smooth slopes across the cell edges.
+#[inline(always)]
+fn value<const N: usize, const AXES: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Value { frequency } = *settings else {
+        unreachable!()
+    };
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    let point = scale::<N, AXES>(pipeline, frequency);
+    let cell = point.map(|coordinate| coordinate.floor());
+    let weight: [Simd<Float, N>; 3] = std::array::from_fn(|axis| {
+        let t = point[axis] - cell[axis];
+        t * t
+            * t
+            * t.mul_add(
+                t.mul_add(Simd::splat(6.0), Simd::splat(-15.0)),
+                Simd::splat(10.0),
+            )
+    });
+
+    let mut result = Simd::splat(0.0);
+    for corner in 0..1 << AXES {
+        let mut corner_weight = Simd::splat(1.0);
+        let position = std::array::from_fn(|axis| {
+            if corner >> axis & 1 == 1 {
+                corner_weight *= weight[axis];
+                cell[axis] + Simd::splat(1.0)
+            } else {
+                if axis < AXES {
+                    corner_weight *= Simd::splat(1.0) - weight[axis];
+                }
+                cell[axis]
+            }
+        });
+        result = lattice(seed, position).mul_add(corner_weight, result);
+    }
+
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+// Catmull-Rom interpolation of the random values at the 4 lattice points around the coordinates
+// along each axis. The weights sum to at most 1.25 in absolute value, the result is scaled by
+// 1.25^-AXES to stay within -1..1.
+#[inline(always)]
+fn value_cubic<const N: usize, const AXES: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::ValueCubic { frequency } = *settings else {
+        unreachable!()
+    };
+
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    let point = scale::<N, AXES>(pipeline, frequency);
+    let cell = point.map(|coordinate| coordinate.floor());
+    // The weights of the lattice points at -1, 0, 1 and 2 from the cell along each axis
+    let weights: [[Simd<Float, N>; 4]; 3] = std::array::from_fn(|axis| {
+        let t = point[axis] - cell[axis];
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let half = Simd::splat(0.5);
+        [
+            half * (t2 + t2 - t3 - t),
+            half * (Simd::splat(3.0) * t3 - Simd::splat(5.0) * t2 + Simd::splat(2.0)),
+            half * (Simd::splat(4.0) * t2 - Simd::splat(3.0) * t3 + t),
+            half * (t3 - t2),
+        ]
+    });
+
+    let mut result = Simd::splat(0.0);
+    for corner in 0..1 << (2 * AXES) {
+        let mut corner_weight = Simd::splat(1.0);
+        let position = std::array::from_fn(|axis| {
+            if axis < AXES {
+                let index = corner >> (2 * axis) & 3;
+                corner_weight *= weights[axis][index];
+                cell[axis] + Simd::splat(index as Float - 1.0)
+            } else {
+                cell[axis]
+            }
+        });
+        result = lattice(seed, position).mul_add(corner_weight, result);
+    }
+
+    let scale = (1.0 / 1.25 as Float).powi(AXES as i32);
+    pipeline.results.push(result * Simd::splat(scale));
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value::<N, 1>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value::<N, 2>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value::<N, 3>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_cubic_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value_cubic::<N, 1>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_cubic_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value_cubic::<N, 2>(pipeline);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn value_cubic_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    value_cubic::<N, 3>(pipeline);
+}
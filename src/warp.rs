@@ -0,0 +1,63 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// The offsets along x, y and z, from the results of the copies of the offset noise, which were
+// added in that order.
+#[inline(always)]
+fn offsets<const N: usize>(pipeline: &mut NoisePipeline<N, Float>) -> [Simd<Float, N>; 3]
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::Warp { amplitude } = *settings else {
+        unreachable!()
+    };
+
+    let amplitude = Simd::splat(amplitude as Float);
+    let z = pipeline.results.pop().unwrap();
+    let y = pipeline.results.pop().unwrap();
+    let x = pipeline.results.pop().unwrap();
+    [x * amplitude, y * amplitude, z * amplitude]
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn warp_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let [x, _, _] = offsets(pipeline);
+    pipeline.save_coordinates();
+    pipeline.x += x;
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn warp_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let [x, _, z] = offsets(pipeline);
+    pipeline.save_coordinates();
+    pipeline.x += x;
+    // The second dimension of 2d noise is stored in y
+    pipeline.y += z;
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn warp_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let [x, y, z] = offsets(pipeline);
+    pipeline.save_coordinates();
+    pipeline.x += x;
+    pipeline.y += y;
+    pipeline.z += z;
+    pipeline.next();
+}
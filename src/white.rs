@@ -0,0 +1,70 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::cellular::random;
+use super::Float;
+use crate::NoisePipeline;
+
+// The bits of the coordinate as an f32, so that f32 and f64 noise agree. Adding 0 turns -0 into
+// 0, they are the same coordinate.
+#[inline(always)]
+fn bits<const N: usize>(coordinate: Simd<Float, N>) -> Simd<i32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    (coordinate.cast::<f32>() + Simd::splat(0.0))
+        .to_bits()
+        .cast::<i32>()
+}
+
+// A random value for the exact coordinates of the point along the first `AXES` axes, nothing is
+// shared between points however close they are.
+#[inline(always)]
+fn white<const N: usize, const AXES: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    point: [Simd<Float, N>; 3],
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let seed = Simd::<i32, N>::splat(pipeline.rng.next());
+    // Each coordinate is mixed in on its own, the bits of nearby coordinates differ too little
+    // for a single multiplication to spread them.
+    let mut hash = seed;
+    for coordinate in &point[..AXES] {
+        hash ^= bits(*coordinate);
+        hash *= Simd::splat(0x27d4eb2d);
+        hash ^= hash >> Simd::splat(15);
+    }
+
+    let result = random(hash, 0).mul_add(Simd::splat(2.0), Simd::splat(-1.0));
+    pipeline.results.push(result);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn white_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let zero = Simd::splat(0.0);
+    white::<N, 1>(pipeline, [pipeline.x, zero, zero]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn white_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The second dimension of 2d noise is stored in y
+    white::<N, 2>(pipeline, [pipeline.x, pipeline.y, Simd::splat(0.0)]);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn white_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    white::<N, 3>(pipeline, [pipeline.x, pipeline.y, pipeline.z]);
+}
@@ -0,0 +1,177 @@
+use std::simd::prelude::*;
+use std::simd::{LaneCount, StdFloat, SupportedLaneCount};
+
+use multiversion::multiversion;
+
+use super::Float;
+use crate::{NoisePipeline, NoiseSettings};
+
+// The coordinates are wrapped into 0..period, a period of 0 leaves them as they are.
+#[inline(always)]
+fn wrap<const N: usize>(coordinate: Simd<Float, N>, period: Float, shift: bool) -> Simd<Float, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    if period <= 0.0 {
+        return coordinate;
+    }
+
+    let period = Simd::splat(period);
+    let wrapped = coordinate - (coordinate / period).floor() * period;
+    if shift {
+        wrapped - period
+    } else {
+        wrapped
+    }
+}
+
+// Blends the copies of the sub-noise, one for each combination of the wrapped axes. The copy
+// moved back one period gets more weight the closer the coordinate is to the end of the period,
+// so that both ends of the period have the same value. Axes that are wrapped but don't exist in
+// the current dimension get a weight of 0.
+#[inline(always)]
+fn blend<const N: usize>(
+    pipeline: &mut NoisePipeline<N, Float>,
+    period: [f32; 3],
+    coordinates: [Option<Simd<Float, N>>; 3],
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let mut fractions = Vec::with_capacity(3);
+    for (period, coordinate) in period.into_iter().zip(coordinates) {
+        if period <= 0.0 {
+            continue;
+        }
+        let period = period as Float;
+        let fraction = match coordinate {
+            Some(coordinate) => wrap(coordinate, period, false) / Simd::splat(period),
+            None => Simd::splat(0.0),
+        };
+        fractions.push(fraction);
+    }
+
+    let mut result = Simd::splat(0.0);
+    for corner in (0..1usize << fractions.len()).rev() {
+        let mut weight = Simd::splat(1.0);
+        for (axis, fraction) in fractions.iter().enumerate() {
+            if corner >> axis & 1 == 1 {
+                weight *= *fraction;
+            } else {
+                weight *= Simd::splat(1.0) - *fraction;
+            }
+        }
+        let noise = pipeline.results.pop().unwrap();
+        result = noise.mul_add(weight, result);
+    }
+
+    pipeline.results.push(result);
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_domain_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapDomain { period, corner } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x = wrap(pipeline.x, period[0] as Float, corner & 1 != 0);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_domain_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapDomain { period, corner } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x = wrap(pipeline.x, period[0] as Float, corner & 1 != 0);
+    // The second dimension of 2d noise is stored in y
+    pipeline.y = wrap(pipeline.y, period[2] as Float, corner & 4 != 0);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_domain_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapDomain { period, corner } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.save_coordinates();
+    pipeline.x = wrap(pipeline.x, period[0] as Float, corner & 1 != 0);
+    pipeline.y = wrap(pipeline.y, period[1] as Float, corner & 2 != 0);
+    pipeline.z = wrap(pipeline.z, period[2] as Float, corner & 4 != 0);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_blend_1d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapBlend { period } = *settings else {
+        unreachable!()
+    };
+
+    let coordinates = [Some(pipeline.x), None, None];
+    blend(pipeline, period, coordinates);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_blend_2d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapBlend { period } = *settings else {
+        unreachable!()
+    };
+
+    let coordinates = [Some(pipeline.x), None, Some(pipeline.y)];
+    blend(pipeline, period, coordinates);
+    pipeline.next();
+}
+
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn wrap_blend_3d<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::WrapBlend { period } = *settings else {
+        unreachable!()
+    };
+
+    let coordinates = [Some(pipeline.x), Some(pipeline.y), Some(pipeline.z)];
+    blend(pipeline, period, coordinates);
+    pipeline.next();
+}
+
+/// Ends the sub-noise started by a node that changed the coordinates.
+#[multiversion(targets = "simd", dispatcher = "pointer")]
+pub fn restore_coordinates<const N: usize>(pipeline: &mut NoisePipeline<N, Float>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let settings = pipeline.current_settings();
+    let NoiseSettings::RestoreCoordinates { rewind } = *settings else {
+        unreachable!()
+    };
+
+    pipeline.restore_coordinates(rewind);
+    pipeline.next();
+}